@@ -14,8 +14,11 @@ import numpy as np
 from fmiopendata.wfs import download_stored_query
 
 
-def collect_data(start_time: str, end_time: str, place: str):
-    collection_string = "fmi::forecast::harmonie::surface::point::multipointcoverage"
+FORECAST_QUERY = "fmi::forecast::harmonie::surface::point::multipointcoverage"
+ENSEMBLE_QUERY = "fmi::forecast::meps::surface::point::multipointcoverage"
+
+
+def collect_data(start_time: str, end_time: str, place: str, collection_string: str = FORECAST_QUERY):
     parameters = ["Temperature"]
     parameters_str = ','.join(parameters)
     snd = download_stored_query(collection_string,
@@ -39,14 +42,23 @@ def reshape_dict(data: dict[datetime, dict[str, dict[str, dict]]]) -> dict[str,
     return new_data_dict
 
 
-def main(start_time: str, end_time: str, step: int, place: str) -> None:
-    data = collect_data(start_time, end_time, place)
+def temperature_series(df: pd.DataFrame, column: str, step: int) -> list:
+    temperature = (df[column] + 273.15).resample(f'{step}min').nearest()
+    temperature.index = temperature.index.strftime('%Y-%m-%dT%H:%M:%S')
+    return [(time, value) for time, value in temperature.to_dict().items()]
+
+
+def main(start_time: str, end_time: str, step: int, place: str, members: bool, ensemble_query: str) -> None:
+    query = ensemble_query if members else FORECAST_QUERY
+    data = collect_data(start_time, end_time, place, query)
     reshaped_data = reshape_dict(data)
     df = pd.DataFrame(index=data.keys(), data=reshaped_data[place])
-    df['Air temperature'] += 273.15
-    mean_temperature = df['Air temperature'].resample(f'{step}min').nearest()
-    mean_temperature.index = mean_temperature.index.strftime('%Y-%m-%dT%H:%M:%S')
-    json_output = json.dumps([(time, temperature) for time, temperature in mean_temperature.to_dict().items()])
+    if members:
+        # Every air temperature parameter in the ensemble query is one member.
+        columns = [column for column in df.columns if column.startswith('Air temperature')]
+        json_output = json.dumps([temperature_series(df, column, step) for column in columns])
+    else:
+        json_output = json.dumps(temperature_series(df, 'Air temperature', step))
     print(json_output)
 
 
@@ -56,5 +68,7 @@ if __name__ == "__main__":
     parser.add_argument('end_time', type=str, help='End time for data collection in UTC and YYYY-MM-DD HH:MM format')
     parser.add_argument('step', type=int, help='Step between time stamps in minutes')
     parser.add_argument('place', type=str, help='Name of the place')
+    parser.add_argument('--members', action='store_true', help='Output every member of an ensemble forecast as a separate series')
+    parser.add_argument('--ensemble-query', type=str, default=ENSEMBLE_QUERY, help='FMI stored query for the ensemble forecast')
     args = parser.parse_args()
-    main(args.start_time, args.end_time, args.step, args.place)
+    main(args.start_time, args.end_time, args.step, args.place, args.members, args.ensemble_query)
@@ -1,19 +1,22 @@
-mod arrow_input;
+pub mod arrow_input;
 mod electricity_price_job_elering;
 mod electricity_price_job_entsoe;
 pub mod job_store;
 pub mod jobs;
 mod optimization_job;
-mod time_series;
+pub mod time_series;
 mod utilities;
 mod weather_forecast_job;
 
 use crate::input_data::{TimeSeries, TimeSeriesData};
 use crate::input_data_base::{BaseInputData, BaseForecastable};
 use crate::model::Model;
+use crate::scenarios::prices::PriceSeries;
 use crate::settings::Settings;
 use crate::time_line_settings::TimeLineSettings;
+use crate::units::Unit;
 use crate::TimeLine;
+use indexmap::IndexMap;
 use job_store::JobStore;
 use jobs::{Job, NewJob};
 use std::sync::Arc;
@@ -44,6 +47,14 @@ type WeatherData = Vec<TimeSeries>;
 #[derive(Clone, Debug, Default)]
 pub struct ElectricityPriceData {
     pub price_data: Option<TimeSeriesData>,
+    pub market_prices: IndexMap<String, MarketPrices>,
+}
+
+/// Price scenarios of a market generated from the price forecast in the market's price unit.
+#[derive(Clone, Debug)]
+pub struct MarketPrices {
+    pub unit: Option<Unit>,
+    pub prices: PriceSeries,
 }
 
 pub async fn start_job(
@@ -65,7 +76,8 @@ pub async fn event_loop(
     mut message_receiver: mpsc::Receiver<NewJob>,
 ) {
     while let Some(new_job) = message_receiver.recv().await {
-        match new_job.job() {
+        let job_id = new_job.job_id();
+        match new_job.into_job() {
             Job::ElectricityPrice => {
                 let (mut found_valid, mut invalid_names) = (false, Vec::<String>::new());
                 let (mut has_elering, mut has_entsoe) = (false, false);
@@ -92,7 +104,7 @@ pub async fn event_loop(
                     );
                     let _ = job_store
                         .set_job_status(
-                            new_job.job_id(),
+                            job_id,
                             Arc::new(JobStatus::Failed(msg.into())),
                         )
                         .await;
@@ -102,11 +114,11 @@ pub async fn event_loop(
                 if !found_valid {
                     println!(
                         "[event_loop] No valid electricity price forecasts found for job {}; skipping fetch",
-                        new_job.job_id()
+                        job_id
                     );
                     let _ = job_store
                         .set_job_status(
-                            new_job.job_id(),
+                            job_id,
                             Arc::new(JobStatus::Finished(JobOutcome::ElectricityPrice(
                                 ElectricityPriceOutcome::new(vec![], vec![]),
                             ))),
@@ -116,7 +128,7 @@ pub async fn event_loop(
                 }
 
                 start_electricity_price_fetch(
-                    new_job.job_id(),
+                    job_id,
                     Arc::clone(&settings),
                     job_store.clone(),
                     model.lock().await.time_line.clone(),
@@ -126,14 +138,13 @@ pub async fn event_loop(
                 .await;
             }
 
-            Job::Optimization => {
-                start_optimization(
-                    new_job.job_id(),
-                    Arc::clone(&settings),
-                    job_store.clone(),
-                    Arc::clone(&model),
-                )
-                .await;
+            Job::Optimization(named_model) => {
+                let model = match named_model {
+                    Some(named_model) => Arc::new(Mutex::new(*named_model)),
+                    None => Arc::clone(&model),
+                };
+                start_optimization(job_id, Arc::clone(&settings), job_store.clone(), model)
+                    .await;
             }
 
             Job::WeatherForecast => {
@@ -154,7 +165,7 @@ pub async fn event_loop(
                 if !has_weather_forecast {
                     let _ = job_store
                         .set_job_status(
-                            new_job.job_id(),
+                            job_id,
                             Arc::new(JobStatus::Finished(
                                 JobOutcome::WeatherForecast(
                                     WeatherForecastOutcome::new(vec![], vec![]),
@@ -166,7 +177,7 @@ pub async fn event_loop(
                 }
 
                 start_weather_forecast_fetch(
-                    new_job.job_id(),
+                    job_id,
                     Arc::clone(&settings),
                     job_store.clone(),
                     model.lock().await.time_line.clone(),
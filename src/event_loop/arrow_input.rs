@@ -33,7 +33,7 @@ pub fn create_and_serialize_record_batches(
 }
 
 // Function to create RecordBatches (implement your Arrow conversion functions)
-fn create_record_batches(
+pub fn create_record_batches(
     input_data: &InputData,
 ) -> Result<Vec<(String, RecordBatch)>, Box<dyn std::error::Error + Send + Sync>> {
     let mut batches = Vec::new();
@@ -46,7 +46,7 @@ pub async fn start(
                 .set_job_status(
                     job_id,
                     Arc::new(JobStatus::Finished(JobOutcome::ElectricityPrice(
-                        forecast_to_outcome(forecast),
+                        forecast_to_outcome(forecast).with_time_zone(time_line_settings.tz()),
                     ))),
                 )
                 .await
@@ -71,7 +71,7 @@ pub async fn start(
     .await
     {
         Ok(prices) => {
-            let outcome = prices_to_outcome(prices);
+            let outcome = prices_to_outcome(prices).with_time_zone(time_line_settings.tz());
             let _ = job_store
                 .set_job_status(
                     job_id,
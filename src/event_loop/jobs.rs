@@ -1,10 +1,15 @@
 use super::optimization_job::ControlSignal;
+use crate::model::Model;
+use crate::time_line_settings::local_time_line;
 use crate::TimeLine;
+use chrono::{DateTime, FixedOffset};
+use chrono_tz::Tz;
 use juniper::{GraphQLObject, GraphQLUnion};
 
 pub enum Job {
     ElectricityPrice,
-    Optimization,
+    /// Optimizes given model or the active model if none is given.
+    Optimization(Option<Box<Model>>),
     WeatherForecast,
 }
 
@@ -23,6 +28,9 @@ impl NewJob {
     pub fn job(&self) -> &Job {
         &self.job
     }
+    pub fn into_job(self) -> Job {
+        self.job
+    }
 }
 
 pub enum JobStatus {
@@ -66,38 +74,65 @@ pub enum JobOutcome {
 #[derive(Clone, GraphQLObject)]
 pub struct ElectricityPriceOutcome {
     time: TimeLine,
+    #[graphql(description = "Time stamps in the model's time zone.")]
+    local_time: Vec<DateTime<FixedOffset>>,
     price: Vec<f64>,
 }
 
 impl ElectricityPriceOutcome {
     pub fn new(time: TimeLine, price: Vec<f64>) -> Self {
-        ElectricityPriceOutcome { time, price }
+        ElectricityPriceOutcome {
+            local_time: local_time_line(&time, Tz::UTC),
+            time,
+            price,
+        }
+    }
+    pub fn with_time_zone(mut self, time_zone: Tz) -> Self {
+        self.local_time = local_time_line(&self.time, time_zone);
+        self
     }
 }
 
 #[derive(Clone, GraphQLObject)]
 pub struct OptimizationOutcome {
     time: TimeLine,
+    #[graphql(description = "Time stamps in the model's time zone.")]
+    local_time: Vec<DateTime<FixedOffset>>,
     control_signals: Vec<ControlSignal>,
 }
 
 impl OptimizationOutcome {
     pub fn new(time: TimeLine, control_signals: Vec<ControlSignal>) -> Self {
         OptimizationOutcome {
+            local_time: local_time_line(&time, Tz::UTC),
             time,
             control_signals,
         }
     }
+    pub fn with_time_zone(mut self, time_zone: Tz) -> Self {
+        self.local_time = local_time_line(&self.time, time_zone);
+        self
+    }
 }
 
 #[derive(Clone, GraphQLObject)]
 pub struct WeatherForecastOutcome {
     time: TimeLine,
+    #[graphql(description = "Time stamps in the model's time zone.")]
+    local_time: Vec<DateTime<FixedOffset>>,
     temperature: Vec<f64>,
 }
 
 impl WeatherForecastOutcome {
     pub fn new(time: TimeLine, temperature: Vec<f64>) -> Self {
-        WeatherForecastOutcome { time, temperature }
+        WeatherForecastOutcome {
+            local_time: local_time_line(&time, Tz::UTC),
+            time,
+            temperature,
+        }
+    }
+    pub fn with_time_zone(mut self, time_zone: Tz) -> Self {
+        self.local_time = local_time_line(&self.time, time_zone);
+        self
     }
 }
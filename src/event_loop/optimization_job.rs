@@ -3,16 +3,19 @@ use super::electricity_price_job_elering;
 use super::electricity_price_job_entsoe;
 use super::job_store::JobStore;
 use super::jobs::{JobOutcome, JobStatus, OptimizationOutcome};
-use super::time_series;
 use super::utilities;
 use super::weather_forecast_job;
-use super::{ElectricityPriceData, OptimizationData, WeatherData};
+use super::{ElectricityPriceData, MarketPrices, OptimizationData, WeatherData};
 use crate::input_data::{Forecastable, InputData, Market, TimeSeries, TimeSeriesData};
 use crate::input_data_base::BaseForecastable;
 use crate::model::Model;
+use crate::resampling::{self, ResamplingMethod};
+use crate::scenarios::ensemble::{self, WeatherEnsemble};
+use crate::scenarios::reduction::{self, ScenarioReduction};
 use crate::scenarios::Scenario;
 use crate::settings::{LocationSettings, Settings};
 use crate::time_line_settings::{TimeLineSettings, compute_timeline_start};
+use crate::units::{self, Unit};
 use crate::{TimeLine, TimeStamp};
 use arrow::array::timezone::Tz;
 use arrow::array::{self, Array};
@@ -60,6 +63,7 @@ pub async fn start(
     let model_snapshot = model.lock().await.clone();
     let optimization_data = OptimizationData::with_input_data(model_snapshot.input_data);
     let start_time = compute_timeline_start(&model_snapshot.time_line);
+    let time_zone = model_snapshot.time_line.tz();
     let mut zmq_port = settings_snapshot.predicer_port;
     if zmq_port == 0 {
         zmq_port = match find_available_port().await {
@@ -80,6 +84,7 @@ pub async fn start(
     let (tx_time_line, rx_time_line) = oneshot::channel::<OptimizationData>();
     let (tx_weather, rx_weather) = oneshot::channel::<OptimizationData>();
     let (tx_elec, rx_elec) = oneshot::channel::<OptimizationData>();
+    let (tx_reduction, rx_reduction) = oneshot::channel::<OptimizationData>();
     let (tx_update, rx_update) = oneshot::channel::<OptimizationData>();
     let (tx_batches, rx_batches) = oneshot::channel::<InputData>();
     let (tx_optimization, rx_optimization) = oneshot::channel::<Vec<(String, Vec<u8>)>>();
@@ -113,19 +118,20 @@ pub async fn start(
     let time_line_settings_clone = model_snapshot.time_line.clone();
     let python_exec_clone = settings_snapshot.python_exec.clone();
     let weather_fetcher_script_clone = settings_snapshot.weather_fetcher_script.clone();
+    let weather_ensemble_clone = model_snapshot.weather_ensemble.clone();
     let fetch_weather_data_handle = tokio::spawn(async move {
         fetch_weather_data_task(
             &location_clone,
             &time_line_settings_clone,
             &python_exec_clone,
             &weather_fetcher_script_clone,
+            weather_ensemble_clone.as_ref(),
             rx_weather,
             tx_elec,
         )
         .await
     });
     let location_clone = location_snapshot.clone();
-    let scenarios_clone = optimization_data.input_data.scenarios.clone();
     let python_exec_clone = settings_snapshot.python_exec.clone();
     let price_fetcher_script_clone = settings_snapshot.price_fetcher_script.clone();
     let api_token = settings_snapshot
@@ -138,12 +144,15 @@ pub async fn start(
         &python_exec_clone,
         &price_fetcher_script_clone,
         &location_clone,
-        &scenarios_clone,
         rx_elec,
-        tx_update,
+        tx_reduction,
     )
     .await
     });
+    let scenario_reduction_clone = model_snapshot.scenario_reduction.clone();
+    let reduce_scenarios_handle = tokio::spawn(async move {
+        reduce_scenarios_task(scenario_reduction_clone, rx_reduction, tx_update).await
+    });
     let update_model_data_handle =
         tokio::spawn(async move { generate_model_task(rx_update, tx_batches).await });
     let data_conversion_handle =
@@ -190,6 +199,7 @@ pub async fn start(
         flatten_handle(update_time_line_handle),
         flatten_handle(fetch_weather_data_handle),
         flatten_handle(fetch_electricity_price_handle),
+        flatten_handle(reduce_scenarios_handle),
         flatten_handle(update_model_data_handle),
         flatten_handle(data_conversion_handle)
     ) {
@@ -224,7 +234,8 @@ pub async fn start(
                         }
                     };
                 
-                let result_data = OptimizationOutcome::new(time_stamps, control_data);
+                let result_data = OptimizationOutcome::new(time_stamps, control_data)
+                    .with_time_zone(time_zone);
                 job_store
                     .set_job_status(
                         job_id,
@@ -382,11 +393,7 @@ async fn update_time_line_task(
     tx: oneshot::Sender<OptimizationData>,
 ) -> Result<(), String> {
     if let Ok(mut optimization_data) = rx.await {
-        optimization_data.time_data = Some(time_series::make_time_data(
-            start_time,
-            time_line.step().to_time_delta(),
-            time_line.duration().to_time_delta(),
-        ));
+        optimization_data.time_data = Some(time_line.make_time_line(start_time));
         if tx.send(optimization_data).is_err() {
             return Err("update_time_line_task: failed to send output data".to_string());
         }
@@ -571,6 +578,127 @@ fn receive_single_predicer_result(socket: &Socket) -> Result<RecordBatch, Box<dy
     }
     Ok(batches.pop().expect("batches should have an element"))
 }
+async fn reduce_scenarios_task(
+    scenario_reduction: Option<ScenarioReduction>,
+    rx: oneshot::Receiver<OptimizationData>,
+    tx: oneshot::Sender<OptimizationData>,
+) -> Result<(), String> {
+    if let Ok(mut optimization_data) = rx.await {
+        generate_market_prices(&mut optimization_data)
+            .map_err(|e| format!("reduce_scenarios_task: {}", e))?;
+        if let Some(scenario_reduction) = scenario_reduction {
+            reduce_scenarios(&mut optimization_data, scenario_reduction.scenario_count())
+                .map_err(|e| format!("reduce_scenarios_task: {}", e))?;
+        }
+        tx.send(optimization_data)
+            .map_err(|_| "reduce_scenarios_task: failed to send optimization data".to_string())
+    } else {
+        Err("reduce_scenarios_task: input channel closed".to_string())
+    }
+}
+
+/// Generates the price scenarios of markets that use the price forecast
+/// so that scenario reduction sees the same prices as the optimization.
+fn generate_market_prices(optimization_data: &mut OptimizationData) -> Result<(), String> {
+    let price_data = match optimization_data.elec_price_data {
+        Some(ref mut price_data) => price_data,
+        None => return Ok(()),
+    };
+    let base_price = match price_data.price_data {
+        Some(ref base_price) => base_price,
+        None => return Ok(()),
+    };
+    for market in &optimization_data.input_data.markets {
+        let forecast = market.price.iter().find_map(|value| match value.value {
+            BaseForecastable::Forecast(ref forecast) => Some(forecast),
+            _ => None,
+        });
+        if let Some(forecast) = forecast {
+            let factor = match (units::forecast_unit(forecast), market.price_unit) {
+                (Some(from), Some(to)) => units::convert(1.0, from, to)?,
+                _ => 1.0,
+            };
+            let price_scenarios = market.price_scenarios.clone().unwrap_or_default();
+            let market_prices = MarketPrices {
+                unit: market.price_unit,
+                prices: price_scenarios.generate(&scale_ts_data(base_price, factor)),
+            };
+            price_data.market_prices.insert(market.name.clone(), market_prices);
+        }
+    }
+    Ok(())
+}
+
+/// Keeps representative scenarios based on weather and price series and drops the data of the others.
+///
+/// Generated market prices are used instead of the price forecast when there are any.
+fn reduce_scenarios(optimization_data: &mut OptimizationData, count: usize) -> Result<(), String> {
+    let scenarios = &optimization_data.input_data.scenarios;
+    let mut series = vec![Vec::new(); scenarios.len()];
+    if let Some(ref weather_data) = optimization_data.weather_data {
+        append_scaled_series(weather_data, scenarios, &mut series)?;
+    }
+    if let Some(ref price_data) = optimization_data.elec_price_data {
+        if price_data.market_prices.is_empty() {
+            if let Some(ref base_price) = price_data.price_data {
+                append_scaled_series(&base_price.ts_data, scenarios, &mut series)?;
+            }
+        }
+        for market_prices in price_data.market_prices.values() {
+            append_scaled_series(&market_prices.prices.price.ts_data, scenarios, &mut series)?;
+        }
+    }
+    let reduced = reduction::reduce(scenarios, &series, count)?;
+    let is_kept = |time_series: &TimeSeries| reduced.iter().any(|s| *s.name() == time_series.scenario);
+    if let Some(ref mut weather_data) = optimization_data.weather_data {
+        weather_data.retain(is_kept);
+    }
+    if let Some(ref mut price_data) = optimization_data.elec_price_data {
+        if let Some(ref mut base_price) = price_data.price_data {
+            base_price.ts_data.retain(is_kept);
+        }
+        for market_prices in price_data.market_prices.values_mut() {
+            let prices = &mut market_prices.prices;
+            for ts_data in [&mut prices.price, &mut prices.up_price, &mut prices.down_price] {
+                ts_data.ts_data.retain(is_kept);
+            }
+        }
+    }
+    for history in optimization_data.input_data.node_histories.iter_mut() {
+        history
+            .steps
+            .retain(|step| reduced.iter().any(|s| *s.name() == step.scenario));
+    }
+    optimization_data.input_data.scenarios = reduced;
+    Ok(())
+}
+
+/// Appends each scenario's values divided by the standard deviation of all values
+/// so that temperatures and prices carry equal weight.
+fn append_scaled_series(
+    time_series: &[TimeSeries],
+    scenarios: &[Scenario],
+    series: &mut [Vec<f64>],
+) -> Result<(), String> {
+    let mut values = Vec::with_capacity(scenarios.len());
+    for scenario in scenarios {
+        let scenario_series = time_series
+            .iter()
+            .find(|ts| ts.scenario == *scenario.name())
+            .ok_or_else(|| format!("no series for scenario {}", scenario.name()))?;
+        values.push(scenario_series.series.values().copied().collect::<Vec<f64>>());
+    }
+    let all_values: Vec<f64> = values.iter().flatten().copied().collect();
+    let mean = all_values.iter().sum::<f64>() / all_values.len() as f64;
+    let variance = all_values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>()
+        / all_values.len() as f64;
+    let scale = if variance > 0.0 { 1.0 / variance.sqrt() } else { 1.0 };
+    for (scenario_series, scenario_values) in series.iter_mut().zip(values) {
+        scenario_series.extend(scenario_values.iter().map(|v| v * scale));
+    }
+    Ok(())
+}
+
 async fn generate_model_task(
     rx: oneshot::Receiver<OptimizationData>,
     tx: oneshot::Sender<InputData>,
@@ -580,7 +708,10 @@ async fn generate_model_task(
             .time_data
             .as_ref()
             .ok_or("generate_model_task: didn't receive time data".to_string())?;
-        let mut input_data = optimization_data.input_data.expand_to_time_series(time_line);
+        let mut input_data = optimization_data
+            .input_data
+            .expand_to_time_series(time_line)
+            .map_err(|error| format!("generate_model_task: failed to expand model: {}", error))?;
         input_data.infer_feature_flags();
         
         if let Some(weather_data) = optimization_data.weather_data.take() {
@@ -595,7 +726,11 @@ async fn generate_model_task(
         }
         
         if let Some(electricity_price_data) = optimization_data.elec_price_data.take() {
-            if let Err(e) = update_npe_market_prices(&mut input_data.markets, &electricity_price_data) {
+            if let Err(e) = update_npe_market_prices(
+                &mut input_data.markets,
+                &electricity_price_data,
+                &input_data.temporals.t,
+            ) {
                 return Err(format!(
                     "update_model_data_task: failed to update NPE market prices: {}",
                     e
@@ -634,24 +769,33 @@ fn scale_ts_data(src: &TimeSeriesData, factor: f64) -> TimeSeriesData {
 fn update_npe_market_prices(
     markets: &mut IndexMap<String, Market>,
     electricity_price_data: &ElectricityPriceData,
+    time_line: &TimeLine,
 ) -> Result<(), String> {
-    // 1) We must have a base price series to work with.
-    let base_price = electricity_price_data
-        .price_data
-        .as_ref()
-        .ok_or("ElectricityPriceData::price_data is None")?;
-
-    // 2) Prepare the three flavours once so we can reuse the clones.
-    let price_ts      = base_price.clone();
-    let up_price_ts   = scale_ts_data(base_price, 1.10);
-    let down_price_ts = scale_ts_data(base_price, 0.90);
-
-    // 3) Update every market that still carries a Forecast.
+    // Update every market that still carries a Forecast with its generated price scenarios,
+    // mapping them onto the time line and converting them to the market's unit
+    // which may have changed when the model's units were harmonized.
     for market in markets.values_mut() {
-        if matches!(market.price, Forecastable::Forecast(_)) {
-            market.price      = Forecastable::TimeSeriesData(price_ts.clone());
-            market.up_price   = Forecastable::TimeSeriesData(up_price_ts.clone());
-            market.down_price = Forecastable::TimeSeriesData(down_price_ts.clone());
+        if let Forecastable::Forecast(ref forecast) = market.price {
+            let market_prices = electricity_price_data
+                .market_prices
+                .get(&market.name)
+                .ok_or_else(|| format!("no generated prices for market {}", market.name))?;
+            let factor = match (market_prices.unit, market.price_unit) {
+                (Some(from), Some(to)) => units::convert(1.0, from, to)?,
+                _ => 1.0,
+            };
+            let method = forecast.resampling();
+            let fit = |ts_data: &TimeSeriesData| -> Result<TimeSeriesData, String> {
+                let resampled = resample_time_series(&ts_data.ts_data, time_line, method)
+                    .map_err(|e| format!("{} market prices: {}", market.name, e))?;
+                Ok(scale_ts_data(&resampled.into(), factor))
+            };
+            let prices = &market_prices.prices;
+            let (price, up_price, down_price) =
+                (fit(&prices.price)?, fit(&prices.up_price)?, fit(&prices.down_price)?);
+            market.price      = Forecastable::TimeSeriesData(price);
+            market.up_price   = Forecastable::TimeSeriesData(up_price);
+            market.down_price = Forecastable::TimeSeriesData(down_price);
         }
     }
 
@@ -662,14 +806,12 @@ fn update_outside_node(
     input_data: &mut InputData,
     weather_data: WeatherData,
 ) -> Result<(), String> {
+    let time_line = &input_data.temporals.t;
     for (node_name, node) in &mut input_data.nodes {
-        if let Forecastable::Forecast(f) = &node.inflow {
-            if f.name() != "FMI" {
-                continue;
-            }
-        } else {
-            continue;
-        }
+        let (forecast_unit, resampling) = match &node.inflow {
+            Forecastable::Forecast(f) if f.name() == "FMI" => (units::forecast_unit(f), f.resampling()),
+            _ => continue,
+        };
 
         if !node.is_inflow {
             return Err(format!("{} node is not marked for inflow", node_name));
@@ -690,7 +832,13 @@ fn update_outside_node(
             ));
         }
 
-        let initial_temperature = weather_data
+        let weather_on_time_line = resample_time_series(&weather_data, time_line, resampling)
+            .map_err(|e| format!("{} node weather: {}", node_name, e))?;
+        let temperatures = match (forecast_unit, state.unit) {
+            (Some(from), Some(to)) => convert_time_series(&weather_on_time_line, from, to)?,
+            _ => weather_on_time_line,
+        };
+        let initial_temperature = temperatures
             .first()
             .and_then(|ts| ts.series.values().next())
             .ok_or_else(|| "weather data should have at least one point".to_string())?;
@@ -705,7 +853,7 @@ fn update_outside_node(
         state.initial_state = *initial_temperature;
 
         node.inflow = Forecastable::TimeSeriesData(
-            weather_data
+            temperatures
                 .iter()
                 .map(|d| time_series_diffs(*initial_temperature, d))
                 .collect::<Vec<TimeSeries>>()
@@ -716,6 +864,43 @@ fn update_outside_node(
     Ok(())
 }
 
+fn resample_time_series(
+    time_series: &[TimeSeries],
+    time_line: &TimeLine,
+    method: ResamplingMethod,
+) -> Result<Vec<TimeSeries>, String> {
+    time_series
+        .iter()
+        .map(|ts| {
+            Ok(TimeSeries {
+                scenario: ts.scenario.clone(),
+                series: resampling::resample(&ts.series, time_line, method)?,
+            })
+        })
+        .collect()
+}
+
+fn convert_time_series(
+    time_series: &[TimeSeries],
+    from: Unit,
+    to: Unit,
+) -> Result<Vec<TimeSeries>, String> {
+    time_series
+        .iter()
+        .map(|ts| {
+            let series = ts
+                .series
+                .iter()
+                .map(|(time_stamp, value)| Ok((*time_stamp, units::convert(*value, from, to)?)))
+                .collect::<Result<_, String>>()?;
+            Ok(TimeSeries {
+                scenario: ts.scenario.clone(),
+                series,
+            })
+        })
+        .collect()
+}
+
 fn time_series_diffs(initial_value: f64, time_series: &TimeSeries) -> TimeSeries {
     let diff_values = diffs(initial_value, &time_series.series);
     TimeSeries {
@@ -772,6 +957,7 @@ pub async fn fetch_weather_data_task(
     time_line_settings: &TimeLineSettings,
     python_exec: &String,
     weather_fetcher_script: &String,
+    weather_ensemble: Option<&WeatherEnsemble>,
     rx: oneshot::Receiver<OptimizationData>,
     tx_elec: oneshot::Sender<OptimizationData>,
 ) -> Result<(), String> {
@@ -786,6 +972,31 @@ pub async fn fetch_weather_data_task(
                 .ok_or("fetch_weather_data_task: did not receive time data".to_string())?;
             let start_time = time_line.first().ok_or("empty time line".to_string())?;
             let end_time = time_line.last().ok_or("empty time line".to_string())?;
+            if let Some(weather_ensemble) = weather_ensemble {
+                let members = weather_forecast_job::fetch_weather_ensemble(
+                    &location.place,
+                    start_time,
+                    end_time,
+                    &time_line_settings.step().to_time_delta(),
+                    python_exec,
+                    weather_fetcher_script,
+                )
+                .map_err(|e| format!("fetch_weather_data_task: failed to fetch weather ensemble: {}", e))?;
+                let (scenarios, weather_data) =
+                    ensemble_weather_data(&members, weather_ensemble)?;
+                optimization_data.input_data.node_histories = optimization_data
+                    .input_data
+                    .node_histories
+                    .iter()
+                    .map(|history| history.map_to_scenarios(&scenarios))
+                    .collect::<Result<_, _>>()
+                    .map_err(|e| format!("fetch_weather_data_task: {}", e))?;
+                optimization_data.input_data.scenarios = scenarios;
+                optimization_data.weather_data = Some(weather_data);
+                return tx_elec.send(optimization_data).map_err(|_| {
+                    "fetch_weather_data_task: failed to forward optimization data".to_string()
+                });
+            }
             match weather_forecast_job::fetch_weather_data(
                 &location.place,
                 &start_time,
@@ -795,11 +1006,9 @@ pub async fn fetch_weather_data_task(
                 weather_fetcher_script,
             ) {
                 Ok(weather_values) => {
-                    utilities::check_stamps_match(&weather_values, &time_line, "weather forecast")?;
-                    let values = time_series::extract_values_from_pairs_checked(&weather_values, &time_line)
-                        .map_err(|e| format!("fetch_weather_data: {}", e))?;
+                    let (stamps, values): (TimeLine, Vec<f64>) = weather_values.into_iter().unzip();
                     optimization_data.weather_data = Some(update_weather_data(
-                        time_line,
+                        &stamps,
                         &values,
                         &optimization_data.input_data.scenarios,
                     ));
@@ -820,6 +1029,34 @@ pub async fn fetch_weather_data_task(
     }
 }
 
+/// Replaces scenarios by ones generated from ensemble members and pairs each with its temperatures.
+///
+/// Members keep their own time stamps which are mapped onto the time line when the model is generated.
+fn ensemble_weather_data(
+    members: &[Vec<(TimeStamp, f64)>],
+    weather_ensemble: &WeatherEnsemble,
+) -> Result<(Vec<Scenario>, WeatherData), String> {
+    let time_stamps: TimeLine = match members.first() {
+        Some(member) => member.iter().map(|(time_stamp, _)| *time_stamp).collect(),
+        None => return Err("weather ensemble has no members".into()),
+    };
+    let mut member_values = Vec::with_capacity(members.len());
+    for member in members {
+        utilities::check_stamps_match(member, &time_stamps, "weather ensemble member")?;
+        member_values.push(member.iter().map(|(_, value)| *value).collect());
+    }
+    let mut scenarios = Vec::new();
+    let mut weather_data = Vec::new();
+    for (scenario, values) in ensemble::make_scenarios(&member_values, weather_ensemble)? {
+        weather_data.push(TimeSeries {
+            scenario: scenario.name().clone(),
+            series: pair_timeseries_with_values(&time_stamps, &values),
+        });
+        scenarios.push(scenario);
+    }
+    Ok((scenarios, weather_data))
+}
+
 fn create_and_update_elec_price_data(
     optimization_data: &mut OptimizationData,
     price_series: &BTreeMap<TimeStamp, f64>,
@@ -833,6 +1070,7 @@ fn create_and_update_elec_price_data(
         ));
         let electricity_price_data = ElectricityPriceData {
             price_data: original_ts_data,
+            ..ElectricityPriceData::default()
         };
         optimization_data.elec_price_data = Some(electricity_price_data);
     } else {
@@ -870,7 +1108,6 @@ async fn fetch_electricity_price_task(
     python_exec: &str,
     price_fetcher_script: &str,
     location: &LocationSettings,
-    scenarios: &Vec<Scenario>,
     rx: oneshot::Receiver<OptimizationData>,
     tx: oneshot::Sender<OptimizationData>,
 ) -> Result<(), String> {
@@ -923,14 +1160,14 @@ async fn fetch_electricity_price_task(
             .unwrap();
 
         let mut insert_prices = |prices: Vec<(TimeStamp, f64)>| -> Result<(), String> {
-            let fitted = fit_prices_to_time_line(&prices, &time_line_owned)?;
-            utilities::check_stamps_match(&fitted, &time_line_owned, "electricity prices")?;
-
+            // Prices keep their own time stamps until each market maps them onto the time line.
             let mut series_map = BTreeMap::new();
-            for (ts, val) in fitted {
+            for (ts, val) in prices {
                 series_map.insert(ts, val);
             }
-            create_and_update_elec_price_data(&mut optimization_data, &series_map, scenarios)
+            // Weather ensembles may have replaced the model's scenarios.
+            let scenarios = optimization_data.input_data.scenarios.clone();
+            create_and_update_elec_price_data(&mut optimization_data, &series_map, &scenarios)
         };
 
         if has_elering {
@@ -974,41 +1211,6 @@ async fn fetch_electricity_price_task(
 }
 
 
-fn fit_prices_to_time_line(
-    prices: &Vec<(TimeStamp, f64)>,
-    time_line: &TimeLine,
-) -> Result<Vec<(TimeStamp, f64)>, String> {
-    let mut fitted = Vec::with_capacity(time_line.len());
-    if prices.is_empty() {
-        return Err("electricity prices should have at least one time stamp".into());
-    }
-    eprintln!(
-    "DEBUG  first price stamp = {}, first time-line stamp = {}",
-    prices[0].0.format("%Y-%m-%dT%H:%M:%S"),
-    time_line[0].format("%Y-%m-%dT%H:%M:%S"),
-    );
-    if prices[0].0 != time_line[0] {
-        return Err("first electricity price time stamp mismatches with time line start".into());
-    }
-    let mut price_iter = prices.iter();
-    let mut current_price = price_iter.next().unwrap().1;
-    let (mut next_time_stamp, mut next_price) = price_iter.next().unwrap();
-    for stamp in time_line {
-        if *stamp == next_time_stamp {
-            current_price = next_price;
-            (next_time_stamp, next_price) = match price_iter.next() {
-                Some((stamp, price)) => (*stamp, *price),
-                None => (
-                    *time_line.last().unwrap() + TimeDelta::hours(1),
-                    current_price,
-                ),
-            };
-        }
-        fitted.push((stamp.clone(), current_price));
-    }
-    Ok(fitted)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1254,45 +1456,258 @@ mod tests {
         }
     }
     
-    mod fit_prices_to_time_line {
+
+    mod convert_time_series {
         use chrono::TimeZone;
 
         use super::*;
         #[test]
-        fn test_last_price_is_extended_until_end_of_time_line() {
-            let prices = [
-                (Utc.with_ymd_and_hms(2024, 12, 4, 11, 0, 0), 2.2),
-                (Utc.with_ymd_and_hms(2024, 12, 4, 12, 0, 0), 2.3),
-            ]
-            .iter()
-            .map(|(s, p)| (s.unwrap(), *p))
-            .collect();
-            let time_line = [
-                Utc.with_ymd_and_hms(2024, 12, 4, 11, 0, 0),
-                Utc.with_ymd_and_hms(2024, 12, 4, 11, 30, 0),
-                Utc.with_ymd_and_hms(2024, 12, 4, 12, 0, 0),
-                Utc.with_ymd_and_hms(2024, 12, 4, 12, 30, 0),
-                Utc.with_ymd_and_hms(2024, 12, 4, 13, 0, 0),
-                Utc.with_ymd_and_hms(2024, 12, 4, 13, 30, 0),
-            ]
-            .iter()
-            .map(|s| s.unwrap())
-            .collect();
-            let fitted_prices =
-                fit_prices_to_time_line(&prices, &time_line).expect("fitting should not fail");
-            assert_eq!(fitted_prices.len(), time_line.len());
-            let expected_prices: Vec<(TimeStamp, f64)> = [
-                (Utc.with_ymd_and_hms(2024, 12, 4, 11, 0, 0), 2.2),
-                (Utc.with_ymd_and_hms(2024, 12, 4, 11, 30, 0), 2.2),
-                (Utc.with_ymd_and_hms(2024, 12, 4, 12, 0, 0), 2.3),
-                (Utc.with_ymd_and_hms(2024, 12, 4, 12, 30, 0), 2.3),
-                (Utc.with_ymd_and_hms(2024, 12, 4, 13, 0, 0), 2.3),
-                (Utc.with_ymd_and_hms(2024, 12, 4, 13, 30, 0), 2.3),
-            ]
-            .iter()
-            .map(|(s, p)| (s.unwrap(), *p))
-            .collect();
-            assert_eq!(fitted_prices, expected_prices);
+        fn kelvins_are_converted_to_celsius() {
+            let time_stamp = Utc.with_ymd_and_hms(2024, 12, 4, 11, 0, 0).unwrap();
+            let weather_data = vec![TimeSeries {
+                scenario: "s1".to_string(),
+                series: BTreeMap::from([(time_stamp, 270.15)]),
+            }];
+            let temperatures =
+                convert_time_series(&weather_data, Unit::Kelvin, Unit::Celsius)
+                    .expect("conversion should not fail");
+            assert_eq!(temperatures[0].scenario, "s1");
+            assert!((temperatures[0].series[&time_stamp] + 3.0).abs() < 1e-9);
+            assert!(convert_time_series(&weather_data, Unit::Kelvin, Unit::Mwh).is_err());
+        }
+    }
+
+    mod reduce_scenarios {
+        use chrono::TimeZone;
+
+        use super::*;
+        use crate::input_data::Forecast;
+        use crate::input_data_base::{
+            BaseMarket, BaseNodeHistory, ForecastValue, MarketType, Series,
+        };
+        use crate::scenarios::prices::{OffsetType, PriceOffset, PriceScenarios};
+        use crate::time_line_settings::Duration;
+        #[test]
+        fn data_of_dropped_scenarios_is_removed() {
+            let time_stamp = Utc.with_ymd_and_hms(2024, 12, 4, 11, 0, 0).unwrap();
+            let make_series = |scenario: &str, value: f64| TimeSeries {
+                scenario: scenario.to_string(),
+                series: BTreeMap::from([(time_stamp, value)]),
+            };
+            let mut optimization_data = OptimizationData::with_input_data(Default::default());
+            optimization_data.input_data.scenarios = vec![
+                Scenario::new("s1", 1.0).unwrap(),
+                Scenario::new("s2", 1.0).unwrap(),
+                Scenario::new("s3", 2.0).unwrap(),
+            ];
+            optimization_data.weather_data = Some(vec![
+                make_series("s1", 270.0),
+                make_series("s2", 270.5),
+                make_series("s3", 280.0),
+            ]);
+            optimization_data.elec_price_data = Some(ElectricityPriceData {
+                price_data: Some(TimeSeriesData {
+                    ts_data: vec![
+                        make_series("s1", 50.0),
+                        make_series("s2", 50.0),
+                        make_series("s3", 50.0),
+                    ],
+                }),
+                ..ElectricityPriceData::default()
+            });
+            let mut history = BaseNodeHistory::new("heat".into());
+            for scenario in ["s1", "s2", "s3"] {
+                history.steps.push(Series {
+                    scenario: scenario.to_string(),
+                    durations: vec![Duration::try_new(1, 0, 0).unwrap()],
+                    values: vec![1.0],
+                });
+            }
+            optimization_data.input_data.node_histories.push(history);
+            reduce_scenarios(&mut optimization_data, 2).expect("reduction should not fail");
+            let scenarios = &optimization_data.input_data.scenarios;
+            assert_eq!(scenarios.len(), 2);
+            assert_eq!(scenarios[0].weight(), 2.0);
+            assert_eq!(scenarios[1].name(), "s3");
+            let kept: Vec<String> = optimization_data
+                .weather_data
+                .unwrap()
+                .iter()
+                .map(|ts| ts.scenario.clone())
+                .collect();
+            assert_eq!(kept, vec![scenarios[0].name().clone(), "s3".to_string()]);
+            assert_eq!(
+                optimization_data.elec_price_data.unwrap().price_data.unwrap().ts_data.len(),
+                2
+            );
+            let history_scenarios: Vec<&String> = optimization_data.input_data.node_histories[0]
+                .steps
+                .iter()
+                .map(|step| &step.scenario)
+                .collect();
+            assert_eq!(history_scenarios, vec![scenarios[0].name(), "s3"]);
+        }
+
+        #[test]
+        fn generated_market_prices_drive_reduction() {
+            let time_stamp = Utc.with_ymd_and_hms(2024, 12, 4, 11, 0, 0).unwrap();
+            let scenarios = vec![
+                Scenario::new("s1", 1.0).unwrap(),
+                Scenario::new("s2", 1.0).unwrap(),
+                Scenario::new("s3", 1.0).unwrap(),
+            ];
+            let market = BaseMarket {
+                name: "npe".into(),
+                m_type: MarketType::Energy,
+                node: "elc".into(),
+                process_group: "p1".into(),
+                direction: None,
+                realisation: Vec::new(),
+                reserve_type: None,
+                is_bid: false,
+                is_limited: false,
+                min_bid: 0.0,
+                max_bid: 0.0,
+                fee: 0.0,
+                price: vec![ForecastValue {
+                    scenario: None,
+                    value: BaseForecastable::Forecast(Forecast::new(
+                        "NORDPOOL".into(),
+                        "electricity".into(),
+                        None,
+                    )),
+                }],
+                up_price: Vec::new(),
+                down_price: Vec::new(),
+                reserve_activation_price: Vec::new(),
+                fixed: Vec::new(),
+                price_unit: None,
+                price_scenarios: Some(PriceScenarios {
+                    offsets: vec![PriceOffset {
+                        scenario: "s1".into(),
+                        offset_type: OffsetType::Additive,
+                        value: 30.0,
+                    }],
+                    ..PriceScenarios::default()
+                }),
+            };
+            let mut optimization_data = OptimizationData::with_input_data(Default::default());
+            optimization_data.input_data.scenarios = scenarios.clone();
+            optimization_data.input_data.markets.push(market);
+            optimization_data.elec_price_data = Some(ElectricityPriceData {
+                price_data: Some(create_modified_price_series_data(
+                    &BTreeMap::from([(time_stamp, 50.0)]),
+                    1.0,
+                    &scenarios,
+                )),
+                ..ElectricityPriceData::default()
+            });
+            generate_market_prices(&mut optimization_data).expect("generation should not fail");
+            reduce_scenarios(&mut optimization_data, 2).expect("reduction should not fail");
+            let scenarios = &optimization_data.input_data.scenarios;
+            assert_eq!(scenarios[0].name(), "s1");
+            assert_eq!(scenarios[0].weight(), 1.0);
+            assert_eq!(scenarios[1].weight(), 2.0);
+            let market_prices = &optimization_data.elec_price_data.unwrap().market_prices["npe"];
+            let prices: Vec<f64> = market_prices
+                .prices
+                .price
+                .ts_data
+                .iter()
+                .map(|ts| ts.series[&time_stamp])
+                .collect();
+            assert_eq!(prices, vec![80.0, 50.0]);
+            assert_eq!(market_prices.prices.up_price.ts_data.len(), 2);
+        }
+    }
+
+    mod resample_time_series {
+        use chrono::TimeZone;
+
+        use super::*;
+        fn hourly_series(count: u32) -> Vec<TimeSeries> {
+            vec![TimeSeries {
+                scenario: "s1".to_string(),
+                series: (0..count)
+                    .map(|hour| {
+                        (
+                            Utc.with_ymd_and_hms(2024, 12, 4, 11 + hour, 0, 0).unwrap(),
+                            hour as f64,
+                        )
+                    })
+                    .collect(),
+            }]
+        }
+        #[test]
+        fn longer_steps_of_variable_step_time_line_are_averaged() {
+            let start = Utc.with_ymd_and_hms(2024, 12, 4, 11, 0, 0).unwrap();
+            let time_line = vec![
+                start,
+                start + TimeDelta::hours(1),
+                start + TimeDelta::hours(3),
+            ];
+            let resampled = resample_time_series(
+                &hourly_series(5),
+                &time_line,
+                ResamplingMethod::MeanAggregation,
+            )
+            .expect("resampling should succeed");
+            assert_eq!(
+                resampled[0].series.values().copied().collect::<Vec<f64>>(),
+                vec![0.0, 1.5, 3.5]
+            );
+        }
+        #[test]
+        fn steps_longer_than_price_interval_skip_held_prices() {
+            let start = Utc.with_ymd_and_hms(2024, 12, 4, 11, 0, 0).unwrap();
+            let time_line = vec![
+                start,
+                start + TimeDelta::hours(2),
+                start + TimeDelta::hours(3),
+            ];
+            let resampled =
+                resample_time_series(&hourly_series(4), &time_line, ResamplingMethod::StepHold)
+                    .expect("resampling should succeed");
+            assert_eq!(
+                resampled[0].series.values().copied().collect::<Vec<f64>>(),
+                vec![0.0, 2.0, 3.0]
+            );
+        }
+    }
+
+    mod ensemble_weather_data {
+        use chrono::TimeZone;
+
+        use super::*;
+        #[test]
+        fn members_keep_their_time_stamps_and_are_resampled_later() {
+            let stamps: Vec<TimeStamp> = (0..3)
+                .map(|hour| Utc.with_ymd_and_hms(2024, 12, 4, 11 + hour, 0, 0).unwrap())
+                .collect();
+            let members: Vec<Vec<(TimeStamp, f64)>> = [270.0, 272.0]
+                .iter()
+                .map(|base| stamps.iter().map(|s| (*s, *base)).collect())
+                .collect();
+            let (scenarios, weather_data) =
+                ensemble_weather_data(&members, &WeatherEnsemble::default())
+                    .expect("ensemble should convert");
+            assert_eq!(scenarios.len(), 2);
+            assert_eq!(weather_data[1].series.keys().copied().collect::<Vec<_>>(), stamps);
+            let time_line = vec![
+                stamps[0] + TimeDelta::minutes(30),
+                stamps[1] + TimeDelta::minutes(30),
+            ];
+            let resampled =
+                resample_time_series(&weather_data, &time_line, ResamplingMethod::StepHold)
+                    .expect("resampling should succeed");
+            assert_eq!(resampled[1].scenario, weather_data[1].scenario);
+            assert_eq!(
+                resampled[1].series.values().copied().collect::<Vec<f64>>(),
+                vec![272.0, 272.0]
+            );
+            let mut shifted = members.clone();
+            shifted[1].pop();
+            assert!(ensemble_weather_data(&shifted, &WeatherEnsemble::default()).is_err());
         }
     }
 }
@@ -1,5 +1,6 @@
 use crate::{TimeLine, TimeStamp};
 use chrono::TimeDelta;
+use std::collections::BTreeMap;
 
 pub fn make_time_data(start_time: TimeStamp, step: TimeDelta, duration: TimeDelta) -> TimeLine {
     let n_steps: i32 = (duration.num_milliseconds() / step.num_milliseconds())
@@ -13,6 +14,55 @@ pub fn make_time_data(start_time: TimeStamp, step: TimeDelta, duration: TimeDelt
     series
 }
 
+/// Returns the shortest step of the time line.
+pub fn finest_step(time_line: &TimeLine) -> Option<TimeDelta> {
+    time_line.windows(2).map(|pair| pair[1] - pair[0]).min()
+}
+
+/// Divides every step of the time line into steps of the shortest step.
+///
+/// Uniform time lines are returned as is.
+pub fn base_time_line(time_line: &TimeLine) -> TimeLine {
+    let step = match finest_step(time_line) {
+        Some(step) if step > TimeDelta::zero() => step,
+        _ => return time_line.clone(),
+    };
+    let mut base = Vec::with_capacity(time_line.len());
+    for pair in time_line.windows(2) {
+        let mut stamp = pair[0];
+        while stamp < pair[1] {
+            base.push(stamp);
+            stamp += step;
+        }
+    }
+    base.extend(time_line.last());
+    base
+}
+
+/// Averages the values within each step of the time line.
+///
+/// The last stamp takes the value at that stamp. Stamps without values are left out.
+pub fn aggregate_to_time_line(
+    series: &BTreeMap<TimeStamp, f64>,
+    time_line: &TimeLine,
+) -> BTreeMap<TimeStamp, f64> {
+    let mut aggregated = BTreeMap::new();
+    for pair in time_line.windows(2) {
+        let (sum, count) = series
+            .range(pair[0]..pair[1])
+            .fold((0.0, 0), |(sum, count), (_, value)| (sum + value, count + 1));
+        if count > 0 {
+            aggregated.insert(pair[0], sum / count as f64);
+        }
+    }
+    if let Some(last) = time_line.last() {
+        if let Some(value) = series.get(last) {
+            aggregated.insert(*last, *value);
+        }
+    }
+    aggregated
+}
+
 pub fn extract_values_from_pairs_checked<T: Clone>(
     pairs: &Vec<(TimeStamp, T)>,
     true_time_data: &TimeLine,
@@ -83,6 +133,36 @@ mod tests {
         assert_eq!(time_data, expected_stamps);
     }
     #[test]
+    fn base_time_line_uses_finest_step() {
+        let start = Utc.with_ymd_and_hms(2024, 11, 6, 8, 0, 0).unwrap();
+        let time_line = vec![
+            start,
+            start + TimeDelta::minutes(30),
+            start + TimeDelta::minutes(90),
+        ];
+        assert_eq!(finest_step(&time_line), Some(TimeDelta::minutes(30)));
+        let base = base_time_line(&time_line);
+        assert_eq!(base, make_time_data(start, TimeDelta::minutes(30), TimeDelta::minutes(90)));
+        let uniform = make_time_data(start, TimeDelta::minutes(15), TimeDelta::hours(1));
+        assert_eq!(base_time_line(&uniform), uniform);
+    }
+    #[test]
+    fn aggregation_averages_over_steps() {
+        let start = Utc.with_ymd_and_hms(2024, 11, 6, 8, 0, 0).unwrap();
+        let base = make_time_data(start, TimeDelta::minutes(30), TimeDelta::hours(2));
+        let series: BTreeMap<TimeStamp, f64> =
+            base.iter().copied().zip([1.0, 2.0, 3.0, 5.0, 7.0]).collect();
+        let time_line = vec![
+            start,
+            start + TimeDelta::minutes(30),
+            start + TimeDelta::hours(2),
+        ];
+        let aggregated = aggregate_to_time_line(&series, &time_line);
+        let values: Vec<f64> = aggregated.values().copied().collect();
+        assert_eq!(values, vec![1.0, 10.0 / 3.0, 7.0]);
+        assert_eq!(aggregate_to_time_line(&series, &base), series);
+    }
+    #[test]
     fn extract_values_with_check_succeeds() {
         let pairs: Vec<(TimeStamp, f64)> = vec![
             (
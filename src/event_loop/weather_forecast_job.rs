@@ -52,7 +52,7 @@ pub async fn start(
                 .set_job_status(
                     job_id,
                     Arc::new(JobStatus::Finished(JobOutcome::WeatherForecast(
-                        forecast_to_outcome(forecast),
+                        forecast_to_outcome(forecast).with_time_zone(time_line_settings.tz()),
                     ))),
                 )
                 .await
@@ -81,9 +81,7 @@ fn forecast_to_outcome(forecast: Vec<(TimeStamp, f64)>) -> WeatherForecastOutcom
     WeatherForecastOutcome::new(time_line, temperatures)
 }
 
-fn parse_weather_fetcher_output(output: &str) -> Result<Vec<(TimeStamp, f64)>, String> {
-    let parsed_json = serde_json::from_str(output)
-        .or_else(|error| Err(format!("failed to parse output: {}", error)))?;
+fn parse_time_series(parsed_json: Value) -> Result<Vec<(TimeStamp, f64)>, String> {
     if let Value::Array(time_series) = parsed_json {
         let mut forecast = Vec::with_capacity(time_series.len());
         for row in time_series {
@@ -121,14 +119,31 @@ fn parse_weather_fetcher_output(output: &str) -> Result<Vec<(TimeStamp, f64)>, S
     }
 }
 
-pub fn fetch_weather_data(
+fn parse_weather_fetcher_output(output: &str) -> Result<Vec<(TimeStamp, f64)>, String> {
+    let parsed_json = serde_json::from_str(output)
+        .or_else(|error| Err(format!("failed to parse output: {}", error)))?;
+    parse_time_series(parsed_json)
+}
+
+fn parse_ensemble_fetcher_output(output: &str) -> Result<Vec<Vec<(TimeStamp, f64)>>, String> {
+    let parsed_json = serde_json::from_str(output)
+        .map_err(|error| format!("failed to parse output: {}", error))?;
+    if let Value::Array(members) = parsed_json {
+        members.into_iter().map(parse_time_series).collect()
+    } else {
+        Err("failed to parse array of ensemble members from output".to_string())
+    }
+}
+
+fn run_weather_fetcher(
     place: &String,
     start_time: &TimeStamp,
     end_time: &TimeStamp,
     step: &TimeDelta,
     python_exec: &String,
     weather_data_script: &String,
-) -> Result<Vec<(TimeStamp, f64)>, String> {
+    members: bool,
+) -> Result<String, String> {
     let format_string = "%Y-%m-%dT%H:%M:%S";
     let mut command = Command::new(python_exec);
     command
@@ -137,6 +152,9 @@ pub fn fetch_weather_data(
         .arg(end_time.format(&format_string).to_string())
         .arg(format!("{}", step.num_minutes()))
         .arg(place);
+    if members {
+        command.arg("--members");
+    }
     let output = match command.output() {
         Ok(bytes) => bytes,
         Err(error) => return Err(format!("Python failed: {}", error)),
@@ -144,13 +162,53 @@ pub fn fetch_weather_data(
     if !output.status.success() {
         return Err("weather fetching returned non-zero exit status".into());
     }
-    let output = match String::from_utf8(output.stdout) {
-        Ok(json_out) => json_out,
-        Err(..) => return Err("non-utf-8 characters in output".to_string()),
-    };
+    match String::from_utf8(output.stdout) {
+        Ok(json_out) => Ok(json_out),
+        Err(..) => Err("non-utf-8 characters in output".to_string()),
+    }
+}
+
+pub fn fetch_weather_data(
+    place: &String,
+    start_time: &TimeStamp,
+    end_time: &TimeStamp,
+    step: &TimeDelta,
+    python_exec: &String,
+    weather_data_script: &String,
+) -> Result<Vec<(TimeStamp, f64)>, String> {
+    let output = run_weather_fetcher(
+        place,
+        start_time,
+        end_time,
+        step,
+        python_exec,
+        weather_data_script,
+        false,
+    )?;
     Ok(parse_weather_fetcher_output(&output)?)
 }
 
+/// Fetches every member of an ensemble temperature forecast.
+pub fn fetch_weather_ensemble(
+    place: &String,
+    start_time: &TimeStamp,
+    end_time: &TimeStamp,
+    step: &TimeDelta,
+    python_exec: &String,
+    weather_data_script: &String,
+) -> Result<Vec<Vec<(TimeStamp, f64)>>, String> {
+    let output = run_weather_fetcher(
+        place,
+        start_time,
+        end_time,
+        step,
+        python_exec,
+        weather_data_script,
+        true,
+    )?;
+    parse_ensemble_fetcher_output(&output)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -207,4 +265,26 @@ mod tests {
             }
         }
     }
+    mod parse_ensemble_fetcher_output {
+        use super::*;
+        use chrono::TimeZone;
+        use chrono::Utc;
+        #[test]
+        fn parses_every_member() {
+            let fetcher_output = r#"
+                [
+                    [["2024-11-08T11:00:00", 279.5], ["2024-11-08T12:00:00", 279.6]],
+                    [["2024-11-08T11:00:00", 280.1], ["2024-11-08T12:00:00", 279.2]]
+                ]"#;
+            let members = parse_ensemble_fetcher_output(fetcher_output)
+                .expect("parsing output should not fail");
+            assert_eq!(members.len(), 2);
+            assert_eq!(
+                members[1][0],
+                (Utc.with_ymd_and_hms(2024, 11, 8, 11, 0, 0).unwrap(), 280.1)
+            );
+            assert_eq!(members[1][1].1, 279.2);
+            assert!(parse_ensemble_fetcher_output(r#"[["2024-11-08T11:00:00", 279.5]]"#).is_err());
+        }
+    }
 }
@@ -1,24 +1,33 @@
+mod batch;
+mod clone;
 mod con_factor_input;
 mod delete;
+mod diff_input;
 mod forecastable;
 mod gen_constraint_input;
 mod group_input;
 mod input_data_setup_input;
 mod job_status;
 mod market_input;
+mod merge;
 mod node_delay_input;
 mod node_diffusion_input;
 mod node_history_input;
 mod node_input;
 mod process_input;
+mod rename;
 mod risk_input;
 mod scenario_input;
 mod state_input;
+mod template_input;
 mod time_line_input;
 mod topology_input;
+mod update;
 mod reserve_type_input;
 mod inflow_block_input;
 
+use batch::{ModelChangesResult, ModelOperation};
+use clone::NodeMappingInput;
 use crate::event_loop;
 use crate::event_loop::job_store::JobStore;
 use crate::event_loop::jobs::{self, Job, JobOutcome, JobStatus, NewJob};
@@ -27,31 +36,43 @@ use crate::input_data_base::{
     BaseConFactor, BaseGenConstraint, BaseInputData, BaseMarket, BaseNode, BaseNodeDiffusion,
     BaseProcess, GroupMember, Members, NodeGroup, ProcessGroup, TypeName, ValueInput
 };
-use crate::model::{self, Model};
+use crate::model::diff::{self, ModelDifference};
+use crate::model::template::{self, TemplateInfo};
+use crate::model::validation::{validate_model, ModelProblem};
+use crate::model::graph::{self, GraphFormat};
+use crate::model::history::{ModelHistory, ModelHistoryInfo};
+use crate::model::summary::{self, ModelSummary};
+use crate::model::library::{self, ModelMetadata};
+use crate::model::{self, Model, ModelFormat};
+use crate::predicer_excel::{self, ImportReport};
 use crate::scenarios::Scenario;
 use crate::settings::{LocationSettings, Settings};
-use gen_constraint_input::NewGenConstraint;
+use diff_input::ModelDifferenceInput;
+use gen_constraint_input::{GenConstraintUpdate, NewGenConstraint};
 use input_data_setup_input::InputDataSetupInput;
 use juniper::{
     graphql_object, Context, EmptySubscription, FieldResult, GraphQLInputObject, GraphQLObject,
     GraphQLUnion, Nullable, RootNode
 };
-use market_input::NewMarket;
+use market_input::{MarketUpdate, NewMarket, PriceScenariosInput};
+use merge::ConflictPolicy;
 use node_delay_input::NewNodeDelay;
 use node_history_input::NewSeries;
-use node_input::NewNode;
-use process_input::NewProcess;
-use risk_input::NewRisk;
-use reserve_type_input::NewReserveType;
-use inflow_block_input::NewInflowBlock;
+use node_input::{NewNode, NodeUpdate};
+use process_input::{NewProcess, ProcessUpdate};
+use risk_input::{NewRisk, RiskUpdate};
+use reserve_type_input::{NewReserveType, ReserveTypeUpdate};
+use inflow_block_input::{InflowBlockUpdate, NewInflowBlock};
 use state_input::{NewState, StateUpdate};
-use node_diffusion_input::NewNodeDiffusion;
-use std::ops::DerefMut;
+use template_input::TemplateParameterInput;
+use node_diffusion_input::{NewNodeDiffusion, NodeDiffusionUpdate};
+use std::ops::{Deref, DerefMut};
+use std::path::Path;
 use std::sync::Arc;
 use time_line_input::TimeLineUpdate;
 use tokio::sync::mpsc;
-use tokio::sync::Mutex;
-use topology_input::NewTopology;
+use tokio::sync::{Mutex, MutexGuard};
+use topology_input::{NewTopology, TopologyUpdate};
 use serde_json;
 
 
@@ -109,6 +130,12 @@ enum SettingsResult {
     Err(ValidationErrors),
 }
 
+#[derive(GraphQLUnion)]
+enum PredicerImportResult {
+    Ok(ImportReport),
+    Err(ValidationErrors),
+}
+
 #[derive(GraphQLObject)]
 struct MaybeError {
     #[graphql(description = "Error message; if null, the operation succeeded.")]
@@ -144,6 +171,7 @@ pub struct HerttaContext {
     settings: Arc<Mutex<Settings>>,
     job_store: JobStore,
     model: Arc<Mutex<Model>>,
+    model_history: Arc<std::sync::Mutex<ModelHistory>>,
     job_sender: mpsc::Sender<NewJob>,
 }
 
@@ -154,12 +182,14 @@ impl HerttaContext {
         settings: Arc<Mutex<Settings>>,
         job_store: JobStore,
         model: Arc<Mutex<Model>>,
+        model_history: Arc<std::sync::Mutex<ModelHistory>>,
         job_sender: mpsc::Sender<NewJob>,
     ) -> Self {
         HerttaContext {
             settings,
             job_store,
             model,
+            model_history,
             job_sender,
         }
     }
@@ -167,6 +197,51 @@ impl HerttaContext {
     pub fn model(&self) -> &Arc<Mutex<Model>> {
         &self.model
     }
+
+    /// Locks the model for an edit that can be undone.
+    async fn edit_model(&self, operation: &'static str) -> ModelEdit<'_> {
+        let model = self.model.lock().await;
+        let before = Some(model.clone());
+        ModelEdit {
+            model,
+            before,
+            operation,
+            history: &self.model_history,
+        }
+    }
+}
+
+/// Model lock that records the model's previous state in the history when released.
+struct ModelEdit<'a> {
+    model: MutexGuard<'a, Model>,
+    before: Option<Model>,
+    operation: &'static str,
+    history: &'a std::sync::Mutex<ModelHistory>,
+}
+
+impl Deref for ModelEdit<'_> {
+    type Target = Model;
+    fn deref(&self) -> &Model {
+        &self.model
+    }
+}
+
+impl DerefMut for ModelEdit<'_> {
+    fn deref_mut(&mut self) -> &mut Model {
+        &mut self.model
+    }
+}
+
+impl Drop for ModelEdit<'_> {
+    fn drop(&mut self) {
+        if let Some(before) = self.before.take() {
+            let mut history = self
+                .history
+                .lock()
+                .expect("model history lock should not be poisoned");
+            history.record(self.operation, before, &self.model);
+        }
+    }
 }
 
 pub struct Query;
@@ -182,6 +257,68 @@ impl Query {
         let model = context.model.lock().await;
         Ok(model.clone())
     }
+    #[graphql(description = "Models stored in the model library.")]
+    async fn list_models() -> FieldResult<Vec<ModelMetadata>> {
+        Ok(library::list_models(&library::make_library_path())?)
+    }
+    #[graphql(description = "The model serialized as JSON, YAML or TOML.")]
+    async fn export_model(format: ModelFormat, context: &HerttaContext) -> FieldResult<String> {
+        let model = context.model.lock().await;
+        Ok(model::model_to_string(&model, format)?)
+    }
+    #[graphql(
+        description = "Render nodes, processes and markets with their connections as a graph. \
+                       If group is given, only the group's members and items directly connected to them are shown."
+    )]
+    async fn model_graph(
+        format: GraphFormat,
+        group: Option<String>,
+        context: &HerttaContext,
+    ) -> FieldResult<String> {
+        let model = context.model.lock().await;
+        Ok(graph::render_graph(
+            &model.input_data,
+            format,
+            group.as_deref(),
+        )?)
+    }
+    #[graphql(
+        description = "Entity counts, capacities, scenarios, forecast bindings and feature flags of the model."
+    )]
+    async fn model_summary(context: &HerttaContext) -> ModelSummary {
+        let model = context.model.lock().await;
+        summary::summarize(&model.input_data)
+    }
+    #[graphql(description = "Model templates and their parameters.")]
+    async fn list_templates() -> FieldResult<Vec<TemplateInfo>> {
+        Ok(template::list_templates(&template::make_template_library_path())?)
+    }
+    #[graphql(description = "Model changes that can be undone or redone.")]
+    async fn model_history(context: &HerttaContext) -> ModelHistoryInfo {
+        context
+            .model_history
+            .lock()
+            .expect("model history lock should not be poisoned")
+            .info()
+    }
+    #[graphql(
+        description = "Compare two models entity by entity. Arguments are names of models in the model library; \
+                       null refers to the active model."
+    )]
+    async fn diff_models(
+        a: Option<String>,
+        b: Option<String>,
+        context: &HerttaContext,
+    ) -> FieldResult<Vec<ModelDifference>> {
+        let old = model_by_name(a, context).await?;
+        let new = model_by_name(b, context).await?;
+        Ok(diff::diff_models(&old, &new)?)
+    }
+    #[graphql(description = "Check the model and return all problems found.")]
+    async fn validate_model(context: &HerttaContext) -> Vec<ModelProblem> {
+        let model = context.model.lock().await;
+        validate_model(&model)
+    }
     async fn gen_constraint(
         name: String,
         context: &HerttaContext,
@@ -364,6 +501,14 @@ impl Query {
     }
 }
 
+/// Returns a model from the model library or a copy of the active model if name is None.
+async fn model_by_name(name: Option<String>, context: &HerttaContext) -> Result<Model, String> {
+    match name {
+        Some(name) => library::load_model(&library::make_library_path(), &name),
+        None => Ok(context.model.lock().await.clone()),
+    }
+}
+
 fn group_members<G: Members + Name, M: Clone + GroupMember + Name + TypeName>(
     groups: &Vec<G>,
     group_name: &str,
@@ -418,11 +563,37 @@ pub struct Mutation;
 #[graphql_object]
 #[graphql(context = HerttaContext)]
 impl Mutation {
-    #[graphql(description = "Start optimization job. Return job ID.")]
-    async fn start_optimization(context: &HerttaContext) -> FieldResult<i32> {
-        let job_id =
-            event_loop::start_job(Job::Optimization, &context.job_store, &context.job_sender)
-                .await?;
+    #[graphql(
+        description = "Start optimization job. Return job ID. If model_name is given, the named model from the model library is optimized instead of the active model. If refuse_invalid_model is true, the job is not started when the model has problems."
+    )]
+    async fn start_optimization(
+        refuse_invalid_model: Option<bool>,
+        model_name: Option<String>,
+        context: &HerttaContext,
+    ) -> FieldResult<i32> {
+        let named_model = match model_name {
+            Some(ref name) => Some(library::load_model(&library::make_library_path(), name)?),
+            None => None,
+        };
+        if refuse_invalid_model.unwrap_or(false) {
+            let problems = match named_model {
+                Some(ref model) => validate_model(model),
+                None => validate_model(&*context.model.lock().await),
+            };
+            if !problems.is_empty() {
+                let messages: Vec<String> = problems
+                    .iter()
+                    .map(|p| format!("{}: {}", p.path, p.message))
+                    .collect();
+                return Err(format!("model is invalid: {}", messages.join("; ")).into());
+            }
+        }
+        let job_id = event_loop::start_job(
+            Job::Optimization(named_model.map(Box::new)),
+            &context.job_store,
+            &context.job_sender,
+        )
+        .await?;
         Ok(job_id)
     }
     #[graphql(description = "Start electricity price fetch job. Return job ID.")]
@@ -452,27 +623,147 @@ impl Mutation {
         time_line_input: TimeLineUpdate,
         context: &HerttaContext,
     ) -> ValidationErrors {
-        let mut model = context.model.lock().await;
+        let mut model = context.edit_model("updateTimeLine").await;
         time_line_input::update_time_line(time_line_input, &mut model.time_line)
     }
 
     #[graphql(description = "Create new scenario.")]
     async fn create_scenario(name: String, weight: f64, context: &HerttaContext) -> MaybeError {
-        let mut model = context.model.lock().await;
+        let mut model = context.edit_model("createScenario").await;
         scenario_input::create_scenario(name, weight, &mut model.input_data.scenarios)
     }
 
+    #[graphql(
+        description = "Generate scenarios from an ensemble weather forecast when optimizing. \
+                       Every ensemble member becomes a scenario unless quantiles are given."
+    )]
+    async fn set_weather_ensemble(quantiles: Vec<f64>, context: &HerttaContext) -> MaybeError {
+        let mut model = context.edit_model("setWeatherEnsemble").await;
+        scenario_input::set_weather_ensemble(quantiles, &mut model.weather_ensemble)
+    }
+
+    #[graphql(description = "Stop generating scenarios from an ensemble weather forecast.")]
+    async fn clear_weather_ensemble(context: &HerttaContext) -> MaybeError {
+        let mut model = context.edit_model("clearWeatherEnsemble").await;
+        model.weather_ensemble = None;
+        MaybeError::new_ok()
+    }
+
+    #[graphql(
+        description = "Reduce scenarios to scenario_count representative ones by fast forward selection \
+                       over weather and electricity price series before optimizing."
+    )]
+    async fn set_scenario_reduction(scenario_count: i32, context: &HerttaContext) -> MaybeError {
+        let mut model = context.edit_model("setScenarioReduction").await;
+        scenario_input::set_scenario_reduction(scenario_count, &mut model.scenario_reduction)
+    }
+
+    #[graphql(description = "Stop reducing scenarios before optimizing.")]
+    async fn clear_scenario_reduction(context: &HerttaContext) -> MaybeError {
+        let mut model = context.edit_model("clearScenarioReduction").await;
+        model.scenario_reduction = None;
+        MaybeError::new_ok()
+    }
+
     #[graphql(description = "Delete a scenario and all items that depend on that scenario.")]
     async fn delete_scenario(name: String, context: &HerttaContext) -> MaybeError {
-        let mut model_ref = context.model.lock().await;
+        let mut model_ref = context.edit_model("deleteScenario").await;
         let model = model_ref.deref_mut();
         scenario_input::delete_scenario(
             &name,
             &mut model.input_data.scenarios,
             &mut model.input_data.node_histories,
+            &mut model.input_data.markets,
+        )
+    }
+
+    #[graphql(description = "Rename scenario and update all values that refer to it.")]
+    async fn rename_scenario(
+        old_name: String,
+        new_name: String,
+        context: &HerttaContext,
+    ) -> MaybeError {
+        let mut model = context.edit_model("renameScenario").await;
+        rename::rename_scenario(&old_name, &new_name, &mut model.input_data)
+    }
+
+    #[graphql(
+        description = "Apply a list of model operations atomically. The operations are applied in order \
+                       and the model is changed only if all of them succeed."
+    )]
+    async fn apply_model_changes(
+        operations: Vec<ModelOperation>,
+        context: &HerttaContext,
+    ) -> ModelChangesResult {
+        let mut model_ref = context.edit_model("applyModelChanges").await;
+        let model = model_ref.deref_mut();
+        batch::apply_model_changes(operations, &mut model.input_data, model.time_line.tz())
+    }
+
+    #[graphql(
+        description = "Merge input data of a model given as JSON into the model. Names of the merged items \
+                       are prefixed with prefix if given. onConflict decides what to do with items that exist \
+                       already; default is FAIL."
+    )]
+    async fn merge_model(
+        json: String,
+        prefix: Option<String>,
+        on_conflict: Option<ConflictPolicy>,
+        context: &HerttaContext,
+    ) -> MaybeError {
+        let mut model = context.edit_model("mergeModel").await;
+        merge::merge_model(
+            &json,
+            prefix.as_deref(),
+            on_conflict.unwrap_or_default(),
+            &mut model.input_data,
         )
     }
 
+    #[graphql(
+        description = "Apply differences returned by diffModels to the model. Changed values must match \
+                       their old values; nothing is changed if any difference fails to apply."
+    )]
+    async fn patch_model(
+        differences: Vec<ModelDifferenceInput>,
+        context: &HerttaContext,
+    ) -> MaybeError {
+        let differences: Vec<ModelDifference> =
+            differences.into_iter().map(ModelDifference::from).collect();
+        let mut model = context.edit_model("patchModel").await;
+        match diff::patch_model(&model, &differences) {
+            Ok(patched) => {
+                *model = patched;
+                MaybeError::new_ok()
+            }
+            Err(error) => error.into(),
+        }
+    }
+
+    #[graphql(description = "Undo the latest model change.")]
+    async fn undo(context: &HerttaContext) -> MaybeError {
+        let mut model = context.model.lock().await;
+        let mut history = context
+            .model_history
+            .lock()
+            .expect("model history lock should not be poisoned");
+        MaybeError {
+            message: history.undo(&mut model).err(),
+        }
+    }
+
+    #[graphql(description = "Redo the latest undone model change.")]
+    async fn redo(context: &HerttaContext) -> MaybeError {
+        let mut model = context.model.lock().await;
+        let mut history = context
+            .model_history
+            .lock()
+            .expect("model history lock should not be poisoned");
+        MaybeError {
+            message: history.redo(&mut model).err(),
+        }
+    }
+
     #[graphql(description = "Save the model on disk.")]
     async fn save_model(context: &HerttaContext) -> MaybeError {
         let file_path = model::make_model_file_path();
@@ -488,7 +779,7 @@ impl Mutation {
     ) -> MaybeError {
         match serde_json::from_str::<Model>(&model_json) {
             Ok(new_model) => {
-                let mut model_guard = context.model.lock().await;
+                let mut model_guard = context.edit_model("loadModelJson").await;
                 *model_guard = new_model;
 
                 let file_path = model::make_model_file_path();
@@ -505,9 +796,129 @@ impl Mutation {
         }
     }
 
+    #[graphql(description = "Store the active model in the model library under given name.")]
+    async fn save_model_as(
+        name: String,
+        description: Option<String>,
+        context: &HerttaContext,
+    ) -> MaybeError {
+        let model = context.model.lock().await;
+        let result =
+            library::save_model_as(&library::make_library_path(), &name, description, &model);
+        MaybeError {
+            message: result.err(),
+        }
+    }
+
+    #[graphql(description = "Replace the active model with a model from the model library and save it to disk.")]
+    async fn load_model(name: String, context: &HerttaContext) -> MaybeError {
+        let new_model = match library::load_model(&library::make_library_path(), &name) {
+            Ok(model) => model,
+            Err(error) => return error.into(),
+        };
+        let mut model = context.edit_model("loadModel").await;
+        *model = new_model;
+        MaybeError {
+            message: model::write_model_to_file(&model, &model::make_model_file_path()).err(),
+        }
+    }
+
+    #[graphql(description = "Replace the active model with a model given as YAML and save it to disk.")]
+    async fn load_model_yaml(yaml: String, context: &HerttaContext) -> MaybeError {
+        let new_model = match model::model_from_str(&yaml, ModelFormat::Yaml) {
+            Ok(model) => model,
+            Err(error) => return format!("failed to parse model: {}", error).into(),
+        };
+        let mut model = context.edit_model("loadModelYaml").await;
+        *model = new_model;
+        MaybeError {
+            message: model::write_model_to_file(&model, &model::make_model_file_path()).err(),
+        }
+    }
+
+    #[graphql(description = "Delete a model from the model library.")]
+    async fn delete_model(name: String) -> MaybeError {
+        MaybeError {
+            message: library::delete_model(&library::make_library_path(), &name).err(),
+        }
+    }
+
+    #[graphql(
+        description = "Store an input data fragment given as JSON as a model template. Strings in the fragment \
+                       may contain ${parameter} placeholders; a string that is only a placeholder can stand for a number."
+    )]
+    async fn save_template(
+        name: String,
+        description: Option<String>,
+        json: String,
+    ) -> FieldResult<TemplateInfo> {
+        Ok(template::save_template(
+            &template::make_template_library_path(),
+            &name,
+            description,
+            &json,
+        )?)
+    }
+
+    #[graphql(description = "Delete a model template.")]
+    async fn delete_template(name: String) -> MaybeError {
+        MaybeError {
+            message: template::delete_template(&template::make_template_library_path(), &name)
+                .err(),
+        }
+    }
+
+    #[graphql(
+        description = "Expand a template with given parameter values into the model. The model is left unchanged \
+                       if the expanded items conflict with existing ones or make the model invalid."
+    )]
+    async fn instantiate_template(
+        name: String,
+        parameters: Vec<TemplateParameterInput>,
+        context: &HerttaContext,
+    ) -> MaybeError {
+        let template =
+            match template::load_template(&template::make_template_library_path(), &name) {
+                Ok(template) => template,
+                Err(error) => return error.into(),
+            };
+        let mut model = context.edit_model("instantiateTemplate").await;
+        template_input::instantiate_template(&template, parameters, &mut model)
+    }
+
+    #[graphql(
+        description = "Replace the model's input data with a Predicer input workbook (.xlsx). The time line is replaced as well if it can be inferred from the workbook."
+    )]
+    async fn import_predicer_excel(path: String, context: &HerttaContext) -> PredicerImportResult {
+        match predicer_excel::import_workbook(Path::new(&path)) {
+            Ok(outcome) => {
+                let mut model = context.edit_model("importPredicerExcel").await;
+                model.input_data = outcome.input_data;
+                if let Some(time_line) = outcome.time_line {
+                    model.time_line = time_line;
+                }
+                PredicerImportResult::Ok(outcome.report)
+            }
+            Err(error) => PredicerImportResult::Err(ValidationErrors::from(
+                ValidationError::new("path", &error),
+            )),
+        }
+    }
+
+    #[graphql(
+        description = "Expand the model on its time line and write it to a Predicer input workbook (.xlsx)."
+    )]
+    async fn export_predicer_excel(path: String, context: &HerttaContext) -> MaybeError {
+        let model = context.model.lock().await;
+        match predicer_excel::export_model(&model, Path::new(&path)) {
+            Ok(()) => MaybeError::new_ok(),
+            Err(error) => MaybeError::from(error),
+        }
+    }
+
     #[graphql(description = "Clear input data from model.")]
     async fn clear_input_data(context: &HerttaContext) -> MaybeError {
-        let mut lock_guard = context.model.lock().await;
+        let mut lock_guard = context.edit_model("clearInputData").await;
         let model = lock_guard.deref_mut();
         model.input_data = BaseInputData::default();
         MaybeError::new_ok()
@@ -518,13 +929,13 @@ impl Mutation {
         setup_update: InputDataSetupInput,
         context: &HerttaContext,
     ) -> ValidationErrors {
-        let mut model = context.model.lock().await;
+        let mut model = context.edit_model("createInputDataSetup").await;
         input_data_setup_input::update_input_data_setup(setup_update, &mut model.input_data.setup)
     }
 
     #[graphql(description = "Create new node group")]
     async fn create_node_group(name: String, context: &HerttaContext) -> MaybeError {
-        let mut model_ref = context.model.lock().await;
+        let mut model_ref = context.edit_model("createNodeGroup").await;
         let model = model_ref.deref_mut();
         group_input::create_node_group(
             name,
@@ -535,7 +946,7 @@ impl Mutation {
 
     #[graphql(description = "Create new process group.")]
     async fn create_process_group(name: String, context: &HerttaContext) -> MaybeError {
-        let mut model_ref = context.model.lock().await;
+        let mut model_ref = context.edit_model("createProcessGroup").await;
         let model = model_ref.deref_mut();
         group_input::create_process_group(
             name,
@@ -545,7 +956,7 @@ impl Mutation {
     }
 
     async fn delete_group(name: String, context: &HerttaContext) -> MaybeError {
-        let mut model_ref = context.model.lock().await;
+        let mut model_ref = context.edit_model("deleteGroup").await;
         let model = model_ref.deref_mut();
         group_input::delete_group(
             &name,
@@ -553,14 +964,41 @@ impl Mutation {
             &mut model.input_data.process_groups,
         )
     }
+
+    #[graphql(description = "Rename node or process group and update its members and markets.")]
+    async fn rename_group(
+        old_name: String,
+        new_name: String,
+        context: &HerttaContext,
+    ) -> MaybeError {
+        let mut model = context.edit_model("renameGroup").await;
+        rename::rename_group(&old_name, &new_name, &mut model.input_data)
+    }
     #[graphql(description = "Create new process.")]
     async fn create_process(process: NewProcess, context: &HerttaContext) -> ValidationErrors {
-        let mut model_ref = context.model.lock().await;
+        let mut model_ref = context.edit_model("createProcess").await;
         let model = model_ref.deref_mut();
         process_input::create_process(
             process,
             &mut model.input_data.processes,
             &mut model.input_data.nodes,
+            model.time_line.tz(),
+        )
+    }
+
+    #[graphql(description = "Update process. Fields that are null are left unchanged.")]
+    async fn update_process(
+        name: String,
+        process: ProcessUpdate,
+        context: &HerttaContext,
+    ) -> ValidationErrors {
+        let mut model_ref = context.edit_model("updateProcess").await;
+        let model = model_ref.deref_mut();
+        process_input::update_process(
+            &name,
+            process,
+            &mut model.input_data.processes,
+            model.time_line.tz(),
         )
     }
 
@@ -570,7 +1008,7 @@ impl Mutation {
         group_name: String,
         context: &HerttaContext,
     ) -> MaybeError {
-        let mut model_ref = context.model.lock().await;
+        let mut model_ref = context.edit_model("addProcessToGroup").await;
         let model = model_ref.deref_mut();
         group_input::add_to_group(
             &process_name,
@@ -582,7 +1020,7 @@ impl Mutation {
 
     #[graphql(description = "Delete a process and all items that depend on that process.")]
     async fn delete_process(name: String, context: &HerttaContext) -> MaybeError {
-        let mut model_ref = context.model.lock().await;
+        let mut model_ref = context.edit_model("deleteProcess").await;
         let model = model_ref.deref_mut();
         process_input::delete_process(
             &name,
@@ -592,6 +1030,35 @@ impl Mutation {
         )
     }
 
+    #[graphql(
+        description = "Copy process with its topologies, group memberships and constraint factors. \
+                       nodeMapping replaces connections to given nodes in the copy."
+    )]
+    async fn clone_process(
+        name: String,
+        new_name: String,
+        node_mapping: Option<Vec<NodeMappingInput>>,
+        context: &HerttaContext,
+    ) -> MaybeError {
+        let mut model = context.edit_model("cloneProcess").await;
+        clone::clone_process(
+            &name,
+            &new_name,
+            &node_mapping.unwrap_or_default(),
+            &mut model.input_data,
+        )
+    }
+
+    #[graphql(description = "Rename process and update all items that refer to it.")]
+    async fn rename_process(
+        old_name: String,
+        new_name: String,
+        context: &HerttaContext,
+    ) -> MaybeError {
+        let mut model = context.edit_model("renameProcess").await;
+        rename::rename_process(&old_name, &new_name, &mut model.input_data)
+    }
+
     #[graphql(description = "Create new topology and add it to process.")]
     async fn create_topology(
         topology: NewTopology,
@@ -600,7 +1067,7 @@ impl Mutation {
         sink_node_name: Option<String>,
         context: &HerttaContext,
     ) -> ValidationErrors {
-        let mut model_ref = context.model.lock().await;
+        let mut model_ref = context.edit_model("createTopology").await;
         let model = model_ref.deref_mut();
         topology_input::create_topology(
             process_name,
@@ -609,6 +1076,27 @@ impl Mutation {
             topology,
             &mut model.input_data.processes,
             &mut model.input_data.nodes,
+            model.time_line.tz(),
+        )
+    }
+
+    #[graphql(description = "Update topology of a process. Fields that are null are left unchanged.")]
+    async fn update_topology(
+        topology: TopologyUpdate,
+        source_node_name: Option<String>,
+        process_name: String,
+        sink_node_name: Option<String>,
+        context: &HerttaContext,
+    ) -> ValidationErrors {
+        let mut model_ref = context.edit_model("updateTopology").await;
+        let model = model_ref.deref_mut();
+        topology_input::update_topology(
+            &process_name,
+            &source_node_name,
+            &sink_node_name,
+            topology,
+            &mut model.input_data.processes,
+            model.time_line.tz(),
         )
     }
 
@@ -618,7 +1106,7 @@ impl Mutation {
         sink_node_name: Option<String>,
         context: &HerttaContext,
     ) -> MaybeError {
-        let mut model = context.model.lock().await;
+        let mut model = context.edit_model("deleteTopology").await;
         topology_input::delete_topology(
             &process_name,
             &source_node_name,
@@ -629,13 +1117,31 @@ impl Mutation {
 
     #[graphql(description = "Create new node.")]
     async fn create_node(node: NewNode, context: &HerttaContext) -> ValidationErrors {
-        let mut model_ref = context.model.lock().await;
+        let mut model_ref = context.edit_model("createNode").await;
         let model = model_ref.deref_mut();
         node_input::create_node(
             node,
             &mut model.input_data.nodes,
             &mut model.input_data.processes,
             &mut model.input_data.scenarios,
+            model.time_line.tz(),
+        )
+    }
+
+    #[graphql(description = "Update node. Fields that are null are left unchanged.")]
+    async fn update_node(
+        name: String,
+        node: NodeUpdate,
+        context: &HerttaContext,
+    ) -> ValidationErrors {
+        let mut model_ref = context.edit_model("updateNode").await;
+        let model = model_ref.deref_mut();
+        node_input::update_node(
+            &name,
+            node,
+            &mut model.input_data.nodes,
+            &model.input_data.scenarios,
+            model.time_line.tz(),
         )
     }
 
@@ -645,7 +1151,7 @@ impl Mutation {
         group_name: String,
         context: &HerttaContext,
     ) -> MaybeError {
-        let mut model_ref = context.model.lock().await;
+        let mut model_ref = context.edit_model("addNodeToGroup").await;
         let model = model_ref.deref_mut();
         group_input::add_to_group(
             &node_name,
@@ -661,7 +1167,7 @@ impl Mutation {
         node_name: String,
         context: &HerttaContext,
     ) -> ValidationErrors {
-        let mut model_ref = context.model.lock().await;
+        let mut model_ref = context.edit_model("setNodeState").await;
         let model = model_ref.deref_mut();
         state_input::set_state_for_node(&node_name, state, &mut model.input_data.nodes)
     }
@@ -672,7 +1178,7 @@ impl Mutation {
         node_name: String,
         context: &HerttaContext,
     ) -> ValidationErrors {
-        let mut model_ref = context.model.lock().await;
+        let mut model_ref = context.edit_model("updateNodeState").await;
         let model = model_ref.deref_mut();
         state_input::update_state_in_node(state, node_name, &mut model.input_data.nodes)
     }
@@ -684,7 +1190,7 @@ impl Mutation {
         api_key: Option<String>,
         context: &HerttaContext,
     ) -> MaybeError {
-        let mut model = context.model.lock().await;
+        let mut model = context.edit_model("connectNodeInflowToTemperatureForecast").await;
 
         node_input::connect_node_inflow_to_temperature_forecast(
             &node_name,
@@ -697,7 +1203,7 @@ impl Mutation {
 
     #[graphql(description = "Delete a node and all items that depend on that node.")]
     async fn delete_node(name: String, context: &HerttaContext) -> MaybeError {
-        let mut model_ref = context.model.lock().await;
+        let mut model_ref = context.edit_model("deleteNode").await;
         let model = model_ref.deref_mut();
         node_input::delete_node(
             &name,
@@ -712,17 +1218,54 @@ impl Mutation {
             &mut model.input_data.gen_constraints,
         )
     }
+
+    #[graphql(
+        description = "Copy node with its state, history, group memberships and state constraint factors."
+    )]
+    async fn clone_node(name: String, new_name: String, context: &HerttaContext) -> MaybeError {
+        let mut model = context.edit_model("cloneNode").await;
+        clone::clone_node(&name, &new_name, &mut model.input_data)
+    }
+
+    #[graphql(description = "Rename node and update all items that refer to it.")]
+    async fn rename_node(
+        old_name: String,
+        new_name: String,
+        context: &HerttaContext,
+    ) -> MaybeError {
+        let mut model = context.edit_model("renameNode").await;
+        rename::rename_node(&old_name, &new_name, &mut model.input_data)
+    }
     #[graphql(description = "Create new diffusion between nodes.")]
     async fn create_node_diffusion(
         new_diffusion: NewNodeDiffusion,
         context: &HerttaContext,
     ) -> ValidationErrors {
-        let mut model_ref = context.model.lock().await;
+        let mut model_ref = context.edit_model("createNodeDiffusion").await;
         let model = model_ref.deref_mut();
         node_diffusion_input::create_node_diffusion(
             new_diffusion,
             &mut model.input_data.node_diffusion,
             &model.input_data.nodes,
+            model.time_line.tz(),
+        )
+    }
+
+    #[graphql(description = "Update diffusion between nodes.")]
+    async fn update_node_diffusion(
+        from_node: String,
+        to_node: String,
+        diffusion: NodeDiffusionUpdate,
+        context: &HerttaContext,
+    ) -> ValidationErrors {
+        let mut model_ref = context.edit_model("updateNodeDiffusion").await;
+        let model = model_ref.deref_mut();
+        node_diffusion_input::update_node_diffusion(
+            &from_node,
+            &to_node,
+            diffusion,
+            &mut model.input_data.node_diffusion,
+            model.time_line.tz(),
         )
     }
 
@@ -731,7 +1274,7 @@ impl Mutation {
         to_node: String,
         context: &HerttaContext,
     ) -> MaybeError {
-        let mut model_ref = context.model.lock().await;
+        let mut model_ref = context.edit_model("deleteNodeDiffusion").await;
         let model = model_ref.deref_mut();
         node_diffusion_input::delete_node_diffusion(
             &from_node,
@@ -741,7 +1284,7 @@ impl Mutation {
     }
 
     async fn create_node_delay(delay: NewNodeDelay, context: &HerttaContext) -> ValidationErrors {
-        let mut model_ref = context.model.lock().await;
+        let mut model_ref = context.edit_model("createNodeDelay").await;
         let model = model_ref.deref_mut();
         node_delay_input::create_node_delay(
             delay,
@@ -755,12 +1298,12 @@ impl Mutation {
         to_node: String,
         context: &HerttaContext,
     ) -> MaybeError {
-        let mut model = context.model.lock().await;
+        let mut model = context.edit_model("deleteNodeDelay").await;
         node_delay_input::delete_node_delay(&from_node, &to_node, &mut model.input_data.node_delay)
     }
 
     async fn create_node_history(node_name: String, context: &HerttaContext) -> ValidationErrors {
-        let mut model_ref = context.model.lock().await;
+        let mut model_ref = context.edit_model("createNodeHistory").await;
         let model = model_ref.deref_mut();
         node_history_input::create_node_history(
             node_name,
@@ -770,7 +1313,7 @@ impl Mutation {
     }
 
     async fn delete_node_history(node_name: String, context: &HerttaContext) -> MaybeError {
-        let mut model = context.model.lock().await;
+        let mut model = context.edit_model("deleteNodeHistory").await;
         node_history_input::delete_node_history(&node_name, &mut model.input_data.node_histories)
     }
 
@@ -779,7 +1322,7 @@ impl Mutation {
         step: NewSeries,
         context: &HerttaContext,
     ) -> ValidationErrors {
-        let mut model_ref = context.model.lock().await;
+        let mut model_ref = context.edit_model("addStepToNodeHistory").await;
         let model = model_ref.deref_mut();
         node_history_input::add_step_to_node_history(
             node_name,
@@ -790,7 +1333,7 @@ impl Mutation {
     }
 
     async fn clear_node_history_steps(node_name: String, context: &HerttaContext) -> MaybeError {
-        let mut model = context.model.lock().await;
+        let mut model = context.edit_model("clearNodeHistorySteps").await;
         node_history_input::clear_node_history_steps(
             &node_name,
             &mut model.input_data.node_histories,
@@ -799,7 +1342,7 @@ impl Mutation {
 
     #[graphql(description = "Create new market.")]
     async fn create_market(market: NewMarket, context: &HerttaContext) -> ValidationErrors {
-        let mut model_ref = context.model.lock().await;
+        let mut model_ref = context.edit_model("createMarket").await;
         let model = model_ref.deref_mut();
         market_input::create_market(
             market,
@@ -808,6 +1351,27 @@ impl Mutation {
             &model.input_data.process_groups,
             &model.input_data.node_groups,
             &model.input_data.scenarios,
+            model.time_line.tz(),
+        )
+    }
+
+    #[graphql(description = "Update market. Fields that are null are left unchanged.")]
+    async fn update_market(
+        name: String,
+        market: MarketUpdate,
+        context: &HerttaContext,
+    ) -> ValidationErrors {
+        let mut model_ref = context.edit_model("updateMarket").await;
+        let model = model_ref.deref_mut();
+        market_input::update_market(
+            &name,
+            market,
+            &mut model.input_data.markets,
+            &model.input_data.nodes,
+            &model.input_data.process_groups,
+            &model.input_data.node_groups,
+            &model.input_data.scenarios,
+            model.time_line.tz(),
         )
     }
 
@@ -821,7 +1385,7 @@ impl Mutation {
         api_key: Option<String>,
         context: &HerttaContext,
     ) -> MaybeError {
-        let mut model = context.model.lock().await;
+        let mut model = context.edit_model("connectMarketPricesToForecast").await;
         market_input::connect_market_prices_to_forecast(
             &market_name,
             forecast_name,
@@ -831,25 +1395,62 @@ impl Mutation {
         )
     }
 
+    #[graphql(
+        description = "Sets how price scenarios are generated from market's price forecast. \
+                       Null price_scenarios restores the default up and down prices."
+    )]
+    async fn set_market_price_scenarios(
+        market_name: String,
+        price_scenarios: Option<PriceScenariosInput>,
+        context: &HerttaContext,
+    ) -> MaybeError {
+        let mut model = context.edit_model("setMarketPriceScenarios").await;
+        market_input::set_market_price_scenarios(
+            &market_name,
+            price_scenarios,
+            &mut model.input_data.markets,
+        )
+    }
+
     async fn delete_market(name: String, context: &HerttaContext) -> MaybeError {
-        let mut model = context.model.lock().await;
+        let mut model = context.edit_model("deleteMarket").await;
         market_input::delete_market(&name, &mut model.input_data.markets)
     }
 
+    #[graphql(description = "Rename market.")]
+    async fn rename_market(
+        old_name: String,
+        new_name: String,
+        context: &HerttaContext,
+    ) -> MaybeError {
+        let mut model = context.edit_model("renameMarket").await;
+        rename::rename_market(&old_name, &new_name, &mut model.input_data)
+    }
+
     #[graphql(description = "Create new risk.")]
     async fn create_risk(risk: NewRisk, context: &HerttaContext) -> ValidationErrors {
-        let mut model = context.model.lock().await;
+        let mut model = context.edit_model("createRisk").await;
         risk_input::create_risk(risk, &mut model.input_data.risk)
     }
 
+    #[graphql(description = "Update risk.")]
+    async fn update_risk(
+        parameter: String,
+        risk: RiskUpdate,
+        context: &HerttaContext,
+    ) -> ValidationErrors {
+        let mut model = context.edit_model("updateRisk").await;
+        risk_input::update_risk(&parameter, risk, &mut model.input_data.risk)
+    }
+
     async fn delete_risk(parameter: String, context: &HerttaContext) -> MaybeError {
-        let mut model = context.model.lock().await;
+        let mut model = context.edit_model("deleteRisk").await;
         risk_input::delete_risk(&parameter, &mut model.input_data.risk)
     }
 
     #[graphql(description = "Create new reserve type.")]
     async fn create_reserve_type(reserve_type: NewReserveType, context: &HerttaContext) -> ValidationErrors {
-        let mut model_ref = context.model.lock().await;
+        let mut model_ref = context.edit_model("createReserveType").await;
         let model = model_ref.deref_mut();
         reserve_type_input::create_reserve_type(
             reserve_type,
@@ -857,13 +1458,40 @@ impl Mutation {
         )
     }
 
+    #[graphql(description = "Update reserve type.")]
+    async fn update_reserve_type(
+        name: String,
+        reserve_type: ReserveTypeUpdate,
+        context: &HerttaContext,
+    ) -> ValidationErrors {
+        let mut model = context.edit_model("updateReserveType").await;
+        reserve_type_input::update_reserve_type(&name, reserve_type, &mut model.input_data.reserve_type)
+    }
+
     #[graphql(description = "Create new inflow block.")]
     async fn create_inflow_block(inflow_block: NewInflowBlock, context: &HerttaContext) -> ValidationErrors {
-        let mut model_ref = context.model.lock().await;
+        let mut model_ref = context.edit_model("createInflowBlock").await;
         let model = model_ref.deref_mut();
         inflow_block_input::create_inflow_block(
             inflow_block,
             &mut model.input_data.inflow_blocks,
+            model.time_line.tz(),
+        )
+    }
+
+    #[graphql(description = "Update inflow block. Fields that are null are left unchanged.")]
+    async fn update_inflow_block(
+        name: String,
+        inflow_block: InflowBlockUpdate,
+        context: &HerttaContext,
+    ) -> ValidationErrors {
+        let mut model_ref = context.edit_model("updateInflowBlock").await;
+        let model = model_ref.deref_mut();
+        inflow_block_input::update_inflow_block(
+            &name,
+            inflow_block,
+            &mut model.input_data.inflow_blocks,
+            model.time_line.tz(),
         )
     }
 
@@ -872,15 +1500,33 @@ impl Mutation {
         constraint: NewGenConstraint,
         context: &HerttaContext,
     ) -> ValidationErrors {
-        let mut model = context.model.lock().await;
+        let mut model_ref = context.edit_model("createGenConstraint").await;
+        let model = model_ref.deref_mut();
         gen_constraint_input::create_gen_constraint(
             constraint,
             &mut model.input_data.gen_constraints,
+            model.time_line.tz(),
+        )
+    }
+
+    #[graphql(description = "Update generic constraint. Fields that are null are left unchanged.")]
+    async fn update_gen_constraint(
+        name: String,
+        constraint: GenConstraintUpdate,
+        context: &HerttaContext,
+    ) -> ValidationErrors {
+        let mut model_ref = context.edit_model("updateGenConstraint").await;
+        let model = model_ref.deref_mut();
+        gen_constraint_input::update_gen_constraint(
+            &name,
+            constraint,
+            &mut model.input_data.gen_constraints,
+            model.time_line.tz(),
         )
     }
 
     async fn delete_gen_constraint(name: String, context: &HerttaContext) -> MaybeError {
-        let mut model = context.model.lock().await;
+        let mut model = context.edit_model("deleteGenConstraint").await;
         gen_constraint_input::delete_gen_constraint(&name, &mut model.input_data.gen_constraints)
     }
     #[graphql(description = "Create new flow constraint factor and add it to generic constraint.")]
@@ -891,7 +1537,7 @@ impl Mutation {
         source_or_sink_node_name: String,
         context: &HerttaContext,
     ) -> ValidationErrors {
-        let mut model_ref = context.model.lock().await;
+        let mut model_ref = context.edit_model("createFlowConFactor").await;
         let model = model_ref.deref_mut();
         con_factor_input::create_flow_con_factor(
             factor,
@@ -900,6 +1546,7 @@ impl Mutation {
             source_or_sink_node_name,
             &mut model.input_data.gen_constraints,
             &model.input_data.processes,
+            model.time_line.tz(),
         )
     }
 
@@ -909,7 +1556,7 @@ impl Mutation {
         source_or_sink_node_name: String,
         context: &HerttaContext,
     ) -> MaybeError {
-        let mut model = context.model.lock().await;
+        let mut model = context.edit_model("deleteFlowConFactor").await;
         con_factor_input::delete_flow_con_factor(
             &constraint_name,
             &process_name,
@@ -925,7 +1572,7 @@ impl Mutation {
         node_name: String,
         context: &HerttaContext,
     ) -> ValidationErrors {
-        let mut model_ref = context.model.lock().await;
+        let mut model_ref = context.edit_model("createStateConFactor").await;
         let model = model_ref.deref_mut();
         con_factor_input::create_state_con_factor(
             factor, 
@@ -933,6 +1580,7 @@ impl Mutation {
             node_name,
             &mut model.input_data.gen_constraints,
             &model.input_data.nodes,
+            model.time_line.tz(),
         )
     }
 
@@ -941,7 +1589,7 @@ impl Mutation {
         node_name: String,
         context: &HerttaContext,
     ) -> MaybeError {
-        let mut model = context.model.lock().await;
+        let mut model = context.edit_model("deleteStateConFactor").await;
         con_factor_input::delete_state_con_factor(
             &constraint_name,
             &node_name,
@@ -956,7 +1604,7 @@ impl Mutation {
         process_name: String,
         context: &HerttaContext,
     ) -> ValidationErrors {
-        let mut model_ref = context.model.lock().await;
+        let mut model_ref = context.edit_model("createOnlineConFactor").await;
         let model = model_ref.deref_mut();
         con_factor_input::create_online_con_factor(
             factor,
@@ -964,6 +1612,7 @@ impl Mutation {
             process_name,
             &mut model.input_data.gen_constraints,
             &model.input_data.processes,
+            model.time_line.tz(),
         )
     }    
 
@@ -972,7 +1621,7 @@ impl Mutation {
         process_name: String,
         context: &HerttaContext,
     ) -> MaybeError {
-        let mut model = context.model.lock().await;
+        let mut model = context.edit_model("deleteOnlineConFactor").await;
         con_factor_input::delete_online_con_factor(
             &constraint_name,
             &process_name,
@@ -1020,8 +1669,9 @@ mod tests {
         let settings = Arc::new(Mutex::new(Settings::default()));
         let job_store = JobStore::default();
         let model = Arc::new(Mutex::new(Model::default()));
+        let model_history = Arc::new(std::sync::Mutex::new(ModelHistory::default()));
         let (tx_optimize, _rx_optimize) = mpsc::channel::<NewJob>(1);
-        HerttaContext::new(settings, job_store, model, tx_optimize)
+        HerttaContext::new(settings, job_store, model, model_history, tx_optimize)
     }
     #[tokio::test]
     async fn update_location_in_settings() {
@@ -1059,4 +1709,25 @@ mod tests {
             );
         }
     }
+    #[tokio::test]
+    async fn model_changes_can_be_undone_and_redone() {
+        let context = default_context();
+        Mutation::create_process_group("heaters".into(), &context).await;
+        Mutation::rename_group("heaters".into(), "boilers".into(), &context).await;
+        Mutation::delete_group("missing".into(), &context).await;
+        let history = Query::model_history(&context).await;
+        let operations: Vec<&str> = history.undo.iter().map(|c| c.operation.as_str()).collect();
+        assert_eq!(operations, vec!["renameGroup", "createProcessGroup"]);
+        assert!(!Mutation::undo(&context).await.is_error());
+        assert_eq!(
+            context.model.lock().await.input_data.process_groups[0].name,
+            "heaters"
+        );
+        assert!(!Mutation::redo(&context).await.is_error());
+        assert_eq!(
+            context.model.lock().await.input_data.process_groups[0].name,
+            "boilers"
+        );
+        assert!(Mutation::redo(&context).await.is_error());
+    }
 }
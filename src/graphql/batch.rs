@@ -0,0 +1,924 @@
+use super::clone::{self, NodeMappingInput};
+use super::con_factor_input;
+use super::gen_constraint_input::{self, GenConstraintUpdate, NewGenConstraint};
+use super::group_input;
+use super::inflow_block_input::{self, InflowBlockUpdate, NewInflowBlock};
+use super::market_input::{self, MarketUpdate, NewMarket, PriceScenariosInput};
+use super::node_delay_input::{self, NewNodeDelay};
+use super::node_diffusion_input::{self, NewNodeDiffusion, NodeDiffusionUpdate};
+use super::node_history_input::{self, NewSeries};
+use super::node_input::{self, NewNode, NodeUpdate};
+use super::process_input::{self, NewProcess, ProcessUpdate};
+use super::rename;
+use super::reserve_type_input::{self, NewReserveType, ReserveTypeUpdate};
+use super::risk_input::{self, NewRisk, RiskUpdate};
+use super::scenario_input;
+use super::state_input::{self, NewState, StateUpdate};
+use super::topology_input::{self, NewTopology, TopologyUpdate};
+use super::{MaybeError, ValidationError, ValidationErrors};
+use crate::input_data_base::{BaseInputData, ValueInput};
+use chrono_tz::Tz;
+use juniper::{GraphQLInputObject, GraphQLObject};
+
+#[derive(GraphQLInputObject)]
+pub struct NewScenarioOperation {
+    name: String,
+    weight: f64,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct GroupMembershipOperation {
+    member_name: String,
+    group_name: String,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct RenameOperation {
+    old_name: String,
+    new_name: String,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct CloneOperation {
+    name: String,
+    new_name: String,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct CloneProcessOperation {
+    name: String,
+    new_name: String,
+    node_mapping: Option<Vec<NodeMappingInput>>,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct UpdateProcessOperation {
+    name: String,
+    process: ProcessUpdate,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct CreateTopologyOperation {
+    process_name: String,
+    source_node_name: Option<String>,
+    sink_node_name: Option<String>,
+    topology: NewTopology,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct UpdateTopologyOperation {
+    process_name: String,
+    source_node_name: Option<String>,
+    sink_node_name: Option<String>,
+    topology: TopologyUpdate,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct DeleteTopologyOperation {
+    process_name: String,
+    source_node_name: Option<String>,
+    sink_node_name: Option<String>,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct UpdateNodeOperation {
+    name: String,
+    node: NodeUpdate,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct SetNodeStateOperation {
+    node_name: String,
+    state: Option<NewState>,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct UpdateNodeStateOperation {
+    node_name: String,
+    state: StateUpdate,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct ConnectNodeInflowOperation {
+    node_name: String,
+    forecast_name: String,
+    forecast_type: String,
+    api_key: Option<String>,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct NodePairOperation {
+    from_node: String,
+    to_node: String,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct UpdateNodeDiffusionOperation {
+    from_node: String,
+    to_node: String,
+    diffusion: NodeDiffusionUpdate,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct AddStepToNodeHistoryOperation {
+    node_name: String,
+    step: NewSeries,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct UpdateMarketOperation {
+    name: String,
+    market: MarketUpdate,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct ConnectMarketPricesOperation {
+    market_name: String,
+    forecast_name: String,
+    forecast_type: String,
+    api_key: Option<String>,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct SetMarketPriceScenariosOperation {
+    market_name: String,
+    price_scenarios: Option<PriceScenariosInput>,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct UpdateRiskOperation {
+    parameter: String,
+    risk: RiskUpdate,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct UpdateReserveTypeOperation {
+    name: String,
+    reserve_type: ReserveTypeUpdate,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct UpdateInflowBlockOperation {
+    name: String,
+    inflow_block: InflowBlockUpdate,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct UpdateGenConstraintOperation {
+    name: String,
+    constraint: GenConstraintUpdate,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct CreateFlowConFactorOperation {
+    factor: Vec<ValueInput>,
+    constraint_name: String,
+    process_name: String,
+    source_or_sink_node_name: String,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct CreateStateConFactorOperation {
+    factor: Vec<ValueInput>,
+    constraint_name: String,
+    node_name: String,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct CreateOnlineConFactorOperation {
+    factor: Vec<ValueInput>,
+    constraint_name: String,
+    process_name: String,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct DeleteFlowConFactorOperation {
+    constraint_name: String,
+    process_name: String,
+    source_or_sink_node_name: String,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct DeleteStateConFactorOperation {
+    constraint_name: String,
+    node_name: String,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct DeleteOnlineConFactorOperation {
+    constraint_name: String,
+    process_name: String,
+}
+
+#[derive(GraphQLInputObject)]
+#[graphql(
+    description = "Single model operation. Exactly one field must be set; the fields correspond to the mutations with the same name."
+)]
+pub struct ModelOperation {
+    create_scenario: Option<NewScenarioOperation>,
+    delete_scenario: Option<String>,
+    rename_scenario: Option<RenameOperation>,
+    create_node_group: Option<String>,
+    create_process_group: Option<String>,
+    delete_group: Option<String>,
+    rename_group: Option<RenameOperation>,
+    create_process: Option<NewProcess>,
+    update_process: Option<UpdateProcessOperation>,
+    add_process_to_group: Option<GroupMembershipOperation>,
+    delete_process: Option<String>,
+    clone_process: Option<CloneProcessOperation>,
+    rename_process: Option<RenameOperation>,
+    create_topology: Option<CreateTopologyOperation>,
+    update_topology: Option<UpdateTopologyOperation>,
+    delete_topology: Option<DeleteTopologyOperation>,
+    create_node: Option<NewNode>,
+    update_node: Option<UpdateNodeOperation>,
+    add_node_to_group: Option<GroupMembershipOperation>,
+    set_node_state: Option<SetNodeStateOperation>,
+    update_node_state: Option<UpdateNodeStateOperation>,
+    connect_node_inflow_to_temperature_forecast: Option<ConnectNodeInflowOperation>,
+    delete_node: Option<String>,
+    clone_node: Option<CloneOperation>,
+    rename_node: Option<RenameOperation>,
+    create_node_diffusion: Option<NewNodeDiffusion>,
+    update_node_diffusion: Option<UpdateNodeDiffusionOperation>,
+    delete_node_diffusion: Option<NodePairOperation>,
+    create_node_delay: Option<NewNodeDelay>,
+    delete_node_delay: Option<NodePairOperation>,
+    create_node_history: Option<String>,
+    add_step_to_node_history: Option<AddStepToNodeHistoryOperation>,
+    clear_node_history_steps: Option<String>,
+    delete_node_history: Option<String>,
+    create_market: Option<NewMarket>,
+    update_market: Option<UpdateMarketOperation>,
+    connect_market_prices_to_forecast: Option<ConnectMarketPricesOperation>,
+    set_market_price_scenarios: Option<SetMarketPriceScenariosOperation>,
+    delete_market: Option<String>,
+    rename_market: Option<RenameOperation>,
+    create_risk: Option<NewRisk>,
+    update_risk: Option<UpdateRiskOperation>,
+    delete_risk: Option<String>,
+    create_reserve_type: Option<NewReserveType>,
+    update_reserve_type: Option<UpdateReserveTypeOperation>,
+    create_inflow_block: Option<NewInflowBlock>,
+    update_inflow_block: Option<UpdateInflowBlockOperation>,
+    create_gen_constraint: Option<NewGenConstraint>,
+    update_gen_constraint: Option<UpdateGenConstraintOperation>,
+    delete_gen_constraint: Option<String>,
+    create_flow_con_factor: Option<CreateFlowConFactorOperation>,
+    delete_flow_con_factor: Option<DeleteFlowConFactorOperation>,
+    create_state_con_factor: Option<CreateStateConFactorOperation>,
+    delete_state_con_factor: Option<DeleteStateConFactorOperation>,
+    create_online_con_factor: Option<CreateOnlineConFactorOperation>,
+    delete_online_con_factor: Option<DeleteOnlineConFactorOperation>,
+}
+
+impl ModelOperation {
+    fn operation_count(&self) -> usize {
+        [
+            self.create_scenario.is_some(),
+            self.delete_scenario.is_some(),
+            self.rename_scenario.is_some(),
+            self.create_node_group.is_some(),
+            self.create_process_group.is_some(),
+            self.delete_group.is_some(),
+            self.rename_group.is_some(),
+            self.create_process.is_some(),
+            self.update_process.is_some(),
+            self.add_process_to_group.is_some(),
+            self.delete_process.is_some(),
+            self.clone_process.is_some(),
+            self.rename_process.is_some(),
+            self.create_topology.is_some(),
+            self.update_topology.is_some(),
+            self.delete_topology.is_some(),
+            self.create_node.is_some(),
+            self.update_node.is_some(),
+            self.add_node_to_group.is_some(),
+            self.set_node_state.is_some(),
+            self.update_node_state.is_some(),
+            self.connect_node_inflow_to_temperature_forecast.is_some(),
+            self.delete_node.is_some(),
+            self.clone_node.is_some(),
+            self.rename_node.is_some(),
+            self.create_node_diffusion.is_some(),
+            self.update_node_diffusion.is_some(),
+            self.delete_node_diffusion.is_some(),
+            self.create_node_delay.is_some(),
+            self.delete_node_delay.is_some(),
+            self.create_node_history.is_some(),
+            self.add_step_to_node_history.is_some(),
+            self.clear_node_history_steps.is_some(),
+            self.delete_node_history.is_some(),
+            self.create_market.is_some(),
+            self.update_market.is_some(),
+            self.connect_market_prices_to_forecast.is_some(),
+            self.set_market_price_scenarios.is_some(),
+            self.delete_market.is_some(),
+            self.rename_market.is_some(),
+            self.create_risk.is_some(),
+            self.update_risk.is_some(),
+            self.delete_risk.is_some(),
+            self.create_reserve_type.is_some(),
+            self.update_reserve_type.is_some(),
+            self.create_inflow_block.is_some(),
+            self.update_inflow_block.is_some(),
+            self.create_gen_constraint.is_some(),
+            self.update_gen_constraint.is_some(),
+            self.delete_gen_constraint.is_some(),
+            self.create_flow_con_factor.is_some(),
+            self.delete_flow_con_factor.is_some(),
+            self.create_state_con_factor.is_some(),
+            self.delete_state_con_factor.is_some(),
+            self.create_online_con_factor.is_some(),
+            self.delete_online_con_factor.is_some(),
+        ]
+        .iter()
+        .filter(|is_set| **is_set)
+        .count()
+    }
+}
+
+#[derive(GraphQLObject)]
+#[graphql(description = "Outcome of a batch of model operations.")]
+pub struct ModelChangesResult {
+    #[graphql(
+        description = "True if every operation succeeded and the changes were applied to the model."
+    )]
+    applied: bool,
+    #[graphql(
+        description = "Validation errors of each operation in the order the operations were given."
+    )]
+    operations: Vec<ValidationErrors>,
+}
+
+/// Applies operations to a copy of input data and replaces the input data with the copy
+/// only if every operation succeeds.
+pub fn apply_model_changes(
+    operations: Vec<ModelOperation>,
+    input_data: &mut BaseInputData,
+    time_zone: Tz,
+) -> ModelChangesResult {
+    let mut copy = input_data.clone();
+    let results: Vec<ValidationErrors> = operations
+        .into_iter()
+        .map(|operation| apply_operation(operation, &mut copy, time_zone))
+        .collect();
+    let applied = results.iter().all(|result| result.errors.is_empty());
+    if applied {
+        *input_data = copy;
+    }
+    ModelChangesResult {
+        applied,
+        operations: results,
+    }
+}
+
+fn to_validation_errors(operation: &str, result: MaybeError) -> ValidationErrors {
+    match result.message {
+        Some(message) => ValidationErrors::from(ValidationError::new(operation, &message)),
+        None => ValidationErrors::default(),
+    }
+}
+
+fn apply_operation(
+    operation: ModelOperation,
+    input_data: &mut BaseInputData,
+    time_zone: Tz,
+) -> ValidationErrors {
+    if operation.operation_count() != 1 {
+        return ValidationErrors::from(ValidationError::new(
+            "operation",
+            "exactly one operation must be given",
+        ));
+    }
+    let d = input_data;
+    if let Some(scenario) = operation.create_scenario {
+        let result =
+            scenario_input::create_scenario(scenario.name, scenario.weight, &mut d.scenarios);
+        return to_validation_errors("createScenario", result);
+    }
+    if let Some(name) = operation.delete_scenario {
+        let result = scenario_input::delete_scenario(
+            &name,
+            &mut d.scenarios,
+            &mut d.node_histories,
+            &mut d.markets,
+        );
+        return to_validation_errors("deleteScenario", result);
+    }
+    if let Some(rename) = operation.rename_scenario {
+        let result = rename::rename_scenario(&rename.old_name, &rename.new_name, d);
+        return to_validation_errors("renameScenario", result);
+    }
+    if let Some(name) = operation.create_node_group {
+        let result = group_input::create_node_group(name, &mut d.node_groups, &d.process_groups);
+        return to_validation_errors("createNodeGroup", result);
+    }
+    if let Some(name) = operation.create_process_group {
+        let result = group_input::create_process_group(name, &mut d.process_groups, &d.node_groups);
+        return to_validation_errors("createProcessGroup", result);
+    }
+    if let Some(name) = operation.delete_group {
+        let result = group_input::delete_group(&name, &mut d.node_groups, &mut d.process_groups);
+        return to_validation_errors("deleteGroup", result);
+    }
+    if let Some(rename) = operation.rename_group {
+        let result = rename::rename_group(&rename.old_name, &rename.new_name, d);
+        return to_validation_errors("renameGroup", result);
+    }
+    if let Some(process) = operation.create_process {
+        return process_input::create_process(process, &mut d.processes, &mut d.nodes, time_zone);
+    }
+    if let Some(update) = operation.update_process {
+        return process_input::update_process(
+            &update.name,
+            update.process,
+            &mut d.processes,
+            time_zone,
+        );
+    }
+    if let Some(membership) = operation.add_process_to_group {
+        let result = group_input::add_to_group(
+            &membership.member_name,
+            &membership.group_name,
+            &mut d.processes,
+            &mut d.process_groups,
+        );
+        return to_validation_errors("addProcessToGroup", result);
+    }
+    if let Some(name) = operation.delete_process {
+        let result = process_input::delete_process(
+            &name,
+            &mut d.processes,
+            &mut d.process_groups,
+            &mut d.gen_constraints,
+        );
+        return to_validation_errors("deleteProcess", result);
+    }
+    if let Some(clone) = operation.clone_process {
+        let result = clone::clone_process(
+            &clone.name,
+            &clone.new_name,
+            &clone.node_mapping.unwrap_or_default(),
+            d,
+        );
+        return to_validation_errors("cloneProcess", result);
+    }
+    if let Some(rename) = operation.rename_process {
+        let result = rename::rename_process(&rename.old_name, &rename.new_name, d);
+        return to_validation_errors("renameProcess", result);
+    }
+    if let Some(create) = operation.create_topology {
+        return topology_input::create_topology(
+            create.process_name,
+            create.source_node_name,
+            create.sink_node_name,
+            create.topology,
+            &mut d.processes,
+            &mut d.nodes,
+            time_zone,
+        );
+    }
+    if let Some(update) = operation.update_topology {
+        return topology_input::update_topology(
+            &update.process_name,
+            &update.source_node_name,
+            &update.sink_node_name,
+            update.topology,
+            &mut d.processes,
+            time_zone,
+        );
+    }
+    if let Some(delete) = operation.delete_topology {
+        let result = topology_input::delete_topology(
+            &delete.process_name,
+            &delete.source_node_name,
+            &delete.sink_node_name,
+            &mut d.processes,
+        );
+        return to_validation_errors("deleteTopology", result);
+    }
+    if let Some(node) = operation.create_node {
+        return node_input::create_node(
+            node,
+            &mut d.nodes,
+            &mut d.processes,
+            &mut d.scenarios,
+            time_zone,
+        );
+    }
+    if let Some(update) = operation.update_node {
+        return node_input::update_node(
+            &update.name,
+            update.node,
+            &mut d.nodes,
+            &d.scenarios,
+            time_zone,
+        );
+    }
+    if let Some(membership) = operation.add_node_to_group {
+        let result = group_input::add_to_group(
+            &membership.member_name,
+            &membership.group_name,
+            &mut d.nodes,
+            &mut d.node_groups,
+        );
+        return to_validation_errors("addNodeToGroup", result);
+    }
+    if let Some(set_state) = operation.set_node_state {
+        return state_input::set_state_for_node(
+            &set_state.node_name,
+            set_state.state,
+            &mut d.nodes,
+        );
+    }
+    if let Some(update) = operation.update_node_state {
+        return state_input::update_state_in_node(update.state, update.node_name, &mut d.nodes);
+    }
+    if let Some(connect) = operation.connect_node_inflow_to_temperature_forecast {
+        let result = node_input::connect_node_inflow_to_temperature_forecast(
+            &connect.node_name,
+            connect.forecast_name,
+            connect.forecast_type,
+            connect.api_key,
+            &mut d.nodes,
+        );
+        return to_validation_errors("connectNodeInflowToTemperatureForecast", result);
+    }
+    if let Some(name) = operation.delete_node {
+        let result = node_input::delete_node(
+            &name,
+            &mut d.nodes,
+            &mut d.node_groups,
+            &mut d.processes,
+            &mut d.node_diffusion,
+            &mut d.node_delay,
+            &mut d.node_histories,
+            &mut d.markets,
+            &mut d.inflow_blocks,
+            &mut d.gen_constraints,
+        );
+        return to_validation_errors("deleteNode", result);
+    }
+    if let Some(clone) = operation.clone_node {
+        let result = clone::clone_node(&clone.name, &clone.new_name, d);
+        return to_validation_errors("cloneNode", result);
+    }
+    if let Some(rename) = operation.rename_node {
+        let result = rename::rename_node(&rename.old_name, &rename.new_name, d);
+        return to_validation_errors("renameNode", result);
+    }
+    if let Some(diffusion) = operation.create_node_diffusion {
+        return node_diffusion_input::create_node_diffusion(
+            diffusion,
+            &mut d.node_diffusion,
+            &d.nodes,
+            time_zone,
+        );
+    }
+    if let Some(update) = operation.update_node_diffusion {
+        return node_diffusion_input::update_node_diffusion(
+            &update.from_node,
+            &update.to_node,
+            update.diffusion,
+            &mut d.node_diffusion,
+            time_zone,
+        );
+    }
+    if let Some(pair) = operation.delete_node_diffusion {
+        let result = node_diffusion_input::delete_node_diffusion(
+            &pair.from_node,
+            &pair.to_node,
+            &mut d.node_diffusion,
+        );
+        return to_validation_errors("deleteNodeDiffusion", result);
+    }
+    if let Some(delay) = operation.create_node_delay {
+        return node_delay_input::create_node_delay(delay, &mut d.node_delay, &d.nodes);
+    }
+    if let Some(pair) = operation.delete_node_delay {
+        let result =
+            node_delay_input::delete_node_delay(&pair.from_node, &pair.to_node, &mut d.node_delay);
+        return to_validation_errors("deleteNodeDelay", result);
+    }
+    if let Some(node_name) = operation.create_node_history {
+        return node_history_input::create_node_history(node_name, &mut d.node_histories, &d.nodes);
+    }
+    if let Some(add_step) = operation.add_step_to_node_history {
+        return node_history_input::add_step_to_node_history(
+            add_step.node_name,
+            add_step.step,
+            &mut d.node_histories,
+            &d.scenarios,
+        );
+    }
+    if let Some(node_name) = operation.clear_node_history_steps {
+        let result =
+            node_history_input::clear_node_history_steps(&node_name, &mut d.node_histories);
+        return to_validation_errors("clearNodeHistorySteps", result);
+    }
+    if let Some(node_name) = operation.delete_node_history {
+        let result = node_history_input::delete_node_history(&node_name, &mut d.node_histories);
+        return to_validation_errors("deleteNodeHistory", result);
+    }
+    if let Some(market) = operation.create_market {
+        return market_input::create_market(
+            market,
+            &mut d.markets,
+            &d.nodes,
+            &d.process_groups,
+            &d.node_groups,
+            &d.scenarios,
+            time_zone,
+        );
+    }
+    if let Some(update) = operation.update_market {
+        return market_input::update_market(
+            &update.name,
+            update.market,
+            &mut d.markets,
+            &d.nodes,
+            &d.process_groups,
+            &d.node_groups,
+            &d.scenarios,
+            time_zone,
+        );
+    }
+    if let Some(connect) = operation.connect_market_prices_to_forecast {
+        let result = market_input::connect_market_prices_to_forecast(
+            &connect.market_name,
+            connect.forecast_name,
+            connect.forecast_type,
+            connect.api_key,
+            &mut d.markets,
+        );
+        return to_validation_errors("connectMarketPricesToForecast", result);
+    }
+    if let Some(set) = operation.set_market_price_scenarios {
+        let result = market_input::set_market_price_scenarios(
+            &set.market_name,
+            set.price_scenarios,
+            &mut d.markets,
+        );
+        return to_validation_errors("setMarketPriceScenarios", result);
+    }
+    if let Some(name) = operation.delete_market {
+        let result = market_input::delete_market(&name, &mut d.markets);
+        return to_validation_errors("deleteMarket", result);
+    }
+    if let Some(rename) = operation.rename_market {
+        let result = rename::rename_market(&rename.old_name, &rename.new_name, d);
+        return to_validation_errors("renameMarket", result);
+    }
+    if let Some(risk) = operation.create_risk {
+        return risk_input::create_risk(risk, &mut d.risk);
+    }
+    if let Some(update) = operation.update_risk {
+        return risk_input::update_risk(&update.parameter, update.risk, &mut d.risk);
+    }
+    if let Some(parameter) = operation.delete_risk {
+        let result = risk_input::delete_risk(&parameter, &mut d.risk);
+        return to_validation_errors("deleteRisk", result);
+    }
+    if let Some(reserve_type) = operation.create_reserve_type {
+        return reserve_type_input::create_reserve_type(reserve_type, &mut d.reserve_type);
+    }
+    if let Some(update) = operation.update_reserve_type {
+        return reserve_type_input::update_reserve_type(
+            &update.name,
+            update.reserve_type,
+            &mut d.reserve_type,
+        );
+    }
+    if let Some(inflow_block) = operation.create_inflow_block {
+        return inflow_block_input::create_inflow_block(
+            inflow_block,
+            &mut d.inflow_blocks,
+            time_zone,
+        );
+    }
+    if let Some(update) = operation.update_inflow_block {
+        return inflow_block_input::update_inflow_block(
+            &update.name,
+            update.inflow_block,
+            &mut d.inflow_blocks,
+            time_zone,
+        );
+    }
+    if let Some(constraint) = operation.create_gen_constraint {
+        return gen_constraint_input::create_gen_constraint(
+            constraint,
+            &mut d.gen_constraints,
+            time_zone,
+        );
+    }
+    if let Some(update) = operation.update_gen_constraint {
+        return gen_constraint_input::update_gen_constraint(
+            &update.name,
+            update.constraint,
+            &mut d.gen_constraints,
+            time_zone,
+        );
+    }
+    if let Some(name) = operation.delete_gen_constraint {
+        let result = gen_constraint_input::delete_gen_constraint(&name, &mut d.gen_constraints);
+        return to_validation_errors("deleteGenConstraint", result);
+    }
+    if let Some(create) = operation.create_flow_con_factor {
+        return con_factor_input::create_flow_con_factor(
+            create.factor,
+            create.constraint_name,
+            create.process_name,
+            create.source_or_sink_node_name,
+            &mut d.gen_constraints,
+            &d.processes,
+            time_zone,
+        );
+    }
+    if let Some(create) = operation.create_state_con_factor {
+        return con_factor_input::create_state_con_factor(
+            create.factor,
+            create.constraint_name,
+            create.node_name,
+            &mut d.gen_constraints,
+            &d.nodes,
+            time_zone,
+        );
+    }
+    if let Some(create) = operation.create_online_con_factor {
+        return con_factor_input::create_online_con_factor(
+            create.factor,
+            create.constraint_name,
+            create.process_name,
+            &mut d.gen_constraints,
+            &d.processes,
+            time_zone,
+        );
+    }
+    if let Some(delete) = operation.delete_flow_con_factor {
+        let result = con_factor_input::delete_flow_con_factor(
+            &delete.constraint_name,
+            &delete.process_name,
+            &delete.source_or_sink_node_name,
+            &mut d.gen_constraints,
+        );
+        return to_validation_errors("deleteFlowConFactor", result);
+    }
+    if let Some(delete) = operation.delete_state_con_factor {
+        let result = con_factor_input::delete_state_con_factor(
+            &delete.constraint_name,
+            &delete.node_name,
+            &mut d.gen_constraints,
+        );
+        return to_validation_errors("deleteStateConFactor", result);
+    }
+    if let Some(delete) = operation.delete_online_con_factor {
+        let result = con_factor_input::delete_online_con_factor(
+            &delete.constraint_name,
+            &delete.process_name,
+            &mut d.gen_constraints,
+        );
+        return to_validation_errors("deleteOnlineConFactor", result);
+    }
+    unreachable!("operation count was checked above")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_operation() -> ModelOperation {
+        ModelOperation {
+            create_scenario: None,
+            delete_scenario: None,
+            rename_scenario: None,
+            create_node_group: None,
+            create_process_group: None,
+            delete_group: None,
+            rename_group: None,
+            create_process: None,
+            update_process: None,
+            add_process_to_group: None,
+            delete_process: None,
+            clone_process: None,
+            rename_process: None,
+            create_topology: None,
+            update_topology: None,
+            delete_topology: None,
+            create_node: None,
+            update_node: None,
+            add_node_to_group: None,
+            set_node_state: None,
+            update_node_state: None,
+            connect_node_inflow_to_temperature_forecast: None,
+            delete_node: None,
+            clone_node: None,
+            rename_node: None,
+            create_node_diffusion: None,
+            update_node_diffusion: None,
+            delete_node_diffusion: None,
+            create_node_delay: None,
+            delete_node_delay: None,
+            create_node_history: None,
+            add_step_to_node_history: None,
+            clear_node_history_steps: None,
+            delete_node_history: None,
+            create_market: None,
+            update_market: None,
+            connect_market_prices_to_forecast: None,
+            set_market_price_scenarios: None,
+            delete_market: None,
+            rename_market: None,
+            create_risk: None,
+            update_risk: None,
+            delete_risk: None,
+            create_reserve_type: None,
+            update_reserve_type: None,
+            create_inflow_block: None,
+            update_inflow_block: None,
+            create_gen_constraint: None,
+            update_gen_constraint: None,
+            delete_gen_constraint: None,
+            create_flow_con_factor: None,
+            delete_flow_con_factor: None,
+            create_state_con_factor: None,
+            delete_state_con_factor: None,
+            create_online_con_factor: None,
+            delete_online_con_factor: None,
+        }
+    }
+
+    fn create_scenario(name: &str) -> ModelOperation {
+        ModelOperation {
+            create_scenario: Some(NewScenarioOperation {
+                name: name.to_string(),
+                weight: 1.0,
+            }),
+            ..empty_operation()
+        }
+    }
+
+    fn create_node_group(name: &str) -> ModelOperation {
+        ModelOperation {
+            create_node_group: Some(name.to_string()),
+            ..empty_operation()
+        }
+    }
+
+    #[test]
+    fn all_operations_are_applied_when_they_succeed() {
+        let mut input_data = BaseInputData::default();
+        let result = apply_model_changes(
+            vec![create_scenario("s1"), create_node_group("heat")],
+            &mut input_data,
+            Tz::UTC,
+        );
+        assert!(result.applied);
+        assert_eq!(result.operations.len(), 2);
+        assert_eq!(input_data.scenarios.len(), 1);
+        assert_eq!(input_data.node_groups[0].name, "heat");
+    }
+
+    #[test]
+    fn nothing_is_applied_when_an_operation_fails() {
+        let mut input_data = BaseInputData::default();
+        let result = apply_model_changes(
+            vec![
+                create_scenario("s1"),
+                create_scenario("s1"),
+                empty_operation(),
+                create_node_group("heat"),
+            ],
+            &mut input_data,
+            Tz::UTC,
+        );
+        assert!(!result.applied);
+        assert!(result.operations[0].errors.is_empty());
+        assert_eq!(result.operations[1].errors.len(), 1);
+        assert_eq!(result.operations[1].errors[0].field, "createScenario");
+        assert_eq!(result.operations[2].errors[0].field, "operation");
+        assert!(result.operations[3].errors.is_empty());
+        assert!(input_data.scenarios.is_empty());
+        assert!(input_data.node_groups.is_empty());
+    }
+
+    #[test]
+    fn renames_are_applied_in_batches() {
+        let mut input_data = BaseInputData::default();
+        let rename = ModelOperation {
+            rename_scenario: Some(RenameOperation {
+                old_name: "s1".to_string(),
+                new_name: "s2".to_string(),
+            }),
+            ..empty_operation()
+        };
+        let result = apply_model_changes(
+            vec![create_scenario("s1"), rename],
+            &mut input_data,
+            Tz::UTC,
+        );
+        assert!(result.applied);
+        assert_eq!(input_data.scenarios[0].name(), "s2");
+    }
+}
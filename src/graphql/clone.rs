@@ -0,0 +1,252 @@
+use super::MaybeError;
+use crate::input_data_base::{BaseInputData, ConstraintFactorType};
+use juniper::GraphQLInputObject;
+
+#[derive(GraphQLInputObject)]
+#[graphql(description = "Replaces connections to node from with connections to node to.")]
+pub struct NodeMappingInput {
+    from: String,
+    to: String,
+}
+
+fn validate_new_name(new_name: &str, input_data: &BaseInputData) -> MaybeError {
+    if new_name.is_empty() {
+        return "name is empty".into();
+    }
+    if input_data.nodes.iter().any(|n| n.name == new_name) {
+        return "a node with the same name exists".into();
+    }
+    if input_data.processes.iter().any(|p| p.name == new_name) {
+        return "a process with the same name exists".into();
+    }
+    MaybeError::new_ok()
+}
+
+fn map_name(
+    name: &str,
+    old_name: &str,
+    new_name: &str,
+    node_mapping: &[NodeMappingInput],
+) -> String {
+    if name == old_name {
+        return new_name.to_string();
+    }
+    match node_mapping.iter().find(|m| m.from == name) {
+        Some(mapping) => mapping.to.clone(),
+        None => name.to_string(),
+    }
+}
+
+/// Copies process with its topologies, group memberships and constraint factors.
+pub fn clone_process(
+    name: &str,
+    new_name: &str,
+    node_mapping: &[NodeMappingInput],
+    input_data: &mut BaseInputData,
+) -> MaybeError {
+    let maybe_error = validate_new_name(new_name, input_data);
+    if maybe_error.is_error() {
+        return maybe_error;
+    }
+    let mut process = match input_data.processes.iter().find(|p| p.name == name) {
+        Some(process) => process.clone(),
+        None => return "no such process".into(),
+    };
+    for mapping in node_mapping {
+        let is_connected = mapping.from != name
+            && process
+                .topos
+                .iter()
+                .any(|t| t.source == mapping.from || t.sink == mapping.from);
+        if !is_connected {
+            return format!("'{}' is not a node connected to the process", mapping.from).into();
+        }
+        if !input_data.nodes.iter().any(|n| n.name == mapping.to) {
+            return format!("no such node '{}'", mapping.to).into();
+        }
+    }
+    process.name = new_name.to_string();
+    for topology in &mut process.topos {
+        topology.source = map_name(&topology.source, name, new_name, node_mapping);
+        topology.sink = map_name(&topology.sink, name, new_name, node_mapping);
+    }
+    for group in &mut input_data.process_groups {
+        if process.groups.contains(&group.name) {
+            group.members.push(new_name.to_string());
+        }
+    }
+    input_data.processes.push(process);
+    for constraint in &mut input_data.gen_constraints {
+        let mut cloned_factors = Vec::new();
+        for factor in &constraint.factors {
+            let is_process_factor = match factor.var_type {
+                ConstraintFactorType::Flow | ConstraintFactorType::Online => {
+                    factor.var_tuple.entity == name
+                }
+                ConstraintFactorType::State => false,
+            };
+            if is_process_factor {
+                let mut factor = factor.clone();
+                factor.var_tuple.entity = new_name.to_string();
+                if let Some(ref mut identifier) = factor.var_tuple.identifier {
+                    *identifier = map_name(identifier, name, new_name, node_mapping);
+                }
+                cloned_factors.push(factor);
+            }
+        }
+        constraint.factors.extend(cloned_factors);
+    }
+    MaybeError::new_ok()
+}
+
+/// Copies node with its state, history, group memberships and state constraint factors.
+pub fn clone_node(name: &str, new_name: &str, input_data: &mut BaseInputData) -> MaybeError {
+    let maybe_error = validate_new_name(new_name, input_data);
+    if maybe_error.is_error() {
+        return maybe_error;
+    }
+    let mut node = match input_data.nodes.iter().find(|n| n.name == name) {
+        Some(node) => node.clone(),
+        None => return "no such node".into(),
+    };
+    node.name = new_name.to_string();
+    for group in &mut input_data.node_groups {
+        if node.groups.contains(&group.name) {
+            group.members.push(new_name.to_string());
+        }
+    }
+    input_data.nodes.push(node);
+    if let Some(history) = input_data.node_histories.iter().find(|h| h.node == name) {
+        let mut history = history.clone();
+        history.node = new_name.to_string();
+        input_data.node_histories.push(history);
+    }
+    for constraint in &mut input_data.gen_constraints {
+        let cloned_factors: Vec<_> = constraint
+            .factors
+            .iter()
+            .filter(|f| {
+                matches!(f.var_type, ConstraintFactorType::State) && f.var_tuple.entity == name
+            })
+            .map(|f| {
+                let mut factor = f.clone();
+                factor.var_tuple.entity = new_name.to_string();
+                factor
+            })
+            .collect();
+        constraint.factors.extend(cloned_factors);
+    }
+    MaybeError::new_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_data_base::{
+        BaseConFactor, BaseGenConstraint, BaseNode, BaseProcess, BaseTopology, ConstraintType,
+        Conversion, ProcessGroup, VariableId,
+    };
+
+    fn make_input_data() -> BaseInputData {
+        let mut process = BaseProcess::new("hp1".into(), Conversion::Unit);
+        process.groups.push("heat pumps".into());
+        process
+            .topos
+            .push(BaseTopology::new("elc".into(), "hp1".into()));
+        process
+            .topos
+            .push(BaseTopology::new("hp1".into(), "heat1".into()));
+        BaseInputData {
+            nodes: vec![
+                BaseNode::new("elc".into()),
+                BaseNode::new("heat1".into()),
+                BaseNode::new("heat2".into()),
+            ],
+            processes: vec![process],
+            process_groups: vec![ProcessGroup {
+                name: "heat pumps".into(),
+                members: vec!["hp1".into()],
+            }],
+            gen_constraints: vec![BaseGenConstraint {
+                name: "c1".into(),
+                gc_type: ConstraintType::Equal,
+                is_setpoint: false,
+                penalty: 0.0,
+                factors: vec![BaseConFactor {
+                    var_type: ConstraintFactorType::Flow,
+                    var_tuple: VariableId {
+                        entity: "hp1".into(),
+                        identifier: Some("heat1".into()),
+                    },
+                    data: Vec::new(),
+                }],
+                constant: Vec::new(),
+            }],
+            ..BaseInputData::default()
+        }
+    }
+
+    #[test]
+    fn cloned_process_has_remapped_topologies_and_dependents() {
+        let mut input_data = make_input_data();
+        let mapping = vec![NodeMappingInput {
+            from: "heat1".into(),
+            to: "heat2".into(),
+        }];
+        let result = clone_process("hp1", "hp2", &mapping, &mut input_data);
+        assert!(!result.is_error(), "{:?}", result.message);
+        let clone = &input_data.processes[1];
+        assert_eq!(clone.name, "hp2");
+        let connections: Vec<(&str, &str)> = clone
+            .topos
+            .iter()
+            .map(|t| (t.source.as_str(), t.sink.as_str()))
+            .collect();
+        assert_eq!(connections, vec![("elc", "hp2"), ("hp2", "heat2")]);
+        assert_eq!(input_data.process_groups[0].members, vec!["hp1", "hp2"]);
+        let factor = &input_data.gen_constraints[0].factors[1];
+        assert_eq!(factor.var_tuple.entity, "hp2");
+        assert_eq!(factor.var_tuple.identifier.as_deref(), Some("heat2"));
+    }
+
+    #[test]
+    fn mapping_must_start_from_connected_node() {
+        let mut input_data = make_input_data();
+        for from in ["heat2", "hp1", "heta1"] {
+            let mapping = vec![NodeMappingInput {
+                from: from.into(),
+                to: "heat2".into(),
+            }];
+            let result = clone_process("hp1", "hp2", &mapping, &mut input_data);
+            assert_eq!(
+                result.message,
+                Some(format!("'{}' is not a node connected to the process", from))
+            );
+        }
+        let mapping = vec![NodeMappingInput {
+            from: "heat1".into(),
+            to: "heat3".into(),
+        }];
+        let result = clone_process("hp1", "hp2", &mapping, &mut input_data);
+        assert_eq!(result.message.as_deref(), Some("no such node 'heat3'"));
+        assert_eq!(input_data.processes.len(), 1);
+    }
+
+    #[test]
+    fn clone_name_must_be_free() {
+        let mut input_data = make_input_data();
+        let result = clone_process("hp1", "elc", &[], &mut input_data);
+        assert_eq!(
+            result.message.as_deref(),
+            Some("a node with the same name exists")
+        );
+        let result = clone_node("heat1", "hp1", &mut input_data);
+        assert_eq!(
+            result.message.as_deref(),
+            Some("a process with the same name exists")
+        );
+        let result = clone_node("heat1", "heat3", &mut input_data);
+        assert!(!result.is_error());
+        assert_eq!(input_data.nodes[3].name, "heat3");
+    }
+}
@@ -2,6 +2,7 @@ use super::{MaybeError, ValidationError, ValidationErrors};
 use crate::input_data_base::{
     BaseConFactor, BaseGenConstraint, BaseNode, BaseProcess, ConstraintFactorType, VariableId, Value, ValueInput
 };
+use chrono_tz::Tz;
 
 pub fn create_flow_con_factor(
     factor_inputs: Vec<ValueInput>, 
@@ -10,11 +11,12 @@ pub fn create_flow_con_factor(
     source_or_sink_node_name: String,
     constraints: &mut Vec<BaseGenConstraint>,
     processes: &Vec<BaseProcess>,
+    time_zone: Tz,
 ) -> ValidationErrors {
 
     let factor: Vec<Value> = match factor_inputs
         .into_iter()
-        .map(Value::try_from)
+        .map(|input| Value::from_input(input, time_zone))
         .collect::<Result<Vec<Value>, String>>() {
         Ok(vec) => vec,
         Err(err) => return ValidationErrors::from(ValidationError::new("factor", &err)),
@@ -112,10 +114,11 @@ pub fn create_state_con_factor(
     node_name: String,
     constraints: &mut Vec<BaseGenConstraint>,
     nodes: &Vec<BaseNode>,
+    time_zone: Tz,
 ) -> ValidationErrors {
     let factor: Vec<Value> = match factor_inputs
         .into_iter()
-        .map(Value::try_from)
+        .map(|input| Value::from_input(input, time_zone))
         .collect::<Result<Vec<Value>, String>>() {
         Ok(vec) => vec,
         Err(err) => return ValidationErrors::from(ValidationError::new("factor", &err)),
@@ -170,10 +173,11 @@ pub fn create_online_con_factor(
     process_name: String,
     constraints: &mut Vec<BaseGenConstraint>,
     processes: &Vec<BaseProcess>,
+    time_zone: Tz,
 ) -> ValidationErrors {
     let factor: Vec<Value> = match factor_inputs
         .into_iter()
-        .map(Value::try_from)
+        .map(|input| Value::from_input(input, time_zone))
         .collect::<Result<Vec<Value>, String>>() {
         Ok(vec) => vec,
         Err(err) => return ValidationErrors::from(ValidationError::new("factor", &err)),
@@ -0,0 +1,22 @@
+use crate::model::diff::{DifferenceKind, ModelDifference};
+use juniper::GraphQLInputObject;
+
+#[derive(GraphQLInputObject)]
+#[graphql(description = "Model difference as returned by diffModels.")]
+pub struct ModelDifferenceInput {
+    kind: DifferenceKind,
+    path: String,
+    old_value: Option<String>,
+    new_value: Option<String>,
+}
+
+impl From<ModelDifferenceInput> for ModelDifference {
+    fn from(input: ModelDifferenceInput) -> Self {
+        ModelDifference {
+            kind: input.kind,
+            path: input.path,
+            old_value: input.old_value,
+            new_value: input.new_value,
+        }
+    }
+}
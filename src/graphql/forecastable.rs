@@ -1,29 +1,28 @@
 use crate::input_data_base::{ForecastValueInput, ForecastValue};
 use crate::scenarios::Scenario;
+use chrono_tz::Tz;
 
 pub fn convert_forecast_value_inputs(
     inputs: Vec<ForecastValueInput>,
     scenarios: &Vec<Scenario>,
-) -> Vec<ForecastValue> {
-    // ✔ Allow empty: no values, no default => just return empty (no panic)
+    time_zone: Tz,
+) -> Result<Vec<ForecastValue>, String> {
+    // ✔ Allow empty: no values, no default => just return empty
     if inputs.is_empty() {
-        return Vec::new();
+        return Ok(Vec::new());
     }
 
     let mut converted: Vec<ForecastValue> = inputs
         .into_iter()
-        .map(|inp| {
-            ForecastValue::try_from(inp)
-                .expect("Error converting ForecastValueInput")
-        })
-        .collect();
+        .map(|input| ForecastValue::from_input(input, time_zone))
+        .collect::<Result<Vec<ForecastValue>, String>>()?;
 
     let default_value: Option<ForecastValue> = {
         let mut iter = converted.iter().filter(|fv| fv.scenario.is_none());
         let first = iter.next().cloned();
 
         if iter.next().is_some() {
-            panic!("Multiple default forecast values provided (scenario == None)");
+            return Err("multiple default forecast values provided".to_string());
         }
         first
     };
@@ -53,10 +52,10 @@ pub fn convert_forecast_value_inputs(
                     .as_ref()
                     .map_or(false, |s| s == scenario.name())
             }) {
-                panic!("Missing forecast value for scenario {}", scenario.name());
+                return Err(format!("missing forecast value for scenario {}", scenario.name()));
             }
         }
     }
 
-    converted
+    Ok(converted)
 }
@@ -1,6 +1,8 @@
 use super::delete;
+use super::update::{self, optional_update};
 use super::{MaybeError, ValidationError, ValidationErrors};
-use crate::input_data_base::{BaseGenConstraint, ConstraintType, Value, ValueInput};
+use crate::input_data_base::{BaseGenConstraint, ConstraintType, ValueInput};
+use chrono_tz::Tz;
 use juniper::GraphQLInputObject;
 
 #[derive(GraphQLInputObject)]
@@ -13,32 +15,37 @@ pub struct NewGenConstraint {
 }
 
 impl NewGenConstraint {
-    fn to_gen_constraint(self) -> BaseGenConstraint {
-        BaseGenConstraint {
+    fn to_gen_constraint(self, time_zone: Tz) -> Result<BaseGenConstraint, Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        let constant =
+            match update::convert_values("constant", Some(self.constant), time_zone, &mut errors) {
+                Some(constant) => constant,
+                None => return Err(errors),
+            };
+        Ok(BaseGenConstraint {
             name: self.name,
             gc_type: self.gc_type,
             is_setpoint: self.is_setpoint,
             penalty: self.penalty,
             factors: Vec::new(),
-            constant: self
-            .constant
-            .into_iter()
-            .map(Value::try_from)
-            .collect::<Result<Vec<Value>, _>>()
-            .expect("Could not parse cost values"),
-        }
+            constant,
+        })
     }
 }
 
 pub fn create_gen_constraint(
     constraint: NewGenConstraint,
     constraints: &mut Vec<BaseGenConstraint>,
+    time_zone: Tz,
 ) -> ValidationErrors {
     let errors = validate_gen_contraint_creation(&constraint, constraints);
     if !errors.is_empty() {
         return ValidationErrors::from(errors);
     }
-    constraints.push(constraint.to_gen_constraint());
+    match constraint.to_gen_constraint(time_zone) {
+        Ok(constraint) => constraints.push(constraint),
+        Err(errors) => return ValidationErrors::from(errors),
+    }
     ValidationErrors::default()
 }
 
@@ -59,6 +66,36 @@ fn validate_gen_contraint_creation(
     errors
 }
 
+#[derive(GraphQLInputObject)]
+pub struct GenConstraintUpdate {
+    gc_type: Option<ConstraintType>,
+    is_setpoint: Option<bool>,
+    penalty: Option<f64>,
+    constant: Option<Vec<ValueInput>>,
+}
+
+pub fn update_gen_constraint(
+    name: &str,
+    update: GenConstraintUpdate,
+    constraints: &mut [BaseGenConstraint],
+    time_zone: Tz,
+) -> ValidationErrors {
+    let constraint = match constraints.iter_mut().find(|c| c.name == name) {
+        Some(constraint) => constraint,
+        None => return ValidationErrors::from(ValidationError::new("name", "no such constraint")),
+    };
+    let mut errors = Vec::new();
+    let constant = update::convert_values("constant", update.constant, time_zone, &mut errors);
+    if !errors.is_empty() {
+        return ValidationErrors::from(errors);
+    }
+    optional_update(update.gc_type, &mut constraint.gc_type);
+    optional_update(update.is_setpoint, &mut constraint.is_setpoint);
+    optional_update(update.penalty, &mut constraint.penalty);
+    optional_update(constant, &mut constraint.constant);
+    ValidationErrors::default()
+}
+
 pub fn delete_gen_constraint(name: &str, constraints: &mut Vec<BaseGenConstraint>) -> MaybeError {
     delete::delete_named(name, constraints)
 }
@@ -1,7 +1,9 @@
+use chrono_tz::Tz;
 use juniper::GraphQLInputObject;
 use super::delete;
+use super::update::{self, optional_update};
 use super::{MaybeError, ValidationError, ValidationErrors};
-use crate::input_data_base::{BaseInflowBlock, ValueInput, Value};
+use crate::input_data_base::{BaseInflowBlock, ValueInput};
 
 #[derive(GraphQLInputObject, Debug, Clone)]
 pub struct NewInflowBlock {
@@ -11,25 +13,33 @@ pub struct NewInflowBlock {
 }
 
 impl NewInflowBlock {
-    pub fn to_inflow_block(self) -> BaseInflowBlock {
-        BaseInflowBlock {
+    pub fn to_inflow_block(self, time_zone: Tz) -> Result<BaseInflowBlock, Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        let data = match update::convert_values("data", Some(self.data), time_zone, &mut errors) {
+            Some(data) => data,
+            None => return Err(errors),
+        };
+        Ok(BaseInflowBlock {
             name: self.name,
             node: self.node,
-            data: self.data
-            .into_iter()
-            .map(Value::try_from)
-            .collect::<Result<Vec<Value>, _>>()
-            .expect("Could not parse cost values"),
-        }
+            data,
+        })
     }
 }
 
-pub fn create_inflow_block(inflow_block: NewInflowBlock, inflow_blocks: &mut Vec<BaseInflowBlock>) -> ValidationErrors {
+pub fn create_inflow_block(
+    inflow_block: NewInflowBlock,
+    inflow_blocks: &mut Vec<BaseInflowBlock>,
+    time_zone: Tz,
+) -> ValidationErrors {
     let errors = validate_inflow_block_creation(&inflow_block);
     if !errors.is_empty() {
         return ValidationErrors::from(errors);
     }
-    inflow_blocks.push(inflow_block.to_inflow_block());
+    match inflow_block.to_inflow_block(time_zone) {
+        Ok(inflow_block) => inflow_blocks.push(inflow_block),
+        Err(errors) => return ValidationErrors::from(errors),
+    }
     ValidationErrors::default()
 }
 
@@ -38,13 +48,48 @@ fn validate_inflow_block_creation(inflow_block: &NewInflowBlock) -> Vec<Validati
     if inflow_block.name.is_empty() {
         errors.push(ValidationError::new("name", "name is empty"));
     }
-    if inflow_block.node.is_empty() {
+    validate_node_and_data(&inflow_block.node, &inflow_block.data, &mut errors);
+    errors
+}
+
+fn validate_node_and_data<T>(node: &str, data: &[T], errors: &mut Vec<ValidationError>) {
+    if node.is_empty() {
         errors.push(ValidationError::new("node", "node is empty"));
     }
-    if inflow_block.data.is_empty() {
+    if data.is_empty() {
         errors.push(ValidationError::new("data", "data is empty"));
     }
-    errors
+}
+
+#[derive(GraphQLInputObject, Debug, Clone)]
+pub struct InflowBlockUpdate {
+    pub node: Option<String>,
+    pub data: Option<Vec<ValueInput>>,
+}
+
+pub fn update_inflow_block(
+    name: &str,
+    update: InflowBlockUpdate,
+    inflow_blocks: &mut [BaseInflowBlock],
+    time_zone: Tz,
+) -> ValidationErrors {
+    let inflow_block = match inflow_blocks.iter_mut().find(|i| i.name == name) {
+        Some(inflow_block) => inflow_block,
+        None => return ValidationErrors::from(ValidationError::new("name", "no such inflow block")),
+    };
+    let mut errors = Vec::new();
+    let data = update::convert_values("data", update.data, time_zone, &mut errors);
+    validate_node_and_data(
+        update.node.as_ref().unwrap_or(&inflow_block.node),
+        data.as_ref().unwrap_or(&inflow_block.data),
+        &mut errors,
+    );
+    if !errors.is_empty() {
+        return ValidationErrors::from(errors);
+    }
+    optional_update(update.node, &mut inflow_block.node);
+    optional_update(data, &mut inflow_block.data);
+    ValidationErrors::default()
 }
 
 pub fn _delete_inflow_block(parameter: &str, inflow_blocks: &mut Vec<BaseInflowBlock>) -> MaybeError {
@@ -1,12 +1,17 @@
 use super::delete;
-use super::forecastable;
+use super::update::{self, optional_update};
 use super::{MaybeError, ValidationError, ValidationErrors};
 use crate::input_data::Forecast;
 use crate::input_data_base::{
-    BaseForecastable, BaseMarket, BaseNode, MarketDirection, MarketType, ProcessGroup, NodeGroup, ValueInput, Value, ForecastValueInput, ForecastValue,
+    BaseForecastable, BaseMarket, BaseNode, MarketDirection, MarketType, ProcessGroup, NodeGroup, ValueInput, ForecastValueInput, ForecastValue,
+};
+use crate::scenarios::prices::{
+    BalancingPriceRule, OffsetType, PriceNoise, PriceOffset, PriceScenarios,
 };
 use crate::scenarios::Scenario;
-use juniper::GraphQLInputObject;
+use crate::units::Unit;
+use chrono_tz::Tz;
+use juniper::{GraphQLInputObject, Nullable};
 
 #[derive(GraphQLInputObject, Debug)]
 pub struct NewMarket {
@@ -26,38 +31,74 @@ pub struct NewMarket {
     up_price: Vec<ForecastValueInput>,
     down_price: Vec<ForecastValueInput>,
     reserve_activation_price: Vec<ValueInput>,
+    price_unit: Option<Unit>,
 }
 
 impl NewMarket {
-    fn to_market(self, scenarios: &Vec<Scenario>) -> BaseMarket {
-        BaseMarket {
-            name: self.name,
-            m_type: self.m_type,
-            node: self.node,
-            process_group: self.process_group,
-            direction: self.direction,
-            realisation: self
-            .realisation
-            .into_iter()
-            .map(Value::try_from)
-            .collect::<Result<Vec<Value>, _>>()
-            .expect("Could not parse reserve activation price values"),
-            reserve_type: self.reserve_type,
-            is_bid: self.is_bid,
-            is_limited: self.is_limited,
-            min_bid: self.min_bid,
-            max_bid: self.max_bid,
-            fee: self.fee,
-            price: forecastable::convert_forecast_value_inputs(self.price, scenarios),
-            up_price: forecastable::convert_forecast_value_inputs(self.up_price, scenarios),
-            down_price: forecastable::convert_forecast_value_inputs(self.down_price, scenarios),
-            reserve_activation_price: self
-                .reserve_activation_price
-                .into_iter()
-                .map(Value::try_from)
-                .collect::<Result<Vec<Value>, _>>()
-                .expect("Could not parse reserve activation price values"),
-            fixed: Vec::new(),
+    fn to_market(
+        self,
+        scenarios: &Vec<Scenario>,
+        time_zone: Tz,
+    ) -> Result<BaseMarket, Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        let realisation =
+            update::convert_values("realisation", Some(self.realisation), time_zone, &mut errors);
+        let price = update::convert_forecast_values(
+            "price",
+            Some(self.price),
+            scenarios,
+            time_zone,
+            &mut errors,
+        );
+        let up_price = update::convert_forecast_values(
+            "up_price",
+            Some(self.up_price),
+            scenarios,
+            time_zone,
+            &mut errors,
+        );
+        let down_price = update::convert_forecast_values(
+            "down_price",
+            Some(self.down_price),
+            scenarios,
+            time_zone,
+            &mut errors,
+        );
+        let reserve_activation_price = update::convert_values(
+            "reserve_activation_price",
+            Some(self.reserve_activation_price),
+            time_zone,
+            &mut errors,
+        );
+        match (realisation, price, up_price, down_price, reserve_activation_price) {
+            (
+                Some(realisation),
+                Some(price),
+                Some(up_price),
+                Some(down_price),
+                Some(reserve_activation_price),
+            ) => Ok(BaseMarket {
+                name: self.name,
+                m_type: self.m_type,
+                node: self.node,
+                process_group: self.process_group,
+                direction: self.direction,
+                realisation,
+                reserve_type: self.reserve_type,
+                is_bid: self.is_bid,
+                is_limited: self.is_limited,
+                min_bid: self.min_bid,
+                max_bid: self.max_bid,
+                fee: self.fee,
+                price,
+                up_price,
+                down_price,
+                reserve_activation_price,
+                fixed: Vec::new(),
+                price_unit: self.price_unit,
+                price_scenarios: None,
+            }),
+            _ => Err(errors),
         }
     }
 }
@@ -69,12 +110,16 @@ pub fn create_market(
     process_groups: &Vec<ProcessGroup>,
     node_groups: &Vec<NodeGroup>,
     scenarios: &Vec<Scenario>,
+    time_zone: Tz,
 ) -> ValidationErrors {
     let errors = validate_market_creation(&market, nodes, process_groups, node_groups);
     if !errors.is_empty() {
         return ValidationErrors::from(errors);
     }
-    markets.push(market.to_market(scenarios));
+    match market.to_market(scenarios, time_zone) {
+        Ok(market) => markets.push(market),
+        Err(errors) => return ValidationErrors::from(errors),
+    }
     ValidationErrors::default()
 }
 
@@ -88,9 +133,29 @@ fn validate_market_creation(
     if market.name.is_empty() {
         errors.push(ValidationError::new("name", "name is empty"));
     }
-    match market.m_type {
+    validate_market_node(market.m_type, &market.node, nodes, node_groups, &mut errors);
+    validate_market_settings(
+        &market.process_group,
+        market.reserve_type.as_ref(),
+        market.min_bid,
+        market.max_bid,
+        groups,
+        &mut errors,
+    );
+    validate_price(&market.price, &mut errors);
+    errors
+}
+
+fn validate_market_node(
+    m_type: MarketType,
+    node: &str,
+    nodes: &[BaseNode],
+    node_groups: &[NodeGroup],
+    errors: &mut Vec<ValidationError>,
+) {
+    match m_type {
         MarketType::Reserve => {
-            let node_group_exists = node_groups.iter().any(|ng| ng.name == market.node);
+            let node_group_exists = node_groups.iter().any(|ng| ng.name == node);
             if !node_group_exists {
                 errors.push(ValidationError::new(
                     "node",
@@ -99,7 +164,7 @@ fn validate_market_creation(
             }
         }
         _ => {
-            let node_exists = nodes.iter().any(|n| n.name == market.node);
+            let node_exists = nodes.iter().any(|n| n.name == node);
             if !node_exists {
                 errors.push(ValidationError::new(
                     "node",
@@ -108,10 +173,20 @@ fn validate_market_creation(
             }
         }
     }
-    if !groups.iter().any(|g| g.name == market.process_group) {
+}
+
+fn validate_market_settings(
+    process_group: &str,
+    reserve_type: Option<&String>,
+    min_bid: f64,
+    max_bid: f64,
+    groups: &[ProcessGroup],
+    errors: &mut Vec<ValidationError>,
+) {
+    if !groups.iter().any(|g| g.name == process_group) {
         errors.push(ValidationError::new("processgroup", "no such group"));
     }
-    if let Some(ref reserve_type) = market.reserve_type {
+    if let Some(reserve_type) = reserve_type {
         if reserve_type.is_empty() {
             errors.push(ValidationError::new(
                 "reserve_type",
@@ -119,17 +194,134 @@ fn validate_market_creation(
             ));
         }
     }
-    if market.min_bid > market.max_bid {
+    if min_bid > max_bid {
         errors.push(ValidationError::new("min_bid", "greater than max_bid"));
     }
-    
-    if market.price.is_empty() {
+}
+
+fn validate_price<T>(price: &[T], errors: &mut Vec<ValidationError>) {
+    if price.is_empty() {
         errors.push(ValidationError::new(
             "price",
             "price is empty: provide per-scenario values or connect a forecast",
         ));
     }
-    errors
+}
+
+#[derive(GraphQLInputObject, Debug)]
+pub struct MarketUpdate {
+    m_type: Option<MarketType>,
+    node: Option<String>,
+    process_group: Option<String>,
+    direction: Nullable<MarketDirection>,
+    realisation: Option<Vec<ValueInput>>,
+    reserve_type: Nullable<String>,
+    is_bid: Option<bool>,
+    is_limited: Option<bool>,
+    min_bid: Option<f64>,
+    max_bid: Option<f64>,
+    fee: Option<f64>,
+    price: Option<Vec<ForecastValueInput>>,
+    up_price: Option<Vec<ForecastValueInput>>,
+    down_price: Option<Vec<ForecastValueInput>>,
+    reserve_activation_price: Option<Vec<ValueInput>>,
+    price_unit: Option<Unit>,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_market(
+    name: &str,
+    update: MarketUpdate,
+    markets: &mut [BaseMarket],
+    nodes: &[BaseNode],
+    process_groups: &[ProcessGroup],
+    node_groups: &[NodeGroup],
+    scenarios: &Vec<Scenario>,
+    time_zone: Tz,
+) -> ValidationErrors {
+    let market = match markets.iter_mut().find(|m| m.name == name) {
+        Some(market) => market,
+        None => return ValidationErrors::from(ValidationError::new("name", "no such market")),
+    };
+    let reserve_type = match update.reserve_type {
+        Nullable::Some(ref reserve_type) => Some(reserve_type),
+        Nullable::ExplicitNull => None,
+        Nullable::ImplicitNull => market.reserve_type.as_ref(),
+    };
+    let mut errors = Vec::new();
+    validate_market_node(
+        update.m_type.unwrap_or(market.m_type),
+        update.node.as_ref().unwrap_or(&market.node),
+        nodes,
+        node_groups,
+        &mut errors,
+    );
+    validate_market_settings(
+        update.process_group.as_ref().unwrap_or(&market.process_group),
+        reserve_type,
+        update.min_bid.unwrap_or(market.min_bid),
+        update.max_bid.unwrap_or(market.max_bid),
+        process_groups,
+        &mut errors,
+    );
+    match update.price {
+        Some(ref price) => validate_price(price, &mut errors),
+        None => validate_price(&market.price, &mut errors),
+    }
+    let realisation =
+        update::convert_values("realisation", update.realisation, time_zone, &mut errors);
+    let reserve_activation_price = update::convert_values(
+        "reserve_activation_price",
+        update.reserve_activation_price,
+        time_zone,
+        &mut errors,
+    );
+    let price =
+        update::convert_forecast_values("price", update.price, scenarios, time_zone, &mut errors);
+    let up_price = update::convert_forecast_values(
+        "up_price",
+        update.up_price,
+        scenarios,
+        time_zone,
+        &mut errors,
+    );
+    let down_price = update::convert_forecast_values(
+        "down_price",
+        update.down_price,
+        scenarios,
+        time_zone,
+        &mut errors,
+    );
+    if !errors.is_empty() {
+        return ValidationErrors::from(errors);
+    }
+    optional_update(update.m_type, &mut market.m_type);
+    optional_update(update.node, &mut market.node);
+    optional_update(update.process_group, &mut market.process_group);
+    match update.direction {
+        Nullable::Some(direction) => market.direction = Some(direction),
+        Nullable::ExplicitNull => market.direction = None,
+        Nullable::ImplicitNull => (),
+    }
+    optional_update(realisation, &mut market.realisation);
+    match update.reserve_type {
+        Nullable::Some(reserve_type) => market.reserve_type = Some(reserve_type),
+        Nullable::ExplicitNull => market.reserve_type = None,
+        Nullable::ImplicitNull => (),
+    }
+    optional_update(update.is_bid, &mut market.is_bid);
+    optional_update(update.is_limited, &mut market.is_limited);
+    optional_update(update.min_bid, &mut market.min_bid);
+    optional_update(update.max_bid, &mut market.max_bid);
+    optional_update(update.fee, &mut market.fee);
+    optional_update(price, &mut market.price);
+    optional_update(up_price, &mut market.up_price);
+    optional_update(down_price, &mut market.down_price);
+    optional_update(reserve_activation_price, &mut market.reserve_activation_price);
+    if update.price_unit.is_some() {
+        market.price_unit = update.price_unit;
+    }
+    ValidationErrors::default()
 }
 
 pub fn connect_market_prices_to_forecast(
@@ -174,7 +366,171 @@ pub fn connect_market_prices_to_forecast(
     MaybeError::new_ok()
 }
 
+#[derive(GraphQLInputObject)]
+pub struct PriceOffsetInput {
+    scenario: String,
+    offset_type: OffsetType,
+    value: f64,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct PriceNoiseInput {
+    #[graphql(description = "Standard deviations of past forecast errors by lead time in hours.")]
+    error_std_devs: Vec<f64>,
+    seed: i32,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct BalancingPriceRuleInput {
+    offset_type: OffsetType,
+    value: f64,
+}
+
+impl From<BalancingPriceRuleInput> for BalancingPriceRule {
+    fn from(input: BalancingPriceRuleInput) -> Self {
+        BalancingPriceRule {
+            offset_type: input.offset_type,
+            value: input.value,
+        }
+    }
+}
+
+#[derive(GraphQLInputObject)]
+#[graphql(description = "Price scenario generation; up and down prices default to 1.1 and 0.9 times price.")]
+pub struct PriceScenariosInput {
+    offsets: Vec<PriceOffsetInput>,
+    noise: Option<PriceNoiseInput>,
+    up_price: Option<BalancingPriceRuleInput>,
+    down_price: Option<BalancingPriceRuleInput>,
+}
+
+impl From<PriceScenariosInput> for PriceScenarios {
+    fn from(input: PriceScenariosInput) -> Self {
+        let defaults = PriceScenarios::default();
+        PriceScenarios {
+            offsets: input
+                .offsets
+                .into_iter()
+                .map(|offset| PriceOffset {
+                    scenario: offset.scenario,
+                    offset_type: offset.offset_type,
+                    value: offset.value,
+                })
+                .collect(),
+            noise: input.noise.map(|noise| PriceNoise {
+                error_std_devs: noise.error_std_devs,
+                seed: noise.seed,
+            }),
+            up_price: input.up_price.map_or(defaults.up_price, BalancingPriceRule::from),
+            down_price: input
+                .down_price
+                .map_or(defaults.down_price, BalancingPriceRule::from),
+        }
+    }
+}
+
+pub fn set_market_price_scenarios(
+    market_name: &str,
+    price_scenarios: Option<PriceScenariosInput>,
+    markets: &mut [BaseMarket],
+) -> MaybeError {
+    let market = match markets.iter_mut().find(|m| m.name == market_name) {
+        Some(market) => market,
+        None => return "no such market".into(),
+    };
+    let price_scenarios = price_scenarios.map(PriceScenarios::from);
+    if let Some(ref price_scenarios) = price_scenarios {
+        if let Err(error) = price_scenarios.validate() {
+            return error.into();
+        }
+    }
+    market.price_scenarios = price_scenarios;
+    MaybeError::new_ok()
+}
 
 pub fn delete_market(name: &str, markets: &mut Vec<BaseMarket>) -> MaybeError {
     delete::delete_named(name, markets)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_data_base::{Constant, NamedGroup};
+
+    fn make_update() -> MarketUpdate {
+        MarketUpdate {
+            m_type: None,
+            node: None,
+            process_group: None,
+            direction: Nullable::ImplicitNull,
+            realisation: None,
+            reserve_type: Nullable::ImplicitNull,
+            is_bid: None,
+            is_limited: None,
+            min_bid: None,
+            max_bid: None,
+            fee: None,
+            price: None,
+            up_price: None,
+            down_price: None,
+            reserve_activation_price: None,
+            price_unit: None,
+        }
+    }
+
+    #[test]
+    fn update_market_clears_direction_and_reserve_type_given_as_null() {
+        let mut markets = vec![BaseMarket {
+            name: "npe".into(),
+            m_type: MarketType::Energy,
+            node: "elc".into(),
+            process_group: "p1".into(),
+            direction: Some(MarketDirection::Up),
+            realisation: Vec::new(),
+            reserve_type: Some("fast".into()),
+            is_bid: false,
+            is_limited: false,
+            min_bid: 0.0,
+            max_bid: 0.0,
+            fee: 0.0,
+            price: vec![ForecastValue {
+                scenario: None,
+                value: BaseForecastable::Constant(Constant::new(1.0)),
+            }],
+            up_price: Vec::new(),
+            down_price: Vec::new(),
+            reserve_activation_price: Vec::new(),
+            fixed: Vec::new(),
+            price_unit: None,
+            price_scenarios: None,
+        }];
+        let nodes = vec![BaseNode::new("elc".into())];
+        let process_groups = vec![ProcessGroup::new("p1".into())];
+        let update_and_check = |markets: &mut Vec<BaseMarket>, update: MarketUpdate| {
+            let errors = update_market(
+                "npe",
+                update,
+                markets,
+                &nodes,
+                &process_groups,
+                &[],
+                &Vec::new(),
+                Tz::UTC,
+            );
+            assert!(errors.errors.is_empty());
+        };
+        update_and_check(&mut markets, make_update());
+        assert_eq!(markets[0].direction, Some(MarketDirection::Up));
+        assert_eq!(markets[0].reserve_type, Some("fast".into()));
+        update_and_check(
+            &mut markets,
+            MarketUpdate {
+                direction: Nullable::ExplicitNull,
+                reserve_type: Nullable::ExplicitNull,
+                ..make_update()
+            },
+        );
+        assert_eq!(markets[0].direction, None);
+        assert_eq!(markets[0].reserve_type, None);
+    }
+}
@@ -0,0 +1,459 @@
+use super::rename;
+use super::MaybeError;
+use crate::input_data::Name;
+use crate::input_data_base::{BaseInputData, GroupMember, Members};
+use crate::model::Model;
+use juniper::GraphQLEnum;
+
+#[derive(Clone, Copy, Debug, Default, GraphQLEnum, PartialEq)]
+pub enum ConflictPolicy {
+    #[default]
+    Fail,
+    Skip,
+    Overwrite,
+}
+
+fn prefix_names(prefix: &str, input_data: &mut BaseInputData) -> Result<(), String> {
+    // Longest names are renamed first so that a prefixed name never collides
+    // with a name that has not been renamed yet.
+    let mut nodes_and_processes: Vec<(String, bool)> = input_data
+        .nodes
+        .iter()
+        .map(|n| (n.name.clone(), true))
+        .chain(input_data.processes.iter().map(|p| (p.name.clone(), false)))
+        .collect();
+    nodes_and_processes.sort_by_key(|(name, _)| std::cmp::Reverse(name.len()));
+    for (name, is_node) in nodes_and_processes {
+        let new_name = format!("{}{}", prefix, name);
+        let result = if is_node {
+            rename::rename_node(&name, &new_name, input_data)
+        } else {
+            rename::rename_process(&name, &new_name, input_data)
+        };
+        check(&name, result)?;
+    }
+    let mut groups: Vec<String> = input_data
+        .node_groups
+        .iter()
+        .map(|g| g.name.clone())
+        .chain(input_data.process_groups.iter().map(|g| g.name.clone()))
+        .collect();
+    groups.sort_by_key(|name| std::cmp::Reverse(name.len()));
+    for name in groups {
+        check(
+            &name,
+            rename::rename_group(&name, &format!("{}{}", prefix, name), input_data),
+        )?;
+    }
+    let mut markets: Vec<String> = input_data.markets.iter().map(|m| m.name.clone()).collect();
+    markets.sort_by_key(|name| std::cmp::Reverse(name.len()));
+    for name in markets {
+        check(
+            &name,
+            rename::rename_market(&name, &format!("{}{}", prefix, name), input_data),
+        )?;
+    }
+    for block in &mut input_data.inflow_blocks {
+        block.name = format!("{}{}", prefix, block.name);
+    }
+    for constraint in &mut input_data.gen_constraints {
+        constraint.name = format!("{}{}", prefix, constraint.name);
+    }
+    Ok(())
+}
+
+fn check(name: &str, result: MaybeError) -> Result<(), String> {
+    match result.message {
+        Some(message) => Err(format!("failed to prefix '{}': {}", name, message)),
+        None => Ok(()),
+    }
+}
+
+/// Merges items into existing ones by key and returns the keys of merged items.
+fn merge_items<T>(
+    type_name: &str,
+    existing: &mut Vec<T>,
+    incoming: Vec<T>,
+    key: impl Fn(&T) -> String,
+    policy: ConflictPolicy,
+    errors: &mut Vec<String>,
+) -> Vec<String> {
+    let mut merged = Vec::new();
+    for item in incoming {
+        let item_key = key(&item);
+        match existing.iter().position(|e| key(e) == item_key) {
+            Some(position) => match policy {
+                ConflictPolicy::Fail => {
+                    errors.push(format!("{} '{}' exists already", type_name, item_key))
+                }
+                ConflictPolicy::Skip => (),
+                ConflictPolicy::Overwrite => {
+                    existing[position] = item;
+                    merged.push(item_key);
+                }
+            },
+            None => {
+                existing.push(item);
+                merged.push(item_key);
+            }
+        }
+    }
+    merged
+}
+
+/// Makes group members agree with the groups of merged items
+/// since an overwritten item may belong to different groups than the one it replaced.
+fn reconcile_members<T: GroupMember + Name, G: Members + Name>(
+    items: &[T],
+    merged: &[String],
+    groups: &mut [G],
+) {
+    for item in items.iter().filter(|i| merged.contains(i.name())) {
+        for group in groups.iter_mut() {
+            let is_listed = item.groups().contains(group.name());
+            let members = group.members_mut();
+            match (is_listed, members.iter().position(|m| m == item.name())) {
+                (true, None) => members.push(item.name().clone()),
+                (false, Some(position)) => {
+                    members.remove(position);
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+fn check_name_clashes(existing: &BaseInputData, incoming: &BaseInputData) -> Vec<String> {
+    let mut errors = Vec::new();
+    for node in &incoming.nodes {
+        if existing.processes.iter().any(|p| p.name == node.name) {
+            errors.push(format!(
+                "node '{}' has the same name as a process",
+                node.name
+            ));
+        }
+    }
+    for process in &incoming.processes {
+        if existing.nodes.iter().any(|n| n.name == process.name) {
+            errors.push(format!(
+                "process '{}' has the same name as a node",
+                process.name
+            ));
+        }
+    }
+    for group in &incoming.node_groups {
+        if existing.process_groups.iter().any(|g| g.name == group.name) {
+            errors.push(format!(
+                "node group '{}' has the same name as a process group",
+                group.name
+            ));
+        }
+    }
+    for group in &incoming.process_groups {
+        if existing.node_groups.iter().any(|g| g.name == group.name) {
+            errors.push(format!(
+                "process group '{}' has the same name as a node group",
+                group.name
+            ));
+        }
+    }
+    errors
+}
+
+pub fn merge_input_data(
+    mut incoming: BaseInputData,
+    prefix: Option<&str>,
+    policy: ConflictPolicy,
+    input_data: &mut BaseInputData,
+) -> Result<(), String> {
+    if let Some(prefix) = prefix {
+        prefix_names(prefix, &mut incoming)?;
+    }
+    let mut errors = check_name_clashes(input_data, &incoming);
+    for scenario in incoming.scenarios {
+        match input_data
+            .scenarios
+            .iter_mut()
+            .find(|s| s.name() == scenario.name())
+        {
+            Some(existing) if existing.weight() != scenario.weight() => match policy {
+                ConflictPolicy::Fail => errors.push(format!(
+                    "scenario '{}' exists already with a different weight",
+                    scenario.name()
+                )),
+                ConflictPolicy::Skip => (),
+                ConflictPolicy::Overwrite => *existing = scenario,
+            },
+            Some(..) => (),
+            None => input_data.scenarios.push(scenario),
+        }
+    }
+    let nodes = merge_items(
+        "node",
+        &mut input_data.nodes,
+        incoming.nodes,
+        |n| n.name.clone(),
+        policy,
+        &mut errors,
+    );
+    let processes = merge_items(
+        "process",
+        &mut input_data.processes,
+        incoming.processes,
+        |p| p.name.clone(),
+        policy,
+        &mut errors,
+    );
+    // Groups with the same name are the same group; only merged members are added to them.
+    for mut group in incoming.node_groups {
+        group.members.retain(|m| nodes.contains(m));
+        match input_data
+            .node_groups
+            .iter_mut()
+            .find(|g| g.name == group.name)
+        {
+            Some(existing) => {
+                for member in group.members {
+                    if !existing.members.contains(&member) {
+                        existing.members.push(member);
+                    }
+                }
+            }
+            None => input_data.node_groups.push(group),
+        }
+    }
+    for mut group in incoming.process_groups {
+        group.members.retain(|m| processes.contains(m));
+        match input_data
+            .process_groups
+            .iter_mut()
+            .find(|g| g.name == group.name)
+        {
+            Some(existing) => {
+                for member in group.members {
+                    if !existing.members.contains(&member) {
+                        existing.members.push(member);
+                    }
+                }
+            }
+            None => input_data.process_groups.push(group),
+        }
+    }
+    reconcile_members(&input_data.nodes, &nodes, &mut input_data.node_groups);
+    reconcile_members(
+        &input_data.processes,
+        &processes,
+        &mut input_data.process_groups,
+    );
+    merge_items(
+        "market",
+        &mut input_data.markets,
+        incoming.markets,
+        |m| m.name.clone(),
+        policy,
+        &mut errors,
+    );
+    merge_items(
+        "node diffusion",
+        &mut input_data.node_diffusion,
+        incoming.node_diffusion,
+        |d| format!("{}->{}", d.from_node, d.to_node),
+        policy,
+        &mut errors,
+    );
+    merge_items(
+        "node delay",
+        &mut input_data.node_delay,
+        incoming.node_delay,
+        |d| format!("{}->{}", d.from_node, d.to_node),
+        policy,
+        &mut errors,
+    );
+    merge_items(
+        "node history",
+        &mut input_data.node_histories,
+        incoming.node_histories,
+        |h| h.node.clone(),
+        policy,
+        &mut errors,
+    );
+    merge_items(
+        "inflow block",
+        &mut input_data.inflow_blocks,
+        incoming.inflow_blocks,
+        |b| b.name.clone(),
+        policy,
+        &mut errors,
+    );
+    merge_items(
+        "generic constraint",
+        &mut input_data.gen_constraints,
+        incoming.gen_constraints,
+        |c| c.name.clone(),
+        policy,
+        &mut errors,
+    );
+    merge_items(
+        "reserve type",
+        &mut input_data.reserve_type,
+        incoming.reserve_type,
+        |r| r.name.clone(),
+        policy,
+        &mut errors,
+    );
+    merge_items(
+        "risk",
+        &mut input_data.risk,
+        incoming.risk,
+        |r| r.parameter.clone(),
+        policy,
+        &mut errors,
+    );
+    if !errors.is_empty() {
+        return Err(errors.join("; "));
+    }
+    Ok(())
+}
+
+/// Merges input data of a model given as JSON into input data.
+///
+/// Input data is left unchanged if merging fails.
+pub fn merge_model(
+    json: &str,
+    prefix: Option<&str>,
+    policy: ConflictPolicy,
+    input_data: &mut BaseInputData,
+) -> MaybeError {
+    let incoming: Model = match serde_json::from_str(json) {
+        Ok(model) => model,
+        Err(error) => return format!("failed to parse model: {}", error).into(),
+    };
+    let mut merged = input_data.clone();
+    match merge_input_data(incoming.input_data, prefix, policy, &mut merged) {
+        Ok(()) => {
+            *input_data = merged;
+            MaybeError::new_ok()
+        }
+        Err(error) => error.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_data_base::{BaseNode, BaseProcess, BaseTopology, Conversion, NodeGroup};
+
+    fn heat_pump_module() -> String {
+        let mut model = Model::default();
+        let mut node = BaseNode::new("heat".into());
+        node.groups.push("heating".into());
+        model.input_data.nodes.push(node);
+        model.input_data.node_groups.push(NodeGroup {
+            name: "heating".into(),
+            members: vec!["heat".into()],
+        });
+        let mut process = BaseProcess::new("hp".into(), Conversion::Unit);
+        process
+            .topos
+            .push(BaseTopology::new("hp".into(), "heat".into()));
+        model.input_data.processes.push(process);
+        serde_json::to_string(&model).unwrap()
+    }
+
+    fn site() -> BaseInputData {
+        let mut input_data = BaseInputData::default();
+        input_data.nodes.push(BaseNode::new("heat".into()));
+        input_data
+    }
+
+    #[test]
+    fn prefixed_merge_rewrites_references() {
+        let mut input_data = site();
+        let result = merge_model(
+            &heat_pump_module(),
+            Some("b1_"),
+            ConflictPolicy::Fail,
+            &mut input_data,
+        );
+        assert!(!result.is_error(), "{:?}", result.message);
+        let names: Vec<&str> = input_data.nodes.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, vec!["heat", "b1_heat"]);
+        let topology = &input_data.processes[0].topos[0];
+        assert_eq!(input_data.processes[0].name, "b1_hp");
+        assert_eq!(
+            (topology.source.as_str(), topology.sink.as_str()),
+            ("b1_hp", "b1_heat")
+        );
+        assert_eq!(input_data.node_groups[0].name, "b1_heating");
+        assert_eq!(input_data.node_groups[0].members, vec!["b1_heat"]);
+        assert_eq!(input_data.nodes[1].groups, vec!["b1_heating"]);
+    }
+
+    #[test]
+    fn conflicts_follow_policy() {
+        let mut input_data = site();
+        let result = merge_model(
+            &heat_pump_module(),
+            None,
+            ConflictPolicy::Fail,
+            &mut input_data,
+        );
+        assert_eq!(
+            result.message.as_deref(),
+            Some("node 'heat' exists already")
+        );
+        assert_eq!(input_data.nodes.len(), 1);
+        assert!(input_data.processes.is_empty());
+        let result = merge_model(
+            &heat_pump_module(),
+            None,
+            ConflictPolicy::Skip,
+            &mut input_data,
+        );
+        assert!(!result.is_error());
+        assert!(input_data.nodes[0].groups.is_empty());
+        assert!(input_data.node_groups[0].members.is_empty());
+        assert_eq!(input_data.processes[0].name, "hp");
+        let mut input_data = site();
+        let result = merge_model(
+            &heat_pump_module(),
+            None,
+            ConflictPolicy::Overwrite,
+            &mut input_data,
+        );
+        assert!(!result.is_error());
+        assert_eq!(input_data.nodes.len(), 1);
+        assert_eq!(input_data.nodes[0].groups, vec!["heating"]);
+        assert_eq!(input_data.node_groups[0].members, vec!["heat"]);
+    }
+
+    #[test]
+    fn overwrite_reconciles_group_members() {
+        let mut input_data = site();
+        input_data.nodes[0].groups.push("storages".into());
+        input_data.node_groups.push(NodeGroup {
+            name: "storages".into(),
+            members: vec!["heat".into()],
+        });
+        let result = merge_model(
+            &heat_pump_module(),
+            None,
+            ConflictPolicy::Overwrite,
+            &mut input_data,
+        );
+        assert!(!result.is_error(), "{:?}", result.message);
+        assert_eq!(input_data.nodes[0].groups, vec!["heating"]);
+        let members: Vec<(&str, &Vec<String>)> = input_data
+            .node_groups
+            .iter()
+            .map(|g| (g.name.as_str(), &g.members))
+            .collect();
+        assert_eq!(
+            members,
+            vec![
+                ("storages", &Vec::new()),
+                ("heating", &vec!["heat".to_string()])
+            ]
+        );
+    }
+}
@@ -1,5 +1,7 @@
+use super::update;
 use super::{MaybeError, ValidationError, ValidationErrors};
-use crate::input_data_base::{BaseNode, BaseNodeDiffusion, Value, ValueInput};
+use crate::input_data_base::{BaseNode, BaseNodeDiffusion, ValueInput};
+use chrono_tz::Tz;
 use juniper::GraphQLInputObject;
 
 #[derive(GraphQLInputObject)]
@@ -10,15 +12,22 @@ pub struct NewNodeDiffusion {
 }
 
 impl NewNodeDiffusion {
-    pub fn to_node_diffusion(self) -> BaseNodeDiffusion {
-        BaseNodeDiffusion {
+    pub fn to_node_diffusion(self, time_zone: Tz) -> Result<BaseNodeDiffusion, Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        let coefficient = match update::convert_values(
+            "coefficient",
+            Some(self.coefficient),
+            time_zone,
+            &mut errors,
+        ) {
+            Some(coefficient) => coefficient,
+            None => return Err(errors),
+        };
+        Ok(BaseNodeDiffusion {
             from_node: self.from_node,
             to_node: self.to_node,
-            coefficient: self.coefficient
-                .into_iter()
-                .map(|v| Value::try_from(v).expect("Could not parse diffusion coefficient"))
-                .collect(),
-        }
+            coefficient,
+        })
     }
 }
 
@@ -26,13 +35,17 @@ pub fn create_node_diffusion(
     new_diffusion: NewNodeDiffusion,
     diffusions: &mut Vec<BaseNodeDiffusion>,
     nodes: &Vec<BaseNode>,
+    time_zone: Tz,
 ) -> ValidationErrors {
     // Validate that from_node and to_node exist and that no diffusion with the same endpoints exists.
     let errors = validate_node_diffusion_creation(&new_diffusion.from_node, &new_diffusion.to_node, diffusions, nodes);
     if !errors.is_empty() {
         return ValidationErrors::from(errors);
     }
-    diffusions.push(new_diffusion.to_node_diffusion());
+    match new_diffusion.to_node_diffusion(time_zone) {
+        Ok(diffusion) => diffusions.push(diffusion),
+        Err(errors) => return ValidationErrors::from(errors),
+    }
     ValidationErrors::default()
 }
 
@@ -67,6 +80,39 @@ fn validate_node_diffusion_creation(
     errors
 }
 
+#[derive(GraphQLInputObject)]
+pub struct NodeDiffusionUpdate {
+    pub coefficient: Option<Vec<ValueInput>>,
+}
+
+pub fn update_node_diffusion(
+    from_node: &str,
+    to_node: &str,
+    update: NodeDiffusionUpdate,
+    diffusions: &mut [BaseNodeDiffusion],
+    time_zone: Tz,
+) -> ValidationErrors {
+    let diffusion = match diffusions
+        .iter_mut()
+        .find(|d| d.from_node == from_node && d.to_node == to_node)
+    {
+        Some(diffusion) => diffusion,
+        None => {
+            return ValidationErrors::from(ValidationError::new(
+                "from_node",
+                "no such node diffusion",
+            ))
+        }
+    };
+    let mut errors = Vec::new();
+    let coefficient = update::convert_values("coefficient", update.coefficient, time_zone, &mut errors);
+    if !errors.is_empty() {
+        return ValidationErrors::from(errors);
+    }
+    update::optional_update(coefficient, &mut diffusion.coefficient);
+    ValidationErrors::default()
+}
+
 pub fn delete_node_diffusion(
     from_node: &str,
     to_node: &str,
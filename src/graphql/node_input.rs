@@ -1,12 +1,13 @@
 use super::delete;
-use super::forecastable;
+use super::update::{self, optional_update};
 use super::{MaybeError, ValidationError, ValidationErrors};
 use crate::input_data::Forecast;
 use crate::input_data_base::{
     BaseForecastable, BaseGenConstraint, BaseInflowBlock, BaseMarket, BaseNode, BaseNodeDiffusion,
-    BaseNodeHistory, BaseProcess, ConstraintFactorType, Delay, NodeGroup, ValueInput, Value, ForecastValue, ForecastValueInput
+    BaseNodeHistory, BaseProcess, ConstraintFactorType, Delay, NodeGroup, ValueInput, ForecastValue, ForecastValueInput
 };
 use crate::scenarios::Scenario;
+use chrono_tz::Tz;
 use juniper::GraphQLInputObject;
 
 #[derive(GraphQLInputObject)]
@@ -20,21 +21,32 @@ pub struct NewNode {
 }
 
 impl NewNode {
-    fn to_node(self, scenarios: &Vec<Scenario>) -> BaseNode {
-        BaseNode {
-            name: self.name,
-            groups: Vec::new(),
-            is_commodity: self.is_commodity,
-            is_market: self.is_market,
-            is_res: self.is_res,
-            state: None,
-            cost: self
-            .cost
-            .into_iter()
-            .map(Value::try_from)
-            .collect::<Result<Vec<Value>, _>>()
-            .expect("Could not parse cost values"),
-            inflow: forecastable::convert_forecast_value_inputs(self.inflow, scenarios),
+    fn to_node(
+        self,
+        scenarios: &Vec<Scenario>,
+        time_zone: Tz,
+    ) -> Result<BaseNode, Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        let cost = update::convert_values("cost", Some(self.cost), time_zone, &mut errors);
+        let inflow = update::convert_forecast_values(
+            "inflow",
+            Some(self.inflow),
+            scenarios,
+            time_zone,
+            &mut errors,
+        );
+        match (cost, inflow) {
+            (Some(cost), Some(inflow)) => Ok(BaseNode {
+                name: self.name,
+                groups: Vec::new(),
+                is_commodity: self.is_commodity,
+                is_market: self.is_market,
+                is_res: self.is_res,
+                state: None,
+                cost,
+                inflow,
+            }),
+            _ => Err(errors),
         }
     }
 }
@@ -44,12 +56,16 @@ pub fn create_node(
     nodes: &mut Vec<BaseNode>,
     processes: &mut Vec<BaseProcess>,
     scenarios: &mut Vec<Scenario>,
+    time_zone: Tz,
 ) -> ValidationErrors {
     let errors = validate_node_creation(&node, nodes, processes);
     if !errors.is_empty() {
         return ValidationErrors::from(errors);
     }
-    nodes.push(node.to_node(scenarios));
+    match node.to_node(scenarios, time_zone) {
+        Ok(node) => nodes.push(node),
+        Err(errors) => return ValidationErrors::from(errors),
+    }
     ValidationErrors::default()
 }
 
@@ -77,6 +93,41 @@ fn validate_node_creation(
     errors
 }
 
+#[derive(GraphQLInputObject)]
+pub struct NodeUpdate {
+    is_commodity: Option<bool>,
+    is_market: Option<bool>,
+    is_res: Option<bool>,
+    cost: Option<Vec<ValueInput>>,
+    inflow: Option<Vec<ForecastValueInput>>,
+}
+
+pub fn update_node(
+    name: &str,
+    update: NodeUpdate,
+    nodes: &mut [BaseNode],
+    scenarios: &Vec<Scenario>,
+    time_zone: Tz,
+) -> ValidationErrors {
+    let node = match nodes.iter_mut().find(|n| n.name == name) {
+        Some(node) => node,
+        None => return ValidationErrors::from(ValidationError::new("name", "no such node")),
+    };
+    let mut errors = Vec::new();
+    let cost = update::convert_values("cost", update.cost, time_zone, &mut errors);
+    let inflow =
+        update::convert_forecast_values("inflow", update.inflow, scenarios, time_zone, &mut errors);
+    if !errors.is_empty() {
+        return ValidationErrors::from(errors);
+    }
+    optional_update(update.is_commodity, &mut node.is_commodity);
+    optional_update(update.is_market, &mut node.is_market);
+    optional_update(update.is_res, &mut node.is_res);
+    optional_update(cost, &mut node.cost);
+    optional_update(inflow, &mut node.inflow);
+    ValidationErrors::default()
+}
+
 pub fn connect_node_inflow_to_temperature_forecast(
     node_name: &str,
     forecast_name: String,
@@ -145,3 +196,37 @@ pub fn delete_node(
 }
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_node_reports_forecast_values_without_all_scenarios() {
+        let mut nodes = vec![BaseNode::new("heat".into())];
+        let scenarios = vec![
+            Scenario::new("s1", 0.5).unwrap(),
+            Scenario::new("s2", 0.5).unwrap(),
+        ];
+        let update = NodeUpdate {
+            is_commodity: None,
+            is_market: None,
+            is_res: Some(true),
+            cost: None,
+            inflow: Some(vec![ForecastValueInput {
+                scenario: Some("s1".into()),
+                constant: Some(-1.0),
+                ..ForecastValueInput::default()
+            }]),
+        };
+        let errors = update_node("heat", update, &mut nodes, &scenarios, Tz::UTC);
+        assert_eq!(errors.errors.len(), 1);
+        assert_eq!(errors.errors[0].field, "inflow");
+        assert_eq!(
+            errors.errors[0].message,
+            "missing forecast value for scenario s2"
+        );
+        assert!(!nodes[0].is_res);
+        assert!(nodes[0].inflow.is_empty());
+    }
+}
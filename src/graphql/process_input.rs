@@ -1,9 +1,11 @@
 use super::delete;
+use super::update::{self, optional_update};
 use super::{MaybeError, ValidationError, ValidationErrors};
 use crate::input_data_base::{
     BaseConFactor, BaseGenConstraint, BaseNode, BaseProcess, ConstraintFactorType, Conversion,
-    ProcessGroup, ValueInput, Value, PointInput,
+    ProcessGroup, ValueInput, PointInput,
 };
+use chrono_tz::Tz;
 use juniper::GraphQLInputObject;
 
 #[derive(GraphQLInputObject)]
@@ -47,12 +49,19 @@ pub struct NewProcess {
 }
 
 impl NewProcess {
-    fn to_process(self) -> BaseProcess {
-        BaseProcess {
+    fn to_process(self, time_zone: Tz) -> Result<BaseProcess, Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        let cf = update::convert_values("cf", Some(self.cf), time_zone, &mut errors);
+        let eff_ts = update::convert_values("eff_ts", Some(self.eff_ts), time_zone, &mut errors);
+        let (cf, eff_ts) = match (cf, eff_ts) {
+            (Some(cf), Some(eff_ts)) => (cf, eff_ts),
+            _ => return Err(errors),
+        };
+        Ok(BaseProcess {
             name: self.name,
             groups: Vec::new(),
             conversion: self.conversion,
-            is_cf: !self.cf.is_empty(),
+            is_cf: !cf.is_empty(),
             is_cf_fix: self.is_cf_fix,
             is_online: self.is_online,
             is_res: self.is_res,
@@ -67,20 +76,10 @@ impl NewProcess {
             initial_state: self.initial_state,
             is_scenario_independent: self.is_scenario_independent,
             topos: Vec::new(),
-            cf: self
-            .cf
-            .into_iter()
-            .map(Value::try_from)
-            .collect::<Result<Vec<Value>, _>>()
-            .expect("Could not parse cost values"),
-            eff_ts: self
-            .eff_ts
-            .into_iter()
-            .map(Value::try_from)
-            .collect::<Result<Vec<Value>, _>>()
-            .expect("Could not parse cost values"),
+            cf,
+            eff_ts,
             eff_ops_fun: self.eff_ops_fun.into_iter().map(Into::into).collect(),
-        }
+        })
     }
 }
 
@@ -88,12 +87,16 @@ pub fn create_process(
     process: NewProcess,
     processes: &mut Vec<BaseProcess>,
     nodes: &mut Vec<BaseNode>,
+    time_zone: Tz,
 ) -> ValidationErrors {
     let errors = validate_process_creation(&process, processes, nodes);
     if !errors.is_empty() {
         return ValidationErrors::from(errors);
     }
-    processes.push(process.to_process());
+    match process.to_process(time_zone) {
+        Ok(process) => processes.push(process),
+        Err(errors) => return ValidationErrors::from(errors),
+    }
     ValidationErrors::default()
 }
 
@@ -118,34 +121,122 @@ fn validate_process_creation(
             "a node with the same name exists",
         ));
     }
-    if process.load_min < 0.0 || process.load_min > 1.0 {
+    validate_loads_and_online_times(
+        process.load_min,
+        process.load_max,
+        process.min_online,
+        process.max_online,
+        process.min_offline,
+        process.max_offline,
+        &mut errors,
+    );
+    errors
+}
+
+fn validate_loads_and_online_times(
+    load_min: f64,
+    load_max: f64,
+    min_online: f64,
+    max_online: f64,
+    min_offline: f64,
+    max_offline: f64,
+    errors: &mut Vec<ValidationError>,
+) {
+    if !(0.0..=1.0).contains(&load_min) {
         errors.push(ValidationError::new("load_min", "should be in [0, 1]"))
     }
-    if process.load_min > process.load_max {
+    if load_min > load_max {
         errors.push(ValidationError::new("load_min", "greater than load_max"));
     }
-    if process.load_max < 0.0 || process.load_max > 1.0 {
+    if !(0.0..=1.0).contains(&load_max) {
         errors.push(ValidationError::new("load_max", "should be in [0, 1]"));
     }
-    if process.min_online > process.max_online
-    && process.min_online > 0.0
-    && process.max_online > 0.0
-    {
+    if min_online > max_online && min_online > 0.0 && max_online > 0.0 {
         errors.push(ValidationError::new(
             "min_online",
             "greater than max_online",
         ));
     }
-    if process.min_offline > process.max_offline
-    && process.min_offline > 0.0
-    && process.max_offline > 0.0
-    {
+    if min_offline > max_offline && min_offline > 0.0 && max_offline > 0.0 {
         errors.push(ValidationError::new(
             "min_offline",
             "greater than max_offline",
         ));
     }
-    errors
+}
+
+#[derive(Default, GraphQLInputObject)]
+pub struct ProcessUpdate {
+    conversion: Option<Conversion>,
+    is_cf_fix: Option<bool>,
+    is_online: Option<bool>,
+    is_res: Option<bool>,
+    eff: Option<f64>,
+    load_min: Option<f64>,
+    load_max: Option<f64>,
+    start_cost: Option<f64>,
+    min_online: Option<f64>,
+    max_online: Option<f64>,
+    min_offline: Option<f64>,
+    max_offline: Option<f64>,
+    initial_state: Option<bool>,
+    is_scenario_independent: Option<bool>,
+    cf: Option<Vec<ValueInput>>,
+    eff_ts: Option<Vec<ValueInput>>,
+    eff_ops_fun: Option<Vec<PointInput>>,
+}
+
+pub fn update_process(
+    name: &str,
+    update: ProcessUpdate,
+    processes: &mut [BaseProcess],
+    time_zone: Tz,
+) -> ValidationErrors {
+    let process = match processes.iter_mut().find(|p| p.name == name) {
+        Some(process) => process,
+        None => return ValidationErrors::from(ValidationError::new("name", "no such process")),
+    };
+    let mut errors = Vec::new();
+    validate_loads_and_online_times(
+        update.load_min.unwrap_or(process.load_min),
+        update.load_max.unwrap_or(process.load_max),
+        update.min_online.unwrap_or(process.min_online),
+        update.max_online.unwrap_or(process.max_online),
+        update.min_offline.unwrap_or(process.min_offline),
+        update.max_offline.unwrap_or(process.max_offline),
+        &mut errors,
+    );
+    let cf = update::convert_values("cf", update.cf, time_zone, &mut errors);
+    let eff_ts = update::convert_values("eff_ts", update.eff_ts, time_zone, &mut errors);
+    if !errors.is_empty() {
+        return ValidationErrors::from(errors);
+    }
+    optional_update(update.conversion, &mut process.conversion);
+    optional_update(update.is_cf_fix, &mut process.is_cf_fix);
+    optional_update(update.is_online, &mut process.is_online);
+    optional_update(update.is_res, &mut process.is_res);
+    optional_update(update.eff, &mut process.eff);
+    optional_update(update.load_min, &mut process.load_min);
+    optional_update(update.load_max, &mut process.load_max);
+    optional_update(update.start_cost, &mut process.start_cost);
+    optional_update(update.min_online, &mut process.min_online);
+    optional_update(update.max_online, &mut process.max_online);
+    optional_update(update.min_offline, &mut process.min_offline);
+    optional_update(update.max_offline, &mut process.max_offline);
+    optional_update(update.initial_state, &mut process.initial_state);
+    optional_update(
+        update.is_scenario_independent,
+        &mut process.is_scenario_independent,
+    );
+    if let Some(cf) = cf {
+        process.is_cf = !cf.is_empty();
+        process.cf = cf;
+    }
+    optional_update(eff_ts, &mut process.eff_ts);
+    if let Some(eff_ops_fun) = update.eff_ops_fun {
+        process.eff_ops_fun = eff_ops_fun.into_iter().map(Into::into).collect();
+    }
+    ValidationErrors::default()
 }
 
 pub fn delete_process(
@@ -176,4 +267,58 @@ pub fn process_con_factor(con_factor: &BaseConFactor, process_name: &str) -> boo
         }
         ConstraintFactorType::State => false,
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_process_changes_given_fields_only() {
+        let mut process = BaseProcess::new("boiler".into(), Conversion::Unit);
+        process.load_max = 1.0;
+        process.eff = 0.9;
+        let mut processes = vec![process];
+        let update = ProcessUpdate {
+            load_min: Some(0.2),
+            cf: Some(vec![ValueInput {
+                scenario: None,
+                constant: Some(0.5),
+                series: None,
+                time_stamps: None,
+                resampling: None,
+            }]),
+            ..ProcessUpdate::default()
+        };
+        let errors = update_process("boiler", update, &mut processes, Tz::UTC);
+        assert!(errors.errors.is_empty());
+        assert_eq!(processes[0].load_min, 0.2);
+        assert_eq!(processes[0].load_max, 1.0);
+        assert_eq!(processes[0].eff, 0.9);
+        assert!(processes[0].is_cf);
+    }
+
+    #[test]
+    fn update_process_validates_against_current_values() {
+        let mut process = BaseProcess::new("boiler".into(), Conversion::Unit);
+        process.load_max = 0.5;
+        let mut processes = vec![process];
+        let update = ProcessUpdate {
+            load_min: Some(0.8),
+            ..ProcessUpdate::default()
+        };
+        let errors = update_process("boiler", update, &mut processes, Tz::UTC);
+        assert_eq!(errors.errors.len(), 1);
+        assert_eq!(errors.errors[0].field, "load_min");
+        assert_eq!(errors.errors[0].message, "greater than load_max");
+        assert_eq!(processes[0].load_min, 0.0);
+    }
+
+    #[test]
+    fn update_process_fails_when_process_does_not_exist() {
+        let mut processes = Vec::new();
+        let errors = update_process("boiler", ProcessUpdate::default(), &mut processes, Tz::UTC);
+        assert_eq!(errors.errors.len(), 1);
+        assert_eq!(errors.errors[0].message, "no such process");
+    }
+}
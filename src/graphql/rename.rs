@@ -0,0 +1,485 @@
+use super::MaybeError;
+use crate::input_data_base::{
+    BaseInputData, ConstraintFactorType, ForecastValue, MarketType, Value,
+};
+
+fn validate_new_name(new_name: &str, taken: impl Fn(&str) -> Option<&'static str>) -> MaybeError {
+    if new_name.is_empty() {
+        return "name is empty".into();
+    }
+    if let Some(type_name) = taken(new_name) {
+        return format!("a {} with the same name exists", type_name).into();
+    }
+    MaybeError::new_ok()
+}
+
+fn replace_name(target: &mut String, old_name: &str, new_name: &str) {
+    if target == old_name {
+        *target = new_name.to_string();
+    }
+}
+
+fn replace_names(targets: &mut [String], old_name: &str, new_name: &str) {
+    for target in targets {
+        replace_name(target, old_name, new_name);
+    }
+}
+
+fn node_or_process_exists(name: &str, input_data: &BaseInputData) -> Option<&'static str> {
+    if input_data.nodes.iter().any(|n| n.name == name) {
+        return Some("node");
+    }
+    if input_data.processes.iter().any(|p| p.name == name) {
+        return Some("process");
+    }
+    None
+}
+
+pub fn rename_node(old_name: &str, new_name: &str, input_data: &mut BaseInputData) -> MaybeError {
+    let maybe_error = validate_new_name(new_name, |name| node_or_process_exists(name, input_data));
+    if maybe_error.is_error() {
+        return maybe_error;
+    }
+    let node = match input_data.nodes.iter_mut().find(|n| n.name == old_name) {
+        Some(node) => node,
+        None => return "no such node".into(),
+    };
+    node.name = new_name.to_string();
+    for group in &mut input_data.node_groups {
+        replace_names(&mut group.members, old_name, new_name);
+    }
+    for process in &mut input_data.processes {
+        for topology in &mut process.topos {
+            replace_name(&mut topology.source, old_name, new_name);
+            replace_name(&mut topology.sink, old_name, new_name);
+        }
+    }
+    for market in &mut input_data.markets {
+        if !matches!(market.m_type, MarketType::Reserve) {
+            replace_name(&mut market.node, old_name, new_name);
+        }
+    }
+    for diffusion in &mut input_data.node_diffusion {
+        replace_name(&mut diffusion.from_node, old_name, new_name);
+        replace_name(&mut diffusion.to_node, old_name, new_name);
+    }
+    for delay in &mut input_data.node_delay {
+        replace_name(&mut delay.from_node, old_name, new_name);
+        replace_name(&mut delay.to_node, old_name, new_name);
+    }
+    for history in &mut input_data.node_histories {
+        replace_name(&mut history.node, old_name, new_name);
+    }
+    for inflow_block in &mut input_data.inflow_blocks {
+        replace_name(&mut inflow_block.node, old_name, new_name);
+    }
+    for constraint in &mut input_data.gen_constraints {
+        for factor in &mut constraint.factors {
+            match factor.var_type {
+                ConstraintFactorType::Flow => {
+                    if let Some(ref mut identifier) = factor.var_tuple.identifier {
+                        replace_name(identifier, old_name, new_name);
+                    }
+                }
+                ConstraintFactorType::State => {
+                    replace_name(&mut factor.var_tuple.entity, old_name, new_name)
+                }
+                ConstraintFactorType::Online => (),
+            }
+        }
+    }
+    MaybeError::new_ok()
+}
+
+pub fn rename_process(
+    old_name: &str,
+    new_name: &str,
+    input_data: &mut BaseInputData,
+) -> MaybeError {
+    let maybe_error = validate_new_name(new_name, |name| node_or_process_exists(name, input_data));
+    if maybe_error.is_error() {
+        return maybe_error;
+    }
+    let process = match input_data.processes.iter_mut().find(|p| p.name == old_name) {
+        Some(process) => process,
+        None => return "no such process".into(),
+    };
+    process.name = new_name.to_string();
+    for topology in &mut process.topos {
+        replace_name(&mut topology.source, old_name, new_name);
+        replace_name(&mut topology.sink, old_name, new_name);
+    }
+    for group in &mut input_data.process_groups {
+        replace_names(&mut group.members, old_name, new_name);
+    }
+    for constraint in &mut input_data.gen_constraints {
+        for factor in &mut constraint.factors {
+            match factor.var_type {
+                ConstraintFactorType::Flow | ConstraintFactorType::Online => {
+                    replace_name(&mut factor.var_tuple.entity, old_name, new_name)
+                }
+                ConstraintFactorType::State => (),
+            }
+        }
+    }
+    MaybeError::new_ok()
+}
+
+pub fn rename_group(old_name: &str, new_name: &str, input_data: &mut BaseInputData) -> MaybeError {
+    let maybe_error = validate_new_name(new_name, |name| {
+        if input_data.node_groups.iter().any(|g| g.name == name) {
+            return Some("node group");
+        }
+        if input_data.process_groups.iter().any(|g| g.name == name) {
+            return Some("process group");
+        }
+        None
+    });
+    if maybe_error.is_error() {
+        return maybe_error;
+    }
+    if let Some(group) = input_data
+        .node_groups
+        .iter_mut()
+        .find(|g| g.name == old_name)
+    {
+        group.name = new_name.to_string();
+        for node in &mut input_data.nodes {
+            replace_names(&mut node.groups, old_name, new_name);
+        }
+        for market in &mut input_data.markets {
+            if matches!(market.m_type, MarketType::Reserve) {
+                replace_name(&mut market.node, old_name, new_name);
+            }
+        }
+    } else if let Some(group) = input_data
+        .process_groups
+        .iter_mut()
+        .find(|g| g.name == old_name)
+    {
+        group.name = new_name.to_string();
+        for process in &mut input_data.processes {
+            replace_names(&mut process.groups, old_name, new_name);
+        }
+        for market in &mut input_data.markets {
+            replace_name(&mut market.process_group, old_name, new_name);
+        }
+    } else {
+        return "no such group".into();
+    }
+    MaybeError::new_ok()
+}
+
+pub fn rename_market(old_name: &str, new_name: &str, input_data: &mut BaseInputData) -> MaybeError {
+    let maybe_error = validate_new_name(new_name, |name| {
+        input_data
+            .markets
+            .iter()
+            .any(|m| m.name == name)
+            .then_some("market")
+    });
+    if maybe_error.is_error() {
+        return maybe_error;
+    }
+    match input_data.markets.iter_mut().find(|m| m.name == old_name) {
+        Some(market) => market.name = new_name.to_string(),
+        None => return "no such market".into(),
+    }
+    MaybeError::new_ok()
+}
+
+fn rename_value_scenarios(values: &mut [Value], old_name: &str, new_name: &str) {
+    for value in values {
+        if let Some(ref mut scenario) = value.scenario {
+            replace_name(scenario, old_name, new_name);
+        }
+    }
+}
+
+fn rename_forecast_value_scenarios(values: &mut [ForecastValue], old_name: &str, new_name: &str) {
+    for value in values {
+        if let Some(ref mut scenario) = value.scenario {
+            replace_name(scenario, old_name, new_name);
+        }
+    }
+}
+
+pub fn rename_scenario(
+    old_name: &str,
+    new_name: &str,
+    input_data: &mut BaseInputData,
+) -> MaybeError {
+    let maybe_error = validate_new_name(new_name, |name| {
+        input_data
+            .scenarios
+            .iter()
+            .any(|s| s.name() == name)
+            .then_some("scenario")
+    });
+    if maybe_error.is_error() {
+        return maybe_error;
+    }
+    match input_data
+        .scenarios
+        .iter_mut()
+        .find(|s| s.name() == old_name)
+    {
+        Some(scenario) => {
+            if let Err(error) = scenario.set_name(new_name) {
+                return error.into();
+            }
+        }
+        None => return "no such scenario".into(),
+    }
+    replace_name(
+        &mut input_data.setup.common_scenario_name,
+        old_name,
+        new_name,
+    );
+    for node in &mut input_data.nodes {
+        rename_value_scenarios(&mut node.cost, old_name, new_name);
+        rename_forecast_value_scenarios(&mut node.inflow, old_name, new_name);
+    }
+    for process in &mut input_data.processes {
+        rename_value_scenarios(&mut process.cf, old_name, new_name);
+        rename_value_scenarios(&mut process.eff_ts, old_name, new_name);
+        for topology in &mut process.topos {
+            rename_value_scenarios(&mut topology.cap_ts, old_name, new_name);
+        }
+    }
+    for market in &mut input_data.markets {
+        rename_value_scenarios(&mut market.realisation, old_name, new_name);
+        rename_value_scenarios(&mut market.reserve_activation_price, old_name, new_name);
+        rename_forecast_value_scenarios(&mut market.price, old_name, new_name);
+        rename_forecast_value_scenarios(&mut market.up_price, old_name, new_name);
+        rename_forecast_value_scenarios(&mut market.down_price, old_name, new_name);
+        if let Some(ref mut price_scenarios) = market.price_scenarios {
+            for offset in &mut price_scenarios.offsets {
+                replace_name(&mut offset.scenario, old_name, new_name);
+            }
+        }
+    }
+    for diffusion in &mut input_data.node_diffusion {
+        rename_value_scenarios(&mut diffusion.coefficient, old_name, new_name);
+    }
+    for history in &mut input_data.node_histories {
+        for step in &mut history.steps {
+            replace_name(&mut step.scenario, old_name, new_name);
+        }
+    }
+    for inflow_block in &mut input_data.inflow_blocks {
+        rename_value_scenarios(&mut inflow_block.data, old_name, new_name);
+    }
+    for constraint in &mut input_data.gen_constraints {
+        rename_value_scenarios(&mut constraint.constant, old_name, new_name);
+        for factor in &mut constraint.factors {
+            rename_value_scenarios(&mut factor.data, old_name, new_name);
+        }
+    }
+    MaybeError::new_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_data_base::{
+        BaseConFactor, BaseGenConstraint, BaseMarket, BaseNode, BaseNodeDiffusion, BaseProcess,
+        BaseTopology, ConstraintType, Conversion, NamedGroup, NodeGroup, ProcessGroup, ValueInput,
+        VariableId,
+    };
+    use crate::scenarios::prices::{OffsetType, PriceOffset, PriceScenarios};
+    use crate::scenarios::Scenario;
+
+    fn make_input_data() -> BaseInputData {
+        let mut heat = BaseNode::new("heat".into());
+        heat.groups.push("heat nodes".into());
+        heat.cost.push(
+            Value::try_from(ValueInput {
+                scenario: Some("s1".into()),
+                constant: Some(2.0),
+                series: None,
+                time_stamps: None,
+                resampling: None,
+            })
+            .unwrap(),
+        );
+        let mut node_group = NodeGroup::new("heat nodes".into());
+        node_group.members.push("heat".into());
+        let mut process = BaseProcess::new("pump".into(), Conversion::Unit);
+        process.groups.push("pumps".into());
+        process
+            .topos
+            .push(BaseTopology::new("elc".into(), "pump".into()));
+        process
+            .topos
+            .push(BaseTopology::new("pump".into(), "heat".into()));
+        let mut process_group = ProcessGroup::new("pumps".into());
+        process_group.members.push("pump".into());
+        BaseInputData {
+            scenarios: vec![Scenario::new("s1", 1.0).unwrap()],
+            nodes: vec![heat, BaseNode::new("elc".into())],
+            node_groups: vec![node_group],
+            processes: vec![process],
+            process_groups: vec![process_group],
+            node_diffusion: vec![BaseNodeDiffusion {
+                from_node: "heat".into(),
+                to_node: "elc".into(),
+                coefficient: Vec::new(),
+            }],
+            gen_constraints: vec![BaseGenConstraint {
+                name: "c1".into(),
+                gc_type: ConstraintType::LessThan,
+                is_setpoint: false,
+                penalty: 0.0,
+                factors: vec![BaseConFactor {
+                    var_type: ConstraintFactorType::Flow,
+                    var_tuple: VariableId {
+                        entity: "pump".into(),
+                        identifier: Some("heat".into()),
+                    },
+                    data: Vec::new(),
+                }],
+                constant: Vec::new(),
+            }],
+            ..BaseInputData::default()
+        }
+    }
+
+    #[test]
+    fn rename_node_updates_references() {
+        let mut input_data = make_input_data();
+        let maybe_error = rename_node("heat", "district heat", &mut input_data);
+        assert!(maybe_error.message.is_none());
+        assert_eq!(input_data.nodes[0].name, "district heat");
+        assert_eq!(
+            input_data.node_groups[0].members,
+            vec!["district heat".to_string()]
+        );
+        assert_eq!(input_data.processes[0].topos[1].sink, "district heat");
+        assert_eq!(input_data.node_diffusion[0].from_node, "district heat");
+        assert_eq!(
+            input_data.gen_constraints[0].factors[0]
+                .var_tuple
+                .identifier,
+            Some("district heat".to_string())
+        );
+    }
+
+    #[test]
+    fn rename_node_refuses_name_of_existing_process() {
+        let mut input_data = make_input_data();
+        let maybe_error = rename_node("heat", "pump", &mut input_data);
+        assert_eq!(
+            maybe_error.message,
+            Some("a process with the same name exists".to_string())
+        );
+        assert_eq!(input_data.nodes[0].name, "heat");
+    }
+
+    #[test]
+    fn rename_process_updates_references() {
+        let mut input_data = make_input_data();
+        let maybe_error = rename_process("pump", "heat pump", &mut input_data);
+        assert!(maybe_error.message.is_none());
+        let process = &input_data.processes[0];
+        assert_eq!(process.name, "heat pump");
+        assert_eq!(process.topos[0].sink, "heat pump");
+        assert_eq!(process.topos[1].source, "heat pump");
+        assert_eq!(
+            input_data.process_groups[0].members,
+            vec!["heat pump".to_string()]
+        );
+        assert_eq!(
+            input_data.gen_constraints[0].factors[0].var_tuple.entity,
+            "heat pump"
+        );
+    }
+
+    #[test]
+    fn rename_group_updates_members_and_markets() {
+        let mut input_data = make_input_data();
+        input_data.markets.push(BaseMarket {
+            name: "npe".into(),
+            m_type: MarketType::Energy,
+            node: "elc".into(),
+            process_group: "pumps".into(),
+            direction: None,
+            realisation: Vec::new(),
+            reserve_type: None,
+            is_bid: false,
+            is_limited: false,
+            min_bid: 0.0,
+            max_bid: 0.0,
+            fee: 0.0,
+            price: Vec::new(),
+            up_price: Vec::new(),
+            down_price: Vec::new(),
+            reserve_activation_price: Vec::new(),
+            fixed: Vec::new(),
+            price_unit: None,
+            price_scenarios: None,
+        });
+        let maybe_error = rename_group("pumps", "heat pumps", &mut input_data);
+        assert!(maybe_error.message.is_none());
+        assert_eq!(input_data.process_groups[0].name, "heat pumps");
+        assert_eq!(
+            input_data.processes[0].groups,
+            vec!["heat pumps".to_string()]
+        );
+        assert_eq!(input_data.markets[0].process_group, "heat pumps");
+        let maybe_error = rename_group("heat pumps", "heat nodes", &mut input_data);
+        assert_eq!(
+            maybe_error.message,
+            Some("a node group with the same name exists".to_string())
+        );
+    }
+
+    #[test]
+    fn rename_scenario_updates_values() {
+        let mut input_data = make_input_data();
+        input_data.markets.push(BaseMarket {
+            name: "npe".into(),
+            m_type: MarketType::Energy,
+            node: "elc".into(),
+            process_group: "pumps".into(),
+            direction: None,
+            realisation: Vec::new(),
+            reserve_type: None,
+            is_bid: false,
+            is_limited: false,
+            min_bid: 0.0,
+            max_bid: 0.0,
+            fee: 0.0,
+            price: Vec::new(),
+            up_price: Vec::new(),
+            down_price: Vec::new(),
+            reserve_activation_price: Vec::new(),
+            fixed: Vec::new(),
+            price_unit: None,
+            price_scenarios: Some(PriceScenarios {
+                offsets: vec![PriceOffset {
+                    scenario: "s1".into(),
+                    offset_type: OffsetType::Additive,
+                    value: 10.0,
+                }],
+                ..PriceScenarios::default()
+            }),
+        });
+        let maybe_error = rename_scenario("s1", "base", &mut input_data);
+        assert!(maybe_error.message.is_none());
+        assert_eq!(input_data.scenarios[0].name(), "base");
+        assert_eq!(
+            input_data.nodes[0].cost[0].scenario,
+            Some("base".to_string())
+        );
+        assert_eq!(
+            input_data.markets[0]
+                .price_scenarios
+                .as_ref()
+                .unwrap()
+                .offsets[0]
+                .scenario,
+            "base"
+        );
+    }
+}
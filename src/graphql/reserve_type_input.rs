@@ -1,5 +1,6 @@
 use juniper::GraphQLInputObject;
 use super::delete;
+use super::update::optional_update;
 use super::{MaybeError, ValidationError, ValidationErrors};
 
 use crate::input_data_base::ReserveType;
@@ -36,6 +37,24 @@ fn validate_reserve_type_creation(reserve_type: &NewReserveType) -> Vec<Validati
     errors
 }
 
+#[derive(GraphQLInputObject, Debug, Clone)]
+pub struct ReserveTypeUpdate {
+    pub ramp_rate: Option<f64>,
+}
+
+pub fn update_reserve_type(
+    name: &str,
+    update: ReserveTypeUpdate,
+    reserve_types: &mut [ReserveType],
+) -> ValidationErrors {
+    let reserve_type = match reserve_types.iter_mut().find(|r| r.name == name) {
+        Some(reserve_type) => reserve_type,
+        None => return ValidationErrors::from(ValidationError::new("name", "no such reserve type")),
+    };
+    optional_update(update.ramp_rate, &mut reserve_type.ramp_rate);
+    ValidationErrors::default()
+}
+
 pub fn _delete_reserve_type(parameter: &str, reserve_types: &mut Vec<ReserveType>) -> MaybeError {
     delete::delete_named(parameter, reserve_types)
 }
@@ -1,4 +1,5 @@
 use super::delete;
+use super::update::optional_update;
 use super::{MaybeError, ValidationError, ValidationErrors};
 use crate::input_data_base::Risk;
 use juniper::GraphQLInputObject;
@@ -35,6 +36,20 @@ fn validate_risk_creation(risk: &NewRisk) -> Vec<ValidationError> {
     errors
 }
 
+#[derive(GraphQLInputObject)]
+pub struct RiskUpdate {
+    value: Option<f64>,
+}
+
+pub fn update_risk(parameter: &str, update: RiskUpdate, risks: &mut [Risk]) -> ValidationErrors {
+    let risk = match risks.iter_mut().find(|r| r.parameter == parameter) {
+        Some(risk) => risk,
+        None => return ValidationErrors::from(ValidationError::new("parameter", "no such risk")),
+    };
+    optional_update(update.value, &mut risk.value);
+    ValidationErrors::default()
+}
+
 pub fn delete_risk(parameter: &str, risks: &mut Vec<Risk>) -> MaybeError {
     delete::delete_named(parameter, risks)
 }
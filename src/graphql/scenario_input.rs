@@ -1,6 +1,8 @@
 use super::delete;
 use super::MaybeError;
-use crate::input_data_base::BaseNodeHistory;
+use crate::input_data_base::{BaseMarket, BaseNodeHistory};
+use crate::scenarios::ensemble::WeatherEnsemble;
+use crate::scenarios::reduction::ScenarioReduction;
 use crate::scenarios::Scenario;
 
 pub fn create_scenario(name: String, weight: f64, scenarios: &mut Vec<Scenario>) -> MaybeError {
@@ -23,6 +25,7 @@ pub fn delete_scenario(
     name: &str,
     scenarios: &mut Vec<Scenario>,
     node_histories: &mut Vec<BaseNodeHistory>,
+    markets: &mut [BaseMarket],
 ) -> MaybeError {
     let maybe_error = delete::delete_named(name, scenarios);
     if maybe_error.is_error() {
@@ -31,5 +34,100 @@ pub fn delete_scenario(
     for history in node_histories {
         history.steps.retain(|s| s.scenario != name);
     }
+    for price_scenarios in markets
+        .iter_mut()
+        .filter_map(|m| m.price_scenarios.as_mut())
+    {
+        price_scenarios.offsets.retain(|o| o.scenario != name);
+    }
     MaybeError::new_ok()
 }
+
+pub fn set_weather_ensemble(
+    quantiles: Vec<f64>,
+    weather_ensemble: &mut Option<WeatherEnsemble>,
+) -> MaybeError {
+    match WeatherEnsemble::new(quantiles) {
+        Ok(ensemble) => {
+            *weather_ensemble = Some(ensemble);
+            MaybeError::new_ok()
+        }
+        Err(error) => error.into(),
+    }
+}
+
+pub fn set_scenario_reduction(
+    scenario_count: i32,
+    scenario_reduction: &mut Option<ScenarioReduction>,
+) -> MaybeError {
+    match ScenarioReduction::new(scenario_count) {
+        Ok(reduction) => {
+            *scenario_reduction = Some(reduction);
+            MaybeError::new_ok()
+        }
+        Err(error) => error.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_data_base::{MarketType, Series};
+    use crate::scenarios::prices::{OffsetType, PriceOffset, PriceScenarios};
+
+    #[test]
+    fn delete_scenario_removes_dependent_items() {
+        let mut scenarios = vec![
+            Scenario::new("s1", 1.0).unwrap(),
+            Scenario::new("s2", 1.0).unwrap(),
+        ];
+        let mut node_histories = vec![BaseNodeHistory::new("tank".into())];
+        for scenario in ["s1", "s2"] {
+            node_histories[0].steps.push(Series {
+                scenario: scenario.into(),
+                durations: Vec::new(),
+                values: Vec::new(),
+            });
+        }
+        let offset = |scenario: &str| PriceOffset {
+            scenario: scenario.into(),
+            offset_type: OffsetType::Multiplicative,
+            value: 1.2,
+        };
+        let mut markets = vec![BaseMarket {
+            name: "npe".into(),
+            m_type: MarketType::Energy,
+            node: "elc".into(),
+            process_group: "p1".into(),
+            direction: None,
+            realisation: Vec::new(),
+            reserve_type: None,
+            is_bid: false,
+            is_limited: false,
+            min_bid: 0.0,
+            max_bid: 0.0,
+            fee: 0.0,
+            price: Vec::new(),
+            up_price: Vec::new(),
+            down_price: Vec::new(),
+            reserve_activation_price: Vec::new(),
+            fixed: Vec::new(),
+            price_unit: None,
+            price_scenarios: Some(PriceScenarios {
+                offsets: vec![offset("s1"), offset("s2")],
+                ..PriceScenarios::default()
+            }),
+        }];
+        let maybe_error = delete_scenario("s1", &mut scenarios, &mut node_histories, &mut markets);
+        assert!(maybe_error.message.is_none());
+        assert_eq!(scenarios.len(), 1);
+        assert_eq!(node_histories[0].steps.len(), 1);
+        assert_eq!(node_histories[0].steps[0].scenario, "s2");
+        assert_eq!(
+            markets[0].price_scenarios.as_ref().unwrap().offsets,
+            vec![offset("s2")]
+        );
+        let maybe_error = delete_scenario("s1", &mut scenarios, &mut node_histories, &mut markets);
+        assert!(maybe_error.is_error());
+    }
+}
@@ -1,8 +1,10 @@
 use std::borrow::BorrowMut;
 
+use super::update::optional_update;
 use super::{ValidationError, ValidationErrors};
 use crate::input_data::State;
 use crate::input_data_base::BaseNode;
+use crate::units::Unit;
 use juniper::GraphQLInputObject;
 
 #[derive(GraphQLInputObject)]
@@ -17,6 +19,7 @@ pub struct NewState {
     is_temp: bool,
     t_e_conversion: f64,
     residual_value: f64,
+    unit: Option<Unit>,
 }
 
 impl NewState {
@@ -32,6 +35,7 @@ impl NewState {
             is_temp: self.is_temp,
             t_e_conversion: self.t_e_conversion,
             residual_value: self.residual_value,
+            unit: self.unit,
         }
     }
 }
@@ -95,6 +99,7 @@ pub struct StateUpdate {
     is_temp: Option<bool>,
     t_e_conversion: Option<f64>,
     residual_value: Option<f64>,
+    unit: Option<Unit>,
 }
 
 impl StateUpdate {
@@ -115,12 +120,9 @@ impl StateUpdate {
         optional_update(self.is_temp, &mut state.is_temp);
         optional_update(self.t_e_conversion, &mut state.t_e_conversion);
         optional_update(self.residual_value, &mut state.residual_value);
-    }
-}
-
-fn optional_update<T>(source: Option<T>, target: &mut T) {
-    if let Some(x) = source {
-        *target = x;
+        if self.unit.is_some() {
+            state.unit = self.unit;
+        }
     }
 }
 
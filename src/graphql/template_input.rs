@@ -0,0 +1,139 @@
+use super::merge::{self, ConflictPolicy};
+use super::MaybeError;
+use crate::model::template::{self, ParameterValue, Template};
+use crate::model::validation::validate_model;
+use crate::model::Model;
+use juniper::GraphQLInputObject;
+
+#[derive(GraphQLInputObject)]
+#[graphql(
+    description = "Value of a template parameter. Exactly one of number and text must be given."
+)]
+pub struct TemplateParameterInput {
+    name: String,
+    number: Option<f64>,
+    text: Option<String>,
+}
+
+impl TemplateParameterInput {
+    fn into_parameter(self) -> Result<(String, ParameterValue), String> {
+        let value = match (self.number, self.text) {
+            (Some(number), None) => ParameterValue::Number(number),
+            (None, Some(text)) => ParameterValue::Text(text),
+            _ => {
+                return Err(format!(
+                    "parameter '{}' must have either a number or a text value",
+                    self.name
+                ))
+            }
+        };
+        Ok((self.name, value))
+    }
+}
+
+fn instantiate_into(
+    template: &Template,
+    parameters: Vec<TemplateParameterInput>,
+    model: &mut Model,
+) -> Result<(), String> {
+    let parameters = parameters
+        .into_iter()
+        .map(TemplateParameterInput::into_parameter)
+        .collect::<Result<Vec<_>, String>>()?;
+    let input_data = template::instantiate(template, &parameters)?;
+    let mut instantiated = model.clone();
+    merge::merge_input_data(
+        input_data,
+        None,
+        ConflictPolicy::Fail,
+        &mut instantiated.input_data,
+    )?;
+    let existing_problems = validate_model(model);
+    let new_problems: Vec<String> = validate_model(&instantiated)
+        .into_iter()
+        .filter(|problem| !existing_problems.contains(problem))
+        .map(|problem| format!("{}: {}", problem.path, problem.message))
+        .collect();
+    if !new_problems.is_empty() {
+        return Err(format!(
+            "instantiated template is invalid: {}",
+            new_problems.join("; ")
+        ));
+    }
+    *model = instantiated;
+    Ok(())
+}
+
+/// Expands template into the model. The model is left unchanged if the template
+/// cannot be instantiated or if it would introduce new validation problems.
+pub fn instantiate_template(
+    template: &Template,
+    parameters: Vec<TemplateParameterInput>,
+    model: &mut Model,
+) -> MaybeError {
+    match instantiate_into(template, parameters, model) {
+        Ok(()) => MaybeError::new_ok(),
+        Err(error) => error.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_data_base::{BaseNode, BaseProcess, BaseTopology, Conversion};
+    use serde_json::{Map, Value};
+
+    fn boiler_template() -> Template {
+        let mut process = BaseProcess::new("boiler".into(), Conversion::Unit);
+        process
+            .topos
+            .push(BaseTopology::new("boiler".into(), "heat".into()));
+        let mut process = serde_json::to_value(process).unwrap();
+        process["name"] = "${name}".into();
+        process["topos"][0]["source"] = "${name}".into();
+        process["topos"][0]["capacity"] = "${capacity}".into();
+        let mut input_data = Map::new();
+        input_data.insert("processes".into(), Value::Array(vec![process]));
+        Template {
+            name: "boiler".into(),
+            description: String::new(),
+            input_data,
+        }
+    }
+
+    fn parameters() -> Vec<TemplateParameterInput> {
+        vec![
+            TemplateParameterInput {
+                name: "name".into(),
+                number: None,
+                text: Some("boiler1".into()),
+            },
+            TemplateParameterInput {
+                name: "capacity".into(),
+                number: Some(2.5),
+                text: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn template_is_instantiated_into_model() {
+        let mut model = Model::default();
+        model.input_data.nodes.push(BaseNode::new("heat".into()));
+        let result = instantiate_template(&boiler_template(), parameters(), &mut model);
+        assert!(!result.is_error(), "{:?}", result.message);
+        assert_eq!(model.input_data.processes[0].name, "boiler1");
+        assert_eq!(model.input_data.processes[0].topos[0].capacity, 2.5);
+    }
+
+    #[test]
+    fn instantiation_that_makes_model_invalid_is_refused() {
+        let mut model = Model::default();
+        let result = instantiate_template(&boiler_template(), parameters(), &mut model);
+        assert!(result
+            .message
+            .unwrap()
+            .starts_with("instantiated template is invalid: processes/boiler1"));
+        assert!(model.input_data.processes.is_empty());
+    }
+}
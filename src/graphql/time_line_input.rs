@@ -1,7 +1,6 @@
 use super::{ValidationError, ValidationErrors};
-use crate::time_line_settings::{Duration, TimeLineSettings, Clock, CustomStartTime, TimeLineStart, ClockChoice};
-use juniper::GraphQLInputObject;
-use chrono::{DateTime, Utc};
+use crate::time_line_settings::{Duration, TimeLineSettings, TimeLineSegment, Clock, CustomStartTime, TimeLineStart, ClockChoice, parse_local_time};
+use juniper::{GraphQLInputObject, Nullable};
 
 #[derive(GraphQLInputObject)]
 pub struct DurationInput {
@@ -21,12 +20,36 @@ pub struct TimeLineUpdate {
     duration: Option<DurationInput>,
     step: Option<DurationInput>,
     start: Option<TimeLineStartInput>,
+    #[graphql(description = "IANA time zone of the model, e.g. Europe/Helsinki; null returns to UTC.")]
+    time_zone: Nullable<String>,
+    #[graphql(
+        description = "Segments of a variable step time line; replaces duration and step. \
+                       Empty list returns to uniform steps."
+    )]
+    segments: Option<Vec<TimeLineSegmentInput>>,
+}
+
+#[derive(GraphQLInputObject)]
+pub struct TimeLineSegmentInput {
+    duration: DurationInput,
+    step: DurationInput,
+}
+
+impl TimeLineSegmentInput {
+    fn into_segment(self) -> Result<TimeLineSegment, String> {
+        TimeLineSegment::try_new(self.duration.to_duration()?, self.step.to_duration()?)
+    }
 }
 
 #[derive(GraphQLInputObject)]
 pub struct TimeLineStartInput {
     clock_choice: Option<Clock>,
+    #[graphql(description = "RFC 3339 time or local time of the model's time zone without offset.")]
     custom_start_time: Option<String>,
+    #[graphql(description = "IANA time zone of the clock choice, e.g. Europe/Helsinki.")]
+    time_zone: Option<String>,
+    #[graphql(description = "Time added to the current time before applying the clock choice.")]
+    offset: Option<DurationInput>,
 }
 
 pub fn update_time_line(
@@ -45,32 +68,64 @@ pub fn update_time_line(
     if let Some(step_input) = input.step {
         duration_from_input(step_input, |d| time_line.set_step(d), "step", &mut errors);
     }
+    if let Some(segment_inputs) = input.segments {
+        match segment_inputs
+            .into_iter()
+            .map(TimeLineSegmentInput::into_segment)
+            .collect::<Result<Vec<TimeLineSegment>, String>>()
+            .and_then(|segments| time_line.set_segments(segments))
+        {
+            Ok(..) => (),
+            Err(error) => errors.push(ValidationError::new("segments", &error)),
+        }
+    }
+    let time_zone = match input.time_zone {
+        Nullable::Some(time_zone) => Some(Some(time_zone)),
+        Nullable::ExplicitNull => Some(None),
+        Nullable::ImplicitNull => None,
+    };
+    if let Some(time_zone) = time_zone {
+        if let Err(error) = time_line.set_time_zone(time_zone) {
+            errors.push(ValidationError::new("timeZone", &error));
+        }
+    }
     // Update timeline start if provided.  Prefer customStartTime over clockChoice.
     if let Some(start_input) = input.start {
         // Handle custom ISO‑8601 start time
         if let Some(start_str) = start_input.custom_start_time {
-            match DateTime::parse_from_rfc3339(&start_str) {
-                Ok(dt) => {
-                    let dt_utc: DateTime<Utc> = dt.with_timezone(&Utc);
-                    let custom_start = CustomStartTime { start_time: dt_utc };
+            match parse_local_time(&start_str, time_line.tz()) {
+                Ok(start_time) => {
+                    let custom_start = CustomStartTime { start_time };
                     let new_start = TimeLineStart::CustomStartTime(custom_start);
                     if let Err(error) = time_line.set_start(new_start) {
                         errors.push(ValidationError::new("customStartTime", &error));
                     }
                 }
-                Err(_) => {
-                    errors.push(ValidationError::new(
-                        "customStartTime",
-                        &format!("invalid datetime format: {}", start_str),
-                    ));
+                Err(error) => {
+                    errors.push(ValidationError::new("customStartTime", &error));
                 }
             }
         }
         // Otherwise handle predefined clock choice
         else if let Some(clock) = start_input.clock_choice {
-            let new_start = TimeLineStart::ClockChoice(ClockChoice { choice: clock });
-            if let Err(error) = time_line.set_start(new_start) {
-                errors.push(ValidationError::new("clockChoice", &error));
+            let mut clock_choice = ClockChoice::new(clock);
+            if let Some(time_zone) = start_input.time_zone {
+                match ClockChoice::parse_time_zone(&time_zone) {
+                    Ok(..) => clock_choice.time_zone = Some(time_zone),
+                    Err(error) => errors.push(ValidationError::new("timeZone", &error)),
+                }
+            }
+            if let Some(offset) = start_input.offset {
+                match offset.to_duration() {
+                    Ok(offset) => clock_choice.offset = offset,
+                    Err(error) => errors.push(ValidationError::new("offset", &error)),
+                }
+            }
+            if errors.is_empty() {
+                let new_start = TimeLineStart::ClockChoice(clock_choice);
+                if let Err(error) = time_line.set_start(new_start) {
+                    errors.push(ValidationError::new("clockChoice", &error));
+                }
             }
         }
     }
@@ -98,7 +153,7 @@ fn duration_from_input<F>(
 mod tests {
     use super::*;
     use crate::time_line_settings::TimeLineSettings;
-    use chrono::TimeDelta;
+    use chrono::{DateTime, TimeDelta, Utc};
 
     #[test]
     fn update_time_line_works() {
@@ -115,6 +170,8 @@ mod tests {
                 seconds: 0,
             }),
             start: None,
+            time_zone: Nullable::ImplicitNull,
+            segments: None,
         };
         let errors = update_time_line(input, &mut time_line_settings);
         assert_eq!(errors.errors.len(), 0);
@@ -143,6 +200,8 @@ mod tests {
                 seconds: 0,
             }),
             start: None,
+            time_zone: Nullable::ImplicitNull,
+            segments: None,
         };
         let errors = update_time_line(input, &mut time_line_settings);
         assert_eq!(errors.errors.len(), 1);
@@ -170,6 +229,8 @@ mod tests {
                 seconds: 0,
             }),
             start: None,
+            time_zone: Nullable::ImplicitNull,
+            segments: None,
         };
         let errors = update_time_line(input, &mut time_line_settings);
         assert_eq!(errors.errors.len(), 1);
@@ -196,6 +257,8 @@ mod tests {
                 seconds: 0,
             }),
             start: None,
+            time_zone: Nullable::ImplicitNull,
+            segments: None,
         };
         let errors = update_time_line(input, &mut time_line_settings);
         assert_eq!(errors.errors.len(), 1);
@@ -222,7 +285,11 @@ mod tests {
                 // Provide no clock choice; we are setting a custom start time instead.
                 clock_choice: None,
                 custom_start_time: Some(future_timestamp.clone()),
+                time_zone: None,
+                offset: None,
             }),
+            time_zone: Nullable::ImplicitNull,
+            segments: None,
         };
         let errors = update_time_line(input, &mut time_line_settings);
 
@@ -249,7 +316,11 @@ mod tests {
             start: Some(TimeLineStartInput {
                 clock_choice: Some(Clock::CurrentHour),
                 custom_start_time: None,
+                time_zone: None,
+                offset: None,
             }),
+            time_zone: Nullable::ImplicitNull,
+            segments: None,
         };
         let errors = update_time_line(input, &mut time_line_settings);
         assert!(errors.errors.is_empty());
@@ -261,4 +332,146 @@ mod tests {
             _ => panic!("Expected ClockChoice variant"),
         }
     }
+
+    #[test]
+    fn clock_choice_with_unknown_time_zone_is_rejected() {
+        let mut time_line_settings = TimeLineSettings::default();
+        let input = TimeLineUpdate {
+            duration: None,
+            step: None,
+            start: Some(TimeLineStartInput {
+                clock_choice: Some(Clock::NextDayMidnight),
+                custom_start_time: None,
+                time_zone: Some("Europe/Atlantis".to_string()),
+                offset: None,
+            }),
+            time_zone: Nullable::ImplicitNull,
+            segments: None,
+        };
+        let errors = update_time_line(input, &mut time_line_settings);
+        assert_eq!(errors.errors.len(), 1);
+        assert_eq!(errors.errors[0].field, "timeZone");
+        assert_eq!(time_line_settings, TimeLineSettings::default());
+        let input = TimeLineUpdate {
+            duration: None,
+            step: None,
+            start: Some(TimeLineStartInput {
+                clock_choice: Some(Clock::NextDayMidnight),
+                custom_start_time: None,
+                time_zone: Some("Europe/Helsinki".to_string()),
+                offset: Some(DurationInput {
+                    hours: 0,
+                    minutes: 10,
+                    seconds: 0,
+                }),
+            }),
+            time_zone: Nullable::ImplicitNull,
+            segments: None,
+        };
+        assert!(update_time_line(input, &mut time_line_settings).errors.is_empty());
+        match time_line_settings.start() {
+            TimeLineStart::ClockChoice(clock_choice) => {
+                assert_eq!(clock_choice.time_zone.as_deref(), Some("Europe/Helsinki"));
+                assert_eq!(clock_choice.offset.to_time_delta(), TimeDelta::minutes(10));
+            }
+            _ => panic!("Expected ClockChoice variant"),
+        }
+    }
+
+    #[test]
+    fn local_custom_start_time_uses_model_time_zone() {
+        let mut time_line_settings = TimeLineSettings::default();
+        let input = TimeLineUpdate {
+            duration: None,
+            step: None,
+            start: Some(TimeLineStartInput {
+                clock_choice: None,
+                custom_start_time: Some("2025-07-01T00:00:00".to_string()),
+                time_zone: None,
+                offset: None,
+            }),
+            time_zone: Nullable::Some("Europe/Helsinki".to_string()),
+            segments: None,
+        };
+        assert!(update_time_line(input, &mut time_line_settings).errors.is_empty());
+        assert_eq!(time_line_settings.time_zone().map(String::as_str), Some("Europe/Helsinki"));
+        match time_line_settings.start() {
+            TimeLineStart::CustomStartTime(custom) => assert_eq!(
+                custom.start_time,
+                DateTime::parse_from_rfc3339("2025-06-30T21:00:00Z").unwrap()
+            ),
+            _ => panic!("Expected CustomStartTime variant"),
+        }
+    }
+
+    fn segment_input(hours: i32, step_minutes: i32) -> TimeLineSegmentInput {
+        TimeLineSegmentInput {
+            duration: DurationInput {
+                hours,
+                minutes: 0,
+                seconds: 0,
+            },
+            step: DurationInput {
+                hours: 0,
+                minutes: step_minutes,
+                seconds: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn setting_segments_updates_duration_and_step() {
+        let mut time_line_settings = TimeLineSettings::default();
+        let input = TimeLineUpdate {
+            duration: None,
+            step: None,
+            start: None,
+            time_zone: Nullable::ImplicitNull,
+            segments: Some(vec![segment_input(6, 15), segment_input(42, 60)]),
+        };
+        assert!(update_time_line(input, &mut time_line_settings).errors.is_empty());
+        assert_eq!(time_line_settings.segments().len(), 2);
+        assert_eq!(
+            time_line_settings.duration().to_time_delta(),
+            TimeDelta::hours(48)
+        );
+        assert_eq!(
+            time_line_settings.step().to_time_delta(),
+            TimeDelta::minutes(15)
+        );
+        let input = TimeLineUpdate {
+            duration: None,
+            step: None,
+            start: None,
+            time_zone: Nullable::ImplicitNull,
+            segments: Some(vec![segment_input(1, 25)]),
+        };
+        let errors = update_time_line(input, &mut time_line_settings);
+        assert_eq!(errors.errors.len(), 1);
+        assert_eq!(errors.errors[0].field, "segments");
+        assert_eq!(time_line_settings.segments().len(), 2);
+    }
+
+    #[test]
+    fn null_time_zone_returns_to_utc() {
+        let mut time_line_settings = TimeLineSettings::default();
+        time_line_settings
+            .set_time_zone(Some("Europe/Helsinki".to_string()))
+            .unwrap();
+        let update = |time_zone| TimeLineUpdate {
+            duration: None,
+            step: None,
+            start: None,
+            time_zone,
+            segments: None,
+        };
+        assert!(update_time_line(update(Nullable::ImplicitNull), &mut time_line_settings)
+            .errors
+            .is_empty());
+        assert_eq!(time_line_settings.time_zone().map(String::as_str), Some("Europe/Helsinki"));
+        assert!(update_time_line(update(Nullable::ExplicitNull), &mut time_line_settings)
+            .errors
+            .is_empty());
+        assert_eq!(time_line_settings.time_zone(), None);
+    }
 }
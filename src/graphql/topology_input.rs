@@ -1,5 +1,8 @@
+use super::update::{self, optional_update};
 use super::{MaybeError, ValidationError, ValidationErrors};
-use crate::input_data_base::{BaseNode, BaseProcess, BaseTopology, Value, ValueInput};
+use crate::input_data_base::{BaseNode, BaseProcess, BaseTopology, ValueInput};
+use crate::units::Unit;
+use chrono_tz::Tz;
 use juniper::GraphQLInputObject;
 
 #[derive(GraphQLInputObject)]
@@ -11,11 +14,22 @@ pub struct NewTopology {
     pub initial_load: f64,
     pub initial_flow: f64,
     pub cap_ts: Vec<ValueInput>,
+    pub capacity_unit: Option<Unit>,
 }
 
 impl NewTopology {
-    fn to_topology(self, source: String, sink: String) -> BaseTopology {
-        BaseTopology {
+    fn to_topology(
+        self,
+        source: String,
+        sink: String,
+        time_zone: Tz,
+    ) -> Result<BaseTopology, Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        let cap_ts = match update::convert_values("cap_ts", Some(self.cap_ts), time_zone, &mut errors) {
+            Some(cap_ts) => cap_ts,
+            None => return Err(errors),
+        };
+        Ok(BaseTopology {
             source: source,
             sink: sink,
             capacity: self.capacity,
@@ -24,13 +38,9 @@ impl NewTopology {
             ramp_down: self.ramp_down,
             initial_load: self.initial_load,
             initial_flow: self.initial_flow,
-            cap_ts: self
-            .cap_ts
-            .into_iter()
-            .map(Value::try_from)
-            .collect::<Result<Vec<Value>, _>>()
-            .expect("Could not parse cost values"),
-        }
+            cap_ts,
+            capacity_unit: self.capacity_unit,
+        })
     }
 }
 
@@ -41,6 +51,7 @@ pub fn create_topology(
     topology: NewTopology,
     processes: &mut Vec<BaseProcess>,
     nodes: &mut Vec<BaseNode>,
+    time_zone: Tz,
 ) -> ValidationErrors {
     let process = match processes.iter_mut().find(|p| p.name == process_name) {
         Some(p) => p,
@@ -63,7 +74,10 @@ pub fn create_topology(
     }
     let source = source_node_name.unwrap_or_else(|| process_name.clone());
     let sink = sink_node_name.unwrap_or_else(|| process_name.clone());
-    process.topos.push(topology.to_topology(source, sink));
+    match topology.to_topology(source, sink, time_zone) {
+        Ok(topology) => process.topos.push(topology),
+        Err(errors) => return ValidationErrors::from(errors),
+    }
     ValidationErrors::default()
 }
 
@@ -109,13 +123,90 @@ fn validate_topology_creation(
             "a topolgy with the same source and sink exists",
         ));
     }
-    if topology.ramp_down < 0.0 || topology.ramp_down > 1.0 {
+    validate_ramps(topology.ramp_up, topology.ramp_down, &mut errors);
+    errors
+}
+
+fn validate_ramps(ramp_up: f64, ramp_down: f64, errors: &mut Vec<ValidationError>) {
+    if !(0.0..=1.0).contains(&ramp_down) {
         errors.push(ValidationError::new("ramp_down", "should be in [0, 1]"))
     }
-    if topology.ramp_up < 0.0 || topology.ramp_up > 1.0 {
+    if !(0.0..=1.0).contains(&ramp_up) {
         errors.push(ValidationError::new("ramp_up", "should be in [0, 1]"))
     }
-    errors
+}
+
+#[derive(Default, GraphQLInputObject)]
+pub struct TopologyUpdate {
+    pub capacity: Option<f64>,
+    pub vom_cost: Option<f64>,
+    pub ramp_up: Option<f64>,
+    pub ramp_down: Option<f64>,
+    pub initial_load: Option<f64>,
+    pub initial_flow: Option<f64>,
+    pub cap_ts: Option<Vec<ValueInput>>,
+    pub capacity_unit: Option<Unit>,
+}
+
+pub fn update_topology(
+    process_name: &str,
+    source_node_name: &Option<String>,
+    sink_node_name: &Option<String>,
+    update: TopologyUpdate,
+    processes: &mut [BaseProcess],
+    time_zone: Tz,
+) -> ValidationErrors {
+    let process = match processes.iter_mut().find(|p| p.name == process_name) {
+        Some(process) => process,
+        None => {
+            return ValidationErrors::from(ValidationError::new(
+                "process_name",
+                "no such process",
+            ))
+        }
+    };
+    let source_name = source_node_name
+        .as_ref()
+        .map(|n| n.as_str())
+        .unwrap_or(process_name);
+    let sink_name = sink_node_name
+        .as_ref()
+        .map(|n| n.as_str())
+        .unwrap_or(process_name);
+    let topology = match process
+        .topos
+        .iter_mut()
+        .find(|t| t.source == source_name && t.sink == sink_name)
+    {
+        Some(topology) => topology,
+        None => {
+            return ValidationErrors::from(ValidationError::new(
+                "source_node",
+                "no such topology",
+            ))
+        }
+    };
+    let mut errors = Vec::new();
+    validate_ramps(
+        update.ramp_up.unwrap_or(topology.ramp_up),
+        update.ramp_down.unwrap_or(topology.ramp_down),
+        &mut errors,
+    );
+    let cap_ts = update::convert_values("cap_ts", update.cap_ts, time_zone, &mut errors);
+    if !errors.is_empty() {
+        return ValidationErrors::from(errors);
+    }
+    optional_update(update.capacity, &mut topology.capacity);
+    optional_update(update.vom_cost, &mut topology.vom_cost);
+    optional_update(update.ramp_up, &mut topology.ramp_up);
+    optional_update(update.ramp_down, &mut topology.ramp_down);
+    optional_update(update.initial_load, &mut topology.initial_load);
+    optional_update(update.initial_flow, &mut topology.initial_flow);
+    optional_update(cap_ts, &mut topology.cap_ts);
+    if update.capacity_unit.is_some() {
+        topology.capacity_unit = update.capacity_unit;
+    }
+    ValidationErrors::default()
 }
 
 pub fn delete_topology(
@@ -148,3 +239,32 @@ pub fn delete_topology(
     }
     MaybeError::new_ok()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_data_base::Conversion;
+
+    #[test]
+    fn update_topology_finds_topology_by_source_and_sink() {
+        let mut process = BaseProcess::new("pump".into(), Conversion::Unit);
+        process.topos.push(BaseTopology::new("pump".into(), "heat".into()));
+        let mut processes = vec![process];
+        let update = TopologyUpdate {
+            capacity: Some(12.0),
+            ramp_up: Some(1.5),
+            ..TopologyUpdate::default()
+        };
+        let errors = update_topology("pump", &None, &Some("heat".into()), update, &mut processes, Tz::UTC);
+        assert_eq!(errors.errors.len(), 1);
+        assert_eq!(errors.errors[0].field, "ramp_up");
+        assert_eq!(processes[0].topos[0].capacity, 0.0);
+        let update = TopologyUpdate {
+            capacity: Some(12.0),
+            ..TopologyUpdate::default()
+        };
+        let errors = update_topology("pump", &None, &Some("heat".into()), update, &mut processes, Tz::UTC);
+        assert!(errors.errors.is_empty());
+        assert_eq!(processes[0].topos[0].capacity, 12.0);
+    }
+}
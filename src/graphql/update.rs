@@ -0,0 +1,50 @@
+use super::forecastable;
+use super::ValidationError;
+use crate::input_data_base::{ForecastValue, ForecastValueInput, Value, ValueInput};
+use crate::scenarios::Scenario;
+use chrono_tz::Tz;
+
+pub fn optional_update<T>(source: Option<T>, target: &mut T) {
+    if let Some(x) = source {
+        *target = x;
+    }
+}
+
+/// Converts optional value inputs of an update; conversion failures are pushed to errors.
+pub fn convert_values(
+    field: &str,
+    inputs: Option<Vec<ValueInput>>,
+    time_zone: Tz,
+    errors: &mut Vec<ValidationError>,
+) -> Option<Vec<Value>> {
+    let inputs = inputs?;
+    match inputs
+        .into_iter()
+        .map(|input| Value::from_input(input, time_zone))
+        .collect::<Result<Vec<Value>, _>>()
+    {
+        Ok(values) => Some(values),
+        Err(error) => {
+            errors.push(ValidationError::new(field, &error));
+            None
+        }
+    }
+}
+
+/// Converts optional forecast value inputs of an update; conversion failures are pushed to errors.
+pub fn convert_forecast_values(
+    field: &str,
+    inputs: Option<Vec<ForecastValueInput>>,
+    scenarios: &Vec<Scenario>,
+    time_zone: Tz,
+    errors: &mut Vec<ValidationError>,
+) -> Option<Vec<ForecastValue>> {
+    let inputs = inputs?;
+    match forecastable::convert_forecast_value_inputs(inputs, scenarios, time_zone) {
+        Ok(values) => Some(values),
+        Err(error) => {
+            errors.push(ValidationError::new(field, &error));
+            None
+        }
+    }
+}
@@ -1,3 +1,6 @@
+use crate::resampling::ResamplingMethod;
+use crate::scenarios::prices::PriceScenarios;
+use crate::units::Unit;
 use crate::{TimeLine, TimeStamp};
 use chrono::DateTime;
 use hertta_derive::Name;
@@ -32,17 +35,32 @@ pub struct InputData {
 }
 
 impl InputData {
+    pub fn features(&self) -> ModelFeatures {
+        ModelFeatures {
+            use_reserves: self.setup.use_reserves,
+            reserve_nodes: self.nodes.values().any(|n| n.is_res),
+            online_processes: self.processes.values().any(|p| p.is_online),
+            states: self.nodes.values().any(|n| n.is_state),
+            piecewise_eff: self.processes.values().any(|p| !p.eff_ops.is_empty()),
+            risk_beta: self.risk.get("beta").copied(),
+            diffusion: !self.node_diffusion.is_empty(),
+            delay: !self.node_delay.is_empty(),
+            markets: !self.markets.is_empty(),
+        }
+    }
+
     pub fn infer_feature_flags(&mut self) {
+        let flags = self.features().flags();
         let s = &mut self.setup;
 
-        s.contains_reserves      = s.use_reserves && self.nodes.values().any(|n| n.is_res);
-        s.contains_online        = self.processes.values().any(|p| p.is_online);
-        s.contains_states        = self.nodes.values().any(|n| n.is_state);
-        s.contains_piecewise_eff = self.processes.values().any(|p| !p.eff_ops.is_empty());
-        s.contains_risk = self.risk.get("beta").map_or(false, |v| *v > 0.0);
-        s.contains_diffusion     = !self.node_diffusion.is_empty();
-        s.contains_delay         = !self.node_delay.is_empty();
-        s.contains_markets       = !self.markets.is_empty();
+        s.contains_reserves      = flags.contains_reserves;
+        s.contains_online        = flags.contains_online;
+        s.contains_states        = flags.contains_states;
+        s.contains_piecewise_eff = flags.contains_piecewise_eff;
+        s.contains_risk          = flags.contains_risk;
+        s.contains_diffusion     = flags.contains_diffusion;
+        s.contains_delay         = flags.contains_delay;
+        s.contains_markets       = flags.contains_markets;
     }
     pub fn with_inferred_flags(mut self) -> Self {
         self.infer_feature_flags();
@@ -138,6 +156,51 @@ impl InputData {
     }
 }
 
+/// Parts of a model that decide which optional features of Predicer it needs.
+///
+/// Both expanded input data and the model summary derive their flags through this
+/// so that the summary shows what the optimization job will use.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ModelFeatures {
+    pub use_reserves: bool,
+    pub reserve_nodes: bool,
+    pub online_processes: bool,
+    pub states: bool,
+    pub piecewise_eff: bool,
+    pub risk_beta: Option<f64>,
+    pub diffusion: bool,
+    pub delay: bool,
+    pub markets: bool,
+}
+
+impl ModelFeatures {
+    pub fn flags(&self) -> FeatureFlags {
+        FeatureFlags {
+            contains_reserves: self.use_reserves && self.reserve_nodes,
+            contains_online: self.online_processes,
+            contains_states: self.states,
+            contains_piecewise_eff: self.piecewise_eff,
+            contains_risk: self.risk_beta.is_some_and(|beta| beta > 0.0),
+            contains_diffusion: self.diffusion,
+            contains_delay: self.delay,
+            contains_markets: self.markets,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, GraphQLObject, PartialEq)]
+#[graphql(description = "Feature flags the optimization job would set for the model.")]
+pub struct FeatureFlags {
+    pub contains_reserves: bool,
+    pub contains_online: bool,
+    pub contains_states: bool,
+    pub contains_piecewise_eff: bool,
+    pub contains_risk: bool,
+    pub contains_diffusion: bool,
+    pub contains_delay: bool,
+    pub contains_markets: bool,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Temporals {
     pub t: TimeLine,
@@ -197,11 +260,25 @@ pub struct Forecast {
     name: String,
     f_type: String,
     api_key: Option<String>,
+    #[serde(default)]
+    unit: Option<Unit>,
+    #[serde(default)]
+    resampling: Option<ResamplingMethod>,
 }
 
 impl Forecast {
     pub fn new(name: String, f_type: String, api_key: Option<String>) -> Self {
-        Forecast { name, f_type, api_key }
+        Forecast { name, f_type, api_key, unit: None, resampling: None }
+    }
+
+    pub fn with_unit(mut self, unit: Option<Unit>) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    pub fn with_resampling(mut self, resampling: Option<ResamplingMethod>) -> Self {
+        self.resampling = resampling;
+        self
     }
 
     pub fn name(&self) -> &str {
@@ -211,6 +288,15 @@ impl Forecast {
     pub fn f_type(&self) -> &str {
         &self.f_type
     }
+
+    pub fn unit(&self) -> Option<Unit> {
+        self.unit
+    }
+
+    /// Returns how forecast data is mapped onto the time line.
+    pub fn resampling(&self) -> ResamplingMethod {
+        self.resampling.unwrap_or_default()
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
@@ -271,6 +357,10 @@ pub struct Market {
     pub down_price: Forecastable,
     pub reserve_activation_price: TimeSeriesData,
     pub fixed: Vec<(String, f64)>,
+    #[serde(default)]
+    pub price_unit: Option<Unit>,
+    #[serde(default)]
+    pub price_scenarios: Option<PriceScenarios>,
 }
 
 #[derive(Clone, Debug, Deserialize, GraphQLEnum, PartialEq, Serialize)]
@@ -451,6 +541,8 @@ pub struct State {
     pub is_temp: bool,
     pub t_e_conversion: f64,
     pub residual_value: f64,
+    #[serde(default)]
+    pub unit: Option<Unit>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
@@ -710,6 +802,8 @@ mod infer_feature_flags {
             down_price: Forecastable::TimeSeriesData(ts()),
             reserve_activation_price: ts(),
             fixed: Vec::new(),
+            price_unit: None,
+            price_scenarios: None,
         });
 
         id.infer_feature_flags();
@@ -1,17 +1,23 @@
+use crate::event_loop::time_series;
 use crate::graphql::HerttaContext;
 use crate::input_data::{
     ConFactor, Forecast, Forecastable, GenConstraint, Group, GroupType, InflowBlock, InputData,
     InputDataSetup, Market, Name, Node, NodeDiffusion, NodeHistory, Process, State, Temporals,
     TimeSeries, TimeSeriesData, Topology,
 };
+use crate::resampling::{self, ResamplingMethod};
+use crate::scenarios::prices::PriceScenarios;
 use crate::scenarios::Scenario;
-use crate::time_line_settings::Duration;
+use crate::time_line_settings::{parse_local_time, Duration};
+use crate::units::{self, Unit};
 use crate::{TimeLine, TimeStamp};
+use chrono_tz::Tz;
 use hertta_derive::{Members, Name};
 use juniper::{graphql_object, FieldResult, GraphQLEnum, GraphQLObject, GraphQLUnion, GraphQLInputObject};
 use serde::{self, Deserialize, Serialize};
 use std::collections::{BTreeMap, HashSet};
 use std::convert::TryFrom;
+use std::fmt;
 use indexmap::IndexMap;
 
 pub trait TypeName {
@@ -23,6 +29,13 @@ pub struct ValueInput {
     pub scenario: Option<String>, 
     pub constant: Option<f64>,  
     pub series: Option<Vec<f64>>, 
+    #[graphql(
+        description = "Time stamps of series values if they do not follow the time line. \
+                       Time stamps without offset are in the model's time zone."
+    )]
+    pub time_stamps: Option<Vec<String>>,
+    #[graphql(description = "How series values are mapped onto the time line.")]
+    pub resampling: Option<ResamplingMethod>,
 }
 
 #[derive(GraphQLInputObject, Clone, Debug, Default, Deserialize, Serialize)]
@@ -30,27 +43,47 @@ pub struct ForecastValueInput {
     pub scenario: Option<String>,
     pub constant: Option<f64>,
     pub series: Option<Vec<f64>>,
+    #[graphql(
+        description = "Time stamps of series values if they do not follow the time line. \
+                       Time stamps without offset are in the model's time zone."
+    )]
+    pub time_stamps: Option<Vec<String>>,
+    #[graphql(description = "How series or forecast values are mapped onto the time line.")]
+    pub resampling: Option<ResamplingMethod>,
     pub forecast: Option<String>,
     pub f_type: Option<String>,
     pub api_key: Option<String>,
+    pub unit: Option<Unit>,
 }
 
-#[derive(GraphQLObject, Clone, Debug, Deserialize, Serialize)]
+#[derive(GraphQLObject, Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct ForecastValue {
     pub scenario: Option<String>,
     pub value: BaseForecastable,
 }
 
-impl TryFrom<ForecastValueInput> for ForecastValue {
-    type Error = String;
-
-    fn try_from(input: ForecastValueInput) -> Result<Self, Self::Error> {
+impl ForecastValue {
+    /// Converts input; time stamps without offset are read in given time zone.
+    pub fn from_input(input: ForecastValueInput, time_zone: Tz) -> Result<Self, String> {
         let scenario = input.scenario;
+        if input.unit.is_some() && input.forecast.is_none() {
+            return Err("`unit` can be given only with `forecast`.".into());
+        }
+        if input.time_stamps.is_some() && input.series.is_none() {
+            return Err("`time_stamps` can be given only with `series`.".into());
+        }
+        if input.resampling.is_some() && input.series.is_none() && input.forecast.is_none() {
+            return Err("`resampling` can be given only with `series` or `forecast`.".into());
+        }
 
         match (input.forecast, input.f_type, input.constant, input.series, input.api_key) {
             (Some(name), Some(f_type), None, None, api_key) => Ok(ForecastValue {
                 scenario,
-                value: BaseForecastable::Forecast(Forecast::new(name, f_type, api_key)),
+                value: BaseForecastable::Forecast(
+                    Forecast::new(name, f_type, api_key)
+                        .with_unit(input.unit)
+                        .with_resampling(input.resampling),
+                ),
             }),
 
             (None, None, Some(c), None, None) => Ok(ForecastValue {
@@ -59,7 +92,11 @@ impl TryFrom<ForecastValueInput> for ForecastValue {
             }),
             (None, None, None, Some(series), None) => Ok(ForecastValue {
                 scenario,
-                value: BaseForecastable::FloatList(FloatList { values: series }),
+                value: BaseForecastable::FloatList(FloatList::try_new(
+                    series,
+                    parse_time_stamps(input.time_stamps, time_zone)?,
+                    input.resampling,
+                )?),
             }),
 
             // Error: only one of forecast / f_type given
@@ -80,21 +117,43 @@ impl TryFrom<ForecastValueInput> for ForecastValue {
     }
 }
 
-#[derive(GraphQLObject, Clone, Debug, Deserialize, Serialize)]
+impl TryFrom<ForecastValueInput> for ForecastValue {
+    type Error = String;
+
+    fn try_from(input: ForecastValueInput) -> Result<Self, Self::Error> {
+        ForecastValue::from_input(input, Tz::UTC)
+    }
+}
+
+fn parse_time_stamps(time_stamps: Option<Vec<String>>, time_zone: Tz) -> Result<Vec<TimeStamp>, String> {
+    time_stamps
+        .unwrap_or_default()
+        .iter()
+        .map(|time_stamp| parse_local_time(time_stamp, time_zone))
+        .collect()
+}
+
+#[derive(GraphQLObject, Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Value {
     pub scenario: Option<String>, 
     pub value: SeriesValue,
 }
 
-impl TryFrom<ValueInput> for Value {
-    type Error = String;
-
-    fn try_from(input: ValueInput) -> Result<Self, Self::Error> {
+impl Value {
+    /// Converts input; time stamps without offset are read in given time zone.
+    pub fn from_input(input: ValueInput, time_zone: Tz) -> Result<Self, String> {
         let scenario = input.scenario;
+        if (input.time_stamps.is_some() || input.resampling.is_some()) && input.series.is_none() {
+            return Err("`time_stamps` and `resampling` can be given only with `series`.".into());
+        }
 
         let value = match (input.constant, input.series) {
             (Some(constant), None) => SeriesValue::Constant(Constant { value: constant }),
-            (None, Some(series)) => SeriesValue::FloatList(FloatList { values: series }),
+            (None, Some(series)) => SeriesValue::FloatList(FloatList::try_new(
+                series,
+                parse_time_stamps(input.time_stamps, time_zone)?,
+                input.resampling,
+            )?),
             (Some(_), Some(_)) => {
                 return Err(
                     "ValueInput cannot have both `constant` and `series` populated simultaneously."
@@ -108,7 +167,15 @@ impl TryFrom<ValueInput> for Value {
     }
 }
 
-#[derive(Clone, Debug, Deserialize, GraphQLObject, Serialize)]
+impl TryFrom<ValueInput> for Value {
+    type Error = String;
+
+    fn try_from(input: ValueInput) -> Result<Self, Self::Error> {
+        Value::from_input(input, Tz::UTC)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, GraphQLObject, PartialEq, Serialize)]
 pub struct Constant {
     value: f64,
 }
@@ -135,17 +202,105 @@ impl From<&f64> for Constant {
     }
 }
 
-#[derive(Clone, Debug, Deserialize, GraphQLObject, Serialize)]
+#[derive(Clone, Debug, Deserialize, GraphQLObject, PartialEq, Serialize)]
 pub struct FloatList {
     values: Vec<f64>,
+    #[serde(default)]
+    #[graphql(description = "Time stamps of values; empty if values follow the time line.")]
+    time_stamps: Vec<TimeStamp>,
+    #[serde(default)]
+    #[graphql(description = "How values are mapped onto the time line; mean aggregation by default.")]
+    resampling: Option<ResamplingMethod>,
+}
+
+impl FloatList {
+    pub fn new(values: Vec<f64>) -> Self {
+        FloatList {
+            values,
+            time_stamps: Vec::new(),
+            resampling: None,
+        }
+    }
+
+    pub fn try_new(
+        values: Vec<f64>,
+        time_stamps: Vec<TimeStamp>,
+        resampling: Option<ResamplingMethod>,
+    ) -> Result<Self, String> {
+        if !time_stamps.is_empty() {
+            if time_stamps.len() != values.len() {
+                return Err(format!(
+                    "series has {} values but {} time stamps",
+                    values.len(),
+                    time_stamps.len()
+                ));
+            }
+            if time_stamps.windows(2).any(|pair| pair[0] >= pair[1]) {
+                return Err("time stamps should be in increasing order".into());
+            }
+        }
+        Ok(FloatList {
+            values,
+            time_stamps,
+            resampling,
+        })
+    }
+
+    pub fn values(&self) -> &Vec<f64> {
+        &self.values
+    }
+
+    pub fn time_stamps(&self) -> &Vec<TimeStamp> {
+        &self.time_stamps
+    }
+
+    pub fn resampling(&self) -> Option<ResamplingMethod> {
+        self.resampling
+    }
+
+    pub fn scale(&mut self, factor: f64) {
+        for value in &mut self.values {
+            *value *= factor;
+        }
+    }
 }
 
-#[derive(Clone, Debug, GraphQLUnion, Deserialize, Serialize)]
+#[derive(Clone, Debug, GraphQLUnion, Deserialize, PartialEq, Serialize)]
 pub enum SeriesValue {
     Constant(Constant),
     FloatList(FloatList),
 }
 
+/// Maps list values to time line stamps.
+///
+/// Lists with time stamps are resampled. Lists without time stamps may also cover
+/// the time line at its finest step in which case they are resampled too.
+fn float_list_to_series(float_list: &FloatList, timeline: &TimeLine) -> Result<BTreeMap<TimeStamp, f64>, String> {
+    let values = &float_list.values;
+    let method = float_list.resampling.unwrap_or_default();
+    if !float_list.time_stamps.is_empty() {
+        let series = float_list.time_stamps.iter().cloned().zip(values.iter().cloned()).collect();
+        return resampling::resample(&series, timeline, method);
+    }
+    if values.len() == timeline.len() {
+        return Ok(timeline.iter().cloned().zip(values.iter().cloned()).collect());
+    }
+    let base_time_line = time_series::base_time_line(timeline);
+    if values.len() != base_time_line.len() {
+        return Err(format!(
+            "time series mismatch in FloatList, expected length {}, found {}",
+            timeline.len(),
+            values.len()
+        ));
+    }
+    let mut base_series: BTreeMap<TimeStamp, f64> = base_time_line.into_iter().zip(values.iter().cloned()).collect();
+    // The last value lasts for the whole last step of the time line.
+    if let ([.., previous, last], Some(last_value)) = (&timeline[..], values.last()) {
+        base_series.insert(*last + (*last - *previous), *last_value);
+    }
+    resampling::resample(&base_series, timeline, method)
+}
+
 fn convert_value_to_series(value: &Value, timeline: &TimeLine) -> Result<BTreeMap<TimeStamp, f64>, String> {
     match &value.value {
         SeriesValue::Constant(constant) => {
@@ -157,19 +312,7 @@ fn convert_value_to_series(value: &Value, timeline: &TimeLine) -> Result<BTreeMa
             Ok(series)
         },
         SeriesValue::FloatList(float_list) => {
-            if float_list.values.len() != timeline.len() {
-                return Err(format!(
-                    "time series mismatch in FloatList, expected length {}, found {}",
-                    timeline.len(),
-                    float_list.values.len()
-                ));
-            }
-            let series: BTreeMap<TimeStamp, f64> = timeline
-                .iter()
-                .cloned()
-                .zip(float_list.values.iter().cloned())
-                .collect();
-            Ok(series)
+            float_list_to_series(float_list, timeline)
         },
     }
 }
@@ -259,19 +402,7 @@ fn convert_forecast_value_to_series(
             Ok(series)
         },
         BaseForecastable::FloatList(float_list) => {
-            if float_list.values.len() != timeline.len() {
-                return Err(format!(
-                    "time series mismatch in FloatList, expected length {}, found {}",
-                    timeline.len(),
-                    float_list.values.len()
-                ));
-            }
-            let series: BTreeMap<TimeStamp, f64> = timeline
-                .iter()
-                .cloned()
-                .zip(float_list.values.iter().cloned())
-                .collect();
-            Ok(series)
+            float_list_to_series(float_list, timeline)
         },
         BaseForecastable::Forecast(_forecast) => {
             Err("Cannot convert Forecast variant to time series".to_string())
@@ -280,8 +411,8 @@ fn convert_forecast_value_to_series(
 }
 
 pub fn forecast_values_to_time_series_data(
-    forecast_values: &Vec<ForecastValue>,
-    scenarios: &Vec<Scenario>,
+    forecast_values: &[ForecastValue],
+    scenarios: &[Scenario],
     timeline: &TimeLine,
 ) -> Result<TimeSeriesData, String> {
     if forecast_values.is_empty() {
@@ -348,24 +479,72 @@ pub fn forecast_values_to_time_series_data(
 }
 
 pub fn forecast_values_to_forecastable(
-    forecast_values: &Vec<ForecastValue>,
-    scenarios: &Vec<Scenario>,
+    forecast_values: &[ForecastValue],
+    scenarios: &[Scenario],
     timeline: &TimeLine,
-) -> Forecastable {
+) -> Result<Forecastable, String> {
 
     if let Some(fv) = forecast_values.iter().find(|fv| {
         matches!(fv.value, BaseForecastable::Forecast(_))
     }) {
         if let BaseForecastable::Forecast(ref forecast) = fv.value {
-            return Forecastable::Forecast(forecast.clone());
+            return Ok(Forecastable::Forecast(forecast.clone()));
         }
     }
 
-    let ts_data = forecast_values_to_time_series_data(forecast_values, scenarios, timeline)
-        .unwrap_or_else(|err| {
-            panic!("Error converting forecast values to time series data: {}", err)
-        });
-    Forecastable::TimeSeriesData(ts_data)
+    let ts_data = forecast_values_to_time_series_data(forecast_values, scenarios, timeline)?;
+    Ok(Forecastable::TimeSeriesData(ts_data))
+}
+
+/// Error raised when a model item cannot be expanded on a time line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExpansionError {
+    pub entity: String,
+    pub field: String,
+    pub message: String,
+}
+
+impl ExpansionError {
+    pub fn new(entity: String, field: &str, message: String) -> Self {
+        ExpansionError {
+            entity,
+            field: field.to_string(),
+            message,
+        }
+    }
+
+    fn within(mut self, parent: &str) -> Self {
+        self.entity = format!("{}/{}", parent, self.entity);
+        self
+    }
+}
+
+impl fmt::Display for ExpansionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}: {}", self.entity, self.field, self.message)
+    }
+}
+
+fn expand_values(
+    values: &[Value],
+    time_line: &TimeLine,
+    scenarios: &[Scenario],
+    entity: &str,
+    field: &str,
+) -> Result<TimeSeriesData, ExpansionError> {
+    values_to_time_series_data(values.to_vec(), scenarios.to_vec(), time_line.clone())
+        .map_err(|error| ExpansionError::new(entity.to_string(), field, error))
+}
+
+fn expand_forecast_values(
+    values: &[ForecastValue],
+    time_line: &TimeLine,
+    scenarios: &[Scenario],
+    entity: &str,
+    field: &str,
+) -> Result<Forecastable, ExpansionError> {
+    forecast_values_to_forecastable(values, scenarios, time_line)
+        .map_err(|error| ExpansionError::new(entity.to_string(), field, error))
 }
 
 pub trait ExpandToTimeSeries {
@@ -374,7 +553,7 @@ pub trait ExpandToTimeSeries {
         &self,
         time_line: &TimeLine,
         scenarios: &Vec<Scenario>,
-    ) -> Self::Expanded;
+    ) -> Result<Self::Expanded, ExpansionError>;
 }
 
 fn use_name_as_key<T: Clone + Name>(x: &T) -> (String, T) {
@@ -385,11 +564,11 @@ fn expand_and_use_name_as_key<T: ExpandToTimeSeries + Name>(
     x: &T,
     time_line: &TimeLine,
     scenarios: &Vec<Scenario>,
-) -> (String, T::Expanded) {
-    (
+) -> Result<(String, T::Expanded), ExpansionError> {
+    Ok((
         x.name().clone(),
-        x.expand_to_time_series(time_line, scenarios),
-    )
+        x.expand_to_time_series(time_line, scenarios)?,
+    ))
 }
 
 pub trait GroupMember {
@@ -397,8 +576,7 @@ pub trait GroupMember {
     fn groups_mut(&mut self) -> &mut Vec<String>;
 }
 
-#[derive(Clone, Debug, Default, Deserialize, GraphQLObject, Serialize)]
-#[graphql(name = "InputData", description = "The model itself.", context = HerttaContext)]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct BaseInputData {
     pub scenarios: Vec<Scenario>,
     pub setup: BaseInputDataSetup,
@@ -416,7 +594,228 @@ pub struct BaseInputData {
     pub gen_constraints: Vec<BaseGenConstraint>,
 }
 
-#[derive(Clone, Debug, Deserialize, GraphQLObject, Serialize)]
+fn name_contains(name: &str, part: &Option<String>) -> bool {
+    part.as_ref()
+        .is_none_or(|part| name.to_lowercase().contains(&part.to_lowercase()))
+}
+
+fn is_none_or_equal<T: PartialEq>(filter: &Option<T>, value: &T) -> bool {
+    filter.as_ref().is_none_or(|f| f == value)
+}
+
+#[derive(Default, GraphQLInputObject)]
+#[graphql(description = "Node filter. Fields that are null are not used for filtering.")]
+pub struct NodeFilter {
+    #[graphql(description = "Node is a member of this group.")]
+    pub group: Option<String>,
+    pub is_commodity: Option<bool>,
+    pub is_market: Option<bool>,
+    pub is_res: Option<bool>,
+    #[graphql(description = "Node has (or does not have) a state.")]
+    pub has_state: Option<bool>,
+    #[graphql(description = "Node's name contains this text ignoring case.")]
+    pub name_contains: Option<String>,
+}
+
+impl NodeFilter {
+    fn matches(&self, node: &BaseNode) -> bool {
+        self.group.as_ref().is_none_or(|g| node.groups.contains(g))
+            && is_none_or_equal(&self.is_commodity, &node.is_commodity)
+            && is_none_or_equal(&self.is_market, &node.is_market)
+            && is_none_or_equal(&self.is_res, &node.is_res)
+            && is_none_or_equal(&self.has_state, &node.state.is_some())
+            && name_contains(&node.name, &self.name_contains)
+    }
+}
+
+#[derive(Default, GraphQLInputObject)]
+#[graphql(description = "Process filter. Fields that are null are not used for filtering.")]
+pub struct ProcessFilter {
+    #[graphql(description = "Process is a member of this group.")]
+    pub group: Option<String>,
+    pub conversion: Option<Conversion>,
+    pub is_online: Option<bool>,
+    pub is_res: Option<bool>,
+    #[graphql(description = "Process has a topology from or to this node.")]
+    pub node: Option<String>,
+    #[graphql(description = "Process's name contains this text ignoring case.")]
+    pub name_contains: Option<String>,
+}
+
+impl ProcessFilter {
+    fn matches(&self, process: &BaseProcess) -> bool {
+        self.group.as_ref().is_none_or(|g| process.groups.contains(g))
+            && is_none_or_equal(&self.conversion, &process.conversion)
+            && is_none_or_equal(&self.is_online, &process.is_online)
+            && is_none_or_equal(&self.is_res, &process.is_res)
+            && self.node.as_ref().is_none_or(|n| {
+                process.topos.iter().any(|t| t.source == *n || t.sink == *n)
+            })
+            && name_contains(&process.name, &self.name_contains)
+    }
+}
+
+#[derive(Default, GraphQLInputObject)]
+#[graphql(description = "Market filter. Fields that are null are not used for filtering.")]
+pub struct MarketFilter {
+    pub m_type: Option<MarketType>,
+    #[graphql(description = "Market's node or, for reserve markets, node group.")]
+    pub node: Option<String>,
+    pub process_group: Option<String>,
+    #[graphql(description = "Market's name contains this text ignoring case.")]
+    pub name_contains: Option<String>,
+}
+
+impl MarketFilter {
+    fn matches(&self, market: &BaseMarket) -> bool {
+        is_none_or_equal(&self.m_type, &market.m_type)
+            && is_none_or_equal(&self.node, &market.node)
+            && is_none_or_equal(&self.process_group, &market.process_group)
+            && name_contains(&market.name, &self.name_contains)
+    }
+}
+
+#[derive(Default, GraphQLInputObject)]
+#[graphql(description = "Generic constraint filter. Fields that are null are not used for filtering.")]
+pub struct GenConstraintFilter {
+    pub gc_type: Option<ConstraintType>,
+    #[graphql(description = "Constraint has a factor for this process or node.")]
+    pub entity: Option<String>,
+    #[graphql(description = "Constraint's name contains this text ignoring case.")]
+    pub name_contains: Option<String>,
+}
+
+impl GenConstraintFilter {
+    fn matches(&self, constraint: &BaseGenConstraint) -> bool {
+        is_none_or_equal(&self.gc_type, &constraint.gc_type)
+            && self.entity.as_ref().is_none_or(|e| {
+                constraint.factors.iter().any(|f| f.var_tuple.entity == *e)
+            })
+            && name_contains(&constraint.name, &self.name_contains)
+    }
+}
+
+/// Returns at most first items in name order that sort after the name given in after.
+///
+/// The item named after need not exist so that paging survives deleting it.
+fn paginate<'a, T: Clone + Name + 'a>(
+    items: impl Iterator<Item = &'a T>,
+    first: Option<i32>,
+    after: Option<String>,
+) -> Result<Vec<T>, String> {
+    let mut items: Vec<&T> = items.collect();
+    items.sort_by(|a, b| a.name().cmp(b.name()));
+    if let Some(after) = after {
+        let position = items.partition_point(|item| *item.name() <= after);
+        items.drain(..position);
+    }
+    if let Some(first) = first {
+        let first = usize::try_from(first).map_err(|_| "first must not be negative")?;
+        items.truncate(first);
+    }
+    Ok(items.into_iter().cloned().collect())
+}
+
+#[graphql_object]
+#[graphql(name = "InputData", description = "The model itself.", context = HerttaContext)]
+impl BaseInputData {
+    fn scenarios(&self) -> &Vec<Scenario> {
+        &self.scenarios
+    }
+    fn setup(&self) -> &BaseInputDataSetup {
+        &self.setup
+    }
+    #[graphql(
+        description = "Processes matching filter. Returns at most first processes starting after the process named after."
+    )]
+    fn processes(
+        &self,
+        filter: Option<ProcessFilter>,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> FieldResult<Vec<BaseProcess>> {
+        let filter = filter.unwrap_or_default();
+        Ok(paginate(
+            self.processes.iter().filter(|p| filter.matches(p)),
+            first,
+            after,
+        )?)
+    }
+    #[graphql(
+        description = "Nodes matching filter. Returns at most first nodes starting after the node named after."
+    )]
+    fn nodes(
+        &self,
+        filter: Option<NodeFilter>,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> FieldResult<Vec<BaseNode>> {
+        let filter = filter.unwrap_or_default();
+        Ok(paginate(
+            self.nodes.iter().filter(|n| filter.matches(n)),
+            first,
+            after,
+        )?)
+    }
+    fn node_diffusion(&self) -> &Vec<BaseNodeDiffusion> {
+        &self.node_diffusion
+    }
+    fn node_delay(&self) -> &Vec<Delay> {
+        &self.node_delay
+    }
+    fn node_histories(&self) -> &Vec<BaseNodeHistory> {
+        &self.node_histories
+    }
+    #[graphql(
+        description = "Markets matching filter. Returns at most first markets starting after the market named after."
+    )]
+    fn markets(
+        &self,
+        filter: Option<MarketFilter>,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> FieldResult<Vec<BaseMarket>> {
+        let filter = filter.unwrap_or_default();
+        Ok(paginate(
+            self.markets.iter().filter(|m| filter.matches(m)),
+            first,
+            after,
+        )?)
+    }
+    fn node_groups(&self) -> &Vec<NodeGroup> {
+        &self.node_groups
+    }
+    fn process_groups(&self) -> &Vec<ProcessGroup> {
+        &self.process_groups
+    }
+    fn reserve_type(&self) -> &Vec<ReserveType> {
+        &self.reserve_type
+    }
+    fn risk(&self) -> &Vec<Risk> {
+        &self.risk
+    }
+    fn inflow_blocks(&self) -> &Vec<BaseInflowBlock> {
+        &self.inflow_blocks
+    }
+    #[graphql(
+        description = "Generic constraints matching filter. Returns at most first constraints starting after the constraint named after."
+    )]
+    fn gen_constraints(
+        &self,
+        filter: Option<GenConstraintFilter>,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> FieldResult<Vec<BaseGenConstraint>> {
+        let filter = filter.unwrap_or_default();
+        Ok(paginate(
+            self.gen_constraints.iter().filter(|c| filter.matches(c)),
+            first,
+            after,
+        )?)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, GraphQLObject, PartialEq, Serialize)]
 pub struct Series {
     pub scenario: String,
     pub durations: Vec<Duration>,
@@ -424,15 +823,25 @@ pub struct Series {
 }
 
 impl Series {
-    fn to_time_series(&self, time_line: &TimeLine) -> TimeSeries {
+    /// Samples the series at the finest step of the time line and averages the samples over each step.
+    fn to_time_series(&self, time_line: &TimeLine) -> Result<TimeSeries, String> {
         if self.values.is_empty() || time_line.is_empty() {
-            return TimeSeries {
+            return Ok(TimeSeries {
                 scenario: self.scenario.clone(),
                 series: BTreeMap::new(),
-            };
+            });
+        }
+        if self.durations.len() < self.values.len() {
+            return Err(format!(
+                "series for scenario '{}' has {} values but only {} durations",
+                self.scenario,
+                self.values.len(),
+                self.durations.len()
+            ));
         }
         let mut time_series: BTreeMap<TimeStamp, f64> = BTreeMap::new();
-        let mut time_line_iter = time_line.iter();
+        let base_time_line = time_series::base_time_line(time_line);
+        let mut time_line_iter = base_time_line.iter();
         let mut epoch_start = time_line_iter.next().unwrap().clone();
         let mut value_iter = self.values.iter();
         let mut epoch_value = *value_iter.next().unwrap();
@@ -450,21 +859,24 @@ impl Series {
             }
             time_series.insert(stamp.clone(), epoch_value);
         }
-        TimeSeries {
+        Ok(TimeSeries {
             scenario: self.scenario.clone(),
-            series: time_series,
-        }
+            series: time_series::aggregate_to_time_line(&time_series, time_line),
+        })
     }
-    fn to_time_series_data(serieses: &Vec<Series>, time_line: &TimeLine) -> TimeSeriesData {
+    fn to_time_series_data(
+        serieses: &Vec<Series>,
+        time_line: &TimeLine,
+    ) -> Result<TimeSeriesData, String> {
         let data = serieses
             .iter()
             .map(|s| s.to_time_series(time_line))
-            .collect();
-        TimeSeriesData { ts_data: data }
+            .collect::<Result<Vec<TimeSeries>, String>>()?;
+        Ok(TimeSeriesData { ts_data: data })
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Delay {
     pub from_node: String,
     pub to_node: String,
@@ -507,7 +919,7 @@ impl Delay {
     }
 }
 
-#[derive(Clone, Debug, GraphQLObject, Deserialize, Serialize)]
+#[derive(Clone, Debug, GraphQLObject, Deserialize, PartialEq, Serialize)]
 pub struct ReserveType {
     pub name: String,
     pub ramp_rate: f64,
@@ -534,7 +946,7 @@ impl ReserveType {
     }
 }
 
-#[derive(Clone, Debug, GraphQLObject, Deserialize, Serialize)]
+#[derive(Clone, Debug, GraphQLObject, Deserialize, PartialEq, Serialize)]
 pub struct Risk {
     pub parameter: String,
     pub value: f64,
@@ -562,7 +974,15 @@ impl Risk {
 }
 
 impl BaseInputData {
-    pub fn expand_to_time_series(&self, time_line: &TimeLine) -> InputData {
+    /// Expands input data on time line; values given in a mix of kW and MW based units are converted to MW based units first.
+    pub fn expand_to_time_series(&self, time_line: &TimeLine) -> Result<InputData, ExpansionError> {
+        match units::harmonize(self)? {
+            Some(harmonized) => harmonized.expand_harmonized(time_line),
+            None => self.expand_harmonized(time_line),
+        }
+    }
+
+    fn expand_harmonized(&self, time_line: &TimeLine) -> Result<InputData, ExpansionError> {
 
         let mut groups = Vec::with_capacity(self.node_groups.len() + self.process_groups.len());
         groups.extend(self.node_groups.iter().map(|g| Group::from(g)));
@@ -570,33 +990,33 @@ impl BaseInputData {
 
         let input_data = InputData {
             temporals: make_temporals(time_line),
-            setup: self.setup.expand_to_time_series(time_line, &self.scenarios),
+            setup: self.setup.expand_to_time_series(time_line, &self.scenarios)?,
             processes: self
                 .processes
                 .iter()
                 .map(|process| expand_and_use_name_as_key(process, time_line, &self.scenarios))
-                .collect(),
+                .collect::<Result<_, _>>()?,
             nodes: self
                 .nodes
                 .iter()
                 .map(|node| expand_and_use_name_as_key(node, time_line, &self.scenarios))
-                .collect(),
+                .collect::<Result<_, _>>()?,
             node_diffusion: self
                 .node_diffusion
                 .iter()
                 .map(|diffusion| diffusion.expand_to_time_series(time_line, &self.scenarios))
-                .collect(),
+                .collect::<Result<_, _>>()?,
             node_delay: self.node_delay.iter().map(|delay| delay.to_tuple()).collect(),
             node_histories: self
                 .node_histories
                 .iter()
                 .map(|history| expand_and_use_name_as_key(history, time_line, &self.scenarios))
-                .collect(),
+                .collect::<Result<_, _>>()?,
             markets: self
                 .markets
                 .iter()
                 .map(|market| expand_and_use_name_as_key(market, time_line, &self.scenarios))
-                .collect(),
+                .collect::<Result<_, _>>()?,
             groups: groups.iter().map(|group| use_name_as_key(group)).collect(),
             scenarios: Scenario::to_indexmap(&self.scenarios),
             reserve_type: ReserveType::to_indexmap(&self.reserve_type),
@@ -605,16 +1025,16 @@ impl BaseInputData {
                 .inflow_blocks
                 .iter()
                 .map(|block| expand_and_use_name_as_key(block, time_line, &self.scenarios))
-                .collect(),
+                .collect::<Result<_, _>>()?,
             bid_slots: IndexMap::new(),
             gen_constraints: self
                 .gen_constraints
                 .iter()
                 .map(|constraint| expand_and_use_name_as_key(constraint, time_line, &self.scenarios))
-                .collect(),
+                .collect::<Result<_, _>>()?,
         };
 
-        input_data
+        Ok(input_data)
     }
 }
 
@@ -638,8 +1058,8 @@ impl ExpandToTimeSeries for BaseInputDataSetup {
         &self,
         _time_line: &TimeLine,
         _scenarios: &Vec<Scenario>,
-    ) -> Self::Expanded {
-        InputDataSetup {
+    ) -> Result<Self::Expanded, ExpansionError> {
+        Ok(InputDataSetup {
             contains_reserves: false,
             contains_online: false,
             contains_states: false,
@@ -657,7 +1077,7 @@ impl ExpandToTimeSeries for BaseInputDataSetup {
             use_ramp_dummy_variables: self.use_ramp_dummy_variables,
             node_dummy_variable_cost: self.node_dummy_variable_cost,
             ramp_dummy_variable_cost: self.ramp_dummy_variable_cost,
-        }
+        })
     }
 }
 
@@ -702,7 +1122,7 @@ impl BaseInputDataSetup {
     }
 }
 
-#[derive(Clone, Copy, Debug, Deserialize, GraphQLEnum, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, GraphQLEnum, PartialEq, Serialize)]
 pub enum Conversion {
     Unit,
     Transfer,
@@ -719,7 +1139,7 @@ impl Conversion {
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Name, Serialize)]
+#[derive(Clone, Debug, Deserialize, Name, PartialEq, Serialize)]
 pub struct BaseProcess {
     pub name: String,
     pub groups: Vec<String>,
@@ -767,7 +1187,7 @@ pub struct PointInput {
     pub y: f64,
 }
 
-#[derive(Clone, Debug, Deserialize, GraphQLObject, Serialize)]
+#[derive(Clone, Debug, Deserialize, GraphQLObject, PartialEq, Serialize)]
 pub struct Point {
     pub x: f64,
     pub y: f64,
@@ -807,11 +1227,12 @@ impl ExpandToTimeSeries for BaseProcess {
         &self,
         time_line: &TimeLine,
         scenarios: &Vec<Scenario>,
-    ) -> Self::Expanded {
+    ) -> Result<Self::Expanded, ExpansionError> {
 
         let (eff_ops, eff_fun) = build_piecewise_eff(&self.eff_ops_fun);
+        let entity = format!("processes/{}", self.name);
 
-        Process {
+        Ok(Process {
             name: self.name.clone(),
             groups: self.groups.clone(),
             conversion: self.conversion.to_input(),
@@ -832,25 +1253,17 @@ impl ExpandToTimeSeries for BaseProcess {
             topos: self
                 .topos
                 .iter()
-                .map(|topology| topology.expand_to_time_series(time_line, scenarios))
-                .collect(),
-            cf: values_to_time_series_data(self.cf.clone(), scenarios.clone(), time_line.clone())
-            .unwrap_or_else(|err| {
-                panic!(
-                    "Failed to convert 'cf' to TimeSeriesData for node '{}': {}",
-                    self.name, err
-                )
-            }),
-            eff_ts: values_to_time_series_data(self.eff_ts.clone(), scenarios.clone(), time_line.clone())
-            .unwrap_or_else(|err| {
-                panic!(
-                    "Failed to convert 'eff_ts' to TimeSeriesData for node '{}': {}",
-                    self.name, err
-                )
-            }),
+                .map(|topology| {
+                    topology
+                        .expand_to_time_series(time_line, scenarios)
+                        .map_err(|error| error.within(&entity))
+                })
+                .collect::<Result<_, _>>()?,
+            cf: expand_values(&self.cf, time_line, scenarios, &entity, "cf")?,
+            eff_ts: expand_values(&self.eff_ts, time_line, scenarios, &entity, "eff_ts")?,
             eff_ops: eff_ops,
             eff_fun: eff_fun,
-        }
+        })
     }
 }
 
@@ -954,7 +1367,7 @@ impl BaseProcess {
     }
 }
 
-#[derive(Clone, Debug, Deserialize, GraphQLUnion, Serialize)]
+#[derive(Clone, Debug, Deserialize, GraphQLUnion, PartialEq, Serialize)]
 #[graphql(name = "Forecastable")]
 pub enum BaseForecastable {
     Constant(Constant),
@@ -962,7 +1375,7 @@ pub enum BaseForecastable {
     Forecast(Forecast),
 }
 
-#[derive(Clone, Debug, Deserialize, Name, Serialize)]
+#[derive(Clone, Debug, Deserialize, Name, PartialEq, Serialize)]
 pub struct BaseNode {
     pub name: String,
     pub groups: Vec<String>,
@@ -987,8 +1400,9 @@ impl ExpandToTimeSeries for BaseNode {
         &self,
         time_line: &TimeLine,
         scenarios: &Vec<Scenario>,
-    ) -> Self::Expanded {
-        Node {
+    ) -> Result<Self::Expanded, ExpansionError> {
+        let entity = format!("nodes/{}", self.name);
+        Ok(Node {
             name: self.name.clone(),
             groups: self.groups.clone(),
             is_commodity: self.is_commodity,
@@ -997,15 +1411,9 @@ impl ExpandToTimeSeries for BaseNode {
             is_res: self.is_res,
             is_inflow: !self.inflow.is_empty(),
             state: self.state.clone(),
-            cost: values_to_time_series_data(self.cost.clone(), scenarios.clone(), time_line.clone())
-                .unwrap_or_else(|err| {
-                    panic!(
-                        "Failed to convert 'cost' to TimeSeriesData for node '{}': {}",
-                        self.name, err
-                    )
-                }),
-            inflow: forecast_values_to_forecastable(&self.inflow, scenarios, time_line),
-        }
+            cost: expand_values(&self.cost, time_line, scenarios, &entity, "cost")?,
+            inflow: expand_forecast_values(&self.inflow, time_line, scenarios, &entity, "inflow")?,
+        })
     }
 }
 
@@ -1069,7 +1477,7 @@ impl BaseNode {
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct BaseNodeDiffusion {
     pub from_node: String,
     pub to_node: String,
@@ -1083,25 +1491,20 @@ impl ExpandToTimeSeries for BaseNodeDiffusion {
         &self,
         time_line: &TimeLine,
         scenarios: &Vec<Scenario>,
-    ) -> Self::Expanded {
-        NodeDiffusion {
+    ) -> Result<Self::Expanded, ExpansionError> {
+        let entity = format!("node_diffusion/{}->{}", self.from_node, self.to_node);
+        if self.coefficient.is_empty() {
+            return Err(ExpansionError::new(
+                entity,
+                "coefficient",
+                "no coefficient values provided".to_string(),
+            ));
+        }
+        Ok(NodeDiffusion {
             node1: self.from_node.clone(),
             node2: self.to_node.clone(),
-            coefficient: if self.coefficient.is_empty() {
-                panic!(
-                    "No coefficient values provided for NodeDiffusion from '{}' to '{}'",
-                    self.from_node, self.to_node
-                );
-            } else {
-                values_to_time_series_data(self.coefficient.clone(), scenarios.clone(), time_line.clone())
-                    .unwrap_or_else(|err| {
-                        panic!(
-                            "Failed to convert 'coefficient' to TimeSeriesData for NodeDiffusion from '{}' to '{}': {}",
-                            self.from_node, self.to_node, err
-                        )
-                    })
-            },
-        }
+            coefficient: expand_values(&self.coefficient, time_line, scenarios, &entity, "coefficient")?,
+        })
     }
 }
 
@@ -1128,7 +1531,7 @@ fn find_node(node_name: &String, node_type: &str, nodes: &Vec<BaseNode>) -> Fiel
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct BaseNodeHistory {
     pub node: String,
     pub steps: Vec<Series>,
@@ -1157,12 +1560,26 @@ impl ExpandToTimeSeries for BaseNodeHistory {
     fn expand_to_time_series(
         &self,
         time_line: &TimeLine,
-        _scenarios: &Vec<Scenario>,
-    ) -> Self::Expanded {
-        NodeHistory {
-            node: self.node.clone(),
-            steps: Series::to_time_series_data(&self.steps, time_line),
+        scenarios: &Vec<Scenario>,
+    ) -> Result<Self::Expanded, ExpansionError> {
+        let entity = format!("node_histories/{}", self.node);
+        if let Some(step) = self
+            .steps
+            .iter()
+            .find(|step| !scenarios.iter().any(|s| *s.name() == step.scenario))
+        {
+            return Err(ExpansionError::new(
+                entity,
+                "steps",
+                format!("no such scenario '{}'", step.scenario),
+            ));
         }
+        Ok(NodeHistory {
+            node: self.node.clone(),
+            steps: Series::to_time_series_data(&self.steps, time_line).map_err(|error| {
+                ExpansionError::new(entity, "steps", error)
+            })?,
+        })
     }
 }
 
@@ -1173,9 +1590,44 @@ impl BaseNodeHistory {
             steps: Vec::new(),
         }
     }
+
+    /// Returns true if the steps are not the same in every scenario.
+    pub fn steps_differ_between_scenarios(&self) -> bool {
+        self.steps.windows(2).any(|pair| {
+            pair[0].durations != pair[1].durations || pair[0].values != pair[1].values
+        })
+    }
+
+    /// Copies the steps to given scenarios, e.g. ones generated from a weather ensemble.
+    ///
+    /// The history is the same for all scenarios that replace the model's own so
+    /// the steps must not differ between the model's scenarios.
+    pub fn map_to_scenarios(&self, scenarios: &[Scenario]) -> Result<BaseNodeHistory, String> {
+        if self.steps_differ_between_scenarios() {
+            return Err(format!(
+                "steps of node history '{}' differ between scenarios",
+                self.node
+            ));
+        }
+        let steps = match self.steps.first() {
+            Some(step) => scenarios
+                .iter()
+                .map(|scenario| Series {
+                    scenario: scenario.name().clone(),
+                    durations: step.durations.clone(),
+                    values: step.values.clone(),
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        Ok(BaseNodeHistory {
+            node: self.node.clone(),
+            steps,
+        })
+    }
 }
 
-#[derive(Clone, Copy, Debug, Deserialize, GraphQLEnum, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, GraphQLEnum, PartialEq, Serialize)]
 pub enum MarketType {
     Energy,
     Reserve,
@@ -1191,7 +1643,7 @@ impl MarketType {
     }
 }
 
-#[derive(Clone, Copy, Debug, Deserialize, GraphQLEnum, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, GraphQLEnum, PartialEq, Serialize)]
 pub enum MarketDirection {
     Up,
     Down,
@@ -1213,7 +1665,7 @@ impl MarketDirection {
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Name, Serialize)]
+#[derive(Clone, Debug, Deserialize, Name, PartialEq, Serialize)]
 pub struct BaseMarket {
     pub name: String,
     pub m_type: MarketType,
@@ -1232,6 +1684,10 @@ pub struct BaseMarket {
     pub down_price: Vec<ForecastValue>,
     pub reserve_activation_price: Vec<Value>,
     pub fixed: Vec<MarketFix>,
+    #[serde(default)]
+    pub price_unit: Option<Unit>,
+    #[serde(default)]
+    pub price_scenarios: Option<PriceScenarios>,
 }
 
 impl TypeName for BaseMarket {
@@ -1240,7 +1696,7 @@ impl TypeName for BaseMarket {
     }
 }
 
-#[derive(Clone, Debug, Deserialize, GraphQLObject, Serialize)]
+#[derive(Clone, Debug, Deserialize, GraphQLObject, PartialEq, Serialize)]
 pub struct MarketFix {
     pub name: String,
     pub factor: f64,
@@ -1252,8 +1708,9 @@ impl ExpandToTimeSeries for BaseMarket {
         &self,
         time_line: &TimeLine,
         scenarios: &Vec<Scenario>,
-    ) -> Self::Expanded {
-        Market {
+    ) -> Result<Self::Expanded, ExpansionError> {
+        let entity = format!("markets/{}", self.name);
+        Ok(Market {
             name: self.name.clone(),
             m_type: self.m_type.to_input(),
             node: self.node.clone(),
@@ -1262,17 +1719,7 @@ impl ExpandToTimeSeries for BaseMarket {
                 Some(dir) => dir.to_input(),
                 None => "none".to_string(),
             },
-            realisation: values_to_time_series_data(
-                self.realisation.clone(),
-                scenarios.clone(),
-                time_line.clone(),
-            )
-            .unwrap_or_else(|err| {
-                panic!(
-                    "Failed to convert 'realisation' to TimeSeriesData for market '{}': {}",
-                    self.name, err
-                )
-            }),
+            realisation: expand_values(&self.realisation, time_line, scenarios, &entity, "realisation")?,
             reserve_type: match self.reserve_type {
                 Some(ref reserve_type) => reserve_type.clone(),
                 None => "none".to_string(),
@@ -1282,22 +1729,20 @@ impl ExpandToTimeSeries for BaseMarket {
             min_bid: self.min_bid,
             max_bid: self.max_bid,
             fee: self.fee,
-            price: forecast_values_to_forecastable(&self.price, scenarios, time_line),
-            up_price: forecast_values_to_forecastable(&self.up_price, scenarios, time_line),
-            down_price: forecast_values_to_forecastable(&self.down_price, scenarios, time_line),
-            reserve_activation_price: values_to_time_series_data(
-                self.reserve_activation_price.clone(),
-                scenarios.clone(),
-                time_line.clone(),
-            )
-            .unwrap_or_else(|err| {
-                panic!(
-                    "Failed to convert 'reserve_activation_price' to TimeSeriesData for market '{}': {}",
-                    self.name, err
-                )
-            }),
+            price: expand_forecast_values(&self.price, time_line, scenarios, &entity, "price")?,
+            up_price: expand_forecast_values(&self.up_price, time_line, scenarios, &entity, "up_price")?,
+            down_price: expand_forecast_values(&self.down_price, time_line, scenarios, &entity, "down_price")?,
+            reserve_activation_price: expand_values(
+                &self.reserve_activation_price,
+                time_line,
+                scenarios,
+                &entity,
+                "reserve_activation_price",
+            )?,
             fixed: self.fixed.iter().map(|fix| (fix.name.clone(), fix.factor)).collect(),
-        }
+            price_unit: self.price_unit,
+            price_scenarios: self.price_scenarios.clone(),
+        })
     }
 }
 
@@ -1380,6 +1825,14 @@ impl BaseMarket {
     fn fixed(&self) -> &Vec<MarketFix> {
         &self.fixed
     }
+    #[graphql(description = "Unit of price, up_price and down_price.")]
+    fn price_unit(&self) -> Option<Unit> {
+        self.price_unit
+    }
+    #[graphql(description = "Generates price scenarios when price is connected to a forecast.")]
+    fn price_scenarios(&self) -> &Option<PriceScenarios> {
+        &self.price_scenarios
+    }
 }
 
 pub trait NamedGroup {
@@ -1489,7 +1942,7 @@ impl From<&ProcessGroup> for Group {
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Name, Serialize)]
+#[derive(Clone, Debug, Deserialize, Name, PartialEq, Serialize)]
 pub struct BaseInflowBlock {
     pub name: String,
     pub node: String,
@@ -1507,23 +1960,17 @@ impl ExpandToTimeSeries for BaseInflowBlock {
         &self,
         time_line: &TimeLine,
         scenarios: &Vec<Scenario>,
-    ) -> Self::Expanded {
-        InflowBlock {
+    ) -> Result<Self::Expanded, ExpansionError> {
+        let entity = format!("inflow_blocks/{}", self.name);
+        let start_time = time_line.first().ok_or_else(|| {
+            ExpansionError::new(entity.clone(), "start_time", "time line is empty".to_string())
+        })?;
+        Ok(InflowBlock {
             name: self.name.clone(),
             node: self.node.clone(),
-            start_time: time_line[0].clone(),
-            data: values_to_time_series_data(
-                self.data.clone(),
-                scenarios.clone(),
-                time_line.clone(),
-            )
-            .unwrap_or_else(|err| {
-                panic!(
-                    "Failed to convert 'data' to TimeSeriesData for inflow block '{}': {}",
-                    self.name, err
-                )
-            }),
-        }
+            start_time: start_time.clone(),
+            data: expand_values(&self.data, time_line, scenarios, &entity, "data")?,
+        })
     }
 }
 
@@ -1542,7 +1989,7 @@ impl BaseInflowBlock {
     }
 }
 
-#[derive(Clone, Copy, Debug, Deserialize, GraphQLEnum, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, GraphQLEnum, PartialEq, Serialize)]
 pub enum ConstraintType {
     LessThan,
     Equal,
@@ -1560,7 +2007,7 @@ impl ConstraintType {
     }
 }
 
-#[derive(Clone, Debug, Deserialize, GraphQLObject, Name, Serialize)]
+#[derive(Clone, Debug, Deserialize, GraphQLObject, Name, PartialEq, Serialize)]
 #[graphql(name = "GenConstraint", context = HerttaContext)]
 pub struct BaseGenConstraint {
     pub name: String,
@@ -1583,8 +2030,9 @@ impl ExpandToTimeSeries for BaseGenConstraint {
         &self,
         time_line: &TimeLine,
         scenarios: &Vec<Scenario>,
-    ) -> Self::Expanded {
-        GenConstraint {
+    ) -> Result<Self::Expanded, ExpansionError> {
+        let entity = format!("gen_constraints/{}", self.name);
+        Ok(GenConstraint {
             name: self.name.clone(),
             gc_type: self.gc_type.to_input(),
             is_setpoint: self.is_setpoint,
@@ -1592,24 +2040,18 @@ impl ExpandToTimeSeries for BaseGenConstraint {
             factors: self
                 .factors
                 .iter()
-                .map(|factor| factor.expand_to_time_series(time_line, scenarios))
-                .collect(),
-            constant: values_to_time_series_data(
-                self.constant.clone(),
-                scenarios.clone(),
-                time_line.clone(),
-            )
-            .unwrap_or_else(|err| {
-                panic!(
-                    "Failed to convert 'constant' to TimeSeriesData for gen constraint '{}': {}",
-                    self.name, err
-                )
-            }),
-        }
+                .map(|factor| {
+                    factor
+                        .expand_to_time_series(time_line, scenarios)
+                        .map_err(|error| error.within(&entity))
+                })
+                .collect::<Result<_, _>>()?,
+            constant: expand_values(&self.constant, time_line, scenarios, &entity, "constant")?,
+        })
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct BaseTopology {
     pub source: String,
     pub sink: String,
@@ -1620,6 +2062,8 @@ pub struct BaseTopology {
     pub initial_load: f64,
     pub initial_flow: f64,
     pub cap_ts: Vec<Value>,
+    #[serde(default)]
+    pub capacity_unit: Option<Unit>,
 }
 
 impl ExpandToTimeSeries for BaseTopology {
@@ -1628,8 +2072,9 @@ impl ExpandToTimeSeries for BaseTopology {
         &self,
         time_line: &TimeLine,
         scenarios: &Vec<Scenario>,
-    ) -> Self::Expanded {
-        Topology {
+    ) -> Result<Self::Expanded, ExpansionError> {
+        let entity = format!("topos/{}->{}", self.source, self.sink);
+        Ok(Topology {
             source: self.source.clone(),
             sink: self.sink.clone(),
             capacity: self.capacity,
@@ -1638,18 +2083,8 @@ impl ExpandToTimeSeries for BaseTopology {
             ramp_down: self.ramp_down,
             initial_load: self.initial_load,
             initial_flow: self.initial_flow,
-            cap_ts: values_to_time_series_data(
-                self.cap_ts.clone(),
-                scenarios.clone(),
-                time_line.clone(),
-            )
-            .unwrap_or_else(|err| {
-                panic!(
-                    "Failed to convert 'cap_ts' to TimeSeriesData for topology ({} -> {}): {}",
-                    self.source, self.sink, err
-                )
-            }),
-        }
+            cap_ts: expand_values(&self.cap_ts, time_line, scenarios, &entity, "cap_ts")?,
+        })
     }
 }
 
@@ -1665,6 +2100,7 @@ impl BaseTopology {
             initial_load: 0.0,
             initial_flow: 0.0,
             cap_ts: Vec::new(),
+            capacity_unit: None,
         }
     }
 }
@@ -1697,6 +2133,10 @@ impl BaseTopology {
             &model.input_data.processes,
         )
     }
+    #[graphql(description = "Unit of capacity.")]
+    fn capacity_unit(&self) -> Option<Unit> {
+        self.capacity_unit
+    }
 }
 
 fn find_node_or_process(
@@ -1733,7 +2173,7 @@ impl ConstraintFactorType {
     }
 }
 
-#[derive(Clone, Debug, Deserialize, GraphQLObject, Serialize)]
+#[derive(Clone, Debug, Deserialize, GraphQLObject, PartialEq, Serialize)]
 #[graphql(name = "ConFactor", context = HerttaContext)]
 pub struct BaseConFactor {
     pub var_type: ConstraintFactorType,
@@ -1741,7 +2181,7 @@ pub struct BaseConFactor {
     pub data: Vec<Value>,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct VariableId {
     pub entity: String,
     pub identifier: Option<String>,
@@ -1753,8 +2193,9 @@ impl ExpandToTimeSeries for BaseConFactor {
         &self,
         time_line: &TimeLine,
         scenarios: &Vec<Scenario>,
-    ) -> Self::Expanded {
-        ConFactor {
+    ) -> Result<Self::Expanded, ExpansionError> {
+        let entity = format!("factors/{}", self.var_tuple.entity);
+        Ok(ConFactor {
             var_type: self.var_type.to_input(),
             var_tuple: (
                 self.var_tuple.entity.clone(),
@@ -1763,14 +2204,8 @@ impl ExpandToTimeSeries for BaseConFactor {
                     .as_ref()
                     .map_or("".into(), |s| s.clone()),
             ),
-            data: values_to_time_series_data(
-                self.data.clone(),
-                scenarios.clone(),
-                time_line.clone()
-            ).unwrap_or_else(|err| {
-                panic!("Failed to convert 'data' to TimeSeriesData for ConFactor '{}': {}", self.var_tuple.entity, err)
-            }),
-        }
+            data: expand_values(&self.data, time_line, scenarios, &entity, "data")?,
+        })
     }
 }
 
@@ -1821,11 +2256,27 @@ impl VariableId {
     }
 }
 
+/// Step lengths in hours are given per stamp if they vary; the last stamp repeats the last step.
 fn make_temporals(time_line: &TimeLine) -> Temporals {
+    let step_hours: Vec<f64> = time_line
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0]).num_seconds() as f64 / 3600.0)
+        .collect();
+    let variable_dt = if step_hours.windows(2).any(|pair| pair[0] != pair[1]) {
+        Some(
+            time_line
+                .iter()
+                .zip(step_hours.iter().chain(step_hours.last()))
+                .map(|(stamp, hours)| (stamp.to_rfc3339(), *hours))
+                .collect(),
+        )
+    } else {
+        None
+    };
     Temporals {
         t: time_line.clone(),
-        dtf: (time_line[1] - time_line[0]).num_seconds() as f64 / 3600.0,
-        variable_dt: None,
+        dtf: step_hours[0],
+        variable_dt,
     }
 }
 
@@ -1855,6 +2306,63 @@ mod tests {
                 .collect(),
         }
     }
+
+    fn node_names(nodes: &[BaseNode]) -> Vec<&str> {
+        nodes.iter().map(|n| n.name.as_str()).collect()
+    }
+
+    #[test]
+    fn nodes_can_be_filtered_and_paginated() {
+        let mut input_data = BaseInputData::default();
+        for name in ["Heat1", "heat2", "elc", "heat3"] {
+            let mut node = BaseNode::new(name.into());
+            node.is_commodity = name == "elc";
+            input_data.nodes.push(node);
+        }
+        let filter = NodeFilter {
+            name_contains: Some("HEAT".into()),
+            ..NodeFilter::default()
+        };
+        let nodes = input_data.nodes(Some(filter), None, None).unwrap();
+        assert_eq!(node_names(&nodes), vec!["Heat1", "heat2", "heat3"]);
+        let filter = NodeFilter {
+            is_commodity: Some(false),
+            ..NodeFilter::default()
+        };
+        let page = input_data
+            .nodes(Some(filter), Some(2), Some("Heat1".into()))
+            .unwrap();
+        assert_eq!(node_names(&page), vec!["heat2", "heat3"]);
+        let page = input_data.nodes(None, None, Some("gas".into())).unwrap();
+        assert_eq!(node_names(&page), vec!["heat2", "heat3"]);
+        assert!(input_data.nodes(None, Some(-1), None).is_err());
+    }
+
+    #[test]
+    fn processes_can_be_filtered_by_connected_node() {
+        let mut input_data = BaseInputData::default();
+        let mut boiler = BaseProcess::new("boiler".into(), Conversion::Unit);
+        boiler
+            .topos
+            .push(BaseTopology::new("boiler".into(), "heat".into()));
+        input_data.processes.push(boiler);
+        input_data
+            .processes
+            .push(BaseProcess::new("line".into(), Conversion::Transfer));
+        let filter = ProcessFilter {
+            node: Some("heat".into()),
+            ..ProcessFilter::default()
+        };
+        let processes = input_data.processes(Some(filter), None, None).unwrap();
+        assert_eq!(processes.len(), 1);
+        assert_eq!(processes[0].name, "boiler");
+        let filter = ProcessFilter {
+            conversion: Some(Conversion::Transfer),
+            ..ProcessFilter::default()
+        };
+        let processes = input_data.processes(Some(filter), Some(5), None).unwrap();
+        assert_eq!(processes[0].name, "line");
+    }
     #[test]
     fn expanding_input_data_works() {
         let time_line: TimeLine = vec![
@@ -1864,7 +2372,8 @@ mod tests {
         let scenarios =
             vec![Scenario::new("S1", 1.0).expect("scenario construction should succeed")];
         let base_setup = BaseInputDataSetup::default();
-        let setup = base_setup.expand_to_time_series(&time_line, &scenarios);
+        let setup = base_setup.expand_to_time_series(&time_line, &scenarios)
+            .expect("expansion should succeed");
         let base_topology = BaseTopology {
             source: "Source".to_string(),
             sink: "Sink".to_string(),
@@ -1878,6 +2387,7 @@ mod tests {
                 scenario: None,
                 value: SeriesValue::Constant(Constant { value: 1.7 }),
             }],
+            capacity_unit: None,
         };
         let base_process = BaseProcess {
             name: "Conversion".to_string(),
@@ -1908,7 +2418,8 @@ mod tests {
             }],
             eff_ops_fun: vec![Point { x: 2.2, y: 2.3 }],
         };
-        let process = base_process.expand_to_time_series(&time_line, &scenarios);
+        let process = base_process.expand_to_time_series(&time_line, &scenarios)
+            .expect("expansion should succeed");
         let base_node = BaseNode {
             name: "East".to_string(),
             groups: vec!["Group".to_string()],
@@ -1925,7 +2436,8 @@ mod tests {
                 value: BaseForecastable::Constant(Constant { value: 1.2 }),
             }],
         };
-        let node = base_node.expand_to_time_series(&time_line, &scenarios);
+        let node = base_node.expand_to_time_series(&time_line, &scenarios)
+            .expect("expansion should succeed");
         let base_node_diffusion = BaseNodeDiffusion {
             from_node: "Node 1".to_string(),
             to_node: "Node 2".to_string(),
@@ -1934,7 +2446,8 @@ mod tests {
                 value: SeriesValue::Constant(Constant { value: -2.3 }),
             }],
         };
-        let node_diffusion = base_node_diffusion.expand_to_time_series(&time_line, &scenarios);
+        let node_diffusion = base_node_diffusion.expand_to_time_series(&time_line, &scenarios)
+            .expect("expansion should succeed");
         let node_delay = vec![Delay {
             from_node: "South".to_string(),
             to_node: "North".to_string(),
@@ -1952,7 +2465,8 @@ mod tests {
                 values: vec![1.1],
             }],
         };
-        let node_history = base_node_history.expand_to_time_series(&time_line, &scenarios);
+        let node_history = base_node_history.expand_to_time_series(&time_line, &scenarios)
+            .expect("expansion should succeed");
         let base_market = BaseMarket {
             name: "Market".to_string(),
             m_type: MarketType::Energy,
@@ -1989,8 +2503,11 @@ mod tests {
                 name: "Fix".to_string(),
                 factor: 1.9,
             }],
+            price_unit: None,
+            price_scenarios: None,
         };
-        let market = base_market.expand_to_time_series(&time_line, &scenarios);
+        let market = base_market.expand_to_time_series(&time_line, &scenarios)
+            .expect("expansion should succeed");
         let node_groups = vec![NodeGroup::new("The node club".into())];
         let process_groups = vec![ProcessGroup::new("The process club".into())];
         let scenarios =
@@ -2011,7 +2528,8 @@ mod tests {
                 value: SeriesValue::Constant(Constant { value: 2.3 }),
             }],
         };
-        let inflow_block = base_inflow_block.expand_to_time_series(&time_line, &scenarios);
+        let inflow_block = base_inflow_block.expand_to_time_series(&time_line, &scenarios)
+            .expect("expansion should succeed");
         let base_con_factor = BaseConFactor {
             var_type: ConstraintFactorType::State,
             var_tuple: VariableId {
@@ -2034,7 +2552,8 @@ mod tests {
                 value: SeriesValue::Constant(Constant { value: 1.2 }),
             }],
         };
-        let gen_constraint = base_gen_constraint.expand_to_time_series(&time_line, &scenarios);
+        let gen_constraint = base_gen_constraint.expand_to_time_series(&time_line, &scenarios)
+            .expect("expansion should succeed");
         let base = BaseInputData {
             scenarios: scenarios,
             setup: base_setup,
@@ -2051,7 +2570,8 @@ mod tests {
             inflow_blocks: vec![base_inflow_block],
             gen_constraints: vec![base_gen_constraint],
         };
-        let input_data = base.expand_to_time_series(&time_line);
+        let input_data = base.expand_to_time_series(&time_line)
+            .expect("expansion should succeed");
         let temporals = Temporals {
             t: time_line,
             dtf: 1.0,
@@ -2120,8 +2640,10 @@ mod tests {
                 scenario: None,
                 value: SeriesValue::Constant(Constant { value: 1.7 }),
             }],
+            capacity_unit: None,
         };
-        let topology = base_topology.expand_to_time_series(&time_line, &scenarios);
+        let topology = base_topology.expand_to_time_series(&time_line, &scenarios)
+            .expect("expansion should succeed");
         let base = BaseProcess {
             name: "Conversion".to_string(),
             groups: vec!["Group".to_string()],
@@ -2156,7 +2678,8 @@ mod tests {
                 Point { x: 4.0, y: 0.5 },
             ],
         };
-        let process = base.expand_to_time_series(&time_line, &scenarios);
+        let process = base.expand_to_time_series(&time_line, &scenarios)
+            .expect("expansion should succeed");
         assert_eq!(process.name, "Conversion");
         assert_eq!(process.groups, vec!["Group".to_string()]);
         assert_eq!(process.conversion, 2);
@@ -2231,7 +2754,8 @@ mod tests {
             }],
         };
 
-        let node = base.expand_to_time_series(&time_line, &scenarios);
+        let node = base.expand_to_time_series(&time_line, &scenarios)
+            .expect("expansion should succeed");
 
         assert_eq!(node.name, "East");
         assert_eq!(node.groups, vec!["Group".to_string()]);
@@ -2285,7 +2809,8 @@ mod tests {
         ];
         let scenarios =
             vec![Scenario::new("S1", 1.0).expect("constructing scenario should succeed")];
-        let node_diffusion = base.expand_to_time_series(&time_line, &scenarios);
+        let node_diffusion = base.expand_to_time_series(&time_line, &scenarios)
+            .expect("expansion should succeed");
         assert_eq!(node_diffusion.node1, "Node 1");
         assert_eq!(node_diffusion.node2, "Node 2");
         assert_eq!(
@@ -2294,6 +2819,47 @@ mod tests {
         );
     }
     #[test]
+    fn expanding_topology_reports_process_and_field_on_error() {
+        let time_line: TimeLine = vec![
+            Utc.with_ymd_and_hms(2024, 11, 19, 13, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2024, 11, 19, 14, 0, 0).unwrap(),
+        ];
+        let scenarios =
+            vec![Scenario::new("S1", 1.0).expect("constructing scenario should succeed")];
+        let mut base = BaseProcess::new("Process 1".to_string(), Conversion::Unit);
+        let mut topology = BaseTopology::new("Node 1".to_string(), "Process 1".to_string());
+        topology.cap_ts.push(Value {
+            scenario: Some("S1".to_string()),
+            value: SeriesValue::FloatList(FloatList::new(vec![1.0])),
+        });
+        base.topos.push(topology);
+        let error = base
+            .expand_to_time_series(&time_line, &scenarios)
+            .expect_err("expansion should fail");
+        assert_eq!(error.entity, "processes/Process 1/topos/Node 1->Process 1");
+        assert_eq!(error.field, "cap_ts");
+        assert_eq!(
+            error.to_string(),
+            "processes/Process 1/topos/Node 1->Process 1/cap_ts: time series mismatch in FloatList, expected length 2, found 1"
+        );
+    }
+    #[test]
+    fn expanding_node_diffusion_without_coefficient_fails() {
+        let base = BaseNodeDiffusion {
+            from_node: "Node 1".to_string(),
+            to_node: "Node 2".to_string(),
+            coefficient: Vec::new(),
+        };
+        let time_line: TimeLine = vec![Utc.with_ymd_and_hms(2024, 11, 19, 13, 0, 0).unwrap()];
+        let scenarios =
+            vec![Scenario::new("S1", 1.0).expect("constructing scenario should succeed")];
+        let error = base
+            .expand_to_time_series(&time_line, &scenarios)
+            .expect_err("expansion should fail");
+        assert_eq!(error.entity, "node_diffusion/Node 1->Node 2");
+        assert_eq!(error.field, "coefficient");
+    }
+    #[test]
     fn expanding_node_history_works() {
         let time_line: TimeLine = vec![
             Utc.with_ymd_and_hms(2024, 11, 19, 13, 0, 0).unwrap().into(),
@@ -2311,7 +2877,8 @@ mod tests {
                 values: vec![1.1],
             }],
         };
-        let node_history = base.expand_to_time_series(&time_line, &scenarios);
+        let node_history = base.expand_to_time_series(&time_line, &scenarios)
+            .expect("expansion should succeed");
         assert_eq!(node_history.node, "South");
         assert_eq!(
             node_history.steps,
@@ -2326,6 +2893,57 @@ mod tests {
         );
     }
 
+    #[test]
+    fn expanding_node_history_rejects_unknown_scenarios() {
+        let time_line: TimeLine = vec![Utc.with_ymd_and_hms(2024, 11, 19, 13, 0, 0).unwrap()];
+        let scenarios =
+            vec![Scenario::new("member_1", 1.0).expect("constructing scenario should succeed")];
+        let base = BaseNodeHistory {
+            node: "South".to_string(),
+            steps: vec![Series {
+                scenario: "S1".into(),
+                durations: vec![Duration::try_new(1, 0, 0).unwrap()],
+                values: vec![1.1],
+            }],
+        };
+        let error = base
+            .expand_to_time_series(&time_line, &scenarios)
+            .expect_err("expansion should fail");
+        assert_eq!(error.entity, "node_histories/South");
+        assert_eq!(error.message, "no such scenario 'S1'");
+    }
+    #[test]
+    fn node_history_is_mapped_to_generated_scenarios() {
+        let step = |scenario: &str, value: f64| Series {
+            scenario: scenario.into(),
+            durations: vec![Duration::try_new(1, 0, 0).unwrap()],
+            values: vec![value],
+        };
+        let scenarios = vec![
+            Scenario::new("member_1", 0.5).unwrap(),
+            Scenario::new("member_2", 0.5).unwrap(),
+        ];
+        let history = BaseNodeHistory {
+            node: "South".to_string(),
+            steps: vec![step("S1", 1.1), step("S2", 1.1)],
+        };
+        assert_eq!(
+            history.map_to_scenarios(&scenarios),
+            Ok(BaseNodeHistory {
+                node: "South".to_string(),
+                steps: vec![step("member_1", 1.1), step("member_2", 1.1)],
+            })
+        );
+        let history = BaseNodeHistory {
+            node: "South".to_string(),
+            steps: vec![step("S1", 1.1), step("S2", 2.2)],
+        };
+        assert_eq!(
+            history.map_to_scenarios(&scenarios),
+            Err("steps of node history 'South' differ between scenarios".to_string())
+        );
+    }
+
     #[test]
     fn expanding_market_works() {
         use std::collections::BTreeMap;
@@ -2372,9 +2990,12 @@ mod tests {
                 name:   "Fix".to_owned(),
                 factor: 1.9,
             }],
+            price_unit: None,
+            price_scenarios: None,
         };
 
-        let market = base.expand_to_time_series(&timeline, &scenarios);
+        let market = base.expand_to_time_series(&timeline, &scenarios)
+            .expect("expansion should succeed");
 
         assert_eq!(market.name,          "Market");
         assert_eq!(market.m_type,        "energy");              
@@ -2448,7 +3069,8 @@ mod tests {
                 value: SeriesValue::Constant(Constant { value: 2.3 }),
             }],
         };
-        let inflow_block = base.expand_to_time_series(&time_line, &scenarios);
+        let inflow_block = base.expand_to_time_series(&time_line, &scenarios)
+            .expect("expansion should succeed");
         assert_eq!(inflow_block.name, "Inflow");
         assert_eq!(inflow_block.node, "West");
         assert_eq!(inflow_block.start_time, time_line[0]);
@@ -2476,7 +3098,8 @@ mod tests {
                 value: SeriesValue::Constant(Constant { value: 23.0 }),
             }],
         };
-        let con_factor = base_con_factor.expand_to_time_series(&time_line, &scenarios);
+        let con_factor = base_con_factor.expand_to_time_series(&time_line, &scenarios)
+            .expect("expansion should succeed");
         let base = BaseGenConstraint {
             name: "Constraint".to_string(),
             gc_type: ConstraintType::GreaterThan,
@@ -2488,7 +3111,8 @@ mod tests {
                 value: SeriesValue::Constant(Constant { value: 1.2 }),
             }],
         };
-        let gen_constraint = base.expand_to_time_series(&time_line, &scenarios);
+        let gen_constraint = base.expand_to_time_series(&time_line, &scenarios)
+            .expect("expansion should succeed");
         assert_eq!(gen_constraint.name, "Constraint");
         assert_eq!(gen_constraint.gc_type, "gt");
         assert!(gen_constraint.is_setpoint);
@@ -2514,6 +3138,7 @@ mod tests {
                 scenario: None,
                 value: SeriesValue::Constant(Constant { value: 1.7 }),
             }],
+            capacity_unit: None,
         };
         let time_line: TimeLine = vec![
             Utc.with_ymd_and_hms(2024, 11, 19, 13, 0, 0).unwrap().into(),
@@ -2521,7 +3146,8 @@ mod tests {
         ];
         let scenarios =
             vec![Scenario::new("S1", 1.0).expect("constructing scenario should succeed")];
-        let topology = base.expand_to_time_series(&time_line, &scenarios);
+        let topology = base.expand_to_time_series(&time_line, &scenarios)
+            .expect("expansion should succeed");
         assert_eq!(topology.source, "Source");
         assert_eq!(topology.sink, "Sink");
         assert_eq!(topology.capacity, 1.1);
@@ -2554,7 +3180,8 @@ mod tests {
         ];
         let scenarios =
             vec![Scenario::new("S1", 1.0).expect("constructing scenario should succeed")];
-        let con_factor = base.expand_to_time_series(&time_line, &scenarios);
+        let con_factor = base.expand_to_time_series(&time_line, &scenarios)
+            .expect("expansion should succeed");
         assert_eq!(con_factor.var_type, "state");
         assert_eq!(
             con_factor.var_tuple,
@@ -2579,6 +3206,114 @@ mod tests {
         assert!(temporals.variable_dt.is_none());
     }
     #[test]
+    fn make_temporals_gives_variable_steps() {
+        let start = Utc.with_ymd_and_hms(2024, 11, 19, 13, 0, 0).unwrap();
+        let time_line: TimeLine = vec![
+            start,
+            start + chrono::TimeDelta::minutes(15),
+            start + chrono::TimeDelta::minutes(30),
+            start + chrono::TimeDelta::minutes(90),
+        ];
+        let temporals = make_temporals(&time_line);
+        assert_eq!(temporals.dtf, 0.25);
+        assert_eq!(
+            temporals.variable_dt,
+            Some(vec![
+                ("2024-11-19T13:00:00+00:00".to_string(), 0.25),
+                ("2024-11-19T13:15:00+00:00".to_string(), 0.25),
+                ("2024-11-19T13:30:00+00:00".to_string(), 1.0),
+                ("2024-11-19T14:30:00+00:00".to_string(), 1.0),
+            ])
+        );
+    }
+    #[test]
+    fn float_lists_at_finest_step_are_averaged() {
+        let start = Utc.with_ymd_and_hms(2024, 11, 19, 13, 0, 0).unwrap();
+        let time_line: TimeLine = vec![
+            start,
+            start + chrono::TimeDelta::minutes(30),
+            start + chrono::TimeDelta::minutes(90),
+        ];
+        let series =
+            float_list_to_series(&FloatList::new(vec![1.0, 2.0, 4.0, 5.0]), &time_line).unwrap();
+        assert_eq!(series.values().copied().collect::<Vec<f64>>(), vec![1.0, 3.0, 5.0]);
+        let series = float_list_to_series(&FloatList::new(vec![1.0, 2.0, 3.0]), &time_line).unwrap();
+        assert_eq!(series.values().copied().collect::<Vec<f64>>(), vec![1.0, 2.0, 3.0]);
+        assert_eq!(
+            float_list_to_series(&FloatList::new(vec![1.0, 2.0]), &time_line),
+            Err("time series mismatch in FloatList, expected length 3, found 2".to_string())
+        );
+    }
+    #[test]
+    fn float_lists_with_time_stamps_are_resampled() {
+        let start = Utc.with_ymd_and_hms(2024, 11, 19, 13, 0, 0).unwrap();
+        let input = ValueInput {
+            series: Some(vec![4.0, 8.0]),
+            time_stamps: Some(vec![
+                "2024-11-19T13:00:00Z".to_string(),
+                "2024-11-19T14:00:00Z".to_string(),
+            ]),
+            resampling: Some(ResamplingMethod::EnergyConserving),
+            ..ValueInput::default()
+        };
+        let value = Value::try_from(input).unwrap();
+        let time_line: TimeLine = (0..4)
+            .map(|i| start + chrono::TimeDelta::minutes(30 * i))
+            .collect();
+        let series = convert_value_to_series(&value, &time_line).unwrap();
+        assert_eq!(series.values().copied().collect::<Vec<f64>>(), vec![2.0, 2.0, 4.0, 4.0]);
+        let input = ValueInput {
+            series: Some(vec![4.0, 8.0]),
+            time_stamps: Some(vec!["2024-11-19T13:00:00Z".to_string()]),
+            ..ValueInput::default()
+        };
+        assert_eq!(
+            Value::try_from(input).err(),
+            Some("series has 2 values but 1 time stamps".to_string())
+        );
+        let input = ForecastValueInput {
+            constant: Some(1.0),
+            resampling: Some(ResamplingMethod::StepHold),
+            ..ForecastValueInput::default()
+        };
+        assert!(ForecastValue::try_from(input).is_err());
+    }
+    #[test]
+    fn time_stamps_without_offset_are_in_given_time_zone() {
+        let input = ValueInput {
+            series: Some(vec![1.0, 2.0]),
+            time_stamps: Some(vec![
+                "2024-11-19T15:00:00".to_string(),
+                "2024-11-19T16:00:00+02:00".to_string(),
+            ]),
+            ..ValueInput::default()
+        };
+        let value = Value::from_input(input, chrono_tz::Europe::Helsinki).unwrap();
+        let SeriesValue::FloatList(list) = value.value else {
+            panic!("value should be a list");
+        };
+        assert_eq!(
+            list.time_stamps(),
+            &vec![
+                Utc.with_ymd_and_hms(2024, 11, 19, 13, 0, 0).unwrap(),
+                Utc.with_ymd_and_hms(2024, 11, 19, 14, 0, 0).unwrap(),
+            ]
+        );
+        let input = ForecastValueInput {
+            series: Some(vec![1.0]),
+            time_stamps: Some(vec!["2024-11-19T15:00:00".to_string()]),
+            ..ForecastValueInput::default()
+        };
+        let value = ForecastValue::from_input(input, chrono_tz::Europe::Helsinki).unwrap();
+        let BaseForecastable::FloatList(list) = value.value else {
+            panic!("value should be a list");
+        };
+        assert_eq!(
+            list.time_stamps(),
+            &vec![Utc.with_ymd_and_hms(2024, 11, 19, 13, 0, 0).unwrap()]
+        );
+    }
+    #[test]
     fn to_time_series_works() {
         let time_line: TimeLine = vec![
             Utc.with_ymd_and_hms(2024, 11, 19, 13, 0, 0).unwrap().into(),
@@ -2607,7 +3342,9 @@ mod tests {
                 Utc.with_ymd_and_hms(2024, 12, 18, 13, 0, 0).unwrap(),
                 Utc.with_ymd_and_hms(2024, 12, 18, 14, 0, 0).unwrap(),
             ];
-            let time_series = series.to_time_series(&time_line);
+            let time_series = series
+                .to_time_series(&time_line)
+                .expect("conversion should succeed");
             assert_eq!(time_series.scenario, "S1");
             assert!(time_series.series.is_empty());
         }
@@ -2622,7 +3359,9 @@ mod tests {
                 Utc.with_ymd_and_hms(2024, 12, 18, 13, 0, 0).unwrap(),
                 Utc.with_ymd_and_hms(2024, 12, 18, 14, 0, 0).unwrap(),
             ];
-            let time_series = series.to_time_series(&time_line);
+            let time_series = series
+                .to_time_series(&time_line)
+                .expect("conversion should succeed");
             assert_eq!(time_series.scenario, "S1");
             assert_eq!(time_series.series.len(), time_line.len());
             let mut expected_series = BTreeMap::new();
@@ -2642,7 +3381,9 @@ mod tests {
                 Utc.with_ymd_and_hms(2024, 12, 18, 13, 0, 0).unwrap(),
                 Utc.with_ymd_and_hms(2024, 12, 18, 14, 0, 0).unwrap(),
             ];
-            let time_series = series.to_time_series(&time_line);
+            let time_series = series
+                .to_time_series(&time_line)
+                .expect("conversion should succeed");
             assert_eq!(time_series.scenario, "S1");
             assert_eq!(time_series.series.len(), 1);
             let mut expected_series = BTreeMap::new();
@@ -2664,7 +3405,9 @@ mod tests {
                 Utc.with_ymd_and_hms(2024, 12, 18, 13, 0, 0).unwrap(),
                 Utc.with_ymd_and_hms(2024, 12, 18, 14, 0, 0).unwrap(),
             ];
-            let time_series = series.to_time_series(&time_line);
+            let time_series = series
+                .to_time_series(&time_line)
+                .expect("conversion should succeed");
             assert_eq!(time_series.scenario, "S1");
             assert_eq!(time_series.series.len(), 2);
             let mut expected_series = BTreeMap::new();
@@ -2672,6 +3415,27 @@ mod tests {
             expected_series.insert(time_line[1], 23.0);
             assert_eq!(time_series.series, expected_series)
         }
+        #[test]
+        fn to_time_series_averages_over_long_steps() {
+            let series = Series {
+                scenario: "S1".into(),
+                durations: vec![
+                    Duration::try_new(0, 45, 0).unwrap(),
+                    Duration::try_new(1, 0, 0).unwrap(),
+                ],
+                values: vec![2.0, 6.0],
+            };
+            let time_line = vec![
+                Utc.with_ymd_and_hms(2024, 12, 18, 13, 0, 0).unwrap(),
+                Utc.with_ymd_and_hms(2024, 12, 18, 13, 15, 0).unwrap(),
+                Utc.with_ymd_and_hms(2024, 12, 18, 14, 15, 0).unwrap(),
+            ];
+            let time_series = series
+                .to_time_series(&time_line)
+                .expect("conversion should succeed");
+            let values: Vec<f64> = time_series.series.values().copied().collect();
+            assert_eq!(values, vec![2.0, 4.0, 6.0]);
+        }
     }
 
     #[test]
@@ -2680,6 +3444,8 @@ mod tests {
             scenario: Some("test_scenario".to_string()),
             constant: Some(42.0),
             series: None,
+            time_stamps: None,
+            resampling: None,
         };
 
         let value = Value::try_from(input).unwrap();
@@ -2695,6 +3461,8 @@ mod tests {
             scenario: Some("test_scenario".to_string()),
             constant: None,
             series: Some(vec![1.0, 2.0, 3.0]),
+            time_stamps: None,
+            resampling: None,
         };
 
         let value = Value::try_from(input).unwrap();
@@ -2711,6 +3479,8 @@ mod tests {
             scenario: Some("invalid_scenario".to_string()),
             constant: Some(42.0),
             series: Some(vec![1.0, 2.0, 3.0]),
+            time_stamps: None,
+            resampling: None,
         };
 
         let result = Value::try_from(input);
@@ -2727,6 +3497,8 @@ mod tests {
             scenario: None,
             constant: None,
             series: None,
+            time_stamps: None,
+            resampling: None,
         };
 
         let value = Value::try_from(input).unwrap();
@@ -2752,7 +3524,8 @@ mod tests {
         let forecast_values = vec![fv];
         let scenarios = vec![Scenario::new("S1", 1.0).unwrap()];
         let timeline = make_timeline(&[(2025, 1, 1, 0, 0, 0), (2025, 1, 1, 1, 0, 0)]);
-        let result = forecast_values_to_forecastable(&forecast_values, &scenarios, &timeline);
+        let result = forecast_values_to_forecastable(&forecast_values, &scenarios, &timeline)
+            .expect("conversion should succeed");
         match result {
             Forecastable::Forecast(f) => {
                 assert_eq!(f.name(), "MyForecast");
@@ -2819,7 +3592,8 @@ mod tests {
             Scenario::new("S2", 1.0).unwrap(),
         ];
         let timeline = make_timeline(&[(2025, 1, 1, 0, 0, 0), (2025, 1, 1, 1, 0, 0)]);
-        let result = forecast_values_to_forecastable(&forecast_values, &scenarios, &timeline);
+        let result = forecast_values_to_forecastable(&forecast_values, &scenarios, &timeline)
+            .expect("conversion should succeed");
         match result {
             Forecastable::TimeSeriesData(ts_data) => {
                 let ts_map: BTreeMap<_, _> = ts_data.ts_data.into_iter()
@@ -3010,9 +3784,7 @@ mod tests {
 
         let value = Value {
             scenario: Some("S1".to_string()),
-            value: SeriesValue::FloatList(FloatList {
-                values: vec![1.1, 2.2, 3.3],
-            }),
+            value: SeriesValue::FloatList(FloatList::new(vec![1.1, 2.2, 3.3])),
         };
         let values = vec![value];
 
@@ -3036,7 +3808,7 @@ mod tests {
         let scenarios = vec![Scenario::new("S1", 1.0).expect("failed to create scenario")];
         let value = Value {
             scenario: Some("S1".to_string()),
-            value: SeriesValue::FloatList(FloatList { values: vec![1.0] }),
+            value: SeriesValue::FloatList(FloatList::new(vec![1.0])),
         };
         let values = vec![value];
 
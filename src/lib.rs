@@ -3,9 +3,12 @@ pub mod graphql;
 mod input_data;
 mod input_data_base;
 pub mod model;
+pub mod predicer_excel;
+mod resampling;
 mod scenarios;
 pub mod settings;
 mod time_line_settings;
+mod units;
 
 use chrono::{DateTime, Utc};
 
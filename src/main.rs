@@ -3,7 +3,11 @@ use hertta::event_loop;
 use hertta::event_loop::job_store::JobStore;
 use hertta::event_loop::jobs::NewJob;
 use hertta::graphql::{HerttaContext, Mutation, Query, Schema};
+use hertta::model::diff;
+use hertta::model::graph::{self, GraphFormat};
+use hertta::model::history::ModelHistory;
 use hertta::model::{self, Model};
+use hertta::predicer_excel;
 use hertta::settings::{self, Settings};
 use juniper::{EmptySubscription, RootNode};
 use std::error::Error;
@@ -25,6 +29,38 @@ struct CommandLineArgs {
     write_settings: bool,
     #[arg(long, help = "print GraphQL schema and exit")]
     print_schema: bool,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "import Predicer input workbook into the model and exit"
+    )]
+    import_predicer_excel: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "export the model as Predicer input workbook and exit"
+    )]
+    export_predicer_excel: Option<PathBuf>,
+    #[arg(
+        long,
+        num_args = 2,
+        value_names = ["OLD", "NEW"],
+        help = "print differences between two model files and exit"
+    )]
+    diff_models: Option<Vec<PathBuf>>,
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        help = "print the model as a dot, mermaid or graphml graph and exit"
+    )]
+    model_graph: Option<GraphFormat>,
+    #[arg(
+        long,
+        value_name = "GROUP",
+        requires = "model_graph",
+        help = "limit the model graph to members of a node or process group"
+    )]
+    graph_group: Option<String>,
 }
 
 fn write_default_settings_to_file(settings_file_path: &PathBuf) -> Result<(), Box<dyn Error>> {
@@ -58,6 +94,50 @@ fn print_schema_json() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+fn import_predicer_excel(workbook_path: &Path) -> Result<(), Box<dyn Error>> {
+    let outcome = predicer_excel::import_workbook(workbook_path)?;
+    let mut model = get_model();
+    model.input_data = outcome.input_data;
+    if let Some(time_line) = outcome.time_line {
+        model.time_line = time_line;
+    }
+    let model_file_path = model::make_model_file_path();
+    model::write_model_to_file(&model, &model_file_path)?;
+    for sheet in &outcome.report.unmapped_sheets {
+        println!("Skipped sheet '{}'", sheet);
+    }
+    for column in &outcome.report.unmapped_columns {
+        println!("Skipped column '{}' in sheet '{}'", column.column, column.sheet);
+    }
+    for note in &outcome.report.notes {
+        println!("Note: {}", note);
+    }
+    println!("Model written to {}", model_file_path.display());
+    Ok(())
+}
+
+fn export_predicer_excel(workbook_path: &Path) -> Result<(), Box<dyn Error>> {
+    let model = get_model();
+    predicer_excel::export_model(&model, workbook_path)?;
+    println!("Workbook written to {}", workbook_path.display());
+    Ok(())
+}
+
+fn print_model_diff(old_path: &PathBuf, new_path: &PathBuf) -> Result<(), Box<dyn Error>> {
+    let old = model::read_model_from_file(old_path)?;
+    let new = model::read_model_from_file(new_path)?;
+    for difference in diff::diff_models(&old, &new)? {
+        println!("{}", difference);
+    }
+    Ok(())
+}
+
+fn print_model_graph(format: GraphFormat, group: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let model = get_model();
+    print!("{}", graph::render_graph(&model.input_data, format, group)?);
+    Ok(())
+}
+
 fn get_model() -> Model {
     let file_path = model::make_model_file_path();
     if file_path.is_file() {
@@ -91,6 +171,22 @@ async fn main() -> Result<(), Box<dyn Error>> {
         print_schema_json()?;
         return Ok(());
     }
+    if let Some(ref workbook_path) = args.import_predicer_excel {
+        import_predicer_excel(workbook_path)?;
+        return Ok(());
+    }
+    if let Some(ref workbook_path) = args.export_predicer_excel {
+        export_predicer_excel(workbook_path)?;
+        return Ok(());
+    }
+    if let Some(ref model_paths) = args.diff_models {
+        print_model_diff(&model_paths[0], &model_paths[1])?;
+        return Ok(());
+    }
+    if let Some(format) = args.model_graph {
+        print_model_graph(format, args.graph_group.as_deref())?;
+        return Ok(());
+    }
     let settings = Arc::new(Mutex::new(settings::make_settings(
         &settings::map_from_environment_variables(),
         &settings::make_settings_file_path(),
@@ -105,6 +201,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let (job_sender, job_receiver) = mpsc::channel::<NewJob>(32);
     let job_store = JobStore::default();
     let model = Arc::new(Mutex::new(get_model()));
+    let model_history = Arc::new(std::sync::Mutex::new(ModelHistory::default()));
     spawn_event_loop(
         Arc::clone(&settings),
         job_store.clone(),
@@ -127,10 +224,19 @@ async fn main() -> Result<(), Box<dyn Error>> {
                 .and(inject_clone(settings))
                 .and(inject_clone(job_store))
                 .and(inject_clone(model))
+                .and(inject_clone(model_history))
                 .and(inject_clone(job_sender))
-                .map(|settings_clone, job_store_clone, model_clone, job_sender| {
-                    HerttaContext::new(settings_clone, job_store_clone, model_clone, job_sender)
-                }),
+                .map(
+                    |settings_clone, job_store_clone, model_clone, model_history_clone, job_sender| {
+                        HerttaContext::new(
+                            settings_clone,
+                            job_store_clone,
+                            model_clone,
+                            model_history_clone,
+                            job_sender,
+                        )
+                    },
+                ),
         ))
         .with(cors);
     let health_route = warp::path("health")
@@ -1,41 +1,220 @@
+pub mod diff;
+pub mod graph;
+pub mod history;
+pub mod library;
+pub mod summary;
+pub mod template;
+pub mod validation;
+
 use crate::graphql::HerttaContext;
 use crate::input_data_base::BaseInputData;
+use crate::scenarios::ensemble::WeatherEnsemble;
+use crate::scenarios::reduction::ScenarioReduction;
 use crate::settings;
 use crate::time_line_settings::TimeLineSettings;
-use juniper::GraphQLObject;
+use juniper::{GraphQLEnum, GraphQLObject};
 use serde::{Deserialize, Serialize};
-use std::fs::{self, File};
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 
-#[derive(Clone, Default, Deserialize, GraphQLObject, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, GraphQLObject, PartialEq, Serialize)]
 #[graphql(description = "Optimization model.", context = HerttaContext)]
 pub struct Model {
     #[serde(default)]
     pub time_line: TimeLineSettings,
     pub input_data: BaseInputData,
+    #[serde(default)]
+    #[graphql(description = "If set, scenarios are generated from an ensemble weather forecast when optimizing.")]
+    pub weather_ensemble: Option<WeatherEnsemble>,
+    #[serde(default)]
+    #[graphql(description = "If set, scenarios are reduced to a few representative ones before optimizing.")]
+    pub scenario_reduction: Option<ScenarioReduction>,
 }
 
 pub fn make_model_file_path() -> PathBuf {
     settings::config_path().join("model.json")
 }
 
+#[derive(Clone, Copy, Debug, GraphQLEnum, PartialEq)]
+pub enum ModelFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl ModelFormat {
+    /// Chooses format by file extension; files without extension are JSON.
+    pub fn from_path(file_path: &Path) -> Result<Self, String> {
+        match file_path.extension().and_then(|e| e.to_str()) {
+            None | Some("json") => Ok(ModelFormat::Json),
+            Some("yaml") | Some("yml") => Ok(ModelFormat::Yaml),
+            Some("toml") => Ok(ModelFormat::Toml),
+            Some(extension) => Err(format!("unknown model file extension '{}'", extension)),
+        }
+    }
+}
+
+pub fn model_to_string(model: &Model, format: ModelFormat) -> Result<String, String> {
+    match format {
+        ModelFormat::Json => serde_json::to_string_pretty(model).map_err(|e| e.to_string()),
+        ModelFormat::Yaml => serde_yaml::to_string(model).map_err(|e| e.to_string()),
+        ModelFormat::Toml => toml::to_string_pretty(model).map_err(|e| e.to_string()),
+    }
+}
+
+pub fn model_from_str(text: &str, format: ModelFormat) -> Result<Model, String> {
+    match format {
+        ModelFormat::Json => serde_json::from_str(text).map_err(|e| e.to_string()),
+        ModelFormat::Yaml => serde_yaml::from_str(text).map_err(|e| e.to_string()),
+        ModelFormat::Toml => toml::from_str(text).map_err(|e| e.to_string()),
+    }
+}
+
+/// Reads model from a JSON, YAML or TOML file depending on file extension.
 pub fn read_model_from_file(file_path: &PathBuf) -> Result<Model, String> {
-    let model_file = File::open(file_path)
-        .or_else(|error| Err(format!("failed to open model file: {}", error)))?;
-    let model = serde_json::from_reader(model_file)
-        .or_else(|error| Err(format!("failed to parse model file: {}", error)))?;
-    Ok(model)
+    let format = ModelFormat::from_path(file_path)?;
+    let text = fs::read_to_string(file_path)
+        .map_err(|error| format!("failed to open model file: {}", error))?;
+    model_from_str(&text, format).map_err(|error| format!("failed to parse model file: {}", error))
 }
 
+/// Writes model to a JSON, YAML or TOML file depending on file extension.
 pub fn write_model_to_file(model: &Model, file_path: &PathBuf) -> Result<(), String> {
+    let format = ModelFormat::from_path(file_path)?;
     match file_path.parent() {
         Some(settings_dir) => fs::create_dir_all(settings_dir)
             .or_else(|_| Err("failed to create config directory".to_string()))?,
         None => return Err("settings file should have a parent directory".into()),
     };
-    let model_file =
-        File::create(file_path).or_else(|_| Err("failed to create model file".to_string()))?;
-    serde_json::to_writer_pretty(model_file, model)
-        .or_else(|_| Err("failed to write model to file".to_string()))?;
+    let text = model_to_string(model, format)
+        .map_err(|error| format!("failed to write model to file: {}", error))?;
+    fs::write(file_path, text).or_else(|_| Err("failed to create model file".to_string()))?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_data::State;
+    use crate::input_data_base::{
+        BaseMarket, BaseNode, BaseNodeDiffusion, BaseProcess, BaseTopology, Conversion,
+        ForecastValue, ForecastValueInput, MarketDirection, MarketType, NodeGroup, Value,
+        ValueInput,
+    };
+    use crate::scenarios::Scenario;
+    use crate::time_line_settings::{CustomStartTime, Duration, TimeLineStart};
+    use chrono::{TimeZone, Utc};
+
+    fn value(scenario: Option<&str>, constant: Option<f64>, series: Option<Vec<f64>>) -> Value {
+        Value::try_from(ValueInput {
+            scenario: scenario.map(String::from),
+            constant,
+            series,
+            time_stamps: None,
+            resampling: None,
+        })
+        .unwrap()
+    }
+
+    fn make_model() -> Model {
+        let time_line = TimeLineSettings::try_new(
+            Duration::try_new(2, 0, 0).unwrap(),
+            Duration::try_new(0, 15, 0).unwrap(),
+            TimeLineStart::CustomStartTime(CustomStartTime {
+                start_time: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap(),
+            }),
+        )
+        .unwrap();
+        let mut heat = BaseNode::new("heat".into());
+        heat.groups.push("heating".into());
+        heat.state = Some(State {
+            state_max: 10.5,
+            ..State::default()
+        });
+        heat.cost.push(value(Some("s1"), None, Some(vec![1.0, 2.5])));
+        heat.inflow.push(
+            ForecastValue::try_from(ForecastValueInput {
+                forecast: Some("FMI".into()),
+                f_type: Some("temperature".into()),
+                ..ForecastValueInput::default()
+            })
+            .unwrap(),
+        );
+        let mut process = BaseProcess::new("boiler".into(), Conversion::Unit);
+        let mut topology = BaseTopology::new("boiler".into(), "heat".into());
+        topology.capacity = 3.0;
+        topology.cap_ts.push(value(None, Some(2.0), None));
+        process.topos.push(topology);
+        Model {
+            time_line,
+            input_data: BaseInputData {
+                scenarios: vec![Scenario::new("s1", 1.0).unwrap()],
+                nodes: vec![heat, BaseNode::new("elc".into())],
+                processes: vec![process],
+                node_groups: vec![NodeGroup {
+                    name: "heating".into(),
+                    members: vec!["heat".into()],
+                }],
+                node_diffusion: vec![BaseNodeDiffusion {
+                    from_node: "heat".into(),
+                    to_node: "elc".into(),
+                    coefficient: vec![value(None, Some(0.1), None)],
+                }],
+                markets: vec![BaseMarket {
+                    name: "npe".into(),
+                    m_type: MarketType::Energy,
+                    node: "elc".into(),
+                    process_group: "p1".into(),
+                    direction: Some(MarketDirection::UpDown),
+                    realisation: Vec::new(),
+                    reserve_type: None,
+                    is_bid: true,
+                    is_limited: false,
+                    min_bid: 0.0,
+                    max_bid: 1.0,
+                    fee: 0.0,
+                    price: vec![ForecastValue::try_from(ForecastValueInput {
+                        series: Some(vec![30.0, 40.0]),
+                        ..ForecastValueInput::default()
+                    })
+                    .unwrap()],
+                    up_price: Vec::new(),
+                    down_price: Vec::new(),
+                    reserve_activation_price: Vec::new(),
+                    fixed: Vec::new(),
+                    price_unit: None,
+                    price_scenarios: None,
+                }],
+                ..BaseInputData::default()
+            },
+            weather_ensemble: Some(WeatherEnsemble::new(vec![0.25, 0.75]).unwrap()),
+            scenario_reduction: Some(ScenarioReduction::new(1).unwrap()),
+        }
+    }
+
+    #[test]
+    fn model_round_trips_in_every_format() {
+        let model = make_model();
+        for format in [ModelFormat::Json, ModelFormat::Yaml, ModelFormat::Toml] {
+            let text = model_to_string(&model, format).unwrap();
+            let read_back = model_from_str(&text, format)
+                .unwrap_or_else(|error| panic!("{:?}: {}", format, error));
+            assert_eq!(read_back, model, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn file_format_is_chosen_by_extension() {
+        let directory = tempfile::tempdir().unwrap();
+        let model = make_model();
+        for file_name in ["model.json", "model.yaml", "model.yml", "model.toml"] {
+            let file_path = directory.path().join(file_name);
+            write_model_to_file(&model, &file_path).unwrap();
+            let read_back = read_model_from_file(&file_path).unwrap();
+            assert_eq!(read_back, model, "{}", file_name);
+        }
+        let text = fs::read_to_string(directory.path().join("model.yaml")).unwrap();
+        assert!(text.starts_with("time_line:"));
+        assert!(write_model_to_file(&model, &directory.path().join("model.xml")).is_err());
+    }
+}
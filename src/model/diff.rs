@@ -0,0 +1,464 @@
+use super::Model;
+use juniper::{GraphQLEnum, GraphQLObject};
+use serde_json::{Map, Value};
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, GraphQLEnum, PartialEq)]
+pub enum DifferenceKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+#[derive(Clone, Debug, GraphQLObject, PartialEq)]
+#[graphql(description = "Single difference between two models.")]
+pub struct ModelDifference {
+    pub kind: DifferenceKind,
+    #[graphql(
+        description = "Path to the item, e.g. processes/boiler/topos/fuel->heat/capacity. Items in collections are identified by name; '~' and '/' in names are written as '~0' and '~1'."
+    )]
+    pub path: String,
+    #[graphql(description = "JSON value in the first model; null for added items.")]
+    pub old_value: Option<String>,
+    #[graphql(description = "JSON value in the second model; null for removed items.")]
+    pub new_value: Option<String>,
+}
+
+impl fmt::Display for ModelDifference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let none = String::from("null");
+        match self.kind {
+            DifferenceKind::Added => write!(f, "+ {}", self.path),
+            DifferenceKind::Removed => write!(f, "- {}", self.path),
+            DifferenceKind::Changed => write!(
+                f,
+                "~ {}: {} -> {}",
+                self.path,
+                self.old_value.as_ref().unwrap_or(&none),
+                self.new_value.as_ref().unwrap_or(&none)
+            ),
+        }
+    }
+}
+
+const INPUT_DATA: &str = "input_data";
+
+/// Returns the identifying key of an item in a collection that is matched by name.
+fn item_key(collection: &str, item: &Value) -> Option<String> {
+    let field = |name: &str| item.get(name).and_then(Value::as_str);
+    match collection {
+        "scenarios" | "processes" | "nodes" | "markets" | "node_groups" | "process_groups"
+        | "reserve_type" | "inflow_blocks" | "gen_constraints" => field("name").map(str::to_string),
+        "risk" => field("parameter").map(str::to_string),
+        "node_histories" => field("node").map(str::to_string),
+        "node_diffusion" | "node_delay" => {
+            Some(format!("{}->{}", field("from_node")?, field("to_node")?))
+        }
+        "topos" => Some(format!("{}->{}", field("source")?, field("sink")?)),
+        _ => None,
+    }
+}
+
+/// Returns items keyed by name or None if the collection cannot be matched item by item.
+fn keyed_items<'a>(collection: &str, items: &'a [Value]) -> Option<Vec<(String, &'a Value)>> {
+    let mut keyed: Vec<(String, &Value)> = Vec::with_capacity(items.len());
+    for item in items {
+        let key = item_key(collection, item)?;
+        if keyed.iter().any(|(k, _)| *k == key) {
+            return None;
+        }
+        keyed.push((key, item));
+    }
+    Some(keyed)
+}
+
+/// Escapes '~' and '/' in names as in JSON pointers so that paths can be split on '/'.
+pub fn escape_segment(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+pub fn unescape_segment(segment: &str) -> String {
+    segment.replace("~1", "/").replace("~0", "~")
+}
+
+fn join_path(parent: &str, segment: &str) -> String {
+    let segment = escape_segment(segment);
+    if parent.is_empty() {
+        segment
+    } else {
+        format!("{}/{}", parent, segment)
+    }
+}
+
+fn to_json(value: &Value) -> String {
+    value.to_string()
+}
+
+fn changed(path: String, old: Option<&Value>, new: Option<&Value>) -> ModelDifference {
+    ModelDifference {
+        kind: DifferenceKind::Changed,
+        path,
+        old_value: old.map(to_json),
+        new_value: new.map(to_json),
+    }
+}
+
+fn diff_objects(
+    path: &str,
+    old: &Map<String, Value>,
+    new: &Map<String, Value>,
+    differences: &mut Vec<ModelDifference>,
+) {
+    for (field, old_value) in old {
+        let field_path = join_path(path, field);
+        match new.get(field) {
+            Some(new_value) => diff_values(&field_path, field, old_value, new_value, differences),
+            None => differences.push(changed(field_path, Some(old_value), None)),
+        }
+    }
+    for (field, new_value) in new {
+        if !old.contains_key(field) {
+            differences.push(changed(join_path(path, field), None, Some(new_value)));
+        }
+    }
+}
+
+fn diff_values(
+    path: &str,
+    field: &str,
+    old: &Value,
+    new: &Value,
+    differences: &mut Vec<ModelDifference>,
+) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => diff_objects(path, old, new, differences),
+        (Value::Array(old_items), Value::Array(new_items)) => {
+            match (keyed_items(field, old_items), keyed_items(field, new_items)) {
+                (Some(old_keyed), Some(new_keyed)) => {
+                    diff_collections(path, &old_keyed, &new_keyed, differences)
+                }
+                _ => {
+                    if old != new {
+                        differences.push(changed(path.to_string(), Some(old), Some(new)));
+                    }
+                }
+            }
+        }
+        _ => {
+            if old != new {
+                differences.push(changed(path.to_string(), Some(old), Some(new)));
+            }
+        }
+    }
+}
+
+fn diff_collections(
+    path: &str,
+    old: &[(String, &Value)],
+    new: &[(String, &Value)],
+    differences: &mut Vec<ModelDifference>,
+) {
+    for (key, old_item) in old {
+        let item_path = join_path(path, key);
+        match new.iter().find(|(k, _)| k == key) {
+            Some((_, new_item)) => diff_values(&item_path, "", old_item, new_item, differences),
+            None => differences.push(ModelDifference {
+                kind: DifferenceKind::Removed,
+                path: item_path,
+                old_value: Some(to_json(old_item)),
+                new_value: None,
+            }),
+        }
+    }
+    for (key, new_item) in new {
+        if !old.iter().any(|(k, _)| k == key) {
+            differences.push(ModelDifference {
+                kind: DifferenceKind::Added,
+                path: join_path(path, key),
+                old_value: None,
+                new_value: Some(to_json(new_item)),
+            });
+        }
+    }
+}
+
+fn model_to_json(model: &Model) -> Result<Value, String> {
+    serde_json::to_value(model).map_err(|error| format!("failed to serialize model: {}", error))
+}
+
+/// Returns the path prefix of a model field; input data items have none.
+fn field_prefix(field: &str) -> &str {
+    if field == INPUT_DATA {
+        ""
+    } else {
+        field
+    }
+}
+
+/// Compares two models entity by entity.
+///
+/// Paths of input data items start with the collection name, e.g. `nodes/heat/is_res`;
+/// other model fields such as time line settings start with the field name, e.g. `time_line`.
+pub fn diff_models(old: &Model, new: &Model) -> Result<Vec<ModelDifference>, String> {
+    let old = model_to_json(old)?;
+    let new = model_to_json(new)?;
+    let fields = old
+        .as_object()
+        .ok_or_else(|| "model should serialize to an object".to_string())?;
+    let mut differences = Vec::new();
+    for (field, old_value) in fields {
+        diff_values(
+            field_prefix(field),
+            field,
+            old_value,
+            &new[field.as_str()],
+            &mut differences,
+        );
+    }
+    Ok(differences)
+}
+
+fn parse_json(path: &str, value: &Option<String>) -> Result<Option<Value>, String> {
+    value
+        .as_ref()
+        .map(|json| {
+            serde_json::from_str(json).map_err(|error| format!("{}: invalid JSON: {}", path, error))
+        })
+        .transpose()
+}
+
+fn item_position(collection: &str, items: &[Value], key: &str) -> Option<usize> {
+    items
+        .iter()
+        .position(|item| item_key(collection, item).as_deref() == Some(key))
+}
+
+/// Walks from root to the value at given path segments.
+fn locate<'a>(
+    root: &'a mut Value,
+    segments: &[String],
+    path: &str,
+) -> Result<&'a mut Value, String> {
+    let mut current = root;
+    let mut previous = "";
+    for segment in segments {
+        current = match current {
+            Value::Object(fields) => fields.get_mut(segment),
+            Value::Array(items) => match item_position(previous, items, segment) {
+                Some(position) => items.get_mut(position),
+                None => None,
+            },
+            _ => None,
+        }
+        .ok_or_else(|| format!("{}: no such item", path))?;
+        previous = segment;
+    }
+    Ok(current)
+}
+
+fn split_path(root: &Value, path: &str) -> Result<(Vec<String>, String), String> {
+    let mut segments: Vec<String> = path.split('/').map(unescape_segment).collect();
+    if segments.iter().any(|s| s.is_empty()) {
+        return Err(format!("{}: invalid path", path));
+    }
+    if segments[0] == INPUT_DATA || root.get(&segments[0]).is_none() {
+        segments.insert(0, INPUT_DATA.to_string());
+    }
+    let last = segments
+        .pop()
+        .ok_or_else(|| format!("{}: invalid path", path))?;
+    Ok((segments, last))
+}
+
+fn apply_difference(root: &mut Value, difference: &ModelDifference) -> Result<(), String> {
+    let path = difference.path.as_str();
+    let (parent_segments, last) = split_path(root, path)?;
+    let last = last.as_str();
+    let old_value = parse_json(path, &difference.old_value)?;
+    let new_value = parse_json(path, &difference.new_value)?;
+    let collection = parent_segments.last().map_or("", String::as_str);
+    let parent = locate(root, &parent_segments, path)?;
+    match difference.kind {
+        DifferenceKind::Added => {
+            let items = parent
+                .as_array_mut()
+                .ok_or_else(|| format!("{}: not an item of a collection", path))?;
+            let item = new_value.ok_or_else(|| format!("{}: added item has no value", path))?;
+            if item_key(collection, &item).as_deref() != Some(last) {
+                return Err(format!("{}: item does not match path", path));
+            }
+            if item_position(collection, items, last).is_some() {
+                return Err(format!("{}: item exists already", path));
+            }
+            items.push(item);
+        }
+        DifferenceKind::Removed => {
+            let items = parent
+                .as_array_mut()
+                .ok_or_else(|| format!("{}: not an item of a collection", path))?;
+            let position = item_position(collection, items, last)
+                .ok_or_else(|| format!("{}: no such item", path))?;
+            items.remove(position);
+        }
+        DifferenceKind::Changed => {
+            let fields = parent
+                .as_object_mut()
+                .ok_or_else(|| format!("{}: not a field", path))?;
+            if let Some(old_value) = old_value {
+                if fields.get(last) != Some(&old_value) {
+                    return Err(format!("{}: current value does not match old value", path));
+                }
+            }
+            match new_value {
+                Some(new_value) => {
+                    fields.insert(last.to_string(), new_value);
+                }
+                None => {
+                    fields.remove(last);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Applies differences produced by [`diff_models`] to a model.
+///
+/// Changed values are checked against their old values so a patch is not applied
+/// on top of conflicting edits. Nothing is changed if any difference fails to apply.
+pub fn patch_model(model: &Model, differences: &[ModelDifference]) -> Result<Model, String> {
+    let mut root = model_to_json(model)?;
+    for difference in differences {
+        apply_difference(&mut root, difference)?;
+    }
+    serde_json::from_value(root).map_err(|error| format!("patched model is invalid: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_data_base::{BaseNode, BaseProcess, BaseTopology, Conversion};
+    use crate::scenarios::ensemble::WeatherEnsemble;
+    use crate::scenarios::reduction::ScenarioReduction;
+
+    fn process_with_topology(name: &str, sink: &str, capacity: f64) -> BaseProcess {
+        let mut process = BaseProcess::new(name.to_string(), Conversion::Unit);
+        let mut topology = BaseTopology::new(name.to_string(), sink.to_string());
+        topology.capacity = capacity;
+        process.topos.push(topology);
+        process
+    }
+
+    fn models() -> (Model, Model) {
+        let mut old = Model::default();
+        old.input_data.nodes.push(BaseNode::new("heat".into()));
+        old.input_data.nodes.push(BaseNode::new("fuel".into()));
+        old.input_data
+            .processes
+            .push(process_with_topology("boiler", "heat", 10.0));
+        let mut new = old.clone();
+        new.input_data.nodes.remove(1);
+        new.input_data.nodes.push(BaseNode::new("power".into()));
+        new.input_data.nodes[0].is_res = true;
+        new.input_data.processes[0].topos[0].capacity = 12.0;
+        (old, new)
+    }
+
+    #[test]
+    fn differences_are_matched_by_name() {
+        let (old, new) = models();
+        let differences = diff_models(&old, &new).unwrap();
+        let summary: Vec<String> = differences
+            .iter()
+            .map(|d| format!("{:?} {}", d.kind, d.path))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                "Changed nodes/heat/is_res",
+                "Removed nodes/fuel",
+                "Added nodes/power",
+                "Changed processes/boiler/topos/boiler->heat/capacity",
+            ]
+        );
+        assert_eq!(differences[3].old_value.as_deref(), Some("10.0"));
+        assert_eq!(differences[3].new_value.as_deref(), Some("12.0"));
+        assert!(diff_models(&old, &old).unwrap().is_empty());
+    }
+
+    #[test]
+    fn patch_turns_old_model_into_new() {
+        let (old, new) = models();
+        let differences = diff_models(&old, &new).unwrap();
+        let patched = patch_model(&old, &differences).unwrap();
+        assert!(diff_models(&patched, &new).unwrap().is_empty());
+    }
+
+    #[test]
+    fn names_with_slashes_round_trip() {
+        let (old, mut new) = models();
+        new.input_data.nodes[0].name = "heat/low~temp".into();
+        new.input_data.processes[0].topos[0].sink = "heat/low~temp".into();
+        new.input_data.processes[0].name = "boiler/1".into();
+        let mut edited = new.clone();
+        edited.input_data.processes[0].topos[0].capacity = 14.0;
+        let differences = diff_models(&new, &edited).unwrap();
+        assert_eq!(
+            differences[0].path,
+            "processes/boiler~11/topos/boiler->heat~1low~0temp/capacity"
+        );
+        let patched = patch_model(&new, &differences).unwrap();
+        assert!(diff_models(&patched, &edited).unwrap().is_empty());
+        let differences = diff_models(&old, &new).unwrap();
+        let patched = patch_model(&old, &differences).unwrap();
+        assert!(diff_models(&patched, &new).unwrap().is_empty());
+    }
+
+    #[test]
+    fn weather_ensemble_is_compared_and_patched() {
+        let (old, _) = models();
+        let mut new = old.clone();
+        new.weather_ensemble = Some(WeatherEnsemble::new(vec![0.1, 0.9]).unwrap());
+        let differences = diff_models(&old, &new).unwrap();
+        assert_eq!(differences.len(), 1);
+        assert_eq!(differences[0].path, "weather_ensemble");
+        assert_eq!(patch_model(&old, &differences).unwrap(), new);
+        let mut edited = new.clone();
+        edited.weather_ensemble = Some(WeatherEnsemble::new(vec![0.5]).unwrap());
+        let differences = diff_models(&new, &edited).unwrap();
+        assert_eq!(differences[0].path, "weather_ensemble/quantiles");
+        assert_eq!(patch_model(&new, &differences).unwrap(), edited);
+        let differences = diff_models(&edited, &old).unwrap();
+        assert_eq!(patch_model(&edited, &differences).unwrap(), old);
+    }
+
+    #[test]
+    fn scenario_reduction_is_compared_and_patched() {
+        let (old, _) = models();
+        let mut new = old.clone();
+        new.scenario_reduction = Some(ScenarioReduction::new(5).unwrap());
+        let differences = diff_models(&old, &new).unwrap();
+        assert_eq!(differences.len(), 1);
+        assert_eq!(differences[0].path, "scenario_reduction");
+        assert_eq!(patch_model(&old, &differences).unwrap(), new);
+        let mut edited = new.clone();
+        edited.scenario_reduction = Some(ScenarioReduction::new(3).unwrap());
+        let differences = diff_models(&new, &edited).unwrap();
+        assert_eq!(differences[0].path, "scenario_reduction/scenario_count");
+        assert_eq!(patch_model(&new, &differences).unwrap(), edited);
+    }
+
+    #[test]
+    fn conflicting_patch_is_rejected() {
+        let (old, new) = models();
+        let differences = diff_models(&old, &new).unwrap();
+        let mut edited = old.clone();
+        edited.input_data.processes[0].topos[0].capacity = 5.0;
+        let error = patch_model(&edited, &differences).err().unwrap();
+        assert_eq!(
+            error,
+            "processes/boiler/topos/boiler->heat/capacity: current value does not match old value"
+        );
+        assert!(patch_model(&new, &differences).is_err());
+    }
+}
@@ -0,0 +1,448 @@
+use crate::input_data_base::{BaseInputData, MarketType, SeriesValue, Value};
+use juniper::GraphQLEnum;
+use quick_xml::escape::escape;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, GraphQLEnum, PartialEq)]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+    Graphml,
+}
+
+impl FromStr for GraphFormat {
+    type Err = String;
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format.to_lowercase().as_str() {
+            "dot" => Ok(GraphFormat::Dot),
+            "mermaid" => Ok(GraphFormat::Mermaid),
+            "graphml" => Ok(GraphFormat::Graphml),
+            _ => Err(format!(
+                "unknown graph format '{}'; expected dot, mermaid or graphml",
+                format
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum VertexKind {
+    Node,
+    Process,
+    Market,
+}
+
+impl VertexKind {
+    fn name(&self) -> &'static str {
+        match self {
+            VertexKind::Node => "node",
+            VertexKind::Process => "process",
+            VertexKind::Market => "market",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum EdgeKind {
+    Flow,
+    Diffusion,
+    Delay,
+    Market,
+}
+
+impl EdgeKind {
+    fn name(&self) -> &'static str {
+        match self {
+            EdgeKind::Flow => "flow",
+            EdgeKind::Diffusion => "diffusion",
+            EdgeKind::Delay => "delay",
+            EdgeKind::Market => "market",
+        }
+    }
+}
+
+struct Vertex {
+    name: String,
+    kind: VertexKind,
+}
+
+struct Edge {
+    from: usize,
+    to: usize,
+    kind: EdgeKind,
+    label: String,
+}
+
+#[derive(Default)]
+struct Graph {
+    vertices: Vec<Vertex>,
+    edges: Vec<Edge>,
+}
+
+impl Graph {
+    fn vertex(&mut self, name: &str, kind: VertexKind) -> usize {
+        let is_market = kind == VertexKind::Market;
+        let position = self
+            .vertices
+            .iter()
+            .position(|v| v.name == name && (v.kind == VertexKind::Market) == is_market);
+        match position {
+            Some(index) => index,
+            None => {
+                self.vertices.push(Vertex {
+                    name: name.to_string(),
+                    kind,
+                });
+                self.vertices.len() - 1
+            }
+        }
+    }
+
+    fn node_or_process(&mut self, name: &str, input_data: &BaseInputData) -> usize {
+        if input_data.processes.iter().any(|p| p.name == name) {
+            self.vertex(name, VertexKind::Process)
+        } else {
+            self.vertex(name, VertexKind::Node)
+        }
+    }
+
+    fn edge(&mut self, from: usize, to: usize, kind: EdgeKind, label: String) {
+        self.edges.push(Edge {
+            from,
+            to,
+            kind,
+            label,
+        });
+    }
+
+    /// Keeps given vertices, their direct neighbours and edges between the kept vertices.
+    fn retain_neighbourhood(self, members: &BTreeSet<usize>) -> Graph {
+        let mut kept = members.clone();
+        for edge in &self.edges {
+            if members.contains(&edge.from) || members.contains(&edge.to) {
+                kept.insert(edge.from);
+                kept.insert(edge.to);
+            }
+        }
+        let new_index = |old: usize| kept.iter().position(|k| *k == old);
+        let mut graph = Graph::default();
+        for edge in self.edges {
+            if let (Some(from), Some(to)) = (new_index(edge.from), new_index(edge.to)) {
+                graph.edges.push(Edge { from, to, ..edge });
+            }
+        }
+        graph.vertices = self
+            .vertices
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| kept.contains(index))
+            .map(|(_, vertex)| vertex)
+            .collect();
+        graph
+    }
+}
+
+fn values_label(values: &[Value]) -> String {
+    let parts: Vec<String> = values
+        .iter()
+        .map(|value| {
+            let number = match value.value {
+                SeriesValue::Constant(ref constant) => constant.value().to_string(),
+                SeriesValue::FloatList(..) => "series".to_string(),
+            };
+            match value.scenario {
+                Some(ref scenario) => format!("{}: {}", scenario, number),
+                None => number,
+            }
+        })
+        .collect();
+    parts.join(", ")
+}
+
+fn build_graph(input_data: &BaseInputData) -> Graph {
+    let mut graph = Graph::default();
+    for node in &input_data.nodes {
+        graph.vertex(&node.name, VertexKind::Node);
+    }
+    for process in &input_data.processes {
+        graph.vertex(&process.name, VertexKind::Process);
+    }
+    for process in &input_data.processes {
+        for topology in &process.topos {
+            let from = graph.node_or_process(&topology.source, input_data);
+            let to = graph.node_or_process(&topology.sink, input_data);
+            graph.edge(
+                from,
+                to,
+                EdgeKind::Flow,
+                format!("cap {}", topology.capacity),
+            );
+        }
+    }
+    for diffusion in &input_data.node_diffusion {
+        let from = graph.vertex(&diffusion.from_node, VertexKind::Node);
+        let to = graph.vertex(&diffusion.to_node, VertexKind::Node);
+        let label = format!("diffusion {}", values_label(&diffusion.coefficient));
+        graph.edge(from, to, EdgeKind::Diffusion, label);
+    }
+    for delay in &input_data.node_delay {
+        let from = graph.vertex(&delay.from_node, VertexKind::Node);
+        let to = graph.vertex(&delay.to_node, VertexKind::Node);
+        graph.edge(from, to, EdgeKind::Delay, format!("delay {}", delay.delay));
+    }
+    for market in &input_data.markets {
+        let market_index = graph.vertex(&market.name, VertexKind::Market);
+        let node_names: Vec<&String> = match market.m_type {
+            MarketType::Energy => vec![&market.node],
+            MarketType::Reserve => input_data
+                .node_groups
+                .iter()
+                .filter(|g| g.name == market.node)
+                .flat_map(|g| g.members.iter())
+                .collect(),
+        };
+        for node_name in node_names {
+            let node = graph.vertex(node_name, VertexKind::Node);
+            graph.edge(market_index, node, EdgeKind::Market, String::new());
+        }
+    }
+    graph
+}
+
+fn group_members(
+    graph: &Graph,
+    input_data: &BaseInputData,
+    group: &str,
+) -> Option<BTreeSet<usize>> {
+    let (members, kind) = if let Some(g) = input_data.node_groups.iter().find(|g| g.name == group) {
+        (&g.members, VertexKind::Node)
+    } else if let Some(g) = input_data.process_groups.iter().find(|g| g.name == group) {
+        (&g.members, VertexKind::Process)
+    } else {
+        return None;
+    };
+    Some(
+        graph
+            .vertices
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| v.kind == kind && members.contains(&v.name))
+            .map(|(index, _)| index)
+            .collect(),
+    )
+}
+
+fn dot_quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn render_dot(graph: &Graph) -> String {
+    let mut dot = String::from("digraph model {\n    rankdir=LR;\n");
+    for (index, vertex) in graph.vertices.iter().enumerate() {
+        let shape = match vertex.kind {
+            VertexKind::Node => "ellipse",
+            VertexKind::Process => "box",
+            VertexKind::Market => "diamond",
+        };
+        let _ = writeln!(
+            dot,
+            "    v{} [label={}, shape={}];",
+            index,
+            dot_quote(&vertex.name),
+            shape
+        );
+    }
+    for edge in &graph.edges {
+        let style = match edge.kind {
+            EdgeKind::Flow => "solid",
+            EdgeKind::Diffusion => "dashed",
+            EdgeKind::Delay => "dotted",
+            EdgeKind::Market => "bold",
+        };
+        let _ = writeln!(
+            dot,
+            "    v{} -> v{} [label={}, style={}];",
+            edge.from,
+            edge.to,
+            dot_quote(&edge.label),
+            style
+        );
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+fn mermaid_quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "#quot;"))
+}
+
+fn render_mermaid(graph: &Graph) -> String {
+    let mut mermaid = String::from("flowchart LR\n");
+    for (index, vertex) in graph.vertices.iter().enumerate() {
+        let label = mermaid_quote(&vertex.name);
+        let _ = match vertex.kind {
+            VertexKind::Node => writeln!(mermaid, "    v{}([{}])", index, label),
+            VertexKind::Process => writeln!(mermaid, "    v{}[{}]", index, label),
+            VertexKind::Market => writeln!(mermaid, "    v{}{{{}}}", index, label),
+        };
+    }
+    for edge in &graph.edges {
+        let arrow = match edge.kind {
+            EdgeKind::Flow => "-->",
+            EdgeKind::Diffusion | EdgeKind::Delay => "-.->",
+            EdgeKind::Market => "==>",
+        };
+        if edge.label.is_empty() {
+            let _ = writeln!(mermaid, "    v{} {} v{}", edge.from, arrow, edge.to);
+        } else {
+            let _ = writeln!(
+                mermaid,
+                "    v{} {}|{}| v{}",
+                edge.from,
+                arrow,
+                mermaid_quote(&edge.label),
+                edge.to
+            );
+        }
+    }
+    mermaid
+}
+
+fn render_graphml(graph: &Graph) -> String {
+    let mut graphml = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+        "  <key id=\"name\" for=\"node\" attr.name=\"name\" attr.type=\"string\"/>\n",
+        "  <key id=\"kind\" for=\"all\" attr.name=\"kind\" attr.type=\"string\"/>\n",
+        "  <key id=\"label\" for=\"edge\" attr.name=\"label\" attr.type=\"string\"/>\n",
+        "  <graph id=\"model\" edgedefault=\"directed\">\n",
+    ));
+    for (index, vertex) in graph.vertices.iter().enumerate() {
+        let _ = writeln!(
+            graphml,
+            "    <node id=\"v{}\"><data key=\"name\">{}</data><data key=\"kind\">{}</data></node>",
+            index,
+            escape(vertex.name.as_str()),
+            vertex.kind.name()
+        );
+    }
+    for edge in &graph.edges {
+        let _ = writeln!(
+            graphml,
+            "    <edge source=\"v{}\" target=\"v{}\"><data key=\"kind\">{}</data><data key=\"label\">{}</data></edge>",
+            edge.from,
+            edge.to,
+            edge.kind.name(),
+            escape(edge.label.as_str())
+        );
+    }
+    graphml.push_str("  </graph>\n</graphml>\n");
+    graphml
+}
+
+/// Renders nodes, processes and markets with their connections as a graph.
+///
+/// If group is given, only the group's members and items directly connected to them are included.
+pub fn render_graph(
+    input_data: &BaseInputData,
+    format: GraphFormat,
+    group: Option<&str>,
+) -> Result<String, String> {
+    let mut graph = build_graph(input_data);
+    if let Some(group) = group {
+        let members = group_members(&graph, input_data, group)
+            .ok_or_else(|| format!("no such group '{}'", group))?;
+        graph = graph.retain_neighbourhood(&members);
+    }
+    Ok(match format {
+        GraphFormat::Dot => render_dot(&graph),
+        GraphFormat::Mermaid => render_mermaid(&graph),
+        GraphFormat::Graphml => render_graphml(&graph),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_data_base::{
+        BaseNode, BaseNodeDiffusion, BaseProcess, BaseTopology, Conversion, NodeGroup, ValueInput,
+    };
+
+    fn make_input_data() -> BaseInputData {
+        let mut process = BaseProcess::new("boiler".into(), Conversion::Unit);
+        let mut topology = BaseTopology::new("boiler".into(), "heat".into());
+        topology.capacity = 5.0;
+        process.topos.push(topology);
+        BaseInputData {
+            nodes: vec![
+                BaseNode::new("heat".into()),
+                BaseNode::new("room \"A\"".into()),
+                BaseNode::new("elc".into()),
+            ],
+            processes: vec![process],
+            node_diffusion: vec![BaseNodeDiffusion {
+                from_node: "heat".into(),
+                to_node: "room \"A\"".into(),
+                coefficient: vec![Value::try_from(ValueInput {
+                    scenario: None,
+                    constant: Some(0.25),
+                    series: None,
+                    time_stamps: None,
+                    resampling: None,
+                })
+                .unwrap()],
+            }],
+            node_groups: vec![NodeGroup {
+                name: "rooms".into(),
+                members: vec!["room \"A\"".into()],
+            }],
+            ..BaseInputData::default()
+        }
+    }
+
+    #[test]
+    fn dot_shows_shapes_and_labels() {
+        let dot = render_graph(&make_input_data(), GraphFormat::Dot, None).unwrap();
+        assert_eq!(
+            dot,
+            concat!(
+                "digraph model {\n",
+                "    rankdir=LR;\n",
+                "    v0 [label=\"heat\", shape=ellipse];\n",
+                "    v1 [label=\"room \\\"A\\\"\", shape=ellipse];\n",
+                "    v2 [label=\"elc\", shape=ellipse];\n",
+                "    v3 [label=\"boiler\", shape=box];\n",
+                "    v3 -> v0 [label=\"cap 5\", style=solid];\n",
+                "    v0 -> v1 [label=\"diffusion 0.25\", style=dashed];\n",
+                "}\n",
+            )
+        );
+    }
+
+    #[test]
+    fn group_limits_graph_to_neighbourhood() {
+        let mermaid =
+            render_graph(&make_input_data(), GraphFormat::Mermaid, Some("rooms")).unwrap();
+        assert_eq!(
+            mermaid,
+            concat!(
+                "flowchart LR\n",
+                "    v0([\"heat\"])\n",
+                "    v1([\"room #quot;A#quot;\"])\n",
+                "    v0 -.->|\"diffusion 0.25\"| v1\n",
+            )
+        );
+        assert!(render_graph(&make_input_data(), GraphFormat::Dot, Some("none")).is_err());
+    }
+
+    #[test]
+    fn graphml_escapes_names() {
+        let graphml = render_graph(&make_input_data(), GraphFormat::Graphml, None).unwrap();
+        assert!(graphml.contains("<data key=\"name\">room &quot;A&quot;</data>"));
+        assert!(graphml.contains(
+            "<edge source=\"v3\" target=\"v0\"><data key=\"kind\">flow</data><data key=\"label\">cap 5</data></edge>"
+        ));
+    }
+}
@@ -0,0 +1,186 @@
+use super::Model;
+use crate::TimeStamp;
+use chrono::Utc;
+use juniper::GraphQLObject;
+use std::collections::VecDeque;
+
+/// Maximum number of model changes that are kept for undo.
+pub const DEFAULT_HISTORY_CAPACITY: usize = 50;
+
+#[derive(Clone, Debug, GraphQLObject, PartialEq)]
+#[graphql(description = "Recorded change of the model.")]
+pub struct ModelChange {
+    #[graphql(description = "Operation that changed the model.")]
+    pub operation: String,
+    #[graphql(description = "Time when the change was made.")]
+    pub time: TimeStamp,
+}
+
+#[derive(Clone, Debug, GraphQLObject, PartialEq)]
+#[graphql(description = "Model changes that can be undone or redone, most recent first.")]
+pub struct ModelHistoryInfo {
+    pub undo: Vec<ModelChange>,
+    pub redo: Vec<ModelChange>,
+}
+
+struct Snapshot {
+    change: ModelChange,
+    model: Model,
+}
+
+/// Bounded undo/redo history of model states.
+pub struct ModelHistory {
+    capacity: usize,
+    undo: VecDeque<Snapshot>,
+    redo: Vec<Snapshot>,
+}
+
+impl Default for ModelHistory {
+    fn default() -> Self {
+        ModelHistory::with_capacity(DEFAULT_HISTORY_CAPACITY)
+    }
+}
+
+impl ModelHistory {
+    pub fn with_capacity(capacity: usize) -> Self {
+        ModelHistory {
+            capacity,
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    /// Records the state before an operation if the operation changed the model.
+    pub fn record(&mut self, operation: &str, before: Model, after: &Model) {
+        if before == *after {
+            return;
+        }
+        self.redo.clear();
+        if self.capacity == 0 {
+            return;
+        }
+        if self.undo.len() == self.capacity {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(Snapshot {
+            change: ModelChange {
+                operation: operation.to_string(),
+                time: Utc::now(),
+            },
+            model: before,
+        });
+    }
+
+    /// Restores the model to the state before the latest change.
+    pub fn undo(&mut self, model: &mut Model) -> Result<ModelChange, String> {
+        let snapshot = self.undo.pop_back().ok_or("nothing to undo")?;
+        let current = std::mem::replace(model, snapshot.model);
+        let change = snapshot.change;
+        self.redo.push(Snapshot {
+            change: change.clone(),
+            model: current,
+        });
+        Ok(change)
+    }
+
+    /// Reapplies the latest undone change.
+    pub fn redo(&mut self, model: &mut Model) -> Result<ModelChange, String> {
+        let snapshot = self.redo.pop().ok_or("nothing to redo")?;
+        let current = std::mem::replace(model, snapshot.model);
+        let change = snapshot.change;
+        self.undo.push_back(Snapshot {
+            change: change.clone(),
+            model: current,
+        });
+        Ok(change)
+    }
+
+    pub fn info(&self) -> ModelHistoryInfo {
+        ModelHistoryInfo {
+            undo: self.undo.iter().rev().map(|s| s.change.clone()).collect(),
+            redo: self.redo.iter().rev().map(|s| s.change.clone()).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_data_base::BaseNode;
+
+    fn model_with_nodes(names: &[&str]) -> Model {
+        let mut model = Model::default();
+        model.input_data.nodes = names.iter().map(|n| BaseNode::new(n.to_string())).collect();
+        model
+    }
+
+    fn node_names(model: &Model) -> Vec<&str> {
+        model
+            .input_data
+            .nodes
+            .iter()
+            .map(|n| n.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn undo_and_redo_restore_model_states() {
+        let mut history = ModelHistory::default();
+        let mut model = model_with_nodes(&["a"]);
+        let before = model.clone();
+        model.input_data.nodes.push(BaseNode::new("b".into()));
+        history.record("createNode", before, &model);
+        let before = model.clone();
+        model.input_data.nodes.remove(0);
+        history.record("deleteNode", before, &model);
+        assert_eq!(node_names(&model), vec!["b"]);
+        let change = history.undo(&mut model).unwrap();
+        assert_eq!(change.operation, "deleteNode");
+        assert_eq!(node_names(&model), vec!["a", "b"]);
+        history.undo(&mut model).unwrap();
+        assert_eq!(node_names(&model), vec!["a"]);
+        assert!(history.undo(&mut model).is_err());
+        let info = history.info();
+        assert!(info.undo.is_empty());
+        assert_eq!(
+            info.redo.iter().map(|c| c.operation.as_str()).collect::<Vec<_>>(),
+            vec!["createNode", "deleteNode"]
+        );
+        history.redo(&mut model).unwrap();
+        assert_eq!(node_names(&model), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn unchanged_model_is_not_recorded() {
+        let mut history = ModelHistory::default();
+        let model = model_with_nodes(&["a"]);
+        history.record("createNode", model.clone(), &model);
+        assert!(history.info().undo.is_empty());
+        let mut changed = model.clone();
+        changed.input_data.nodes[0].is_res = true;
+        history.record("updateNode", model, &changed);
+        assert_eq!(history.info().undo.len(), 1);
+    }
+
+    #[test]
+    fn new_change_clears_redo_and_history_is_bounded() {
+        let mut history = ModelHistory::with_capacity(2);
+        let mut model = Model::default();
+        for name in ["a", "b", "c"] {
+            let before = model.clone();
+            model.input_data.nodes.push(BaseNode::new(name.into()));
+            history.record(name, before, &model);
+        }
+        let info = history.info();
+        assert_eq!(
+            info.undo.iter().map(|c| c.operation.as_str()).collect::<Vec<_>>(),
+            vec!["c", "b"]
+        );
+        history.undo(&mut model).unwrap();
+        assert_eq!(history.info().redo.len(), 1);
+        let before = model.clone();
+        model.input_data.nodes.clear();
+        history.record("clearInputData", before, &model);
+        assert!(history.info().redo.is_empty());
+    }
+}
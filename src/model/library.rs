@@ -0,0 +1,208 @@
+use super::Model;
+use crate::settings;
+use crate::TimeStamp;
+use chrono::Utc;
+use juniper::GraphQLObject;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+const MODEL_FILE_EXTENSION: &str = "json";
+
+#[derive(Clone, Debug, Deserialize, GraphQLObject, PartialEq, Serialize)]
+#[graphql(description = "Information about a model stored in the model library.")]
+pub struct ModelMetadata {
+    #[graphql(description = "Model's name.")]
+    pub name: String,
+    #[graphql(description = "Free-form description of the model.")]
+    pub description: String,
+    #[graphql(description = "Time when the model was first saved.")]
+    pub created: TimeStamp,
+    #[graphql(description = "Time when the model was last saved.")]
+    pub modified: TimeStamp,
+}
+
+#[derive(Deserialize, Serialize)]
+struct StoredModel {
+    metadata: ModelMetadata,
+    model: Model,
+}
+
+pub fn make_library_path() -> PathBuf {
+    settings::config_path().join("models")
+}
+
+/// Checks that name can be used as a file name in the library.
+pub fn validate_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("name is empty".into());
+    }
+    if name.trim() != name {
+        return Err("name has leading or trailing white space".into());
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == ' ' || c == '-' || c == '_')
+    {
+        return Err(
+            "name may contain only letters, digits, spaces, hyphens and underscores".into(),
+        );
+    }
+    Ok(())
+}
+
+fn model_file_path(library: &Path, name: &str) -> Result<PathBuf, String> {
+    validate_name(name)?;
+    Ok(library.join(format!("{}.{}", name, MODEL_FILE_EXTENSION)))
+}
+
+fn read_stored_model(file_path: &Path) -> Result<StoredModel, String> {
+    let file =
+        File::open(file_path).map_err(|error| format!("failed to open model file: {}", error))?;
+    serde_json::from_reader(file).map_err(|error| format!("failed to parse model file: {}", error))
+}
+
+/// Stores model in the library under given name, replacing an existing model with the same name.
+///
+/// Description of an existing model is kept unless a new one is given.
+pub fn save_model_as(
+    library: &Path,
+    name: &str,
+    description: Option<String>,
+    model: &Model,
+) -> Result<ModelMetadata, String> {
+    let file_path = model_file_path(library, name)?;
+    let now = Utc::now();
+    let metadata = match read_stored_model(&file_path) {
+        Ok(stored) => ModelMetadata {
+            description: description.unwrap_or(stored.metadata.description),
+            modified: now,
+            ..stored.metadata
+        },
+        Err(..) => ModelMetadata {
+            name: name.to_string(),
+            description: description.unwrap_or_default(),
+            created: now,
+            modified: now,
+        },
+    };
+    fs::create_dir_all(library)
+        .map_err(|error| format!("failed to create model library directory: {}", error))?;
+    let file = File::create(&file_path)
+        .map_err(|error| format!("failed to create model file: {}", error))?;
+    let stored = StoredModel {
+        metadata,
+        model: model.clone(),
+    };
+    serde_json::to_writer_pretty(file, &stored)
+        .map_err(|error| format!("failed to write model file: {}", error))?;
+    Ok(stored.metadata)
+}
+
+/// Lists models in the library sorted by name.
+///
+/// Model files that cannot be read are skipped and reported on standard error.
+pub fn list_models(library: &Path) -> Result<Vec<ModelMetadata>, String> {
+    if !library.exists() {
+        return Ok(Vec::new());
+    }
+    let entries = fs::read_dir(library)
+        .map_err(|error| format!("failed to read model library directory: {}", error))?;
+    let mut models = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|error| format!("failed to read model library directory: {}", error))?
+            .path();
+        if path.extension().and_then(|e| e.to_str()) != Some(MODEL_FILE_EXTENSION) {
+            continue;
+        }
+        match read_stored_model(&path) {
+            Ok(stored) => models.push(stored.metadata),
+            Err(error) => eprintln!("skipping {} in model library: {}", path.display(), error),
+        }
+    }
+    models.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(models)
+}
+
+pub fn load_model(library: &Path, name: &str) -> Result<Model, String> {
+    let file_path = model_file_path(library, name)?;
+    if !file_path.exists() {
+        return Err(format!("no such model '{}'", name));
+    }
+    Ok(read_stored_model(&file_path)?.model)
+}
+
+pub fn delete_model(library: &Path, name: &str) -> Result<(), String> {
+    let file_path = model_file_path(library, name)?;
+    if !file_path.exists() {
+        return Err(format!("no such model '{}'", name));
+    }
+    fs::remove_file(&file_path).map_err(|error| format!("failed to delete model file: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_data_base::BaseNode;
+
+    #[test]
+    fn saved_models_can_be_listed_loaded_and_deleted() {
+        let directory = tempfile::tempdir().unwrap();
+        let library = directory.path().join("models");
+        assert!(list_models(&library).unwrap().is_empty());
+        let mut winter = Model::default();
+        winter
+            .input_data
+            .nodes
+            .push(BaseNode::new("district heat".into()));
+        save_model_as(&library, "winter", Some("cold season".into()), &winter).unwrap();
+        save_model_as(&library, "summer", None, &Model::default()).unwrap();
+        let models = list_models(&library).unwrap();
+        assert_eq!(
+            models.iter().map(|m| m.name.as_str()).collect::<Vec<_>>(),
+            vec!["summer", "winter"]
+        );
+        assert_eq!(models[1].description, "cold season");
+        let loaded = load_model(&library, "winter").unwrap();
+        assert_eq!(loaded.input_data.nodes[0].name, "district heat");
+        delete_model(&library, "winter").unwrap();
+        assert!(load_model(&library, "winter").is_err());
+        assert!(delete_model(&library, "winter").is_err());
+    }
+
+    #[test]
+    fn saving_again_keeps_creation_time_and_description() {
+        let directory = tempfile::tempdir().unwrap();
+        let first = save_model_as(
+            directory.path(),
+            "with battery",
+            Some("battery storage".into()),
+            &Model::default(),
+        )
+        .unwrap();
+        let second =
+            save_model_as(directory.path(), "with battery", None, &Model::default()).unwrap();
+        assert_eq!(second.created, first.created);
+        assert_eq!(second.description, "battery storage");
+        assert!(second.modified >= first.modified);
+    }
+
+    #[test]
+    fn unreadable_model_files_are_skipped_in_listing() {
+        let directory = tempfile::tempdir().unwrap();
+        save_model_as(directory.path(), "summer", None, &Model::default()).unwrap();
+        fs::write(directory.path().join("broken.json"), "{").unwrap();
+        let models = list_models(directory.path()).unwrap();
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].name, "summer");
+    }
+
+    #[test]
+    fn invalid_names_are_rejected() {
+        let directory = tempfile::tempdir().unwrap();
+        for name in ["", "../winter", " winter", "winter.json"] {
+            assert!(save_model_as(directory.path(), name, None, &Model::default()).is_err());
+        }
+    }
+}
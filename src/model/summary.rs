@@ -0,0 +1,352 @@
+use super::diff::escape_segment;
+use crate::input_data::{FeatureFlags, ModelFeatures};
+use crate::input_data_base::{build_piecewise_eff, BaseForecastable, BaseInputData, ForecastValue};
+use crate::scenarios::Scenario;
+use juniper::GraphQLObject;
+
+#[derive(Clone, Debug, Default, GraphQLObject, PartialEq)]
+#[graphql(description = "Number of items of each type in the model.")]
+pub struct EntityCounts {
+    pub nodes: i32,
+    pub processes: i32,
+    pub topologies: i32,
+    pub markets: i32,
+    pub node_groups: i32,
+    pub process_groups: i32,
+    pub node_diffusions: i32,
+    pub node_delays: i32,
+    pub node_histories: i32,
+    pub inflow_blocks: i32,
+    pub gen_constraints: i32,
+    pub reserve_types: i32,
+    pub risks: i32,
+}
+
+#[derive(Clone, Debug, GraphQLObject, PartialEq)]
+#[graphql(description = "Total capacity of topologies connected to a node.")]
+pub struct NodeCapacity {
+    pub node: String,
+    #[graphql(description = "Sum of capacities of topologies that have the node as sink.")]
+    pub input_capacity: f64,
+    #[graphql(description = "Sum of capacities of topologies that have the node as source.")]
+    pub output_capacity: f64,
+}
+
+#[derive(Clone, Debug, GraphQLObject, PartialEq)]
+#[graphql(description = "Storage capacity of a node with a state.")]
+pub struct StorageCapacity {
+    pub node: String,
+    pub state_max: f64,
+    pub state_min: f64,
+    pub in_max: f64,
+    pub out_max: f64,
+}
+
+#[derive(Clone, Debug, GraphQLObject, PartialEq)]
+#[graphql(description = "Forecast used by a forecastable value.")]
+pub struct ForecastBinding {
+    #[graphql(
+        description = "Path to the value, e.g. markets/npe/price, with names escaped as in model diffs."
+    )]
+    pub path: String,
+    #[graphql(description = "Scenario the forecast applies to; null means all scenarios.")]
+    pub scenario: Option<String>,
+    pub forecast: String,
+    pub f_type: String,
+}
+
+#[derive(Clone, Debug, GraphQLObject, PartialEq)]
+#[graphql(description = "Overview of the model for a quick sanity check.")]
+pub struct ModelSummary {
+    pub entity_counts: EntityCounts,
+    #[graphql(description = "Installed topology capacity per node.")]
+    pub node_capacities: Vec<NodeCapacity>,
+    #[graphql(description = "Storage capacities of nodes that have a state.")]
+    pub storages: Vec<StorageCapacity>,
+    #[graphql(description = "Sum of state_max over all storages.")]
+    pub total_storage_capacity: f64,
+    pub scenario_count: i32,
+    pub scenarios: Vec<Scenario>,
+    #[graphql(description = "Sum of scenario weights.")]
+    pub total_scenario_weight: f64,
+    pub forecast_bindings: Vec<ForecastBinding>,
+    pub feature_flags: FeatureFlags,
+}
+
+fn count<T>(items: &[T]) -> i32 {
+    items.len() as i32
+}
+
+fn collect_forecast_bindings(
+    values: &[ForecastValue],
+    path: String,
+    bindings: &mut Vec<ForecastBinding>,
+) {
+    for value in values {
+        if let BaseForecastable::Forecast(ref forecast) = value.value {
+            bindings.push(ForecastBinding {
+                path: path.clone(),
+                scenario: value.scenario.clone(),
+                forecast: forecast.name().to_string(),
+                f_type: forecast.f_type().to_string(),
+            });
+        }
+    }
+}
+
+/// Computes the same flags as `InputData::infer_feature_flags` without expanding the input data.
+pub fn feature_flags(input_data: &BaseInputData) -> FeatureFlags {
+    ModelFeatures {
+        use_reserves: input_data.setup.use_reserves,
+        reserve_nodes: input_data.nodes.iter().any(|n| n.is_res),
+        online_processes: input_data.processes.iter().any(|p| p.is_online),
+        states: input_data.nodes.iter().any(|n| n.state.is_some()),
+        piecewise_eff: input_data
+            .processes
+            .iter()
+            .any(|p| !build_piecewise_eff(&p.eff_ops_fun).0.is_empty()),
+        risk_beta: input_data
+            .risk
+            .iter()
+            .rfind(|r| r.parameter == "beta")
+            .map(|r| r.value),
+        diffusion: !input_data.node_diffusion.is_empty(),
+        delay: !input_data.node_delay.is_empty(),
+        markets: !input_data.markets.is_empty(),
+    }
+    .flags()
+}
+
+pub fn summarize(input_data: &BaseInputData) -> ModelSummary {
+    let entity_counts = EntityCounts {
+        nodes: count(&input_data.nodes),
+        processes: count(&input_data.processes),
+        topologies: input_data.processes.iter().map(|p| count(&p.topos)).sum(),
+        markets: count(&input_data.markets),
+        node_groups: count(&input_data.node_groups),
+        process_groups: count(&input_data.process_groups),
+        node_diffusions: count(&input_data.node_diffusion),
+        node_delays: count(&input_data.node_delay),
+        node_histories: count(&input_data.node_histories),
+        inflow_blocks: count(&input_data.inflow_blocks),
+        gen_constraints: count(&input_data.gen_constraints),
+        reserve_types: count(&input_data.reserve_type),
+        risks: count(&input_data.risk),
+    };
+    let node_capacities = input_data
+        .nodes
+        .iter()
+        .map(|node| {
+            let topologies = input_data.processes.iter().flat_map(|p| &p.topos);
+            let mut capacity = NodeCapacity {
+                node: node.name.clone(),
+                input_capacity: 0.0,
+                output_capacity: 0.0,
+            };
+            for topology in topologies {
+                if topology.sink == node.name {
+                    capacity.input_capacity += topology.capacity;
+                }
+                if topology.source == node.name {
+                    capacity.output_capacity += topology.capacity;
+                }
+            }
+            capacity
+        })
+        .collect();
+    let storages: Vec<StorageCapacity> = input_data
+        .nodes
+        .iter()
+        .filter_map(|node| {
+            node.state.as_ref().map(|state| StorageCapacity {
+                node: node.name.clone(),
+                state_max: state.state_max,
+                state_min: state.state_min,
+                in_max: state.in_max,
+                out_max: state.out_max,
+            })
+        })
+        .collect();
+    let mut forecast_bindings = Vec::new();
+    for node in &input_data.nodes {
+        collect_forecast_bindings(
+            &node.inflow,
+            format!("nodes/{}/inflow", escape_segment(&node.name)),
+            &mut forecast_bindings,
+        );
+    }
+    for market in &input_data.markets {
+        for (field, values) in [
+            ("price", &market.price),
+            ("up_price", &market.up_price),
+            ("down_price", &market.down_price),
+        ] {
+            collect_forecast_bindings(
+                values,
+                format!("markets/{}/{}", escape_segment(&market.name), field),
+                &mut forecast_bindings,
+            );
+        }
+    }
+    ModelSummary {
+        entity_counts,
+        node_capacities,
+        total_storage_capacity: storages.iter().map(|s| s.state_max).sum(),
+        storages,
+        scenario_count: count(&input_data.scenarios),
+        scenarios: input_data.scenarios.clone(),
+        total_scenario_weight: input_data.scenarios.iter().map(|s| s.weight()).sum(),
+        forecast_bindings,
+        feature_flags: feature_flags(input_data),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_data::{Forecast, State};
+    use crate::input_data_base::{
+        BaseMarket, BaseNode, BaseProcess, BaseTopology, Conversion, Delay, MarketType, Point, Risk,
+    };
+    use chrono::{TimeZone, Utc};
+
+    fn make_input_data() -> BaseInputData {
+        let mut storage = BaseNode::new("heat".into());
+        storage.state = Some(State {
+            state_max: 40.0,
+            in_max: 10.0,
+            out_max: 8.0,
+            ..State::default()
+        });
+        let mut process = BaseProcess::new("hp".into(), Conversion::Unit);
+        let mut input = BaseTopology::new("elc".into(), "hp".into());
+        input.capacity = 5.0;
+        let mut output = BaseTopology::new("hp".into(), "heat".into());
+        output.capacity = 12.0;
+        process.topos = vec![input, output];
+        let market = BaseMarket {
+            name: "npe/2".into(),
+            m_type: MarketType::Energy,
+            node: "elc".into(),
+            process_group: "p1".into(),
+            direction: None,
+            realisation: Vec::new(),
+            reserve_type: None,
+            is_bid: false,
+            is_limited: false,
+            min_bid: 0.0,
+            max_bid: 0.0,
+            fee: 0.0,
+            price: vec![ForecastValue {
+                scenario: Some("s1".into()),
+                value: BaseForecastable::Forecast(Forecast::new(
+                    "ELERING".into(),
+                    "electricity".into(),
+                    None,
+                )),
+            }],
+            up_price: Vec::new(),
+            down_price: Vec::new(),
+            reserve_activation_price: Vec::new(),
+            fixed: Vec::new(),
+            price_unit: None,
+            price_scenarios: None,
+        };
+        BaseInputData {
+            scenarios: vec![
+                Scenario::new("s1", 0.25).unwrap(),
+                Scenario::new("s2", 0.75).unwrap(),
+            ],
+            nodes: vec![BaseNode::new("elc".into()), storage],
+            processes: vec![process],
+            markets: vec![market],
+            risk: vec![Risk {
+                parameter: "beta".into(),
+                value: 0.0,
+            }],
+            ..BaseInputData::default()
+        }
+    }
+
+    #[test]
+    fn summary_counts_capacities_and_bindings() {
+        let summary = summarize(&make_input_data());
+        assert_eq!(summary.entity_counts.nodes, 2);
+        assert_eq!(summary.entity_counts.topologies, 2);
+        assert_eq!(summary.entity_counts.markets, 1);
+        assert_eq!(
+            summary.node_capacities,
+            vec![
+                NodeCapacity {
+                    node: "elc".into(),
+                    input_capacity: 0.0,
+                    output_capacity: 5.0,
+                },
+                NodeCapacity {
+                    node: "heat".into(),
+                    input_capacity: 12.0,
+                    output_capacity: 0.0,
+                },
+            ]
+        );
+        assert_eq!(summary.storages.len(), 1);
+        assert_eq!(summary.total_storage_capacity, 40.0);
+        assert_eq!(summary.scenario_count, 2);
+        assert_eq!(summary.total_scenario_weight, 1.0);
+        assert_eq!(
+            summary.forecast_bindings,
+            vec![ForecastBinding {
+                path: "markets/npe~12/price".into(),
+                scenario: Some("s1".into()),
+                forecast: "ELERING".into(),
+                f_type: "electricity".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn feature_flags_follow_input_data() {
+        let mut input_data = make_input_data();
+        assert_eq!(
+            feature_flags(&input_data),
+            FeatureFlags {
+                contains_states: true,
+                contains_markets: true,
+                ..FeatureFlags::default()
+            }
+        );
+        input_data.risk[0].value = 0.5;
+        input_data.nodes[0].is_res = true;
+        input_data.node_delay.push(Delay {
+            from_node: "elc".into(),
+            to_node: "heat".into(),
+            delay: 2.0,
+            min_delay_flow: 0.0,
+            max_delay_flow: 1.0,
+        });
+        let flags = feature_flags(&input_data);
+        assert!(flags.contains_risk);
+        assert!(flags.contains_delay);
+        assert!(!flags.contains_reserves);
+        input_data.setup.use_reserves = true;
+        assert!(feature_flags(&input_data).contains_reserves);
+    }
+
+    #[test]
+    fn feature_flags_match_expanded_input_data() {
+        let mut input_data = make_input_data();
+        input_data.risk[0].value = 0.5;
+        input_data.nodes[0].is_res = true;
+        input_data.setup.use_reserves = true;
+        input_data.processes[0].is_online = true;
+        input_data.processes[0].eff_ops_fun = vec![Point { x: 0.5, y: 0.9 }];
+        let time_line = vec![
+            Utc.with_ymd_and_hms(2025, 4, 20, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2025, 4, 20, 1, 0, 0).unwrap(),
+        ];
+        let expanded = input_data.expand_to_time_series(&time_line).unwrap();
+        let flags = feature_flags(&input_data);
+        assert_eq!(flags, expanded.features().flags());
+        assert!(flags.contains_piecewise_eff && flags.contains_online && flags.contains_reserves);
+    }
+}
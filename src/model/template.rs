@@ -0,0 +1,352 @@
+use super::library::validate_name;
+use crate::input_data_base::BaseInputData;
+use crate::settings;
+use juniper::GraphQLObject;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+const TEMPLATE_FILE_EXTENSION: &str = "json";
+
+/// Input data fragment whose values and names may contain `${parameter}` placeholders.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Template {
+    pub name: String,
+    pub description: String,
+    pub input_data: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, GraphQLObject, PartialEq)]
+#[graphql(description = "Information about a stored model template.")]
+pub struct TemplateInfo {
+    #[graphql(description = "Template's name.")]
+    pub name: String,
+    #[graphql(description = "Free-form description of the template.")]
+    pub description: String,
+    #[graphql(description = "Names of the template's parameters.")]
+    pub parameters: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParameterValue {
+    Number(f64),
+    Text(String),
+}
+
+impl ParameterValue {
+    fn to_text(&self) -> String {
+        match self {
+            ParameterValue::Number(number) => number.to_string(),
+            ParameterValue::Text(text) => text.clone(),
+        }
+    }
+
+    fn to_json(&self) -> Result<Value, String> {
+        match self {
+            ParameterValue::Number(number) => serde_json::Number::from_f64(*number)
+                .map(Value::Number)
+                .ok_or_else(|| format!("{} is not a finite number", number)),
+            ParameterValue::Text(text) => Ok(Value::String(text.clone())),
+        }
+    }
+}
+
+impl Template {
+    pub fn info(&self) -> TemplateInfo {
+        TemplateInfo {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            parameters: self.parameters(),
+        }
+    }
+
+    /// Returns the names of parameters used in the template in alphabetical order.
+    pub fn parameters(&self) -> Vec<String> {
+        let mut names = BTreeSet::new();
+        for value in self.input_data.values() {
+            collect_parameters(value, &mut names);
+        }
+        names.into_iter().collect()
+    }
+}
+
+pub fn make_template_library_path() -> PathBuf {
+    settings::config_path().join("templates")
+}
+
+/// Returns placeholder name if text is `${name}` with a valid name.
+fn placeholder(text: &str) -> Option<&str> {
+    let name = text.strip_prefix("${")?.strip_suffix('}')?;
+    let is_valid = !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+    is_valid.then_some(name)
+}
+
+/// Returns start, end and name of each placeholder in text.
+fn placeholders(text: &str) -> Vec<(usize, usize, &str)> {
+    let mut found = Vec::new();
+    let mut offset = 0;
+    while let Some(start) = text[offset..].find("${") {
+        let start = offset + start;
+        match text[start..].find('}') {
+            Some(length) => {
+                let end = start + length + 1;
+                if let Some(name) = placeholder(&text[start..end]) {
+                    found.push((start, end, name));
+                }
+                offset = end;
+            }
+            None => break,
+        }
+    }
+    found
+}
+
+fn collect_parameters(value: &Value, names: &mut BTreeSet<String>) {
+    match value {
+        Value::String(text) => {
+            for (_, _, name) in placeholders(text) {
+                names.insert(name.to_string());
+            }
+        }
+        Value::Array(items) => items
+            .iter()
+            .for_each(|item| collect_parameters(item, names)),
+        Value::Object(fields) => fields
+            .values()
+            .for_each(|field| collect_parameters(field, names)),
+        _ => (),
+    }
+}
+
+fn substitute(value: &mut Value, parameters: &[(String, ParameterValue)]) -> Result<(), String> {
+    let find = |name: &str| {
+        parameters
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value)
+            .ok_or_else(|| format!("missing value for parameter '{}'", name))
+    };
+    match value {
+        Value::String(text) => {
+            if let Some(name) = placeholder(text) {
+                *value = find(name)?.to_json()?;
+                return Ok(());
+            }
+            let mut substituted = String::new();
+            let mut offset = 0;
+            for (start, end, name) in placeholders(text) {
+                substituted.push_str(&text[offset..start]);
+                substituted.push_str(&find(name)?.to_text());
+                offset = end;
+            }
+            substituted.push_str(&text[offset..]);
+            *text = substituted;
+        }
+        Value::Array(items) => {
+            for item in items {
+                substitute(item, parameters)?;
+            }
+        }
+        Value::Object(fields) => {
+            for field in fields.values_mut() {
+                substitute(field, parameters)?;
+            }
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+fn empty_input_data() -> Result<Map<String, Value>, String> {
+    match serde_json::to_value(BaseInputData::default()) {
+        Ok(Value::Object(fields)) => Ok(fields),
+        Ok(..) => Err("input data is not an object".into()),
+        Err(error) => Err(format!("failed to serialize input data: {}", error)),
+    }
+}
+
+/// Expands template with given parameter values to input data.
+///
+/// Fields missing from the template are left empty.
+pub fn instantiate(
+    template: &Template,
+    parameters: &[(String, ParameterValue)],
+) -> Result<BaseInputData, String> {
+    let template_parameters = template.parameters();
+    for (name, _) in parameters {
+        if !template_parameters.contains(name) {
+            return Err(format!("template has no parameter '{}'", name));
+        }
+    }
+    let mut input_data = empty_input_data()?;
+    for (field, value) in &template.input_data {
+        let mut value = value.clone();
+        substitute(&mut value, parameters)?;
+        input_data.insert(field.clone(), value);
+    }
+    serde_json::from_value(Value::Object(input_data))
+        .map_err(|error| format!("template does not match input data: {}", error))
+}
+
+fn template_file_path(library: &Path, name: &str) -> Result<PathBuf, String> {
+    validate_name(name)?;
+    Ok(library.join(format!("{}.{}", name, TEMPLATE_FILE_EXTENSION)))
+}
+
+fn read_template(file_path: &Path) -> Result<Template, String> {
+    let file = File::open(file_path)
+        .map_err(|error| format!("failed to open template file: {}", error))?;
+    serde_json::from_reader(file)
+        .map_err(|error| format!("failed to parse template file: {}", error))
+}
+
+/// Stores an input data fragment given as JSON as a template, replacing an existing template with the same name.
+pub fn save_template(
+    library: &Path,
+    name: &str,
+    description: Option<String>,
+    json: &str,
+) -> Result<TemplateInfo, String> {
+    let file_path = template_file_path(library, name)?;
+    let input_data: Map<String, Value> = serde_json::from_str(json)
+        .map_err(|error| format!("failed to parse template: {}", error))?;
+    let known_fields = empty_input_data()?;
+    if let Some(field) = input_data.keys().find(|f| !known_fields.contains_key(*f)) {
+        return Err(format!("unknown input data field '{}'", field));
+    }
+    let template = Template {
+        name: name.to_string(),
+        description: description.unwrap_or_default(),
+        input_data,
+    };
+    fs::create_dir_all(library)
+        .map_err(|error| format!("failed to create template directory: {}", error))?;
+    let file = File::create(&file_path)
+        .map_err(|error| format!("failed to create template file: {}", error))?;
+    serde_json::to_writer_pretty(file, &template)
+        .map_err(|error| format!("failed to write template file: {}", error))?;
+    Ok(template.info())
+}
+
+/// Lists templates sorted by name.
+pub fn list_templates(library: &Path) -> Result<Vec<TemplateInfo>, String> {
+    if !library.exists() {
+        return Ok(Vec::new());
+    }
+    let entries = fs::read_dir(library)
+        .map_err(|error| format!("failed to read template directory: {}", error))?;
+    let mut templates = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|error| format!("failed to read template directory: {}", error))?
+            .path();
+        if path.extension().and_then(|e| e.to_str()) != Some(TEMPLATE_FILE_EXTENSION) {
+            continue;
+        }
+        let template =
+            read_template(&path).map_err(|error| format!("{}: {}", path.display(), error))?;
+        templates.push(template.info());
+    }
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(templates)
+}
+
+pub fn load_template(library: &Path, name: &str) -> Result<Template, String> {
+    let file_path = template_file_path(library, name)?;
+    if !file_path.exists() {
+        return Err(format!("no such template '{}'", name));
+    }
+    read_template(&file_path)
+}
+
+pub fn delete_template(library: &Path, name: &str) -> Result<(), String> {
+    let file_path = template_file_path(library, name)?;
+    if !file_path.exists() {
+        return Err(format!("no such template '{}'", name));
+    }
+    fs::remove_file(&file_path)
+        .map_err(|error| format!("failed to delete template file: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_data_base::{BaseProcess, BaseTopology, Conversion};
+
+    fn heat_pump_template_json() -> String {
+        let mut process = BaseProcess::new("hp".into(), Conversion::Unit);
+        process
+            .topos
+            .push(BaseTopology::new("hp".into(), "heat".into()));
+        let mut process = serde_json::to_value(process).unwrap();
+        process["name"] = "${site}_hp".into();
+        process["eff"] = "${cop}".into();
+        process["topos"][0]["source"] = "${site}_hp".into();
+        process["topos"][0]["capacity"] = "${hp_capacity}".into();
+        serde_json::json!({ "processes": [process] }).to_string()
+    }
+
+    fn parameters(capacity: ParameterValue) -> Vec<(String, ParameterValue)> {
+        vec![
+            ("site".to_string(), ParameterValue::Text("school".into())),
+            ("cop".to_string(), ParameterValue::Number(3.5)),
+            ("hp_capacity".to_string(), capacity),
+        ]
+    }
+
+    #[test]
+    fn stored_template_is_instantiated_with_parameters() {
+        let directory = tempfile::tempdir().unwrap();
+        let info = save_template(
+            directory.path(),
+            "heat pump",
+            Some("air to water".into()),
+            &heat_pump_template_json(),
+        )
+        .unwrap();
+        assert_eq!(info.parameters, vec!["cop", "hp_capacity", "site"]);
+        assert_eq!(list_templates(directory.path()).unwrap(), vec![info]);
+        let template = load_template(directory.path(), "heat pump").unwrap();
+        let input_data =
+            instantiate(&template, &parameters(ParameterValue::Number(120.0))).unwrap();
+        let process = &input_data.processes[0];
+        assert_eq!(process.name, "school_hp");
+        assert_eq!(process.eff, 3.5);
+        assert_eq!(process.topos[0].source, "school_hp");
+        assert_eq!(process.topos[0].capacity, 120.0);
+        assert!(input_data.nodes.is_empty());
+        delete_template(directory.path(), "heat pump").unwrap();
+        assert!(load_template(directory.path(), "heat pump").is_err());
+    }
+
+    #[test]
+    fn parameters_are_checked() {
+        let directory = tempfile::tempdir().unwrap();
+        save_template(directory.path(), "hp", None, &heat_pump_template_json()).unwrap();
+        let template = load_template(directory.path(), "hp").unwrap();
+        let error = instantiate(&template, &parameters(ParameterValue::Text("big".into())))
+            .err()
+            .unwrap();
+        assert!(error.starts_with("template does not match input data: invalid type"));
+        let mut missing = parameters(ParameterValue::Number(1.0));
+        missing.remove(0);
+        assert_eq!(
+            instantiate(&template, &missing).err().unwrap(),
+            "missing value for parameter 'site'"
+        );
+        let mut unknown = parameters(ParameterValue::Number(1.0));
+        unknown.push(("volume".into(), ParameterValue::Number(2.0)));
+        assert_eq!(
+            instantiate(&template, &unknown).err().unwrap(),
+            "template has no parameter 'volume'"
+        );
+        assert_eq!(
+            save_template(directory.path(), "hp", None, r#"{"pumps": []}"#)
+                .err()
+                .unwrap(),
+            "unknown input data field 'pumps'"
+        );
+    }
+}
@@ -0,0 +1,969 @@
+use super::diff::escape_segment;
+use super::Model;
+use crate::event_loop::time_series;
+use crate::input_data_base::{
+    BaseForecastable, BaseInputData, BaseProcess, ConstraintFactorType, FloatList, ForecastValue,
+    MarketType, SeriesValue, Value,
+};
+use crate::time_line_settings::{compute_timeline_start, ClockChoice, TimeLineStart};
+use crate::units;
+use crate::TimeStamp;
+use juniper::GraphQLObject;
+
+/// Forecast providers that the optimization job knows how to replace with data.
+const ELECTRICITY_FORECAST_TYPE: &str = "electricity";
+const ELECTRICITY_PRICE_PROVIDERS: [&str; 2] = ["ELERING", "ENTSOE"];
+const TEMPERATURE_PROVIDERS: [&str; 1] = ["FMI"];
+
+#[derive(Clone, Debug, GraphQLObject, PartialEq)]
+#[graphql(description = "Problem found in the model.")]
+pub struct ModelProblem {
+    #[graphql(description = "Path to the offending item, e.g. markets/npe/process_group.")]
+    pub path: String,
+    #[graphql(description = "Description of the problem.")]
+    pub message: String,
+}
+
+struct Problems {
+    problems: Vec<ModelProblem>,
+}
+
+impl Problems {
+    fn push(&mut self, path: String, message: impl Into<String>) {
+        self.problems.push(ModelProblem {
+            path,
+            message: message.into(),
+        });
+    }
+}
+
+/// Checks the whole model and returns every problem found; an empty list means the model is valid.
+pub fn validate_model(model: &Model) -> Vec<ModelProblem> {
+    let time_line_start = compute_timeline_start(&model.time_line);
+    let time_line = model.time_line.make_time_line(time_line_start);
+    let mut problems = Problems {
+        problems: Vec::new(),
+    };
+    let input_data = &model.input_data;
+    let checker = SeriesChecker {
+        input_data,
+        series_length: time_line.len(),
+        base_series_length: time_series::base_time_line(&time_line).len(),
+        time_line_start,
+        scenario_source: if model.weather_ensemble.is_some() {
+            Some("scenarios come from a weather ensemble")
+        } else if model.scenario_reduction.is_some() {
+            Some("scenarios are reduced")
+        } else {
+            None
+        },
+        scenarios_generated: model.weather_ensemble.is_some(),
+    };
+    check_time_line(model, &mut problems);
+    check_scenarios(input_data, &mut problems);
+    check_nodes(input_data, &checker, &mut problems);
+    check_processes(input_data, &checker, &mut problems);
+    check_groups(input_data, &mut problems);
+    check_markets(input_data, &checker, &mut problems);
+    check_node_connections(input_data, &checker, &mut problems);
+    check_gen_constraints(input_data, &checker, &mut problems);
+    check_units(input_data, &mut problems);
+    problems.problems
+}
+
+fn check_time_line(model: &Model, problems: &mut Problems) {
+    if let Some(time_zone) = model.time_line.time_zone() {
+        if let Err(error) = ClockChoice::parse_time_zone(time_zone) {
+            problems.push("time_line/time_zone".into(), error);
+        }
+    }
+    if let TimeLineStart::ClockChoice(ref clock_choice) = model.time_line.start() {
+        if let Some(ref time_zone) = clock_choice.time_zone {
+            if let Err(error) = ClockChoice::parse_time_zone(time_zone) {
+                problems.push("time_line/start/time_zone".into(), error);
+            }
+        }
+    }
+}
+
+fn check_units(input_data: &BaseInputData, problems: &mut Problems) {
+    for problem in units::check_units(input_data) {
+        problems.push(format!("{}/{}", problem.entity, problem.field), problem.message);
+    }
+}
+
+fn node_exists(name: &str, input_data: &BaseInputData) -> bool {
+    input_data.nodes.iter().any(|n| n.name == name)
+}
+
+fn has_reserve_markets(input_data: &BaseInputData) -> bool {
+    input_data
+        .markets
+        .iter()
+        .any(|m| matches!(m.m_type, MarketType::Reserve))
+}
+
+fn check_scenarios(input_data: &BaseInputData, problems: &mut Problems) {
+    if input_data.scenarios.is_empty() {
+        problems.push("scenarios".into(), "model has no scenarios");
+    }
+    let common_scenario = &input_data.setup.common_scenario_name;
+    if !common_scenario.is_empty()
+        && !input_data
+            .scenarios
+            .iter()
+            .any(|s| s.name() == common_scenario)
+    {
+        problems.push(
+            "setup/common_scenario_name".into(),
+            format!("no such scenario '{}'", common_scenario),
+        );
+    }
+}
+
+fn check_nodes(input_data: &BaseInputData, checker: &SeriesChecker, problems: &mut Problems) {
+    let reserve_markets_exist = has_reserve_markets(input_data);
+    for node in &input_data.nodes {
+        let path = format!("nodes/{}", escape_segment(&node.name));
+        for group in &node.groups {
+            if !input_data.node_groups.iter().any(|g| g.name == *group) {
+                problems.push(
+                    format!("{}/groups", path),
+                    format!("no such node group '{}'", group),
+                );
+            }
+        }
+        if node.is_res && !reserve_markets_exist {
+            problems.push(
+                format!("{}/is_res", path),
+                "node participates in reserves but the model has no reserve markets",
+            );
+        }
+        checker.check_values(&node.cost, &format!("{}/cost", path), problems);
+        checker.check_forecast_values(&node.inflow, &format!("{}/inflow", path), problems);
+        for value in &node.inflow {
+            if let BaseForecastable::Forecast(ref forecast) = value.value {
+                if !TEMPERATURE_PROVIDERS.contains(&forecast.name()) {
+                    problems.push(
+                        format!("{}/inflow", path),
+                        format!("no provider for forecast '{}'", forecast.name()),
+                    );
+                } else if node.state.as_ref().is_none_or(|s| !s.is_temp) {
+                    problems.push(
+                        format!("{}/inflow", path),
+                        "temperature forecast requires a temperature state",
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn check_processes(input_data: &BaseInputData, checker: &SeriesChecker, problems: &mut Problems) {
+    let reserve_markets_exist = has_reserve_markets(input_data);
+    for process in &input_data.processes {
+        let path = format!("processes/{}", escape_segment(&process.name));
+        for group in &process.groups {
+            if !input_data.process_groups.iter().any(|g| g.name == *group) {
+                problems.push(
+                    format!("{}/groups", path),
+                    format!("no such process group '{}'", group),
+                );
+            }
+        }
+        if process.is_res && !reserve_markets_exist {
+            problems.push(
+                format!("{}/is_res", path),
+                "process participates in reserves but the model has no reserve markets",
+            );
+        }
+        if process.is_cf && process.cf.is_empty() {
+            problems.push(
+                format!("{}/cf", path),
+                "process is marked capacity factor process but has no cf values",
+            );
+        }
+        if process.topos.is_empty() {
+            problems.push(format!("{}/topos", path), "process has no topologies");
+        }
+        checker.check_values(&process.cf, &format!("{}/cf", path), problems);
+        checker.check_values(&process.eff_ts, &format!("{}/eff_ts", path), problems);
+        for topology in &process.topos {
+            let topology_path = format!(
+                "{}/topos/{}->{}",
+                path,
+                escape_segment(&topology.source),
+                escape_segment(&topology.sink)
+            );
+            check_topology_end(
+                &topology.source,
+                process,
+                input_data,
+                &format!("{}/source", topology_path),
+                problems,
+            );
+            check_topology_end(
+                &topology.sink,
+                process,
+                input_data,
+                &format!("{}/sink", topology_path),
+                problems,
+            );
+            if topology.source == topology.sink {
+                problems.push(topology_path.clone(), "source and sink are the same");
+            }
+            checker.check_values(
+                &topology.cap_ts,
+                &format!("{}/cap_ts", topology_path),
+                problems,
+            );
+        }
+    }
+}
+
+fn check_topology_end(
+    end: &str,
+    process: &BaseProcess,
+    input_data: &BaseInputData,
+    path: &str,
+    problems: &mut Problems,
+) {
+    if end != process.name && !node_exists(end, input_data) {
+        problems.push(path.to_string(), format!("no such node '{}'", end));
+    }
+}
+
+fn check_groups(input_data: &BaseInputData, problems: &mut Problems) {
+    for group in &input_data.node_groups {
+        for member in &group.members {
+            if !node_exists(member, input_data) {
+                problems.push(
+                    format!("node_groups/{}/members", escape_segment(&group.name)),
+                    format!("no such node '{}'", member),
+                );
+            }
+        }
+    }
+    for group in &input_data.process_groups {
+        for member in &group.members {
+            if !input_data.processes.iter().any(|p| p.name == *member) {
+                problems.push(
+                    format!("process_groups/{}/members", escape_segment(&group.name)),
+                    format!("no such process '{}'", member),
+                );
+            }
+        }
+    }
+}
+
+fn check_markets(input_data: &BaseInputData, checker: &SeriesChecker, problems: &mut Problems) {
+    for market in &input_data.markets {
+        let path = format!("markets/{}", escape_segment(&market.name));
+        match market.m_type {
+            MarketType::Energy => {
+                if !node_exists(&market.node, input_data) {
+                    problems.push(
+                        format!("{}/node", path),
+                        format!("no such node '{}'", market.node),
+                    );
+                }
+            }
+            MarketType::Reserve => {
+                if !input_data.node_groups.iter().any(|g| g.name == market.node) {
+                    problems.push(
+                        format!("{}/node", path),
+                        format!("no such node group '{}'", market.node),
+                    );
+                }
+                match market.reserve_type {
+                    Some(ref reserve_type) => {
+                        if !input_data
+                            .reserve_type
+                            .iter()
+                            .any(|r| r.name == *reserve_type)
+                        {
+                            problems.push(
+                                format!("{}/reserve_type", path),
+                                format!("no such reserve type '{}'", reserve_type),
+                            );
+                        }
+                    }
+                    None => problems.push(
+                        format!("{}/reserve_type", path),
+                        "reserve market has no reserve type",
+                    ),
+                }
+            }
+        }
+        if !input_data
+            .process_groups
+            .iter()
+            .any(|g| g.name == market.process_group)
+        {
+            problems.push(
+                format!("{}/process_group", path),
+                format!("no such process group '{}'", market.process_group),
+            );
+        }
+        if market.price.is_empty() {
+            problems.push(format!("{}/price", path), "market has no price");
+        }
+        checker.check_values(
+            &market.realisation,
+            &format!("{}/realisation", path),
+            problems,
+        );
+        checker.check_values(
+            &market.reserve_activation_price,
+            &format!("{}/reserve_activation_price", path),
+            problems,
+        );
+        let price_is_forecast = market
+            .price
+            .iter()
+            .any(|v| matches!(v.value, BaseForecastable::Forecast(_)));
+        for (field, values) in [
+            ("price", &market.price),
+            ("up_price", &market.up_price),
+            ("down_price", &market.down_price),
+        ] {
+            let field_path = format!("{}/{}", path, field);
+            checker.check_forecast_values(values, &field_path, problems);
+            for value in values {
+                if let BaseForecastable::Forecast(ref forecast) = value.value {
+                    if forecast.f_type() != ELECTRICITY_FORECAST_TYPE
+                        || !ELECTRICITY_PRICE_PROVIDERS.contains(&forecast.name())
+                    {
+                        problems.push(
+                            field_path.clone(),
+                            format!(
+                                "no provider for forecast '{}' of type '{}'",
+                                forecast.name(),
+                                forecast.f_type()
+                            ),
+                        );
+                    } else if !price_is_forecast {
+                        problems.push(
+                            field_path.clone(),
+                            "forecast is used only if price is connected to a forecast as well",
+                        );
+                    }
+                }
+            }
+        }
+        if let Some(ref price_scenarios) = market.price_scenarios {
+            let path = format!("{}/price_scenarios", path);
+            if let Err(error) = price_scenarios.validate() {
+                problems.push(path.clone(), error);
+            }
+            if checker.scenario_source.is_none() {
+                for offset in &price_scenarios.offsets {
+                    if !input_data
+                        .scenarios
+                        .iter()
+                        .any(|s| *s.name() == offset.scenario)
+                    {
+                        problems.push(
+                            path.clone(),
+                            format!("no such scenario '{}'", offset.scenario),
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn check_node_connections(
+    input_data: &BaseInputData,
+    checker: &SeriesChecker,
+    problems: &mut Problems,
+) {
+    for diffusion in &input_data.node_diffusion {
+        let path = format!(
+            "node_diffusion/{}->{}",
+            escape_segment(&diffusion.from_node),
+            escape_segment(&diffusion.to_node)
+        );
+        for (field, node) in [
+            ("from_node", &diffusion.from_node),
+            ("to_node", &diffusion.to_node),
+        ] {
+            if !node_exists(node, input_data) {
+                problems.push(
+                    format!("{}/{}", path, field),
+                    format!("no such node '{}'", node),
+                );
+            }
+        }
+        checker.check_values(
+            &diffusion.coefficient,
+            &format!("{}/coefficient", path),
+            problems,
+        );
+    }
+    for delay in &input_data.node_delay {
+        let path = format!(
+            "node_delay/{}->{}",
+            escape_segment(&delay.from_node),
+            escape_segment(&delay.to_node)
+        );
+        for (field, node) in [("from_node", &delay.from_node), ("to_node", &delay.to_node)] {
+            if !node_exists(node, input_data) {
+                problems.push(
+                    format!("{}/{}", path, field),
+                    format!("no such node '{}'", node),
+                );
+            }
+        }
+    }
+    for history in &input_data.node_histories {
+        let path = format!("node_histories/{}", escape_segment(&history.node));
+        if !node_exists(&history.node, input_data) {
+            problems.push(
+                format!("{}/node", path),
+                format!("no such node '{}'", history.node),
+            );
+        }
+        for step in &history.steps {
+            if !input_data
+                .scenarios
+                .iter()
+                .any(|s| *s.name() == step.scenario)
+            {
+                problems.push(
+                    format!("{}/steps", path),
+                    format!("no such scenario '{}'", step.scenario),
+                );
+            }
+        }
+        if checker.scenarios_generated && history.steps_differ_between_scenarios() {
+            problems.push(
+                format!("{}/steps", path),
+                "steps must be the same in every scenario when scenarios come from a weather ensemble",
+            );
+        }
+    }
+    for inflow_block in &input_data.inflow_blocks {
+        let path = format!("inflow_blocks/{}", escape_segment(&inflow_block.name));
+        if !node_exists(&inflow_block.node, input_data) {
+            problems.push(
+                format!("{}/node", path),
+                format!("no such node '{}'", inflow_block.node),
+            );
+        }
+        checker.check_values(&inflow_block.data, &format!("{}/data", path), problems);
+    }
+}
+
+fn check_gen_constraints(
+    input_data: &BaseInputData,
+    checker: &SeriesChecker,
+    problems: &mut Problems,
+) {
+    for constraint in &input_data.gen_constraints {
+        let path = format!("gen_constraints/{}", escape_segment(&constraint.name));
+        checker.check_values(
+            &constraint.constant,
+            &format!("{}/constant", path),
+            problems,
+        );
+        for (index, factor) in constraint.factors.iter().enumerate() {
+            let factor_path = format!("{}/factors/{}", path, index);
+            let variable = &factor.var_tuple;
+            match factor.var_type {
+                ConstraintFactorType::Flow | ConstraintFactorType::Online => {
+                    match input_data
+                        .processes
+                        .iter()
+                        .find(|p| p.name == variable.entity)
+                    {
+                        Some(process) => {
+                            if factor.var_type == ConstraintFactorType::Online && !process.is_online
+                            {
+                                problems.push(
+                                    format!("{}/var_tuple", factor_path),
+                                    format!("process '{}' is not an online process", process.name),
+                                );
+                            }
+                            if factor.var_type == ConstraintFactorType::Flow {
+                                check_flow_identifier(
+                                    process,
+                                    variable.identifier.as_deref(),
+                                    &factor_path,
+                                    problems,
+                                );
+                            }
+                        }
+                        None => problems.push(
+                            format!("{}/var_tuple", factor_path),
+                            format!("no such process '{}'", variable.entity),
+                        ),
+                    }
+                }
+                ConstraintFactorType::State => {
+                    match input_data.nodes.iter().find(|n| n.name == variable.entity) {
+                        Some(node) => {
+                            if node.state.is_none() {
+                                problems.push(
+                                    format!("{}/var_tuple", factor_path),
+                                    format!("node '{}' has no state", node.name),
+                                );
+                            }
+                        }
+                        None => problems.push(
+                            format!("{}/var_tuple", factor_path),
+                            format!("no such node '{}'", variable.entity),
+                        ),
+                    }
+                }
+            }
+            checker.check_values(&factor.data, &format!("{}/data", factor_path), problems);
+        }
+    }
+}
+
+fn check_flow_identifier(
+    process: &BaseProcess,
+    identifier: Option<&str>,
+    factor_path: &str,
+    problems: &mut Problems,
+) {
+    match identifier {
+        Some(node) => {
+            if !process
+                .topos
+                .iter()
+                .any(|t| t.source == node || t.sink == node)
+            {
+                problems.push(
+                    format!("{}/var_tuple", factor_path),
+                    format!(
+                        "process '{}' has no topology connected to '{}'",
+                        process.name, node
+                    ),
+                );
+            }
+        }
+        None => problems.push(
+            format!("{}/var_tuple", factor_path),
+            "flow factor has no node",
+        ),
+    }
+}
+
+/// Checks scenario coverage and series lengths of value lists.
+struct SeriesChecker<'a> {
+    input_data: &'a BaseInputData,
+    series_length: usize,
+    /// Length of the time line at its finest step; lists of this length are aggregated.
+    base_series_length: usize,
+    time_line_start: TimeStamp,
+    scenario_source: Option<&'static str>,
+    scenarios_generated: bool,
+}
+
+impl SeriesChecker<'_> {
+    fn check_values(&self, values: &[Value], path: &str, problems: &mut Problems) {
+        let scenarios = values.iter().map(|v| v.scenario.as_ref());
+        self.check_scenario_coverage(scenarios, path, problems);
+        for value in values {
+            if let SeriesValue::FloatList(ref list) = value.value {
+                self.check_list(list, value.scenario.as_ref(), path, problems);
+            }
+        }
+    }
+
+    fn check_forecast_values(&self, values: &[ForecastValue], path: &str, problems: &mut Problems) {
+        if values
+            .iter()
+            .any(|v| matches!(v.value, BaseForecastable::Forecast(_)))
+        {
+            return;
+        }
+        let scenarios = values.iter().map(|v| v.scenario.as_ref());
+        self.check_scenario_coverage(scenarios, path, problems);
+        for value in values {
+            if let BaseForecastable::FloatList(ref list) = value.value {
+                self.check_list(list, value.scenario.as_ref(), path, problems);
+            }
+        }
+    }
+
+    fn check_scenario_coverage<'b>(
+        &self,
+        scenarios: impl Iterator<Item = Option<&'b String>> + Clone,
+        path: &str,
+        problems: &mut Problems,
+    ) {
+        if scenarios.clone().next().is_none() {
+            return;
+        }
+        let default_count = scenarios.clone().filter(|s| s.is_none()).count();
+        if default_count > 1 {
+            problems.push(path.to_string(), "multiple values without scenario");
+        }
+        if let Some(source) = self.scenario_source {
+            if scenarios.clone().flatten().next().is_some() {
+                problems.push(
+                    path.to_string(),
+                    format!("values for individual scenarios cannot be used when {}", source),
+                );
+            }
+        }
+        for scenario in scenarios.clone().flatten() {
+            if !self
+                .input_data
+                .scenarios
+                .iter()
+                .any(|s| s.name() == scenario)
+            {
+                problems.push(path.to_string(), format!("no such scenario '{}'", scenario));
+            }
+        }
+        if default_count == 0 {
+            for scenario in &self.input_data.scenarios {
+                if !scenarios.clone().flatten().any(|s| s == scenario.name()) {
+                    problems.push(
+                        path.to_string(),
+                        format!("no value for scenario '{}'", scenario.name()),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Lists with time stamps are resampled so they only need to cover the time line start.
+    fn check_list(
+        &self,
+        list: &FloatList,
+        scenario: Option<&String>,
+        path: &str,
+        problems: &mut Problems,
+    ) {
+        match list.time_stamps().first() {
+            Some(first) if *first > self.time_line_start => {
+                let scenario = scenario.map(|s| s.as_str()).unwrap_or("default");
+                problems.push(
+                    path.to_string(),
+                    format!("series for {} starts after the time line start", scenario),
+                );
+            }
+            Some(..) => (),
+            None => self.check_length(list.values().len(), scenario, path, problems),
+        }
+    }
+
+    fn check_length(
+        &self,
+        length: usize,
+        scenario: Option<&String>,
+        path: &str,
+        problems: &mut Problems,
+    ) {
+        if length != self.series_length && length != self.base_series_length {
+            let scenario = scenario.map(|s| s.as_str()).unwrap_or("default");
+            problems.push(
+                path.to_string(),
+                format!(
+                    "series for {} has {} values but the time line has {} steps",
+                    scenario, length, self.series_length
+                ),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_data::Forecast;
+    use crate::input_data_base::{
+        BaseConFactor, BaseGenConstraint, BaseMarket, BaseNode, BaseNodeHistory, BaseTopology,
+        ConstraintType, Conversion, NamedGroup, ProcessGroup, Series, ValueInput, VariableId,
+    };
+    use crate::resampling::ResamplingMethod;
+    use crate::scenarios::ensemble::WeatherEnsemble;
+    use crate::scenarios::reduction::ScenarioReduction;
+    use crate::scenarios::Scenario;
+    use crate::time_line_settings::{
+        Clock, ClockChoice, Duration, TimeLineSegment, TimeLineSettings, TimeLineStart,
+    };
+
+    fn make_model() -> Model {
+        let time_line = TimeLineSettings::try_new(
+            Duration::try_new(2, 0, 0).unwrap(),
+            Duration::try_new(1, 0, 0).unwrap(),
+            TimeLineStart::ClockChoice(ClockChoice::new(Clock::CurrentHour)),
+        )
+        .unwrap();
+        let mut process = BaseProcess::new("pump".into(), Conversion::Unit);
+        process
+            .topos
+            .push(BaseTopology::new("elc".into(), "pump".into()));
+        process
+            .topos
+            .push(BaseTopology::new("pump".into(), "heat".into()));
+        Model {
+            time_line,
+            input_data: BaseInputData {
+                scenarios: vec![
+                    Scenario::new("s1", 0.5).unwrap(),
+                    Scenario::new("s2", 0.5).unwrap(),
+                ],
+                nodes: vec![BaseNode::new("elc".into()), BaseNode::new("heat".into())],
+                processes: vec![process],
+                process_groups: vec![ProcessGroup::new("p1".into())],
+                ..BaseInputData::default()
+            },
+            weather_ensemble: None,
+            scenario_reduction: None,
+        }
+    }
+
+    fn paths(problems: &[ModelProblem]) -> Vec<&str> {
+        problems.iter().map(|p| p.path.as_str()).collect()
+    }
+
+    #[test]
+    fn valid_model_has_no_problems() {
+        assert!(validate_model(&make_model()).is_empty());
+    }
+
+    #[test]
+    fn dangling_references_are_found() {
+        let mut model = make_model();
+        model.input_data.processes[0]
+            .topos
+            .push(BaseTopology::new("pump".into(), "gas".into()));
+        model.input_data.markets.push(BaseMarket {
+            name: "npe".into(),
+            m_type: MarketType::Energy,
+            node: "elc".into(),
+            process_group: "p2".into(),
+            direction: None,
+            realisation: Vec::new(),
+            reserve_type: None,
+            is_bid: false,
+            is_limited: false,
+            min_bid: 0.0,
+            max_bid: 0.0,
+            fee: 0.0,
+            price: vec![ForecastValue {
+                scenario: None,
+                value: BaseForecastable::Forecast(Forecast::new(
+                    "NORDPOOL".into(),
+                    "electricity".into(),
+                    None,
+                )),
+            }],
+            up_price: Vec::new(),
+            down_price: Vec::new(),
+            reserve_activation_price: Vec::new(),
+            fixed: Vec::new(),
+            price_unit: None,
+            price_scenarios: None,
+        });
+        model.input_data.gen_constraints.push(BaseGenConstraint {
+            name: "c1".into(),
+            gc_type: ConstraintType::Equal,
+            is_setpoint: false,
+            penalty: 0.0,
+            factors: vec![BaseConFactor {
+                var_type: ConstraintFactorType::Flow,
+                var_tuple: VariableId {
+                    entity: "boiler".into(),
+                    identifier: Some("heat".into()),
+                },
+                data: Vec::new(),
+            }],
+            constant: Vec::new(),
+        });
+        let problems = validate_model(&model);
+        assert_eq!(
+            paths(&problems),
+            vec![
+                "processes/pump/topos/pump->gas/sink",
+                "markets/npe/process_group",
+                "markets/npe/price",
+                "gen_constraints/c1/factors/0/var_tuple",
+            ]
+        );
+        assert_eq!(
+            problems[2].message,
+            "no provider for forecast 'NORDPOOL' of type 'electricity'"
+        );
+    }
+
+    #[test]
+    fn slashes_in_names_are_escaped_in_paths() {
+        let mut model = make_model();
+        model.input_data.processes[0]
+            .topos
+            .push(BaseTopology::new("heat/1".into(), "gas~2".into()));
+        let problems = validate_model(&model);
+        assert_eq!(
+            paths(&problems),
+            vec![
+                "processes/pump/topos/heat~11->gas~02/source",
+                "processes/pump/topos/heat~11->gas~02/sink",
+            ]
+        );
+    }
+
+    #[test]
+    fn scenario_gaps_and_series_lengths_are_found() {
+        let mut model = make_model();
+        model.input_data.nodes[1].cost.push(
+            Value::try_from(ValueInput {
+                scenario: Some("s1".into()),
+                constant: None,
+                series: Some(vec![1.0, 2.0]),
+                time_stamps: None,
+                resampling: None,
+            })
+            .unwrap(),
+        );
+        model.input_data.nodes[1].is_res = true;
+        let problems = validate_model(&model);
+        assert_eq!(
+            problems,
+            vec![
+                ModelProblem {
+                    path: "nodes/heat/is_res".into(),
+                    message: "node participates in reserves but the model has no reserve markets"
+                        .into(),
+                },
+                ModelProblem {
+                    path: "nodes/heat/cost".into(),
+                    message: "no value for scenario 's2'".into(),
+                },
+                ModelProblem {
+                    path: "nodes/heat/cost".into(),
+                    message: "series for s1 has 2 values but the time line has 3 steps".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn lists_at_finest_step_of_segmented_time_line_are_accepted() {
+        let mut model = make_model();
+        let segments = vec![
+            TimeLineSegment::try_new(
+                Duration::try_new(1, 0, 0).unwrap(),
+                Duration::try_new(0, 30, 0).unwrap(),
+            )
+            .unwrap(),
+            TimeLineSegment::try_new(
+                Duration::try_new(2, 0, 0).unwrap(),
+                Duration::try_new(1, 0, 0).unwrap(),
+            )
+            .unwrap(),
+        ];
+        model.time_line.set_segments(segments).unwrap();
+        for length in [5, 7] {
+            model.input_data.nodes[1].cost = vec![Value::try_from(ValueInput {
+                scenario: None,
+                constant: None,
+                series: Some(vec![1.0; length]),
+                time_stamps: None,
+                resampling: None,
+            })
+            .unwrap()];
+            assert!(validate_model(&model).is_empty());
+        }
+    }
+
+    #[test]
+    fn lists_with_time_stamps_need_to_cover_time_line_start() {
+        let mut model = make_model();
+        let make_value = |year: i32| {
+            Value::try_from(ValueInput {
+                scenario: None,
+                constant: None,
+                series: Some(vec![1.0, 2.0]),
+                time_stamps: Some(vec![
+                    format!("{}-01-01T00:00:00Z", year),
+                    format!("{}-01-01T01:00:00Z", year),
+                ]),
+                resampling: Some(ResamplingMethod::StepHold),
+            })
+            .unwrap()
+        };
+        model.input_data.nodes[1].cost = vec![make_value(2000)];
+        assert!(validate_model(&model).is_empty());
+        model.input_data.nodes[1].cost = vec![make_value(2500)];
+        assert_eq!(
+            validate_model(&model),
+            vec![ModelProblem {
+                path: "nodes/heat/cost".into(),
+                message: "series for default starts after the time line start".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn scenario_values_conflict_with_weather_ensemble() {
+        let mut model = make_model();
+        model.input_data.nodes[1].cost.push(
+            Value::try_from(ValueInput {
+                scenario: Some("s1".into()),
+                constant: Some(1.0),
+                series: None,
+                time_stamps: None,
+                resampling: None,
+            })
+            .unwrap(),
+        );
+        model.input_data.nodes[1].cost.push(
+            Value::try_from(ValueInput {
+                scenario: Some("s2".into()),
+                constant: Some(2.0),
+                series: None,
+                time_stamps: None,
+                resampling: None,
+            })
+            .unwrap(),
+        );
+        assert!(validate_model(&model).is_empty());
+        model.weather_ensemble = Some(WeatherEnsemble::default());
+        assert_eq!(
+            validate_model(&model),
+            vec![ModelProblem {
+                path: "nodes/heat/cost".into(),
+                message: "values for individual scenarios cannot be used when scenarios come from a weather ensemble".into(),
+            }]
+        );
+        model.weather_ensemble = None;
+        model.scenario_reduction = Some(ScenarioReduction::new(1).unwrap());
+        assert_eq!(
+            validate_model(&model)[0].message,
+            "values for individual scenarios cannot be used when scenarios are reduced"
+        );
+    }
+
+    #[test]
+    fn differing_node_history_steps_conflict_with_weather_ensemble() {
+        let mut model = make_model();
+        let step = |scenario: &str, value: f64| Series {
+            scenario: scenario.into(),
+            durations: vec![Duration::try_new(1, 0, 0).unwrap()],
+            values: vec![value],
+        };
+        model.input_data.node_histories.push(BaseNodeHistory {
+            node: "heat".into(),
+            steps: vec![step("s1", 1.0), step("s2", 1.0)],
+        });
+        model.weather_ensemble = Some(WeatherEnsemble::default());
+        assert!(validate_model(&model).is_empty());
+        model.input_data.node_histories[0].steps[1].values[0] = 2.0;
+        assert_eq!(
+            validate_model(&model),
+            vec![ModelProblem {
+                path: "node_histories/heat/steps".into(),
+                message: "steps must be the same in every scenario when scenarios come from a weather ensemble".into(),
+            }]
+        );
+    }
+}
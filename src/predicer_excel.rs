@@ -0,0 +1,171 @@
+mod export;
+mod import;
+
+use crate::TimeStamp;
+use calamine::{open_workbook_auto, Data, Reader};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use std::path::Path;
+
+pub use export::{export_input_data, export_model};
+pub use import::{import_sheets, ImportOutcome, ImportReport, UnmappedColumn};
+
+/// Single cell of a Predicer input workbook.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Cell {
+    Empty,
+    Bool(bool),
+    Number(f64),
+    Text(String),
+    TimeStamp(TimeStamp),
+}
+
+impl Cell {
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Cell::Empty)
+    }
+
+    pub fn as_text(&self) -> Option<String> {
+        match self {
+            Cell::Empty => None,
+            Cell::Bool(value) => Some(value.to_string()),
+            Cell::Number(value) => Some(value.to_string()),
+            Cell::Text(value) => Some(value.clone()),
+            Cell::TimeStamp(value) => Some(value.format("%Y-%m-%dT%H:%M:%S").to_string()),
+        }
+    }
+
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            Cell::Number(value) => Some(*value),
+            Cell::Bool(value) => Some(if *value { 1.0 } else { 0.0 }),
+            Cell::Text(value) => value.trim().parse::<f64>().ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Cell::Bool(value) => Some(*value),
+            Cell::Number(value) => Some(*value != 0.0),
+            Cell::Text(value) => match value.trim().to_lowercase().as_str() {
+                "true" | "1" | "yes" => Some(true),
+                "false" | "0" | "no" => Some(false),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    pub fn as_time_stamp(&self) -> Option<TimeStamp> {
+        match self {
+            Cell::TimeStamp(value) => Some(*value),
+            Cell::Text(value) => parse_time_stamp(value.trim()),
+            _ => None,
+        }
+    }
+}
+
+fn parse_time_stamp(text: &str) -> Option<TimeStamp> {
+    if let Ok(stamp) = DateTime::parse_from_rfc3339(text) {
+        return Some(stamp.with_timezone(&Utc));
+    }
+    for format in [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M",
+    ] {
+        if let Ok(stamp) = NaiveDateTime::parse_from_str(text, format) {
+            return Some(stamp.and_utc());
+        }
+    }
+    None
+}
+
+impl From<&Data> for Cell {
+    fn from(value: &Data) -> Self {
+        match value {
+            Data::Int(value) => Cell::Number(*value as f64),
+            Data::Float(value) => Cell::Number(*value),
+            Data::String(value) if value.trim().is_empty() => Cell::Empty,
+            Data::String(value) => Cell::Text(value.trim().to_string()),
+            Data::Bool(value) => Cell::Bool(*value),
+            Data::DateTime(value) => match value.as_datetime() {
+                Some(stamp) => Cell::TimeStamp(stamp.and_utc()),
+                None => Cell::Number(value.as_f64()),
+            },
+            Data::DateTimeIso(value) => match parse_time_stamp(value) {
+                Some(stamp) => Cell::TimeStamp(stamp),
+                None => Cell::Text(value.clone()),
+            },
+            Data::DurationIso(value) => Cell::Text(value.clone()),
+            Data::Error(..) | Data::Empty => Cell::Empty,
+        }
+    }
+}
+
+/// Worksheet with its first row interpreted as column headers.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Sheet {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Cell>>,
+}
+
+impl Sheet {
+    pub fn new(name: &str, columns: Vec<String>, rows: Vec<Vec<Cell>>) -> Self {
+        Sheet {
+            name: name.to_string(),
+            columns,
+            rows,
+        }
+    }
+
+    /// Returns the index of given column; matching ignores case.
+    pub fn column_index(&self, column: &str) -> Option<usize> {
+        self.columns
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(column))
+    }
+
+    pub fn cell(&self, row: usize, column: usize) -> &Cell {
+        self.rows[row].get(column).unwrap_or(&Cell::Empty)
+    }
+
+    /// Returns true if the sheet has no data rows.
+    pub fn is_empty(&self) -> bool {
+        self.rows
+            .iter()
+            .all(|row| row.iter().all(|cell| cell.is_empty()))
+    }
+}
+
+/// Reads all worksheets of an .xlsx, .xls or .ods file.
+pub fn read_workbook(path: &Path) -> Result<Vec<Sheet>, String> {
+    let mut workbook =
+        open_workbook_auto(path).map_err(|error| format!("failed to open workbook: {}", error))?;
+    let mut sheets = Vec::new();
+    for name in workbook.sheet_names() {
+        let range = workbook
+            .worksheet_range(&name)
+            .map_err(|error| format!("failed to read sheet '{}': {}", name, error))?;
+        let mut rows = range.rows();
+        let columns = match rows.next() {
+            Some(header) => header
+                .iter()
+                .map(|cell| cell.to_string().trim().to_string())
+                .collect(),
+            None => Vec::new(),
+        };
+        let rows = rows
+            .map(|row| row.iter().map(Cell::from).collect())
+            .collect();
+        sheets.push(Sheet::new(&name, columns, rows));
+    }
+    Ok(sheets)
+}
+
+/// Reads a Predicer input workbook from disk and converts it to Hertta's input data.
+pub fn import_workbook(path: &Path) -> Result<ImportOutcome, String> {
+    let sheets = read_workbook(path)?;
+    import_sheets(&sheets)
+}
@@ -0,0 +1,181 @@
+use crate::event_loop::arrow_input;
+use crate::input_data::InputData;
+use crate::model::Model;
+use crate::time_line_settings::compute_timeline_start;
+use arrow::array::{Array, AsArray};
+use arrow::datatypes::{
+    DataType, Float64Type, Int32Type, Int64Type, TimeUnit, TimestampMillisecondType,
+};
+use arrow::record_batch::RecordBatch;
+use chrono::DateTime;
+use rust_xlsxwriter::{Workbook, Worksheet};
+use std::path::Path;
+
+/// Batches that are part of the Arrow transfer protocol but not of Predicer's input workbook.
+const SKIPPED_BATCHES: [&str; 1] = ["temps"];
+
+/// Expands the model on its time line and writes it to a Predicer input workbook.
+pub fn export_model(model: &Model, path: &Path) -> Result<(), String> {
+    let time_line = model
+        .time_line
+        .make_time_line(compute_timeline_start(&model.time_line));
+    let mut input_data = model
+        .input_data
+        .expand_to_time_series(&time_line)
+        .map_err(|error| format!("failed to expand model: {}", error))?;
+    input_data.infer_feature_flags();
+    input_data.check_ts_data_against_temporals()?;
+    export_input_data(&input_data, path)
+}
+
+/// Writes expanded input data to a Predicer input workbook.
+///
+/// Each Arrow record batch sent to Predicer becomes a sheet with the same name and columns.
+pub fn export_input_data(input_data: &InputData, path: &Path) -> Result<(), String> {
+    let batches = arrow_input::create_record_batches(input_data)
+        .map_err(|error| format!("failed to convert input data: {}", error))?;
+    let mut workbook = Workbook::new();
+    for (name, batch) in &batches {
+        if SKIPPED_BATCHES.contains(&name.as_str()) {
+            continue;
+        }
+        let worksheet = workbook.add_worksheet();
+        worksheet
+            .set_name(name)
+            .map_err(|error| format!("failed to create sheet '{}': {}", name, error))?;
+        write_batch(worksheet, batch)
+            .map_err(|error| format!("failed to write sheet '{}': {}", name, error))?;
+    }
+    workbook
+        .save(path)
+        .map_err(|error| format!("failed to save workbook: {}", error))
+}
+
+fn write_batch(worksheet: &mut Worksheet, batch: &RecordBatch) -> Result<(), String> {
+    for (column, field) in batch.schema().fields().iter().enumerate() {
+        let column = column as u16;
+        worksheet
+            .write_string(0, column, field.name())
+            .map_err(|error| error.to_string())?;
+        let array = batch.column(column as usize);
+        for row in 0..array.len() {
+            write_cell(worksheet, row as u32 + 1, column, array.as_ref(), row)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_cell(
+    worksheet: &mut Worksheet,
+    row: u32,
+    column: u16,
+    array: &dyn Array,
+    index: usize,
+) -> Result<(), String> {
+    if array.is_null(index) {
+        return Ok(());
+    }
+    let result = match array.data_type() {
+        DataType::Utf8 => {
+            worksheet.write_string(row, column, array.as_string::<i32>().value(index))
+        }
+        DataType::Boolean => worksheet.write_boolean(row, column, array.as_boolean().value(index)),
+        DataType::Float64 => {
+            let value = array.as_primitive::<Float64Type>().value(index);
+            if value.is_nan() {
+                return Ok(());
+            }
+            worksheet.write_number(row, column, value)
+        }
+        DataType::Int32 => {
+            worksheet.write_number(row, column, array.as_primitive::<Int32Type>().value(index))
+        }
+        DataType::Int64 => worksheet.write_number(
+            row,
+            column,
+            array.as_primitive::<Int64Type>().value(index) as f64,
+        ),
+        DataType::Timestamp(TimeUnit::Millisecond, _) => {
+            let millis = array
+                .as_primitive::<TimestampMillisecondType>()
+                .value(index);
+            let stamp = DateTime::from_timestamp_millis(millis)
+                .ok_or_else(|| format!("time stamp {} out of range", millis))?;
+            worksheet.write_string(
+                row,
+                column,
+                stamp.format("%Y-%m-%dT%H:%M:%S%:z").to_string(),
+            )
+        }
+        DataType::Union(..) => {
+            let union = array.as_union();
+            let child = union.child(union.type_id(index));
+            return write_cell(
+                worksheet,
+                row,
+                column,
+                child.as_ref(),
+                union.value_offset(index),
+            );
+        }
+        other => return Err(format!("unsupported column type {}", other)),
+    };
+    result.map(|_| ()).map_err(|error| error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::predicer_excel::{import_workbook, read_workbook};
+    use std::fs::File;
+    use std::io::BufReader;
+    use std::path::PathBuf;
+
+    fn load_test_data() -> InputData {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("tests/predicer/predicer_all.json");
+        let file = File::open(path).expect("Failed to open test file");
+        serde_json::from_reader(BufReader::new(file)).expect("Failed to parse JSON")
+    }
+
+    #[test]
+    fn exported_workbook_follows_arrow_conventions() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("model.xlsx");
+        export_input_data(&load_test_data(), &path).expect("export should succeed");
+        let sheets = read_workbook(&path).expect("reading workbook should succeed");
+        assert!(sheets.iter().all(|sheet| sheet.name != "temps"));
+        let nodes = sheets.iter().find(|sheet| sheet.name == "nodes").unwrap();
+        assert_eq!(nodes.columns[0], "node");
+        assert_eq!(nodes.rows.len(), 9);
+        let scenarios = sheets
+            .iter()
+            .find(|sheet| sheet.name == "scenarios")
+            .unwrap();
+        assert_eq!(scenarios.rows.len(), 3);
+        assert_eq!(scenarios.rows[0][1].as_number(), Some(0.4));
+        let cf = sheets.iter().find(|sheet| sheet.name == "cf").unwrap();
+        assert_eq!(cf.columns[0], "t");
+        assert_eq!(
+            cf.rows[0][0].as_text(),
+            Some("2022-04-20T00:00:00+00:00".to_string())
+        );
+        let setup = sheets.iter().find(|sheet| sheet.name == "setup").unwrap();
+        assert!(setup
+            .rows
+            .iter()
+            .any(|row| row[0].as_text() == Some("use_market_bids".to_string())));
+    }
+
+    #[test]
+    fn exported_workbook_can_be_imported_back() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("model.xlsx");
+        export_input_data(&load_test_data(), &path).expect("export should succeed");
+        let outcome = import_workbook(&path).expect("import should succeed");
+        assert_eq!(outcome.input_data.nodes.len(), 9);
+        assert_eq!(outcome.input_data.processes.len(), 8);
+        assert_eq!(outcome.input_data.scenarios.len(), 3);
+        assert!(outcome.time_line.is_some());
+    }
+}
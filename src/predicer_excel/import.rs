@@ -0,0 +1,1774 @@
+use super::{Cell, Sheet};
+use crate::input_data::State;
+use crate::input_data_base::{
+    BaseConFactor, BaseForecastable, BaseGenConstraint, BaseInflowBlock, BaseInputData,
+    BaseInputDataSetup, BaseMarket, BaseNode, BaseNodeDiffusion, BaseNodeHistory, BaseProcess,
+    BaseTopology, ConstraintFactorType, ConstraintType, Conversion, Delay, ForecastValue,
+    MarketDirection, MarketFix, MarketType, NamedGroup, NodeGroup, Point, ProcessGroup,
+    ReserveType, Risk, Series, SeriesValue, Value, ValueInput, VariableId,
+};
+use crate::scenarios::Scenario;
+use crate::time_line_settings::{CustomStartTime, Duration, TimeLineSettings, TimeLineStart};
+use crate::TimeLine;
+use chrono::TimeDelta;
+use juniper::GraphQLObject;
+use std::collections::HashMap;
+
+const KNOWN_SHEETS: [&str; 28] = [
+    "temps",
+    "setup",
+    "nodes",
+    "processes",
+    "groups",
+    "process_topology",
+    "node_history",
+    "node_delay",
+    "node_diffusion",
+    "inflow_blocks",
+    "markets",
+    "scenarios",
+    "efficiencies",
+    "reserve_type",
+    "risk",
+    "cap_ts",
+    "gen_constraint",
+    "constraints",
+    "bid_slots",
+    "cf",
+    "inflow",
+    "market_prices",
+    "reserve_realisation",
+    "reserve_activation_price",
+    "price",
+    "eff_ts",
+    "fixed_ts",
+    "balance_prices",
+];
+
+const TIME_SERIES_SHEETS: [&str; 11] = [
+    "cf",
+    "inflow",
+    "market_prices",
+    "price",
+    "eff_ts",
+    "cap_ts",
+    "gen_constraint",
+    "reserve_realisation",
+    "reserve_activation_price",
+    "balance_prices",
+    "node_diffusion",
+];
+
+const DERIVED_SETUP_PARAMETERS: [&str; 8] = [
+    "contains_online",
+    "contains_states",
+    "contains_piecewise_eff",
+    "contains_risk",
+    "contains_diffusion",
+    "contains_delay",
+    "contains_markets",
+    "contains_reserves",
+];
+
+#[derive(Clone, Debug, GraphQLObject, PartialEq)]
+#[graphql(description = "Workbook column that could not be mapped to the model.")]
+pub struct UnmappedColumn {
+    #[graphql(description = "Sheet name.")]
+    pub sheet: String,
+    #[graphql(description = "Column header.")]
+    pub column: String,
+}
+
+#[derive(Clone, Debug, Default, GraphQLObject)]
+#[graphql(description = "Summary of a Predicer workbook import.")]
+pub struct ImportReport {
+    #[graphql(description = "Sheets that have no counterpart in the model and were skipped.")]
+    pub unmapped_sheets: Vec<String>,
+    #[graphql(description = "Columns that have no counterpart in the model and were skipped.")]
+    pub unmapped_columns: Vec<UnmappedColumn>,
+    #[graphql(description = "Data that was imported but could not be represented exactly.")]
+    pub notes: Vec<String>,
+}
+
+impl ImportReport {
+    fn unmapped_column(&mut self, sheet: &Sheet, column: &str) {
+        self.unmapped_columns.push(UnmappedColumn {
+            sheet: sheet.name.clone(),
+            column: column.to_string(),
+        });
+    }
+}
+
+/// Input data built from a Predicer workbook.
+pub struct ImportOutcome {
+    pub input_data: BaseInputData,
+    /// Time line inferred from the time stamps of the workbook, if they are evenly spaced.
+    pub time_line: Option<TimeLineSettings>,
+    pub report: ImportReport,
+}
+
+/// Builds input data from the sheets of a Predicer input workbook.
+pub fn import_sheets(sheets: &[Sheet]) -> Result<ImportOutcome, String> {
+    let mut importer = Importer::new(sheets);
+    importer.import_scenarios()?;
+    importer.import_setup()?;
+    importer.import_nodes()?;
+    importer.import_processes()?;
+    importer.import_groups()?;
+    importer.import_topologies()?;
+    importer.import_efficiencies()?;
+    importer.import_node_delays()?;
+    importer.import_reserve_types()?;
+    importer.import_markets()?;
+    importer.import_risk()?;
+    importer.import_constraints()?;
+    importer.find_time_stamps();
+    importer.import_process_time_series()?;
+    importer.import_node_time_series()?;
+    importer.apply_inflow_flags()?;
+    importer.import_market_time_series()?;
+    importer.import_capacities()?;
+    importer.import_gen_constraints()?;
+    importer.import_node_diffusion()?;
+    importer.import_node_histories()?;
+    importer.import_inflow_blocks()?;
+    importer.import_fixed_market_values()?;
+    importer.check_bid_slots();
+    Ok(importer.finish())
+}
+
+struct Importer<'a> {
+    sheets: HashMap<String, &'a Sheet>,
+    input_data: BaseInputData,
+    time_stamps: Option<TimeLine>,
+    /// Node name, is_inflow flag and row of nodes that have the flag set in the nodes sheet.
+    inflow_flags: Vec<(String, bool, usize)>,
+    report: ImportReport,
+}
+
+impl<'a> Importer<'a> {
+    fn new(sheets: &'a [Sheet]) -> Self {
+        let mut report = ImportReport::default();
+        let mut sheet_map = HashMap::new();
+        for sheet in sheets {
+            let key = sheet.name.trim().to_lowercase();
+            if KNOWN_SHEETS.contains(&key.as_str()) {
+                sheet_map.insert(key, sheet);
+            } else {
+                report.unmapped_sheets.push(sheet.name.clone());
+            }
+        }
+        Importer {
+            sheets: sheet_map,
+            input_data: BaseInputData::default(),
+            time_stamps: None,
+            inflow_flags: Vec::new(),
+            report,
+        }
+    }
+
+    fn sheet(&self, name: &str) -> Option<&'a Sheet> {
+        self.sheets.get(name).copied()
+    }
+
+    /// Returns the sheet and reports columns that are not among the known ones.
+    fn table(&mut self, name: &str, known_columns: &[&str]) -> Option<&'a Sheet> {
+        let sheet = self.sheet(name)?;
+        for column in &sheet.columns {
+            if column.is_empty() {
+                continue;
+            }
+            if !known_columns
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(column))
+            {
+                self.report.unmapped_column(sheet, column);
+            }
+        }
+        Some(sheet)
+    }
+
+    fn import_scenarios(&mut self) -> Result<(), String> {
+        let Some(sheet) = self.table("scenarios", &["name", "probability"]) else {
+            return Ok(());
+        };
+        for row in data_rows(sheet, "name") {
+            let name = text(sheet, row, "name")?;
+            let weight = number_or(sheet, row, "probability", 1.0)?;
+            let scenario = Scenario::new(&name, weight)
+                .map_err(|error| format!("scenario '{}': {}", name, error))?;
+            self.input_data.scenarios.push(scenario);
+        }
+        Ok(())
+    }
+
+    fn import_setup(&mut self) -> Result<(), String> {
+        let Some(sheet) = self.table("setup", &["parameter", "value"]) else {
+            return Ok(());
+        };
+        let mut setup = BaseInputDataSetup::default();
+        for row in data_rows(sheet, "parameter") {
+            let parameter = text(sheet, row, "parameter")?;
+            match parameter.as_str() {
+                "use_reserves" => setup.use_reserves = flag_or(sheet, row, "value", false)?,
+                "use_reserve_realisation" => {
+                    setup.use_reserve_realisation = flag_or(sheet, row, "value", false)?
+                }
+                "use_market_bids" => setup.use_market_bids = flag_or(sheet, row, "value", false)?,
+                "use_node_dummy_variables" => {
+                    setup.use_node_dummy_variables = flag_or(sheet, row, "value", false)?
+                }
+                "use_ramp_dummy_variables" => {
+                    setup.use_ramp_dummy_variables = flag_or(sheet, row, "value", false)?
+                }
+                "node_dummy_variable_cost" => {
+                    setup.node_dummy_variable_cost = number_or(sheet, row, "value", 0.0)?
+                }
+                "ramp_dummy_variable_cost" => {
+                    setup.ramp_dummy_variable_cost = number_or(sheet, row, "value", 0.0)?
+                }
+                "common_timesteps" => {
+                    setup.common_timesteps = number_or(sheet, row, "value", 0.0)? as i32
+                }
+                "common_scenario_name" => {
+                    let name = text_or(sheet, row, "value", "");
+                    if name != "missing" {
+                        setup.common_scenario_name = name;
+                    }
+                }
+                parameter if DERIVED_SETUP_PARAMETERS.contains(&parameter) => (),
+                parameter => self.report.notes.push(format!(
+                    "setup parameter '{}' is not supported and was skipped",
+                    parameter
+                )),
+            }
+        }
+        self.input_data.setup = setup;
+        Ok(())
+    }
+
+    fn import_nodes(&mut self) -> Result<(), String> {
+        let Some(sheet) = self.table(
+            "nodes",
+            &[
+                "node",
+                "is_commodity",
+                "is_market",
+                "is_state",
+                "is_res",
+                "is_inflow",
+                "state_max",
+                "state_min",
+                "in_max",
+                "out_max",
+                "initial_state",
+                "state_loss_proportional",
+                "scenario_independent_state",
+                "is_temp",
+                "t_e_conversion",
+                "residual_value",
+            ],
+        ) else {
+            return Ok(());
+        };
+        for row in data_rows(sheet, "node") {
+            let state = if flag_or(sheet, row, "is_state", false)? {
+                Some(State {
+                    in_max: number_or(sheet, row, "in_max", 0.0)?,
+                    out_max: number_or(sheet, row, "out_max", 0.0)?,
+                    state_loss_proportional: number_or(sheet, row, "state_loss_proportional", 0.0)?,
+                    state_max: number_or(sheet, row, "state_max", 0.0)?,
+                    state_min: number_or(sheet, row, "state_min", 0.0)?,
+                    initial_state: number_or(sheet, row, "initial_state", 0.0)?,
+                    is_scenario_independent: flag_or(
+                        sheet,
+                        row,
+                        "scenario_independent_state",
+                        false,
+                    )?,
+                    is_temp: flag_or(sheet, row, "is_temp", false)?,
+                    t_e_conversion: number_or(sheet, row, "t_e_conversion", 1.0)?,
+                    residual_value: number_or(sheet, row, "residual_value", 0.0)?,
+                    unit: None,
+                })
+            } else {
+                None
+            };
+            let name = text(sheet, row, "node")?;
+            if sheet.column_index("is_inflow").is_some() {
+                let is_inflow = flag_or(sheet, row, "is_inflow", false)?;
+                self.inflow_flags.push((name.clone(), is_inflow, row));
+            }
+            self.input_data.nodes.push(BaseNode {
+                name,
+                groups: Vec::new(),
+                is_commodity: flag_or(sheet, row, "is_commodity", false)?,
+                is_market: flag_or(sheet, row, "is_market", false)?,
+                is_res: flag_or(sheet, row, "is_res", false)?,
+                state,
+                cost: Vec::new(),
+                inflow: Vec::new(),
+            });
+        }
+        Ok(())
+    }
+
+    fn import_processes(&mut self) -> Result<(), String> {
+        let Some(sheet) = self.table(
+            "processes",
+            &[
+                "process",
+                "is_cf",
+                "is_cf_fix",
+                "is_online",
+                "is_res",
+                "conversion",
+                "eff",
+                "load_min",
+                "load_max",
+                "start_cost",
+                "min_online",
+                "min_offline",
+                "max_online",
+                "max_offline",
+                "initial_state",
+                "scenario_independent_online",
+                "delay",
+            ],
+        ) else {
+            return Ok(());
+        };
+        for row in data_rows(sheet, "process") {
+            let name = text(sheet, row, "process")?;
+            let conversion = match number_or(sheet, row, "conversion", 1.0)? as i64 {
+                1 => Conversion::Unit,
+                2 => Conversion::Transfer,
+                3 => Conversion::Market,
+                other => {
+                    return Err(format!(
+                        "process '{}': unknown conversion type {}",
+                        name, other
+                    ))
+                }
+            };
+            self.input_data.processes.push(BaseProcess {
+                name,
+                groups: Vec::new(),
+                conversion,
+                is_cf: flag_or(sheet, row, "is_cf", false)?,
+                is_cf_fix: flag_or(sheet, row, "is_cf_fix", false)?,
+                is_online: flag_or(sheet, row, "is_online", false)?,
+                is_res: flag_or(sheet, row, "is_res", false)?,
+                eff: number_or(sheet, row, "eff", 1.0)?,
+                load_min: number_or(sheet, row, "load_min", 0.0)?,
+                load_max: number_or(sheet, row, "load_max", 1.0)?,
+                start_cost: number_or(sheet, row, "start_cost", 0.0)?,
+                min_online: number_or(sheet, row, "min_online", 0.0)?,
+                min_offline: number_or(sheet, row, "min_offline", 0.0)?,
+                max_online: number_or(sheet, row, "max_online", 0.0)?,
+                max_offline: number_or(sheet, row, "max_offline", 0.0)?,
+                initial_state: flag_or(sheet, row, "initial_state", false)?,
+                is_scenario_independent: flag_or(sheet, row, "scenario_independent_online", false)?,
+                topos: Vec::new(),
+                cf: Vec::new(),
+                eff_ts: Vec::new(),
+                eff_ops_fun: Vec::new(),
+            });
+        }
+        Ok(())
+    }
+
+    fn import_groups(&mut self) -> Result<(), String> {
+        let Some(sheet) = self.table("groups", &["group_type", "type", "entity", "group"]) else {
+            return Ok(());
+        };
+        let type_column = if sheet.column_index("group_type").is_some() {
+            "group_type"
+        } else {
+            "type"
+        };
+        let input_data = &mut self.input_data;
+        for row in data_rows(sheet, "group") {
+            let group_type = text(sheet, row, type_column)?;
+            let entity = text(sheet, row, "entity")?;
+            let group_name = text(sheet, row, "group")?;
+            match group_type.as_str() {
+                "node" => {
+                    let node = input_data
+                        .nodes
+                        .iter_mut()
+                        .find(|node| node.name == entity)
+                        .ok_or_else(|| {
+                            format!("group '{}': no such node '{}'", group_name, entity)
+                        })?;
+                    node.groups.push(group_name.clone());
+                    let group = match input_data
+                        .node_groups
+                        .iter_mut()
+                        .position(|group| group.name == group_name)
+                    {
+                        Some(index) => &mut input_data.node_groups[index],
+                        None => {
+                            input_data.node_groups.push(NodeGroup::new(group_name));
+                            input_data.node_groups.last_mut().unwrap()
+                        }
+                    };
+                    group.members.push(entity);
+                }
+                "process" => {
+                    let process = input_data
+                        .processes
+                        .iter_mut()
+                        .find(|process| process.name == entity)
+                        .ok_or_else(|| {
+                            format!("group '{}': no such process '{}'", group_name, entity)
+                        })?;
+                    process.groups.push(group_name.clone());
+                    let group = match input_data
+                        .process_groups
+                        .iter_mut()
+                        .position(|group| group.name == group_name)
+                    {
+                        Some(index) => &mut input_data.process_groups[index],
+                        None => {
+                            input_data
+                                .process_groups
+                                .push(ProcessGroup::new(group_name));
+                            input_data.process_groups.last_mut().unwrap()
+                        }
+                    };
+                    group.members.push(entity);
+                }
+                other => {
+                    return Err(format!(
+                        "group '{}': unknown group type '{}'",
+                        group_name, other
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn import_topologies(&mut self) -> Result<(), String> {
+        let Some(sheet) = self.table(
+            "process_topology",
+            &[
+                "process",
+                "source_sink",
+                "node",
+                "conversion_coeff",
+                "capacity",
+                "vom_cost",
+                "ramp_up",
+                "ramp_down",
+                "initial_load",
+                "initial_flow",
+            ],
+        ) else {
+            return Ok(());
+        };
+        for row in data_rows(sheet, "process") {
+            let process_name = text(sheet, row, "process")?;
+            let node_name = text(sheet, row, "node")?;
+            if !self
+                .input_data
+                .nodes
+                .iter()
+                .any(|node| node.name == node_name)
+            {
+                return Err(row_error(
+                    sheet,
+                    row,
+                    &format!("no such node '{}'", node_name),
+                ));
+            }
+            let process = self
+                .input_data
+                .processes
+                .iter_mut()
+                .find(|process| process.name == process_name)
+                .ok_or_else(|| format!("process_topology: no such process '{}'", process_name))?;
+            let mut topology = match text(sheet, row, "source_sink")?.as_str() {
+                "source" => BaseTopology::new(node_name.clone(), process_name.clone()),
+                "sink" => BaseTopology::new(process_name.clone(), node_name.clone()),
+                other => {
+                    return Err(format!(
+                        "process_topology: unknown source_sink value '{}' for process '{}'",
+                        other, process_name
+                    ))
+                }
+            };
+            topology.capacity = number_or(sheet, row, "capacity", 0.0)?;
+            topology.vom_cost = number_or(sheet, row, "vom_cost", 0.0)?;
+            topology.ramp_up = number_or(sheet, row, "ramp_up", 0.0)?;
+            topology.ramp_down = number_or(sheet, row, "ramp_down", 0.0)?;
+            topology.initial_load = number_or(sheet, row, "initial_load", 0.0)?;
+            topology.initial_flow = number_or(sheet, row, "initial_flow", 0.0)?;
+            process.topos.push(topology);
+            let conversion_coeff = number_or(sheet, row, "conversion_coeff", 1.0)?;
+            if conversion_coeff != 1.0 {
+                self.report.notes.push(format!(
+                    "conversion coefficient {} of topology {}-{} is not supported; 1.0 will be used",
+                    conversion_coeff, process_name, node_name
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn import_efficiencies(&mut self) -> Result<(), String> {
+        let Some(sheet) = self.sheet("efficiencies") else {
+            return Ok(());
+        };
+        let Some(process_column) = sheet.column_index("process") else {
+            return Err("efficiencies: missing column 'process'".to_string());
+        };
+        let mut point_columns = Vec::new();
+        for (index, column) in sheet.columns.iter().enumerate() {
+            if index == process_column || column.is_empty() {
+                continue;
+            }
+            if column.parse::<f64>().is_ok() {
+                point_columns.push(index);
+            } else {
+                self.report.unmapped_column(sheet, column);
+            }
+        }
+        let mut operating_points: HashMap<String, Vec<Option<f64>>> = HashMap::new();
+        let mut efficiencies: HashMap<String, Vec<Option<f64>>> = HashMap::new();
+        for row in 0..sheet.rows.len() {
+            let Some(label) = sheet.cell(row, process_column).as_text() else {
+                continue;
+            };
+            let values = point_columns
+                .iter()
+                .map(|&column| sheet.cell(row, column).as_number())
+                .collect();
+            match label.rsplit_once(',') {
+                Some((process, "op")) => operating_points.insert(process.to_string(), values),
+                Some((process, "eff")) => efficiencies.insert(process.to_string(), values),
+                _ => {
+                    return Err(format!(
+                        "efficiencies: row label '{}' should end with ',op' or ',eff'",
+                        label
+                    ))
+                }
+            };
+        }
+        for (process_name, xs) in operating_points {
+            let ys = efficiencies.remove(&process_name).ok_or_else(|| {
+                format!(
+                    "efficiencies: missing 'eff' row for process '{}'",
+                    process_name
+                )
+            })?;
+            let process = self
+                .input_data
+                .processes
+                .iter_mut()
+                .find(|process| process.name == process_name)
+                .ok_or_else(|| format!("efficiencies: no such process '{}'", process_name))?;
+            process.eff_ops_fun = xs
+                .into_iter()
+                .zip(ys)
+                .filter_map(|(x, y)| Some(Point { x: x?, y: y? }))
+                .collect();
+        }
+        if let Some(process_name) = efficiencies.keys().next() {
+            return Err(format!(
+                "efficiencies: missing 'op' row for process '{}'",
+                process_name
+            ));
+        }
+        Ok(())
+    }
+
+    fn import_node_delays(&mut self) -> Result<(), String> {
+        let Some(sheet) = self.table(
+            "node_delay",
+            &["node1", "node2", "delay_t", "min_flow", "max_flow"],
+        ) else {
+            return Ok(());
+        };
+        for row in data_rows(sheet, "node1") {
+            self.input_data.node_delay.push(Delay {
+                from_node: text(sheet, row, "node1")?,
+                to_node: text(sheet, row, "node2")?,
+                delay: number_or(sheet, row, "delay_t", 0.0)?,
+                min_delay_flow: number_or(sheet, row, "min_flow", 0.0)?,
+                max_delay_flow: number_or(sheet, row, "max_flow", 0.0)?,
+            });
+        }
+        Ok(())
+    }
+
+    fn import_reserve_types(&mut self) -> Result<(), String> {
+        let Some(sheet) = self.table("reserve_type", &["reserve_type", "ramp_factor"]) else {
+            return Ok(());
+        };
+        for row in data_rows(sheet, "reserve_type") {
+            self.input_data.reserve_type.push(ReserveType {
+                name: text(sheet, row, "reserve_type")?,
+                ramp_rate: number_or(sheet, row, "ramp_factor", 0.0)?,
+            });
+        }
+        Ok(())
+    }
+
+    fn import_markets(&mut self) -> Result<(), String> {
+        let Some(sheet) = self.table(
+            "markets",
+            &[
+                "market",
+                "market_type",
+                "node",
+                "processgroup",
+                "direction",
+                "reserve_type",
+                "is_bid",
+                "is_limited",
+                "min_bid",
+                "max_bid",
+                "fee",
+            ],
+        ) else {
+            return Ok(());
+        };
+        for row in data_rows(sheet, "market") {
+            let name = text(sheet, row, "market")?;
+            let m_type = match text(sheet, row, "market_type")?.as_str() {
+                "energy" => MarketType::Energy,
+                "reserve" => MarketType::Reserve,
+                other => {
+                    return Err(format!(
+                        "market '{}': unknown market type '{}'",
+                        name, other
+                    ))
+                }
+            };
+            let direction = match text_or(sheet, row, "direction", "none").as_str() {
+                "up" => Some(MarketDirection::Up),
+                "down" | "dw" => Some(MarketDirection::Down),
+                "up_down" | "updown" => Some(MarketDirection::UpDown),
+                "res_up" => Some(MarketDirection::ResUp),
+                "res_down" | "res_dw" => Some(MarketDirection::ResDown),
+                "none" => None,
+                other => return Err(format!("market '{}': unknown direction '{}'", name, other)),
+            };
+            let reserve_type = match text_or(sheet, row, "reserve_type", "none") {
+                reserve_type if reserve_type == "none" => None,
+                reserve_type => Some(reserve_type),
+            };
+            let node = text(sheet, row, "node")?;
+            let node_exists = match m_type {
+                MarketType::Energy => self.input_data.nodes.iter().any(|n| n.name == node),
+                MarketType::Reserve => self.input_data.node_groups.iter().any(|g| g.name == node),
+            };
+            if !node_exists {
+                let message = match m_type {
+                    MarketType::Energy => format!("no such node '{}'", node),
+                    MarketType::Reserve => format!("no such node group '{}'", node),
+                };
+                return Err(row_error(sheet, row, &message));
+            }
+            self.input_data.markets.push(BaseMarket {
+                name,
+                m_type,
+                node,
+                process_group: text_or(sheet, row, "processgroup", ""),
+                direction,
+                realisation: Vec::new(),
+                reserve_type,
+                is_bid: flag_or(sheet, row, "is_bid", false)?,
+                is_limited: flag_or(sheet, row, "is_limited", false)?,
+                min_bid: number_or(sheet, row, "min_bid", 0.0)?,
+                max_bid: number_or(sheet, row, "max_bid", 0.0)?,
+                fee: number_or(sheet, row, "fee", 0.0)?,
+                price: Vec::new(),
+                up_price: Vec::new(),
+                down_price: Vec::new(),
+                reserve_activation_price: Vec::new(),
+                fixed: Vec::new(),
+                price_unit: None,
+                price_scenarios: None,
+            });
+        }
+        Ok(())
+    }
+
+    fn import_risk(&mut self) -> Result<(), String> {
+        let Some(sheet) = self.table("risk", &["parameter", "value"]) else {
+            return Ok(());
+        };
+        for row in data_rows(sheet, "parameter") {
+            self.input_data.risk.push(Risk {
+                parameter: text(sheet, row, "parameter")?,
+                value: number_or(sheet, row, "value", 0.0)?,
+            });
+        }
+        Ok(())
+    }
+
+    fn import_constraints(&mut self) -> Result<(), String> {
+        let Some(sheet) = self.table(
+            "constraints",
+            &["name", "operator", "is_setpoint", "penalty"],
+        ) else {
+            return Ok(());
+        };
+        for row in data_rows(sheet, "name") {
+            let name = text(sheet, row, "name")?;
+            let gc_type = match text(sheet, row, "operator")?.as_str() {
+                "st" | "lt" => ConstraintType::LessThan,
+                "eq" => ConstraintType::Equal,
+                "gt" => ConstraintType::GreaterThan,
+                other => {
+                    return Err(format!(
+                        "constraint '{}': unknown operator '{}'",
+                        name, other
+                    ))
+                }
+            };
+            self.input_data.gen_constraints.push(BaseGenConstraint {
+                name,
+                gc_type,
+                is_setpoint: flag_or(sheet, row, "is_setpoint", false)?,
+                penalty: number_or(sheet, row, "penalty", 0.0)?,
+                factors: Vec::new(),
+                constant: Vec::new(),
+            });
+        }
+        Ok(())
+    }
+
+    fn find_time_stamps(&mut self) {
+        let candidates = std::iter::once("temps").chain(TIME_SERIES_SHEETS);
+        for name in candidates {
+            let Some(sheet) = self.sheet(name) else {
+                continue;
+            };
+            let Some(t_column) = sheet.column_index("t") else {
+                continue;
+            };
+            let stamps: Option<TimeLine> = (0..sheet.rows.len())
+                .filter(|&row| !sheet.cell(row, t_column).is_empty())
+                .map(|row| sheet.cell(row, t_column).as_time_stamp())
+                .collect();
+            if let Some(stamps) = stamps {
+                if !stamps.is_empty() {
+                    self.time_stamps = Some(stamps);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Calls `apply` for each time series column of given sheet.
+    ///
+    /// Column headers are comma-separated identifiers followed by a scenario name.
+    /// `apply` receives the identifiers and a value for the column,
+    /// and returns false if the identifiers do not match anything in the model.
+    fn import_series_columns<F>(&mut self, sheet_name: &str, mut apply: F) -> Result<(), String>
+    where
+        F: FnMut(&mut BaseInputData, &[&str], Value) -> bool,
+    {
+        let Some(sheet) = self.sheet(sheet_name) else {
+            return Ok(());
+        };
+        let t_column = sheet.column_index("t");
+        for (index, column) in sheet.columns.iter().enumerate() {
+            if Some(index) == t_column || column.is_empty() {
+                continue;
+            }
+            let parts: Vec<&str> = column.split(',').map(|part| part.trim()).collect();
+            let Some((scenario, identifiers)) = parts.split_last() else {
+                continue;
+            };
+            if identifiers.is_empty()
+                || !self
+                    .input_data
+                    .scenarios
+                    .iter()
+                    .any(|s| s.name() == *scenario)
+            {
+                self.report.unmapped_column(sheet, column);
+                continue;
+            }
+            let Some(values) = self.column_values(sheet, index)? else {
+                continue;
+            };
+            let value = series_value(scenario, values);
+            if !apply(&mut self.input_data, identifiers, value) {
+                self.report.unmapped_column(sheet, column);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the numeric values of a column or None if the column is empty.
+    fn column_values(&self, sheet: &Sheet, column: usize) -> Result<Option<Vec<f64>>, String> {
+        let cells: Vec<&Cell> = (0..sheet.rows.len())
+            .map(|row| sheet.cell(row, column))
+            .collect();
+        if cells.iter().all(|cell| cell.is_empty()) {
+            return Ok(None);
+        }
+        let mut values = Vec::with_capacity(cells.len());
+        for (row, cell) in cells.iter().enumerate() {
+            let value = cell.as_number().ok_or_else(|| {
+                format!(
+                    "sheet '{}', row {}, column '{}': expected a number",
+                    sheet.name,
+                    row + 2,
+                    sheet.columns[column]
+                )
+            })?;
+            values.push(value);
+        }
+        if let Some(ref stamps) = self.time_stamps {
+            if values.len() != stamps.len() {
+                return Err(format!(
+                    "sheet '{}', column '{}': expected {} values, found {}",
+                    sheet.name,
+                    sheet.columns[column],
+                    stamps.len(),
+                    values.len()
+                ));
+            }
+        }
+        Ok(Some(values))
+    }
+
+    fn import_process_time_series(&mut self) -> Result<(), String> {
+        self.import_series_columns("cf", |input_data, identifiers, value| {
+            match find_process(input_data, identifiers) {
+                Some(process) => {
+                    process.cf.push(value);
+                    true
+                }
+                None => false,
+            }
+        })?;
+        self.import_series_columns(
+            "eff_ts",
+            |input_data, identifiers, value| match find_process(input_data, identifiers) {
+                Some(process) => {
+                    process.eff_ts.push(value);
+                    true
+                }
+                None => false,
+            },
+        )
+    }
+
+    fn import_node_time_series(&mut self) -> Result<(), String> {
+        self.import_series_columns("inflow", |input_data, identifiers, value| {
+            match find_node(input_data, identifiers) {
+                Some(node) => {
+                    node.inflow.push(to_forecast_value(value));
+                    true
+                }
+                None => false,
+            }
+        })?;
+        self.import_series_columns("price", |input_data, identifiers, value| {
+            match find_node(input_data, identifiers) {
+                Some(node) => {
+                    node.cost.push(value);
+                    true
+                }
+                None => false,
+            }
+        })
+    }
+
+    /// Drops inflow of nodes whose is_inflow is false as Predicer does
+    /// and rejects nodes whose is_inflow is true but that have no inflow.
+    fn apply_inflow_flags(&mut self) -> Result<(), String> {
+        let Some(sheet) = self.sheet("nodes") else {
+            return Ok(());
+        };
+        for (name, is_inflow, row) in &self.inflow_flags {
+            let Some(node) = self.input_data.nodes.iter_mut().find(|n| n.name == *name) else {
+                continue;
+            };
+            if *is_inflow && node.inflow.is_empty() {
+                return Err(row_error(
+                    sheet,
+                    *row,
+                    &format!("node '{}' has is_inflow set but no inflow data", name),
+                ));
+            }
+            if !is_inflow && !node.inflow.is_empty() {
+                node.inflow.clear();
+                self.report.notes.push(format!(
+                    "inflow of node '{}' was skipped because its is_inflow is false",
+                    name
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn import_market_time_series(&mut self) -> Result<(), String> {
+        self.import_series_columns("market_prices", |input_data, identifiers, value| {
+            match find_market(input_data, identifiers) {
+                Some(market) => {
+                    market.price.push(to_forecast_value(value));
+                    true
+                }
+                None => false,
+            }
+        })?;
+        self.import_series_columns("reserve_realisation", |input_data, identifiers, value| {
+            match find_market(input_data, identifiers) {
+                Some(market) => {
+                    market.realisation.push(value);
+                    true
+                }
+                None => false,
+            }
+        })?;
+        self.import_series_columns(
+            "reserve_activation_price",
+            |input_data, identifiers, value| match find_market(input_data, identifiers) {
+                Some(market) => {
+                    market.reserve_activation_price.push(value);
+                    true
+                }
+                None => false,
+            },
+        )?;
+        self.import_series_columns("balance_prices", |input_data, identifiers, value| {
+            let [market_name, direction] = identifiers else {
+                return false;
+            };
+            let Some(market) = find_market(input_data, &[*market_name]) else {
+                return false;
+            };
+            match *direction {
+                "up" => market.up_price.push(to_forecast_value(value)),
+                "dw" | "down" => market.down_price.push(to_forecast_value(value)),
+                _ => return false,
+            }
+            true
+        })
+    }
+
+    fn import_capacities(&mut self) -> Result<(), String> {
+        self.import_series_columns("cap_ts", |input_data, identifiers, value| {
+            let [process_name, node_name] = identifiers else {
+                return false;
+            };
+            let Some(process) = find_process(input_data, &[*process_name]) else {
+                return false;
+            };
+            match process
+                .topos
+                .iter_mut()
+                .find(|topology| topology.source == *node_name || topology.sink == *node_name)
+            {
+                Some(topology) => {
+                    topology.cap_ts.push(value);
+                    true
+                }
+                None => false,
+            }
+        })
+    }
+
+    fn import_gen_constraints(&mut self) -> Result<(), String> {
+        self.import_series_columns("gen_constraint", |input_data, identifiers, value| {
+            let Some((constraint_name, variable)) = identifiers.split_first() else {
+                return false;
+            };
+            let factor = match variable {
+                [] => None,
+                [entity] => {
+                    let var_type = if input_data.nodes.iter().any(|n| n.name == *entity) {
+                        ConstraintFactorType::State
+                    } else if input_data.processes.iter().any(|p| p.name == *entity) {
+                        ConstraintFactorType::Online
+                    } else {
+                        return false;
+                    };
+                    Some((var_type, entity.to_string(), None))
+                }
+                [process_name, node_name] => {
+                    if !input_data.processes.iter().any(|p| p.name == *process_name)
+                        || !input_data.nodes.iter().any(|n| n.name == *node_name)
+                    {
+                        return false;
+                    }
+                    Some((
+                        ConstraintFactorType::Flow,
+                        process_name.to_string(),
+                        Some(node_name.to_string()),
+                    ))
+                }
+                _ => return false,
+            };
+            let Some(constraint) = input_data
+                .gen_constraints
+                .iter_mut()
+                .find(|constraint| constraint.name == *constraint_name)
+            else {
+                return false;
+            };
+            match factor {
+                None => constraint.constant.push(value),
+                Some((var_type, entity, identifier)) => {
+                    match constraint.factors.iter_mut().find(|factor| {
+                        factor.var_type == var_type
+                            && factor.var_tuple.entity == entity
+                            && factor.var_tuple.identifier == identifier
+                    }) {
+                        Some(factor) => factor.data.push(value),
+                        None => constraint.factors.push(BaseConFactor {
+                            var_type,
+                            var_tuple: VariableId { entity, identifier },
+                            data: vec![value],
+                        }),
+                    }
+                }
+            }
+            true
+        })
+    }
+
+    fn import_node_diffusion(&mut self) -> Result<(), String> {
+        let Some(sheet) = self.sheet("node_diffusion") else {
+            return Ok(());
+        };
+        if sheet.column_index("diff_coeff").is_some() {
+            let sheet = self
+                .table("node_diffusion", &["node1", "node2", "diff_coeff"])
+                .expect("sheet should exist");
+            for row in data_rows(sheet, "node1") {
+                let coefficient = number_or(sheet, row, "diff_coeff", 0.0)?;
+                self.input_data.node_diffusion.push(BaseNodeDiffusion {
+                    from_node: text(sheet, row, "node1")?,
+                    to_node: text(sheet, row, "node2")?,
+                    coefficient: vec![constant_value(coefficient)],
+                });
+            }
+            return Ok(());
+        }
+        self.import_series_columns("node_diffusion", |input_data, identifiers, value| {
+            let [from_node, to_node] = identifiers else {
+                return false;
+            };
+            if find_node(input_data, &[*from_node]).is_none()
+                || find_node(input_data, &[*to_node]).is_none()
+            {
+                return false;
+            }
+            match input_data.node_diffusion.iter_mut().find(|diffusion| {
+                diffusion.from_node == *from_node && diffusion.to_node == *to_node
+            }) {
+                Some(diffusion) => diffusion.coefficient.push(value),
+                None => input_data.node_diffusion.push(BaseNodeDiffusion {
+                    from_node: from_node.to_string(),
+                    to_node: to_node.to_string(),
+                    coefficient: vec![value],
+                }),
+            }
+            true
+        })
+    }
+
+    fn import_node_histories(&mut self) -> Result<(), String> {
+        let Some(sheet) = self.sheet("node_history") else {
+            return Ok(());
+        };
+        for (index, column) in sheet.columns.iter().enumerate() {
+            let parts: Vec<&str> = column.split(',').map(|part| part.trim()).collect();
+            let [node_name, "t", scenario] = parts.as_slice() else {
+                if parts.len() != 2 && !column.eq_ignore_ascii_case("t") {
+                    self.report.unmapped_column(sheet, column);
+                }
+                continue;
+            };
+            let value_header = format!("{},{}", node_name, scenario);
+            let Some(value_column) = sheet.column_index(&value_header) else {
+                return Err(format!(
+                    "node_history: missing value column '{}'",
+                    value_header
+                ));
+            };
+            if !self.input_data.nodes.iter().any(|n| n.name == *node_name) {
+                self.report.unmapped_column(sheet, column);
+                self.report.unmapped_column(sheet, &value_header);
+                continue;
+            }
+            let mut stamps = Vec::new();
+            let mut values = Vec::new();
+            for row in 0..sheet.rows.len() {
+                let stamp_cell = sheet.cell(row, index);
+                if stamp_cell.is_empty() {
+                    continue;
+                }
+                let stamp = stamp_cell.as_time_stamp().ok_or_else(|| {
+                    format!(
+                        "sheet 'node_history', row {}, column '{}': expected a time stamp",
+                        row + 2,
+                        column
+                    )
+                })?;
+                let value = sheet.cell(row, value_column).as_number().ok_or_else(|| {
+                    format!(
+                        "sheet 'node_history', row {}, column '{}': expected a number",
+                        row + 2,
+                        value_header
+                    )
+                })?;
+                stamps.push(stamp);
+                values.push(value);
+            }
+            let series = Series {
+                scenario: scenario.to_string(),
+                durations: durations_between(&stamps)?,
+                values,
+            };
+            match self
+                .input_data
+                .node_histories
+                .iter_mut()
+                .find(|history| history.node == *node_name)
+            {
+                Some(history) => history.steps.push(series),
+                None => {
+                    let mut history = BaseNodeHistory::new(node_name.to_string());
+                    history.steps.push(series);
+                    self.input_data.node_histories.push(history);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn import_inflow_blocks(&mut self) -> Result<(), String> {
+        let Some(sheet) = self.sheet("inflow_blocks") else {
+            return Ok(());
+        };
+        let t_column = sheet.column_index("t");
+        for (index, column) in sheet.columns.iter().enumerate() {
+            if Some(index) == t_column || column.is_empty() {
+                continue;
+            }
+            let Some((block_name, second)) = column.split_once(',') else {
+                self.report.unmapped_column(sheet, column);
+                continue;
+            };
+            if let Some(block) = self
+                .input_data
+                .inflow_blocks
+                .iter_mut()
+                .find(|block| block.name == block_name)
+            {
+                if !self.input_data.scenarios.iter().any(|s| s.name() == second) {
+                    self.report.unmapped_column(sheet, column);
+                    continue;
+                }
+                let values = (0..sheet.rows.len())
+                    .map(|row| sheet.cell(row, index))
+                    .filter(|cell| !cell.is_empty())
+                    .map(|cell| {
+                        cell.as_number().ok_or_else(|| {
+                            format!(
+                                "sheet 'inflow_blocks', column '{}': expected numbers",
+                                column
+                            )
+                        })
+                    })
+                    .collect::<Result<Vec<f64>, String>>()?;
+                block.data.push(series_value(second, values));
+            } else if self.input_data.nodes.iter().any(|n| n.name == second) {
+                self.input_data.inflow_blocks.push(BaseInflowBlock {
+                    name: block_name.to_string(),
+                    node: second.to_string(),
+                    data: Vec::new(),
+                });
+            } else {
+                self.report.unmapped_column(sheet, column);
+            }
+        }
+        Ok(())
+    }
+
+    fn import_fixed_market_values(&mut self) -> Result<(), String> {
+        let Some(sheet) = self.sheet("fixed_ts") else {
+            return Ok(());
+        };
+        let Some(t_column) = sheet.column_index("t") else {
+            if !sheet.is_empty() {
+                self.report.unmapped_sheets.push(sheet.name.clone());
+            }
+            return Ok(());
+        };
+        for (index, column) in sheet.columns.iter().enumerate() {
+            if index == t_column || column.is_empty() {
+                continue;
+            }
+            let Some(market) = self
+                .input_data
+                .markets
+                .iter_mut()
+                .find(|market| market.name == *column)
+            else {
+                self.report.unmapped_column(sheet, column);
+                continue;
+            };
+            for row in 0..sheet.rows.len() {
+                let (Some(name), Some(factor)) = (
+                    sheet.cell(row, t_column).as_text(),
+                    sheet.cell(row, index).as_number(),
+                ) else {
+                    continue;
+                };
+                market.fixed.push(MarketFix { name, factor });
+            }
+        }
+        Ok(())
+    }
+
+    fn check_bid_slots(&mut self) {
+        let Some(sheet) = self.sheet("bid_slots") else {
+            return;
+        };
+        if sheet
+            .columns
+            .iter()
+            .any(|column| !column.is_empty() && !column.eq_ignore_ascii_case("t"))
+        {
+            self.report.unmapped_sheets.push(sheet.name.clone());
+        }
+    }
+
+    fn finish(mut self) -> ImportOutcome {
+        let time_line = match self.time_stamps {
+            Some(ref stamps) => match time_line_from_stamps(stamps) {
+                Ok(time_line) => Some(time_line),
+                Err(error) => {
+                    self.report
+                        .notes
+                        .push(format!("time line could not be imported: {}", error));
+                    None
+                }
+            },
+            None => None,
+        };
+        ImportOutcome {
+            input_data: self.input_data,
+            time_line,
+            report: self.report,
+        }
+    }
+}
+
+/// Returns indices of rows that have a value in given key column.
+fn data_rows(sheet: &Sheet, key_column: &str) -> Vec<usize> {
+    match sheet.column_index(key_column) {
+        Some(column) => (0..sheet.rows.len())
+            .filter(|&row| !sheet.cell(row, column).is_empty())
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+fn text(sheet: &Sheet, row: usize, column: &str) -> Result<String, String> {
+    sheet
+        .column_index(column)
+        .and_then(|index| sheet.cell(row, index).as_text())
+        .ok_or_else(|| {
+            format!(
+                "sheet '{}', row {}, column '{}': value is missing",
+                sheet.name,
+                row + 2,
+                column
+            )
+        })
+}
+
+fn row_error(sheet: &Sheet, row: usize, message: &str) -> String {
+    format!("sheet '{}', row {}: {}", sheet.name, row + 2, message)
+}
+
+fn text_or(sheet: &Sheet, row: usize, column: &str, default: &str) -> String {
+    sheet
+        .column_index(column)
+        .and_then(|index| sheet.cell(row, index).as_text())
+        .unwrap_or_else(|| default.to_string())
+}
+
+fn number_or(sheet: &Sheet, row: usize, column: &str, default: f64) -> Result<f64, String> {
+    let Some(index) = sheet.column_index(column) else {
+        return Ok(default);
+    };
+    let cell = sheet.cell(row, index);
+    if cell.is_empty() {
+        return Ok(default);
+    }
+    cell.as_number().ok_or_else(|| {
+        format!(
+            "sheet '{}', row {}, column '{}': expected a number",
+            sheet.name,
+            row + 2,
+            column
+        )
+    })
+}
+
+fn flag_or(sheet: &Sheet, row: usize, column: &str, default: bool) -> Result<bool, String> {
+    let Some(index) = sheet.column_index(column) else {
+        return Ok(default);
+    };
+    let cell = sheet.cell(row, index);
+    if cell.is_empty() {
+        return Ok(default);
+    }
+    cell.as_bool().ok_or_else(|| {
+        format!(
+            "sheet '{}', row {}, column '{}': expected a boolean",
+            sheet.name,
+            row + 2,
+            column
+        )
+    })
+}
+
+fn find_process<'d>(
+    input_data: &'d mut BaseInputData,
+    identifiers: &[&str],
+) -> Option<&'d mut BaseProcess> {
+    let [name] = identifiers else {
+        return None;
+    };
+    input_data.processes.iter_mut().find(|p| p.name == *name)
+}
+
+fn find_node<'d>(
+    input_data: &'d mut BaseInputData,
+    identifiers: &[&str],
+) -> Option<&'d mut BaseNode> {
+    let [name] = identifiers else {
+        return None;
+    };
+    input_data.nodes.iter_mut().find(|n| n.name == *name)
+}
+
+fn find_market<'d>(
+    input_data: &'d mut BaseInputData,
+    identifiers: &[&str],
+) -> Option<&'d mut BaseMarket> {
+    let [name] = identifiers else {
+        return None;
+    };
+    input_data.markets.iter_mut().find(|m| m.name == *name)
+}
+
+fn series_value(scenario: &str, values: Vec<f64>) -> Value {
+    Value::try_from(ValueInput {
+        scenario: Some(scenario.to_string()),
+        constant: None,
+        series: Some(values),
+        time_stamps: None,
+        resampling: None,
+    })
+    .expect("value input with series only should always convert")
+}
+
+fn constant_value(constant: f64) -> Value {
+    Value::try_from(ValueInput {
+        scenario: None,
+        constant: Some(constant),
+        series: None,
+        time_stamps: None,
+        resampling: None,
+    })
+    .expect("value input with constant only should always convert")
+}
+
+fn to_forecast_value(value: Value) -> ForecastValue {
+    let forecastable = match value.value {
+        SeriesValue::Constant(constant) => BaseForecastable::Constant(constant),
+        SeriesValue::FloatList(list) => BaseForecastable::FloatList(list),
+    };
+    ForecastValue {
+        scenario: value.scenario,
+        value: forecastable,
+    }
+}
+
+fn to_duration(delta: TimeDelta) -> Result<Duration, String> {
+    let seconds = delta.num_seconds();
+    if seconds <= 0 {
+        return Err("time stamps should be in increasing order".to_string());
+    }
+    Duration::try_new(
+        (seconds / 3600) as i32,
+        ((seconds % 3600) / 60) as i32,
+        (seconds % 60) as i32,
+    )
+}
+
+fn durations_between(stamps: &TimeLine) -> Result<Vec<Duration>, String> {
+    let mut durations = Vec::with_capacity(stamps.len());
+    for pair in stamps.windows(2) {
+        durations.push(to_duration(pair[1] - pair[0])?);
+    }
+    if let Some(last) = durations.last() {
+        durations.push(last.clone());
+    } else if !stamps.is_empty() {
+        durations.push(to_duration(TimeDelta::hours(1))?);
+    }
+    Ok(durations)
+}
+
+fn time_line_from_stamps(stamps: &TimeLine) -> Result<TimeLineSettings, String> {
+    if stamps.len() < 2 {
+        return Err("at least two time stamps are needed".to_string());
+    }
+    let step = stamps[1] - stamps[0];
+    if stamps.windows(2).any(|pair| pair[1] - pair[0] != step) {
+        return Err("time stamps are not evenly spaced".to_string());
+    }
+    let duration = step * (stamps.len() as i32 - 1);
+    TimeLineSettings::try_new(
+        to_duration(duration)?,
+        to_duration(step)?,
+        TimeLineStart::CustomStartTime(CustomStartTime {
+            start_time: stamps[0],
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn header(columns: &[&str]) -> Vec<String> {
+        columns.iter().map(|column| column.to_string()).collect()
+    }
+
+    fn text_cell(text: &str) -> Cell {
+        Cell::Text(text.to_string())
+    }
+
+    fn time_stamp_cells(count: usize) -> Vec<Cell> {
+        (0..count)
+            .map(|hour| {
+                Cell::TimeStamp(
+                    Utc.with_ymd_and_hms(2024, 11, 6, hour as u32, 0, 0)
+                        .single()
+                        .unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    fn small_workbook() -> Vec<Sheet> {
+        let scenarios = Sheet::new(
+            "scenarios",
+            header(&["name", "probability"]),
+            vec![
+                vec![text_cell("s1"), Cell::Number(0.4)],
+                vec![text_cell("s2"), Cell::Number(0.6)],
+            ],
+        );
+        let nodes = Sheet::new(
+            "nodes",
+            header(&[
+                "node",
+                "is_commodity",
+                "is_state",
+                "state_max",
+                "T_E_conversion",
+                "color",
+            ]),
+            vec![
+                vec![
+                    text_cell("ng"),
+                    Cell::Number(1.0),
+                    Cell::Number(0.0),
+                    Cell::Number(0.0),
+                    Cell::Number(1.0),
+                    text_cell("red"),
+                ],
+                vec![
+                    text_cell("dh"),
+                    Cell::Bool(false),
+                    Cell::Bool(true),
+                    Cell::Number(100.0),
+                    Cell::Number(0.5),
+                    Cell::Empty,
+                ],
+            ],
+        );
+        let processes = Sheet::new(
+            "processes",
+            header(&["process", "conversion", "eff", "is_cf"]),
+            vec![vec![
+                text_cell("boiler"),
+                Cell::Number(1.0),
+                Cell::Number(0.9),
+                Cell::Bool(true),
+            ]],
+        );
+        let topology = Sheet::new(
+            "process_topology",
+            header(&["process", "source_sink", "node", "capacity", "VOM_cost"]),
+            vec![
+                vec![
+                    text_cell("boiler"),
+                    text_cell("source"),
+                    text_cell("ng"),
+                    Cell::Number(10.0),
+                    Cell::Number(0.0),
+                ],
+                vec![
+                    text_cell("boiler"),
+                    text_cell("sink"),
+                    text_cell("dh"),
+                    Cell::Number(9.0),
+                    Cell::Number(1.5),
+                ],
+            ],
+        );
+        let groups = Sheet::new(
+            "groups",
+            header(&["group_type", "entity", "group"]),
+            vec![vec![
+                text_cell("process"),
+                text_cell("boiler"),
+                text_cell("p1"),
+            ]],
+        );
+        let stamps = time_stamp_cells(3);
+        let cf = Sheet::new(
+            "cf",
+            header(&["t", "boiler,s1", "boiler,s2", "pump,s1", "boiler,s3"]),
+            stamps
+                .iter()
+                .enumerate()
+                .map(|(i, stamp)| {
+                    vec![
+                        stamp.clone(),
+                        Cell::Number(i as f64),
+                        Cell::Number(2.0 * i as f64),
+                        Cell::Number(0.0),
+                        Cell::Number(0.0),
+                    ]
+                })
+                .collect(),
+        );
+        let price = Sheet::new(
+            "price",
+            header(&["t", "ng,s1", "ng,s2"]),
+            stamps
+                .iter()
+                .map(|stamp| vec![stamp.clone(), Cell::Number(30.0), Cell::Number(31.0)])
+                .collect(),
+        );
+        let extra = Sheet::new("notes", header(&["text"]), Vec::new());
+        vec![
+            scenarios, nodes, processes, topology, groups, cf, price, extra,
+        ]
+    }
+
+    #[test]
+    fn imports_entities_from_sheets() {
+        let outcome = import_sheets(&small_workbook()).expect("import should succeed");
+        let input_data = &outcome.input_data;
+        assert_eq!(input_data.scenarios.len(), 2);
+        assert_eq!(input_data.scenarios[1].name(), "s2");
+        assert_eq!(input_data.scenarios[1].weight(), 0.6);
+        assert_eq!(input_data.nodes.len(), 2);
+        assert!(input_data.nodes[0].is_commodity);
+        assert!(input_data.nodes[0].state.is_none());
+        let state = input_data.nodes[1]
+            .state
+            .as_ref()
+            .expect("dh should have state");
+        assert_eq!(state.state_max, 100.0);
+        assert_eq!(state.t_e_conversion, 0.5);
+        let boiler = &input_data.processes[0];
+        assert_eq!(boiler.eff, 0.9);
+        assert!(boiler.is_cf);
+        assert_eq!(boiler.groups, vec!["p1".to_string()]);
+        assert_eq!(
+            input_data.process_groups[0].members,
+            vec!["boiler".to_string()]
+        );
+        assert_eq!(boiler.topos.len(), 2);
+        assert_eq!(boiler.topos[0].source, "ng");
+        assert_eq!(boiler.topos[0].sink, "boiler");
+        assert_eq!(boiler.topos[1].source, "boiler");
+        assert_eq!(boiler.topos[1].sink, "dh");
+        assert_eq!(boiler.topos[1].vom_cost, 1.5);
+    }
+
+    #[test]
+    fn imports_time_series_and_time_line() {
+        let outcome = import_sheets(&small_workbook()).expect("import should succeed");
+        let boiler = &outcome.input_data.processes[0];
+        assert_eq!(boiler.cf.len(), 2);
+        assert_eq!(boiler.cf[1].scenario, Some("s2".to_string()));
+        match boiler.cf[1].value {
+            crate::input_data_base::SeriesValue::FloatList(ref list) => {
+                assert_eq!(list.values(), &vec![0.0, 2.0, 4.0])
+            }
+            _ => panic!("expected a float list"),
+        }
+        assert_eq!(outcome.input_data.nodes[0].cost.len(), 2);
+        let time_line = outcome.time_line.expect("time line should be inferred");
+        assert_eq!(time_line.step().to_time_delta(), TimeDelta::hours(1));
+        assert_eq!(time_line.duration().to_time_delta(), TimeDelta::hours(2));
+        match time_line.start() {
+            TimeLineStart::CustomStartTime(start) => assert_eq!(
+                start.start_time,
+                Utc.with_ymd_and_hms(2024, 11, 6, 0, 0, 0).single().unwrap()
+            ),
+            _ => panic!("expected custom start time"),
+        }
+    }
+
+    #[test]
+    fn reports_unmapped_sheets_and_columns() {
+        let outcome = import_sheets(&small_workbook()).expect("import should succeed");
+        let report = outcome.report;
+        assert_eq!(report.unmapped_sheets, vec!["notes".to_string()]);
+        let unmapped: Vec<(&str, &str)> = report
+            .unmapped_columns
+            .iter()
+            .map(|column| (column.sheet.as_str(), column.column.as_str()))
+            .collect();
+        assert_eq!(
+            unmapped,
+            vec![("nodes", "color"), ("cf", "pump,s1"), ("cf", "boiler,s3")]
+        );
+    }
+
+    #[test]
+    fn imports_generic_constraint_factors() {
+        let mut sheets = small_workbook();
+        sheets.push(Sheet::new(
+            "constraints",
+            header(&["name", "operator", "is_setpoint", "penalty"]),
+            vec![vec![
+                text_cell("c1"),
+                text_cell("gt"),
+                Cell::Bool(false),
+                Cell::Number(0.0),
+            ]],
+        ));
+        let stamps = time_stamp_cells(3);
+        sheets.push(Sheet::new(
+            "gen_constraint",
+            header(&["t", "c1,s1", "c1,boiler,dh,s1", "c1,dh,s1", "c1,boiler,s1"]),
+            stamps
+                .iter()
+                .map(|stamp| {
+                    vec![
+                        stamp.clone(),
+                        Cell::Number(5.0),
+                        Cell::Number(1.0),
+                        Cell::Number(-1.0),
+                        Cell::Number(2.0),
+                    ]
+                })
+                .collect(),
+        ));
+        let outcome = import_sheets(&sheets).expect("import should succeed");
+        let constraint = &outcome.input_data.gen_constraints[0];
+        assert!(matches!(constraint.gc_type, ConstraintType::GreaterThan));
+        assert_eq!(constraint.constant.len(), 1);
+        assert_eq!(constraint.factors.len(), 3);
+        assert_eq!(constraint.factors[0].var_type, ConstraintFactorType::Flow);
+        assert_eq!(constraint.factors[0].var_tuple.entity, "boiler");
+        assert_eq!(
+            constraint.factors[0].var_tuple.identifier,
+            Some("dh".to_string())
+        );
+        assert_eq!(constraint.factors[1].var_type, ConstraintFactorType::State);
+        assert_eq!(constraint.factors[2].var_type, ConstraintFactorType::Online);
+    }
+
+    #[test]
+    fn topologies_and_markets_need_existing_nodes() {
+        let mut sheets = small_workbook();
+        sheets[3].rows[1][2] = text_cell("heat");
+        assert_eq!(
+            import_sheets(&sheets).err(),
+            Some("sheet 'process_topology', row 3: no such node 'heat'".to_string())
+        );
+        let mut sheets = small_workbook();
+        sheets.push(Sheet::new(
+            "markets",
+            header(&["market", "market_type", "node", "processgroup"]),
+            vec![vec![
+                text_cell("npe"),
+                text_cell("energy"),
+                text_cell("elc"),
+                text_cell("p1"),
+            ]],
+        ));
+        assert_eq!(
+            import_sheets(&sheets).err(),
+            Some("sheet 'markets', row 2: no such node 'elc'".to_string())
+        );
+    }
+
+    #[test]
+    fn inflow_follows_is_inflow_flags() {
+        let mut sheets = small_workbook();
+        sheets[1].columns.push("is_inflow".to_string());
+        sheets[1].rows[0].push(Cell::Bool(false));
+        sheets[1].rows[1].push(Cell::Bool(true));
+        let inflow = |columns: &[&str]| {
+            Sheet::new(
+                "inflow",
+                header(columns),
+                time_stamp_cells(3)
+                    .into_iter()
+                    .map(|stamp| {
+                        let mut row = vec![stamp];
+                        row.resize(columns.len(), Cell::Number(-1.0));
+                        row
+                    })
+                    .collect(),
+            )
+        };
+        let mut with_inflow = sheets.clone();
+        with_inflow.push(inflow(&["t", "ng,s1", "ng,s2"]));
+        assert_eq!(
+            import_sheets(&with_inflow).err(),
+            Some(
+                "sheet 'nodes', row 3: node 'dh' has is_inflow set but no inflow data".to_string()
+            )
+        );
+        sheets.push(inflow(&["t", "ng,s1", "ng,s2", "dh,s1", "dh,s2"]));
+        let outcome = import_sheets(&sheets).expect("import should succeed");
+        assert!(outcome.input_data.nodes[0].inflow.is_empty());
+        assert_eq!(outcome.input_data.nodes[1].inflow.len(), 2);
+        assert_eq!(
+            outcome.report.notes,
+            vec!["inflow of node 'ng' was skipped because its is_inflow is false".to_string()]
+        );
+    }
+
+    #[test]
+    fn invalid_numbers_fail_the_import() {
+        let mut sheets = small_workbook();
+        sheets[2].rows[0][2] = text_cell("high");
+        match import_sheets(&sheets) {
+            Ok(..) => panic!("import should fail"),
+            Err(error) => assert_eq!(
+                error,
+                "sheet 'processes', row 2, column 'eff': expected a number"
+            ),
+        }
+    }
+}
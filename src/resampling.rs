@@ -0,0 +1,227 @@
+use crate::{TimeLine, TimeStamp};
+use juniper::GraphQLEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, GraphQLEnum, PartialEq, Serialize)]
+#[graphql(description = "Maps values from their own time stamps onto the time line.")]
+pub enum ResamplingMethod {
+    #[graphql(description = "Each value holds until the next time stamp of the data.")]
+    StepHold,
+    #[graphql(description = "Values are interpolated linearly between time stamps of the data.")]
+    LinearInterpolation,
+    #[default]
+    #[graphql(description = "Held values are averaged over each time line step.")]
+    MeanAggregation,
+    #[graphql(description = "Values are amounts per data step, e.g. energy, \
+                       that are split or summed so that their total is kept.")]
+    EnergyConserving,
+}
+
+/// Time from start to end in milliseconds.
+fn span(start: TimeStamp, end: TimeStamp) -> f64 {
+    (end - start).num_milliseconds() as f64
+}
+
+/// Length of overlap of [start, end) with [interval_start, interval_end); end None is open.
+fn overlap(
+    start: TimeStamp,
+    end: Option<TimeStamp>,
+    interval_start: TimeStamp,
+    interval_end: TimeStamp,
+) -> f64 {
+    let end = end.map_or(interval_end, |end| end.min(interval_end));
+    span(start.max(interval_start), end).max(0.0)
+}
+
+fn held_value(points: &[(TimeStamp, f64)], stamp: TimeStamp) -> f64 {
+    let index = points.partition_point(|(time_stamp, _)| *time_stamp <= stamp);
+    points[index - 1].1
+}
+
+fn interpolated_value(points: &[(TimeStamp, f64)], stamp: TimeStamp) -> f64 {
+    let index = points.partition_point(|(time_stamp, _)| *time_stamp <= stamp);
+    match points.get(index) {
+        Some((next_stamp, next_value)) => {
+            let (previous_stamp, previous_value) = points[index - 1];
+            let fraction = span(previous_stamp, stamp) / span(previous_stamp, *next_stamp);
+            previous_value + (next_value - previous_value) * fraction
+        }
+        None => points[index - 1].1,
+    }
+}
+
+/// Maps series onto the time line.
+///
+/// Each time line stamp stands for the step that starts from it; the last stamp
+/// has the same step as the one before it. The series should start no later than the time line;
+/// aggregated series should also last until the end of its last step, the last data step
+/// being as long as the one before it.
+pub fn resample(
+    series: &BTreeMap<TimeStamp, f64>,
+    time_line: &TimeLine,
+    method: ResamplingMethod,
+) -> Result<BTreeMap<TimeStamp, f64>, String> {
+    let points: Vec<(TimeStamp, f64)> = series.iter().map(|(t, v)| (*t, *v)).collect();
+    let (first_stamp, _) = *points.first().ok_or("nothing to resample")?;
+    let start = match time_line.first() {
+        Some(start) => *start,
+        None => return Ok(BTreeMap::new()),
+    };
+    if first_stamp > start {
+        return Err(format!(
+            "data starts at {} which is after time line start {}",
+            first_stamp, start
+        ));
+    }
+    let mut ends: Vec<Option<TimeStamp>> = points.windows(2).map(|pair| Some(pair[1].0)).collect();
+    if method == ResamplingMethod::MeanAggregation || method == ResamplingMethod::EnergyConserving {
+        let last_step = match points.len() {
+            1 => return Err("aggregating resampling needs at least two time stamps".into()),
+            n => points[n - 1].0 - points[n - 2].0,
+        };
+        let data_end = points[points.len() - 1].0 + last_step;
+        if let [.., previous, last] = time_line[..] {
+            let time_line_end = last + (last - previous);
+            if data_end < time_line_end {
+                return Err(format!(
+                    "data ends at {} which is before time line end {}",
+                    data_end, time_line_end
+                ));
+            }
+        }
+        ends.push(Some(data_end));
+    } else {
+        ends.push(None);
+    }
+    let mut resampled = BTreeMap::new();
+    for (index, stamp) in time_line.iter().enumerate() {
+        let step_end = match (time_line.get(index + 1), index) {
+            (Some(next), _) => *next,
+            (None, 0) => {
+                resampled.insert(*stamp, held_value(&points, *stamp));
+                continue;
+            }
+            (None, _) => *stamp + (*stamp - time_line[index - 1]),
+        };
+        let value = match method {
+            ResamplingMethod::StepHold => held_value(&points, *stamp),
+            ResamplingMethod::LinearInterpolation => interpolated_value(&points, *stamp),
+            ResamplingMethod::MeanAggregation => {
+                points
+                    .iter()
+                    .zip(&ends)
+                    .map(|((point_stamp, value), end)| {
+                        value * overlap(*point_stamp, *end, *stamp, step_end)
+                    })
+                    .sum::<f64>()
+                    / span(*stamp, step_end)
+            }
+            ResamplingMethod::EnergyConserving => points
+                .iter()
+                .zip(&ends)
+                .map(|((point_stamp, value), end)| {
+                    let end = end.expect("energy conserving steps should have ends");
+                    value * overlap(*point_stamp, Some(end), *stamp, step_end)
+                        / span(*point_stamp, end)
+                })
+                .sum(),
+        };
+        resampled.insert(*stamp, value);
+    }
+    Ok(resampled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeDelta, TimeZone, Utc};
+
+    fn stamp(hour: u32, minute: u32) -> TimeStamp {
+        Utc.with_ymd_and_hms(2024, 12, 4, hour, minute, 0).unwrap()
+    }
+
+    fn hourly_series() -> BTreeMap<TimeStamp, f64> {
+        [
+            (stamp(11, 0), 2.0),
+            (stamp(12, 0), 4.0),
+            (stamp(13, 0), 8.0),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    fn values(series: &BTreeMap<TimeStamp, f64>) -> Vec<f64> {
+        series.values().copied().collect()
+    }
+
+    #[test]
+    fn finer_time_line_is_filled() {
+        let time_line: TimeLine = (0..6)
+            .map(|i| stamp(11, 0) + TimeDelta::minutes(30 * i))
+            .collect();
+        let series = hourly_series();
+        let hold = resample(&series, &time_line, ResamplingMethod::StepHold).unwrap();
+        assert_eq!(values(&hold), vec![2.0, 2.0, 4.0, 4.0, 8.0, 8.0]);
+        let mean = resample(&series, &time_line, ResamplingMethod::MeanAggregation).unwrap();
+        assert_eq!(mean, hold);
+        let linear = resample(&series, &time_line, ResamplingMethod::LinearInterpolation).unwrap();
+        assert_eq!(values(&linear), vec![2.0, 3.0, 4.0, 6.0, 8.0, 8.0]);
+        let energy = resample(&series, &time_line, ResamplingMethod::EnergyConserving).unwrap();
+        assert_eq!(values(&energy), vec![1.0, 1.0, 2.0, 2.0, 4.0, 4.0]);
+    }
+
+    #[test]
+    fn coarser_time_line_is_aggregated() {
+        let time_line = vec![stamp(11, 0), stamp(13, 0)];
+        let mut series = hourly_series();
+        series.insert(stamp(14, 0), 16.0);
+        let mean = resample(&series, &time_line, ResamplingMethod::MeanAggregation).unwrap();
+        assert_eq!(values(&mean), vec![3.0, 12.0]);
+        let energy = resample(&series, &time_line, ResamplingMethod::EnergyConserving).unwrap();
+        assert_eq!(values(&energy), vec![6.0, 24.0]);
+        let hold = resample(&series, &time_line, ResamplingMethod::StepHold).unwrap();
+        assert_eq!(values(&hold), vec![2.0, 8.0]);
+    }
+
+    #[test]
+    fn time_line_may_start_between_time_stamps() {
+        let time_line = vec![stamp(11, 15), stamp(11, 45), stamp(12, 15)];
+        let series = hourly_series();
+        let mean = resample(&series, &time_line, ResamplingMethod::MeanAggregation).unwrap();
+        assert_eq!(values(&mean), vec![2.0, 3.0, 4.0]);
+        let linear = resample(&series, &time_line, ResamplingMethod::LinearInterpolation).unwrap();
+        assert_eq!(values(&linear), vec![2.5, 3.5, 5.0]);
+    }
+
+    #[test]
+    fn data_should_cover_time_line_start() {
+        let time_line = vec![stamp(10, 0), stamp(11, 0)];
+        assert!(resample(&hourly_series(), &time_line, ResamplingMethod::StepHold).is_err());
+        assert!(resample(&BTreeMap::new(), &time_line, ResamplingMethod::StepHold).is_err());
+        let single: BTreeMap<TimeStamp, f64> = [(stamp(9, 0), 1.0)].into_iter().collect();
+        assert!(resample(&single, &time_line, ResamplingMethod::EnergyConserving).is_err());
+        assert!(resample(&single, &time_line, ResamplingMethod::MeanAggregation).is_err());
+    }
+
+    #[test]
+    fn aggregated_data_should_cover_time_line_end() {
+        let time_line = vec![stamp(11, 0), stamp(13, 0)];
+        let series = hourly_series();
+        assert!(resample(&series, &time_line, ResamplingMethod::MeanAggregation).is_err());
+        assert!(resample(&series, &time_line, ResamplingMethod::EnergyConserving).is_err());
+        assert!(resample(&series, &time_line, ResamplingMethod::StepHold).is_ok());
+    }
+
+    #[test]
+    fn last_price_is_extended_until_end_of_time_line() {
+        let prices: BTreeMap<TimeStamp, f64> = [(stamp(11, 0), 2.2), (stamp(12, 0), 2.3)]
+            .into_iter()
+            .collect();
+        let time_line: TimeLine = (0..6)
+            .map(|i| stamp(11, 0) + TimeDelta::minutes(30 * i))
+            .collect();
+        let fitted = resample(&prices, &time_line, ResamplingMethod::StepHold).unwrap();
+        assert_eq!(values(&fitted), vec![2.2, 2.2, 2.3, 2.3, 2.3, 2.3]);
+    }
+}
@@ -1,3 +1,7 @@
+pub mod ensemble;
+pub mod prices;
+pub mod reduction;
+
 use crate::input_data::Name;
 use crate::input_data_base::TypeName;
 use hertta_derive::Name;
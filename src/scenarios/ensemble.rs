@@ -0,0 +1,154 @@
+use super::Scenario;
+use juniper::GraphQLObject;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Default, Deserialize, GraphQLObject, PartialEq, Serialize)]
+#[graphql(description = "Generates scenarios from the members of an ensemble weather forecast.")]
+pub struct WeatherEnsemble {
+    #[graphql(
+        description = "Quantiles between 0 and 1 in increasing order that become scenarios. \
+                       If empty, every ensemble member becomes a scenario."
+    )]
+    quantiles: Vec<f64>,
+}
+
+impl WeatherEnsemble {
+    pub fn new(quantiles: Vec<f64>) -> Result<Self, String> {
+        if quantiles.iter().any(|q| *q <= 0.0 || *q >= 1.0) {
+            return Err("quantiles should be between 0 and 1".into());
+        }
+        if quantiles.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err("quantiles should be in increasing order".into());
+        }
+        Ok(WeatherEnsemble { quantiles })
+    }
+
+    pub fn quantiles(&self) -> &Vec<f64> {
+        &self.quantiles
+    }
+}
+
+fn member_name(index: usize) -> String {
+    format!("member_{}", index + 1)
+}
+
+/// Names quantile by its percentage, e.g. 0.1 becomes p10.
+fn quantile_name(quantile: f64) -> String {
+    format!("p{}", (quantile * 1000.0).round() / 10.0)
+}
+
+/// Returns the quantile of sorted values using linear interpolation.
+fn quantile(sorted: &[f64], quantile: f64) -> f64 {
+    let position = quantile * (sorted.len() - 1) as f64;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f64)
+}
+
+/// Each quantile gets the probability mass between the midpoints to its neighbours.
+fn quantile_weights(quantiles: &[f64]) -> Vec<f64> {
+    let last = quantiles.len() - 1;
+    (0..quantiles.len())
+        .map(|i| {
+            let lower = if i == 0 {
+                0.0
+            } else {
+                (quantiles[i - 1] + quantiles[i]) / 2.0
+            };
+            let upper = if i == last {
+                1.0
+            } else {
+                (quantiles[i] + quantiles[i + 1]) / 2.0
+            };
+            upper - lower
+        })
+        .collect()
+}
+
+/// Turns ensemble members into scenarios and their series.
+///
+/// Every member becomes an equally weighted scenario unless quantiles are given,
+/// in which case each quantile over the members becomes a scenario.
+pub fn make_scenarios(
+    members: &[Vec<f64>],
+    ensemble: &WeatherEnsemble,
+) -> Result<Vec<(Scenario, Vec<f64>)>, String> {
+    let length = match members.first() {
+        Some(member) => member.len(),
+        None => return Err("ensemble has no members".into()),
+    };
+    if members.iter().any(|m| m.len() != length) {
+        return Err("ensemble members have different lengths".into());
+    }
+    if ensemble.quantiles.is_empty() {
+        let weight = 1.0 / members.len() as f64;
+        return members
+            .iter()
+            .enumerate()
+            .map(|(i, member)| Ok((Scenario::new(&member_name(i), weight)?, member.clone())))
+            .collect();
+    }
+    let columns: Vec<Vec<f64>> = (0..length)
+        .map(|step| {
+            let mut column: Vec<f64> = members.iter().map(|m| m[step]).collect();
+            column.sort_by(f64::total_cmp);
+            column
+        })
+        .collect();
+    ensemble
+        .quantiles
+        .iter()
+        .zip(quantile_weights(&ensemble.quantiles))
+        .map(|(q, weight)| {
+            let values = columns.iter().map(|column| quantile(column, *q)).collect();
+            Ok((Scenario::new(&quantile_name(*q), weight)?, values))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn members() -> Vec<Vec<f64>> {
+        vec![
+            vec![270.0, 272.0],
+            vec![274.0, 271.0],
+            vec![272.0, 275.0],
+            vec![276.0, 273.0],
+            vec![278.0, 274.0],
+        ]
+    }
+
+    #[test]
+    fn every_member_becomes_a_scenario() {
+        let scenarios = make_scenarios(&members(), &WeatherEnsemble::default()).unwrap();
+        assert_eq!(scenarios.len(), 5);
+        assert_eq!(scenarios[1].0.name(), "member_2");
+        assert_eq!(scenarios[1].0.weight(), 0.2);
+        assert_eq!(scenarios[1].1, vec![274.0, 271.0]);
+    }
+
+    #[test]
+    fn quantiles_become_weighted_scenarios() {
+        let ensemble = WeatherEnsemble::new(vec![0.1, 0.5, 0.9]).unwrap();
+        let scenarios = make_scenarios(&members(), &ensemble).unwrap();
+        let names: Vec<&str> = scenarios.iter().map(|(s, _)| s.name().as_str()).collect();
+        assert_eq!(names, vec!["p10", "p50", "p90"]);
+        let weights: Vec<f64> = scenarios.iter().map(|(s, _)| s.weight()).collect();
+        assert!((weights[0] - 0.3).abs() < 1e-12);
+        assert!((weights[1] - 0.4).abs() < 1e-12);
+        assert!((weights[2] - 0.3).abs() < 1e-12);
+        assert_eq!(scenarios[1].1, vec![274.0, 273.0]);
+        let low = &scenarios[0].1;
+        assert!((low[0] - 270.8).abs() < 1e-9);
+        assert!((low[1] - 271.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn quantiles_are_validated() {
+        assert!(WeatherEnsemble::new(vec![0.0, 0.5]).is_err());
+        assert!(WeatherEnsemble::new(vec![0.5, 0.5]).is_err());
+        assert!(make_scenarios(&[], &WeatherEnsemble::default()).is_err());
+    }
+}
@@ -0,0 +1,304 @@
+use crate::input_data::{TimeSeries, TimeSeriesData};
+use juniper::{GraphQLEnum, GraphQLObject};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Deserialize, GraphQLEnum, PartialEq, Serialize)]
+pub enum OffsetType {
+    #[graphql(description = "Price is multiplied by the value.")]
+    Multiplicative,
+    #[graphql(description = "Value is added to price.")]
+    Additive,
+}
+
+impl OffsetType {
+    fn apply(self, price: f64, value: f64) -> f64 {
+        match self {
+            OffsetType::Multiplicative => price * value,
+            OffsetType::Additive => price + value,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, GraphQLObject, PartialEq, Serialize)]
+#[graphql(description = "Offset of the forecast price in a scenario.")]
+pub struct PriceOffset {
+    pub scenario: String,
+    pub offset_type: OffsetType,
+    pub value: f64,
+}
+
+#[derive(Clone, Debug, Deserialize, GraphQLObject, PartialEq, Serialize)]
+#[graphql(description = "Normally distributed forecast error added to the price in each scenario.")]
+pub struct PriceNoise {
+    #[graphql(
+        description = "Standard deviations of past forecast errors by lead time in hours. \
+                       The last one is used for longer lead times."
+    )]
+    pub error_std_devs: Vec<f64>,
+    #[graphql(description = "Seed of the random numbers; same seed gives same noise.")]
+    pub seed: i32,
+}
+
+#[derive(Clone, Debug, Deserialize, GraphQLObject, PartialEq, Serialize)]
+#[graphql(description = "Rule that derives up or down price from the price of a scenario.")]
+pub struct BalancingPriceRule {
+    pub offset_type: OffsetType,
+    pub value: f64,
+}
+
+#[derive(Clone, Debug, Deserialize, GraphQLObject, PartialEq, Serialize)]
+#[graphql(
+    description = "Generates price, up price and down price scenarios from a price forecast."
+)]
+pub struct PriceScenarios {
+    #[graphql(
+        description = "Offsets per scenario; scenarios without offset use the forecast as is."
+    )]
+    pub offsets: Vec<PriceOffset>,
+    pub noise: Option<PriceNoise>,
+    pub up_price: BalancingPriceRule,
+    pub down_price: BalancingPriceRule,
+}
+
+impl Default for PriceScenarios {
+    fn default() -> Self {
+        PriceScenarios {
+            offsets: Vec::new(),
+            noise: None,
+            up_price: BalancingPriceRule {
+                offset_type: OffsetType::Multiplicative,
+                value: 1.1,
+            },
+            down_price: BalancingPriceRule {
+                offset_type: OffsetType::Multiplicative,
+                value: 0.9,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PriceSeries {
+    pub price: TimeSeriesData,
+    pub up_price: TimeSeriesData,
+    pub down_price: TimeSeriesData,
+}
+
+/// Pseudo random numbers so that noise is reproducible without extra dependencies.
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns uniform number in (0, 1].
+    fn next_uniform(&mut self) -> f64 {
+        ((self.next_u64() >> 11) + 1) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns standard normal number by the Box-Muller transform.
+    fn next_normal(&mut self) -> f64 {
+        let radius = (-2.0 * self.next_uniform().ln()).sqrt();
+        radius * (2.0 * std::f64::consts::PI * self.next_uniform()).cos()
+    }
+}
+
+impl PriceScenarios {
+    pub fn validate(&self) -> Result<(), String> {
+        for (index, offset) in self.offsets.iter().enumerate() {
+            if self.offsets[..index]
+                .iter()
+                .any(|o| o.scenario == offset.scenario)
+            {
+                return Err(format!(
+                    "multiple offsets for scenario '{}'",
+                    offset.scenario
+                ));
+            }
+        }
+        if let Some(ref noise) = self.noise {
+            if noise.error_std_devs.iter().any(|s| *s < 0.0) {
+                return Err("negative standard deviation of forecast error".into());
+            }
+        }
+        Ok(())
+    }
+
+    fn scenario_price(&self, time_series: &TimeSeries, scenario_index: usize) -> TimeSeries {
+        let offset = self
+            .offsets
+            .iter()
+            .find(|o| o.scenario == time_series.scenario);
+        let mut random = self.noise.as_ref().map(|noise| SplitMix64 {
+            state: (noise.seed as u64) << 32 | scenario_index as u64,
+        });
+        let start = time_series.series.keys().next().copied();
+        let series = time_series
+            .series
+            .iter()
+            .map(|(time_stamp, price)| {
+                let mut price = match offset {
+                    Some(offset) => offset.offset_type.apply(*price, offset.value),
+                    None => *price,
+                };
+                if let (Some(noise), Some(random), Some(start)) =
+                    (self.noise.as_ref(), random.as_mut(), start)
+                {
+                    let lead_hours = (*time_stamp - start).num_hours() as usize;
+                    let std_dev = noise
+                        .error_std_devs
+                        .get(lead_hours)
+                        .or(noise.error_std_devs.last())
+                        .copied()
+                        .unwrap_or(0.0);
+                    price += std_dev * random.next_normal();
+                }
+                (*time_stamp, price)
+            })
+            .collect();
+        TimeSeries {
+            scenario: time_series.scenario.clone(),
+            series,
+        }
+    }
+
+    /// Generates prices of every scenario in the forecast and derives up and down prices from them.
+    pub fn generate(&self, forecast: &TimeSeriesData) -> PriceSeries {
+        let price: Vec<TimeSeries> = forecast
+            .ts_data
+            .iter()
+            .enumerate()
+            .map(|(index, time_series)| self.scenario_price(time_series, index))
+            .collect();
+        let derive = |rule: &BalancingPriceRule| -> TimeSeriesData {
+            price
+                .iter()
+                .map(|time_series| TimeSeries {
+                    scenario: time_series.scenario.clone(),
+                    series: time_series
+                        .series
+                        .iter()
+                        .map(|(time_stamp, p)| {
+                            (*time_stamp, rule.offset_type.apply(*p, rule.value))
+                        })
+                        .collect(),
+                })
+                .collect::<Vec<TimeSeries>>()
+                .into()
+        };
+        PriceSeries {
+            up_price: derive(&self.up_price),
+            down_price: derive(&self.down_price),
+            price: price.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use std::collections::BTreeMap;
+
+    fn make_forecast() -> TimeSeriesData {
+        let series: BTreeMap<_, _> = (0..3)
+            .map(|hour| {
+                (
+                    Utc.with_ymd_and_hms(2024, 12, 4, 11 + hour, 0, 0).unwrap(),
+                    50.0,
+                )
+            })
+            .collect();
+        vec![
+            TimeSeries {
+                scenario: "s1".into(),
+                series: series.clone(),
+            },
+            TimeSeries {
+                scenario: "s2".into(),
+                series,
+            },
+        ]
+        .into()
+    }
+
+    fn values(time_series: &TimeSeries) -> Vec<f64> {
+        time_series.series.values().copied().collect()
+    }
+
+    #[test]
+    fn default_derives_up_and_down_prices() {
+        let prices = PriceScenarios::default().generate(&make_forecast());
+        assert_eq!(prices.price, make_forecast());
+        assert!(values(&prices.up_price.ts_data[0])
+            .iter()
+            .all(|p| (p - 55.0).abs() < 1e-9));
+        assert!(values(&prices.down_price.ts_data[1])
+            .iter()
+            .all(|p| (p - 45.0).abs() < 1e-9));
+    }
+
+    #[test]
+    fn offsets_apply_to_their_scenarios() {
+        let price_scenarios = PriceScenarios {
+            offsets: vec![PriceOffset {
+                scenario: "s2".into(),
+                offset_type: OffsetType::Additive,
+                value: 10.0,
+            }],
+            up_price: BalancingPriceRule {
+                offset_type: OffsetType::Additive,
+                value: 5.0,
+            },
+            ..PriceScenarios::default()
+        };
+        let prices = price_scenarios.generate(&make_forecast());
+        assert_eq!(values(&prices.price.ts_data[0]), vec![50.0, 50.0, 50.0]);
+        assert_eq!(values(&prices.price.ts_data[1]), vec![60.0, 60.0, 60.0]);
+        assert_eq!(values(&prices.up_price.ts_data[1]), vec![65.0, 65.0, 65.0]);
+        assert_eq!(
+            values(&prices.down_price.ts_data[1]),
+            vec![54.0, 54.0, 54.0]
+        );
+    }
+
+    #[test]
+    fn noise_grows_with_lead_time_and_is_reproducible() {
+        let price_scenarios = PriceScenarios {
+            noise: Some(PriceNoise {
+                error_std_devs: vec![0.0, 2.0],
+                seed: 7,
+            }),
+            ..PriceScenarios::default()
+        };
+        let prices = price_scenarios.generate(&make_forecast());
+        let first = values(&prices.price.ts_data[0]);
+        let second = values(&prices.price.ts_data[1]);
+        assert_eq!(first[0], 50.0);
+        assert_ne!(first[1], 50.0);
+        assert_ne!(first[1..], second[1..]);
+        assert_eq!(price_scenarios.generate(&make_forecast()), prices);
+    }
+
+    #[test]
+    fn duplicate_offsets_are_invalid() {
+        let offset = PriceOffset {
+            scenario: "s1".into(),
+            offset_type: OffsetType::Multiplicative,
+            value: 1.2,
+        };
+        let price_scenarios = PriceScenarios {
+            offsets: vec![offset.clone(), offset],
+            ..PriceScenarios::default()
+        };
+        assert!(price_scenarios.validate().is_err());
+        assert!(PriceScenarios::default().validate().is_ok());
+    }
+}
@@ -0,0 +1,133 @@
+use super::Scenario;
+use juniper::GraphQLObject;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, GraphQLObject, PartialEq, Serialize)]
+#[graphql(description = "Reduces the scenarios of an optimization to a few representative ones.")]
+pub struct ScenarioReduction {
+    #[graphql(description = "Number of scenarios to keep.")]
+    scenario_count: i32,
+}
+
+impl ScenarioReduction {
+    pub fn new(scenario_count: i32) -> Result<Self, String> {
+        if scenario_count < 1 {
+            return Err("scenario count should be positive".into());
+        }
+        Ok(ScenarioReduction { scenario_count })
+    }
+
+    pub fn scenario_count(&self) -> usize {
+        self.scenario_count as usize
+    }
+}
+
+fn distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| (x - y) * (x - y))
+        .sum::<f64>()
+        .sqrt()
+}
+
+/// Selects scenarios by fast forward selection.
+///
+/// `series` holds the combined series of each scenario. The weights of the scenarios
+/// that are left out are added to the closest selected scenario.
+/// Selected scenarios are returned in their original order.
+pub fn reduce(
+    scenarios: &[Scenario],
+    series: &[Vec<f64>],
+    count: usize,
+) -> Result<Vec<Scenario>, String> {
+    if scenarios.len() != series.len() {
+        return Err("every scenario should have a series".into());
+    }
+    if count >= scenarios.len() {
+        return Ok(scenarios.to_vec());
+    }
+    let distances: Vec<Vec<f64>> = series
+        .iter()
+        .map(|a| series.iter().map(|b| distance(a, b)).collect())
+        .collect();
+    let mut nearest_distance = vec![f64::INFINITY; scenarios.len()];
+    let mut selected = vec![false; scenarios.len()];
+    for _ in 0..count {
+        let mut best: Option<(usize, f64)> = None;
+        for candidate in (0..scenarios.len()).filter(|i| !selected[*i]) {
+            let cost: f64 = (0..scenarios.len())
+                .filter(|k| !selected[*k] && *k != candidate)
+                .map(|k| scenarios[k].weight() * nearest_distance[k].min(distances[k][candidate]))
+                .sum();
+            if best.is_none_or(|(_, best_cost)| cost < best_cost) {
+                best = Some((candidate, cost));
+            }
+        }
+        let (chosen, _) = best.expect("there should be unselected scenarios left");
+        selected[chosen] = true;
+        for k in 0..scenarios.len() {
+            nearest_distance[k] = nearest_distance[k].min(distances[k][chosen]);
+        }
+    }
+    let mut weights: Vec<f64> = scenarios.iter().map(|s| s.weight()).collect();
+    for k in (0..scenarios.len()).filter(|k| !selected[*k]) {
+        let closest = (0..scenarios.len())
+            .filter(|i| selected[*i])
+            .min_by(|i, j| distances[k][*i].total_cmp(&distances[k][*j]))
+            .expect("at least one scenario should be selected");
+        weights[closest] += weights[k];
+    }
+    (0..scenarios.len())
+        .filter(|i| selected[*i])
+        .map(|i| Scenario::new(scenarios[i].name(), weights[i]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_scenarios(count: usize) -> Vec<Scenario> {
+        (0..count)
+            .map(|i| Scenario::new(&format!("s{}", i + 1), 1.0 / count as f64).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn clusters_are_represented_by_single_scenarios() {
+        let scenarios = make_scenarios(6);
+        let series = vec![
+            vec![0.0, 0.0],
+            vec![10.0, 10.0],
+            vec![0.1, -0.1],
+            vec![10.1, 9.9],
+            vec![-0.1, 0.1],
+            vec![9.9, 10.1],
+        ];
+        let reduced = reduce(&scenarios, &series, 2).unwrap();
+        assert_eq!(reduced.len(), 2);
+        assert_eq!(reduced[0].name(), "s1");
+        assert_eq!(reduced[1].name(), "s2");
+        assert!((reduced[0].weight() - 0.5).abs() < 1e-12);
+        assert!((reduced[1].weight() - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn weights_follow_cluster_sizes() {
+        let scenarios = make_scenarios(4);
+        let series = vec![vec![1.0], vec![1.1], vec![0.9], vec![5.0]];
+        let reduced = reduce(&scenarios, &series, 2).unwrap();
+        assert_eq!(reduced[1].name(), "s4");
+        assert!((reduced[0].weight() - 0.75).abs() < 1e-12);
+        assert!((reduced[1].weight() - 0.25).abs() < 1e-12);
+    }
+
+    #[test]
+    fn nothing_to_reduce_keeps_scenarios() {
+        let scenarios = make_scenarios(2);
+        let series = vec![vec![1.0], vec![2.0]];
+        assert_eq!(reduce(&scenarios, &series, 3).unwrap(), scenarios);
+        assert!(reduce(&scenarios, &series[..1], 1).is_err());
+        assert!(ScenarioReduction::new(0).is_err());
+    }
+}
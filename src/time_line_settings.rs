@@ -1,7 +1,17 @@
-use chrono::{TimeDelta, Utc, DurationRound};
+use chrono::{
+    DateTime, DurationRound, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Utc,
+};
+use chrono_tz::Tz;
 use juniper::{GraphQLObject, GraphQLUnion, GraphQLEnum};
 use serde::{Deserialize, Serialize};
-use crate::TimeStamp;
+use crate::event_loop::time_series;
+use crate::{TimeLine, TimeStamp};
+
+/// Longest uniform time line in hours.
+const MAX_UNIFORM_DURATION_HOURS: i64 = 24;
+/// Longest segmented time line in hours; coarser steps later in the time line keep
+/// the number of steps near that of a uniform day so a longer horizon is allowed.
+const MAX_SEGMENTED_DURATION_HOURS: i64 = 48;
 
 #[derive(Clone, Debug, Deserialize, GraphQLObject, PartialEq, Serialize)]
 #[graphql(description = "Optimization time line settings.")]
@@ -12,6 +22,43 @@ pub struct TimeLineSettings {
     step: Duration,
     #[graphql(description = "Start of the time line.")]
     start: TimeLineStart,
+    #[serde(default)]
+    #[graphql(description = "IANA time zone of the model, e.g. Europe/Helsinki. Defaults to UTC.")]
+    time_zone: Option<String>,
+    #[serde(default)]
+    #[graphql(
+        description = "Segments of a variable step time line in order; empty for uniform steps."
+    )]
+    segments: Vec<TimeLineSegment>,
+}
+
+#[derive(Clone, Debug, Deserialize, GraphQLObject, PartialEq, Serialize)]
+#[graphql(description = "Part of the time line with its own step.")]
+pub struct TimeLineSegment {
+    #[graphql(description = "Segment duration; a multiple of step.")]
+    duration: Duration,
+    #[graphql(description = "Time step length within the segment.")]
+    step: Duration,
+}
+
+impl TimeLineSegment {
+    pub fn try_new(duration: Duration, step: Duration) -> Result<Self, String> {
+        let step_millis = step.to_time_delta().num_milliseconds();
+        if step_millis <= 0 {
+            return Err("segment step should be positive".to_string());
+        }
+        let duration_millis = duration.to_time_delta().num_milliseconds();
+        if duration_millis <= 0 || duration_millis % step_millis != 0 {
+            return Err("segment duration should be a positive multiple of step".to_string());
+        }
+        Ok(TimeLineSegment { duration, step })
+    }
+    pub fn duration(&self) -> &Duration {
+        &self.duration
+    }
+    pub fn step(&self) -> &Duration {
+        &self.step
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, GraphQLUnion, PartialEq)]
@@ -26,21 +73,130 @@ pub enum TimeLineStart {
 pub struct ClockChoice {
     #[graphql(description = "Predefined clock option.")]
     pub choice: Clock,
+    #[serde(default)]
+    #[graphql(description = "IANA time zone of the clock, e.g. Europe/Helsinki. Defaults to UTC.")]
+    pub time_zone: Option<String>,
+    #[serde(default)]
+    #[graphql(description = "Time added to the current time before choosing the start, e.g. to leave time for the solve.")]
+    pub offset: Duration,
+}
+
+impl ClockChoice {
+    pub fn new(choice: Clock) -> Self {
+        ClockChoice {
+            choice,
+            time_zone: None,
+            offset: Duration::default(),
+        }
+    }
+
+    pub fn parse_time_zone(time_zone: &str) -> Result<Tz, String> {
+        time_zone
+            .parse::<Tz>()
+            .map_err(|_| format!("unknown time zone '{}'", time_zone))
+    }
+
+    /// Returns the clock's own time zone or the model's time zone if the clock has none.
+    pub fn tz(&self, model_time_zone: Tz) -> Tz {
+        match self.time_zone {
+            Some(ref time_zone) => parse_time_zone_or_utc(time_zone),
+            None => model_time_zone,
+        }
+    }
+
+    /// Calculates start time from given current time so that the start lines up with step.
+    pub fn start_time(&self, now: TimeStamp, step: TimeDelta, model_time_zone: Tz) -> TimeStamp {
+        let now = (now + self.offset.to_time_delta()).with_timezone(&self.tz(model_time_zone));
+        align_to_step(self.choice.calculate_start_time(now), step).with_timezone(&Utc)
+    }
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize, GraphQLEnum, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, GraphQLEnum, PartialEq)]
 #[graphql(description = "Represents predefined clock options.")]
 pub enum Clock {
     /// Use the current hour truncated to the nearest hour.
     CurrentHour,
+    /// Use the current time truncated to the nearest quarter hour.
+    CurrentQuarterHour,
+    /// Use the beginning of the next hour.
+    NextHour,
+    /// Use the next midnight in the clock's time zone.
+    NextDayMidnight,
+    /// Use the current time rounded up to the time line step.
+    NowRoundedUpToStep,
+}
+
+/// Unknown time zones fall back to UTC; validation reports them.
+fn parse_time_zone_or_utc(time_zone: &str) -> Tz {
+    ClockChoice::parse_time_zone(time_zone).unwrap_or(Tz::UTC)
+}
+
+/// Parses RFC 3339 time or local time without offset, e.g. 2025-04-20T00:00:00, in given time zone.
+pub fn parse_local_time(text: &str, time_zone: Tz) -> Result<TimeStamp, String> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    let local = NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S")
+        .map_err(|_| format!("invalid datetime format: {}", text))?;
+    match time_zone.from_local_datetime(&local).earliest() {
+        Some(time) => Ok(time.with_timezone(&Utc)),
+        None => Err(format!("{} does not exist in time zone {}", text, time_zone)),
+    }
+}
+
+/// Converts time stamps to local time of given time zone.
+pub fn local_time_line(time_line: &[TimeStamp], time_zone: Tz) -> Vec<DateTime<FixedOffset>> {
+    time_line
+        .iter()
+        .map(|time_stamp| time_stamp.with_timezone(&time_zone).fixed_offset())
+        .collect()
+}
+
+/// Returns the first instant of the local date; midnight may fall into a daylight saving gap.
+fn local_midnight(date: NaiveDate, time_zone: Tz) -> DateTime<Tz> {
+    let mut local = date.and_time(NaiveTime::MIN);
+    loop {
+        if let Some(midnight) = time_zone.from_local_datetime(&local).earliest() {
+            return midnight;
+        }
+        local += TimeDelta::minutes(15);
+    }
+}
+
+/// Rounds time up to a whole number of steps from local midnight.
+fn align_to_step(time: DateTime<Tz>, step: TimeDelta) -> DateTime<Tz> {
+    if step <= TimeDelta::zero() {
+        return time;
+    }
+    let midnight = local_midnight(time.date_naive(), time.timezone());
+    let elapsed = (time - midnight).num_milliseconds();
+    let step_millis = step.num_milliseconds();
+    let steps = (elapsed + step_millis - 1).div_euclid(step_millis);
+    midnight + TimeDelta::milliseconds(steps * step_millis)
 }
 
 impl Clock {
-    pub fn calculate_start_time(&self) -> TimeStamp {
+    pub fn calculate_start_time(&self, now: DateTime<Tz>) -> DateTime<Tz> {
         match self {
-            Clock::CurrentHour => Utc::now()
-                .duration_trunc(chrono::Duration::hours(1))
+            Clock::CurrentHour => now
+                .duration_trunc(TimeDelta::hours(1))
                 .expect("Truncation to nearest hour should succeed"),
+            Clock::CurrentQuarterHour => now
+                .duration_trunc(TimeDelta::minutes(15))
+                .expect("Truncation to nearest quarter hour should succeed"),
+            Clock::NextHour => {
+                now.duration_trunc(TimeDelta::hours(1))
+                    .expect("Truncation to nearest hour should succeed")
+                    + TimeDelta::hours(1)
+            }
+            Clock::NextDayMidnight => {
+                let tomorrow = now
+                    .date_naive()
+                    .succ_opt()
+                    .expect("next day should be representable");
+                local_midnight(tomorrow, now.timezone())
+            }
+            Clock::NowRoundedUpToStep => now,
         }
     }
 }
@@ -102,23 +258,32 @@ impl Default for TimeLineSettings {
                 .expect("constructing default duration should always succeed"),
             step: Duration::try_new(0, 15, 0)
                 .expect("constructing default step should always succeed"),
-            start: TimeLineStart::ClockChoice(ClockChoice {
-                choice: Clock::CurrentHour,
-            }),
+            start: TimeLineStart::ClockChoice(ClockChoice::new(Clock::CurrentHour)),
+            time_zone: None,
+            segments: Vec::new(),
         }
     }
 }
 
 impl TimeLineSettings {
     pub fn try_new(duration: Duration, step: Duration, start: TimeLineStart) -> Result<Self, String> {
-        let time_line = TimeLineSettings { duration, step, start };
+        let time_line = TimeLineSettings {
+            duration,
+            step,
+            start,
+            time_zone: None,
+            segments: Vec::new(),
+        };
         time_line.validate()?; // Validate all parameters
         Ok(time_line)
     }
     fn validate(&self) -> Result<(), String> {
         let duration = self.duration.to_time_delta();
-        if duration.num_hours() > 24 {
-            return Err("time line duration should not exceed 24 hours".to_string());
+        if duration.num_hours() > MAX_UNIFORM_DURATION_HOURS {
+            return Err(format!(
+                "time line duration should not exceed {} hours",
+                MAX_UNIFORM_DURATION_HOURS
+            ));
         }
         let step = self.step.to_time_delta();
         if step > duration {
@@ -132,35 +297,126 @@ impl TimeLineSettings {
     pub fn duration(&self) -> &Duration {
         &self.duration
     }
+    /// Sets uniform duration; segments are cleared.
     pub fn set_duration(&mut self, duration: Duration) -> Result<(), String> {
-        if duration.to_time_delta().num_hours() > 24 {
-            return Err("time line duration should not exceed 24 hours".to_string());
+        if duration.to_time_delta().num_hours() > MAX_UNIFORM_DURATION_HOURS {
+            return Err(format!(
+                "time line duration should not exceed {} hours",
+                MAX_UNIFORM_DURATION_HOURS
+            ));
         }
         self.duration = duration;
+        self.segments.clear();
         Ok(())
     }
+    /// Returns the finest step if the time line has segments.
     pub fn step(&self) -> &Duration {
         &self.step
     }
+    /// Sets uniform step; segments are cleared.
     pub fn set_step(&mut self, step: Duration) -> Result<(), String> {
         let duration = self.duration.to_time_delta();
         if step.to_time_delta() > duration {
             return Err("time line step should not exceed duration".to_string());
         }
         self.step = step;
+        self.segments.clear();
+        Ok(())
+    }
+    pub fn segments(&self) -> &Vec<TimeLineSegment> {
+        &self.segments
+    }
+    /// Sets variable step segments; duration becomes their total and step the finest step.
+    ///
+    /// Every step should be a multiple of the finest step. Empty segments give back a uniform time line.
+    pub fn set_segments(&mut self, segments: Vec<TimeLineSegment>) -> Result<(), String> {
+        let finest_step = match segments.iter().map(|s| s.step.to_time_delta()).min() {
+            Some(step) => step,
+            None => {
+                self.segments.clear();
+                return Ok(());
+            }
+        };
+        if segments
+            .iter()
+            .any(|s| s.step.to_time_delta().num_milliseconds() % finest_step.num_milliseconds() != 0)
+        {
+            return Err("segment steps should be multiples of the finest step".to_string());
+        }
+        let total: TimeDelta = segments.iter().map(|s| s.duration.to_time_delta()).sum();
+        if total > TimeDelta::hours(MAX_SEGMENTED_DURATION_HOURS) {
+            return Err(format!(
+                "segmented time line duration should not exceed {} hours",
+                MAX_SEGMENTED_DURATION_HOURS
+            ));
+        }
+        self.duration = duration_from_time_delta(total);
+        self.step = duration_from_time_delta(finest_step);
+        self.segments = segments;
         Ok(())
     }
+    /// Makes the time stamps of the time line starting at given time.
+    pub fn make_time_line(&self, start: TimeStamp) -> TimeLine {
+        if self.segments.is_empty() {
+            return time_series::make_time_data(
+                start,
+                self.step.to_time_delta(),
+                self.duration.to_time_delta(),
+            );
+        }
+        let mut time_line = vec![start];
+        let mut segment_start = start;
+        for segment in &self.segments {
+            let mut segment_line = time_series::make_time_data(
+                segment_start,
+                segment.step.to_time_delta(),
+                segment.duration.to_time_delta(),
+            );
+            segment_start += segment.duration.to_time_delta();
+            time_line.extend(segment_line.drain(1..));
+        }
+        time_line
+    }
     pub fn set_start(&mut self, start: TimeLineStart) -> Result<(), String> {
         self.start = start;
         Ok(())
     }
+    pub fn time_zone(&self) -> Option<&String> {
+        self.time_zone.as_ref()
+    }
+    /// Returns the model's time zone, UTC if none is set.
+    pub fn tz(&self) -> Tz {
+        self.time_zone
+            .as_deref()
+            .map_or(Tz::UTC, parse_time_zone_or_utc)
+    }
+    pub fn set_time_zone(&mut self, time_zone: Option<String>) -> Result<(), String> {
+        if let Some(ref time_zone) = time_zone {
+            ClockChoice::parse_time_zone(time_zone)?;
+        }
+        self.time_zone = time_zone;
+        Ok(())
+    }
+
+}
 
+fn duration_from_time_delta(time_delta: TimeDelta) -> Duration {
+    let seconds = time_delta.num_seconds();
+    Duration {
+        hours: (seconds / 3600) as i32,
+        minutes: (seconds % 3600 / 60) as i32,
+        seconds: (seconds % 60) as i32,
+    }
 }
 
 pub fn compute_timeline_start(time_line_settings: &TimeLineSettings) -> TimeStamp {
     match time_line_settings.start() {
         TimeLineStart::ClockChoice(clock_choice) => {
-            clock_choice.choice.calculate_start_time()
+            clock_choice.start_time(
+                Utc::now(),
+                time_line_settings.step().to_time_delta(),
+                time_line_settings.tz(),
+            )
         }
         TimeLineStart::CustomStartTime(custom_start) => {
             custom_start.start_time
@@ -177,9 +433,7 @@ mod tests {
     fn constructs_time_line_correctly_with_current_hour() {
         let duration = Duration::try_new(13, 0, 0).expect("constructing duration should succeed");
         let step = Duration::try_new(0, 15, 0).expect("constructing step should succeed");
-        let start = TimeLineStart::ClockChoice(ClockChoice {
-            choice: Clock::CurrentHour,
-        });
+        let start = TimeLineStart::ClockChoice(ClockChoice::new(Clock::CurrentHour));
         let time_line = TimeLineSettings::try_new(duration, step, start)
             .expect("time line construction should succeed");
         assert_eq!(time_line.duration().to_time_delta(), TimeDelta::hours(13));
@@ -210,9 +464,7 @@ mod tests {
     fn rejects_too_long_durations() -> Result<(), Box<dyn Error>> {
         let duration = Duration::try_new(25, 0, 0).expect("constructing duration should succeed");
         let step = Duration::try_new(0, 15, 0).expect("constructing step should succeed");
-        let start = TimeLineStart::ClockChoice(ClockChoice {
-            choice: Clock::CurrentHour,
-        });
+        let start = TimeLineStart::ClockChoice(ClockChoice::new(Clock::CurrentHour));
         if let Err(message) = TimeLineSettings::try_new(duration, step, start) {
             assert_eq!(message, "time line duration should not exceed 24 hours");
         } else {
@@ -224,9 +476,7 @@ mod tests {
     fn rejects_steps_that_are_longer_than_duration() -> Result<(), Box<dyn Error>> {
         let duration = Duration::try_new(4, 0, 0).expect("constructing duration should succeed");
         let step = Duration::try_new(5, 0, 0).expect("constructing step should succeed");
-        let start = TimeLineStart::ClockChoice(ClockChoice {
-            choice: Clock::CurrentHour,
-        });
+        let start = TimeLineStart::ClockChoice(ClockChoice::new(Clock::CurrentHour));
         if let Err(message) = TimeLineSettings::try_new(duration, step, start) {
             assert_eq!(message, "time line step should not exceed duration");
         } else {
@@ -234,4 +484,182 @@ mod tests {
         }
         Ok(())
     }
+
+    fn make_segments() -> Vec<TimeLineSegment> {
+        vec![
+            TimeLineSegment::try_new(
+                Duration::try_new(6, 0, 0).unwrap(),
+                Duration::try_new(0, 15, 0).unwrap(),
+            )
+            .unwrap(),
+            TimeLineSegment::try_new(
+                Duration::try_new(42, 0, 0).unwrap(),
+                Duration::try_new(1, 0, 0).unwrap(),
+            )
+            .unwrap(),
+        ]
+    }
+
+    #[test]
+    fn segments_make_variable_step_time_line() {
+        let mut settings = TimeLineSettings::default();
+        settings.set_segments(make_segments()).unwrap();
+        assert_eq!(settings.duration().to_time_delta(), TimeDelta::hours(48));
+        assert_eq!(settings.step().to_time_delta(), TimeDelta::minutes(15));
+        let start = Utc.with_ymd_and_hms(2025, 3, 29, 12, 0, 0).unwrap();
+        let time_line = settings.make_time_line(start);
+        assert_eq!(time_line.len(), 6 * 4 + 42 + 1);
+        assert_eq!(time_line[24] - time_line[23], TimeDelta::minutes(15));
+        assert_eq!(time_line[25] - time_line[24], TimeDelta::hours(1));
+        assert_eq!(*time_line.last().unwrap(), start + TimeDelta::hours(48));
+        settings
+            .set_step(Duration::try_new(0, 30, 0).unwrap())
+            .unwrap();
+        assert!(settings.segments().is_empty());
+        assert_eq!(settings.make_time_line(start).len(), 97);
+    }
+
+    #[test]
+    fn invalid_segments_are_rejected() {
+        assert!(TimeLineSegment::try_new(
+            Duration::try_new(1, 0, 0).unwrap(),
+            Duration::try_new(0, 25, 0).unwrap()
+        )
+        .is_err());
+        assert!(TimeLineSegment::try_new(
+            Duration::try_new(1, 0, 0).unwrap(),
+            Duration::default()
+        )
+        .is_err());
+        let mut segments = make_segments();
+        segments.push(
+            TimeLineSegment::try_new(
+                Duration::try_new(1, 0, 0).unwrap(),
+                Duration::try_new(0, 20, 0).unwrap(),
+            )
+            .unwrap(),
+        );
+        let mut settings = TimeLineSettings::default();
+        assert_eq!(
+            settings.set_segments(segments),
+            Err("segment steps should be multiples of the finest step".to_string())
+        );
+        let mut segments = make_segments();
+        segments.push(segments[1].clone());
+        assert!(settings.set_segments(segments).is_err());
+        assert_eq!(settings, TimeLineSettings::default());
+    }
+
+    fn clock_start(clock_choice: &ClockChoice, now: &str, step_minutes: i64) -> String {
+        let now = DateTime::parse_from_rfc3339(now).unwrap().with_timezone(&Utc);
+        clock_choice
+            .start_time(now, TimeDelta::minutes(step_minutes), Tz::UTC)
+            .to_rfc3339()
+    }
+
+    #[test]
+    fn clock_choices_give_expected_starts() {
+        let now = "2025-03-29T13:47:12Z";
+        let expected = [
+            (Clock::CurrentHour, "2025-03-29T13:00:00+00:00"),
+            (Clock::CurrentQuarterHour, "2025-03-29T13:45:00+00:00"),
+            (Clock::NextHour, "2025-03-29T14:00:00+00:00"),
+            (Clock::NextDayMidnight, "2025-03-30T00:00:00+00:00"),
+            (Clock::NowRoundedUpToStep, "2025-03-29T13:50:00+00:00"),
+        ];
+        for (clock, start) in expected {
+            assert_eq!(clock_start(&ClockChoice::new(clock), now, 5), start);
+        }
+    }
+
+    #[test]
+    fn start_lines_up_with_step() {
+        let clock_choice = ClockChoice::new(Clock::CurrentQuarterHour);
+        assert_eq!(
+            clock_start(&clock_choice, "2025-03-29T13:47:12Z", 60),
+            "2025-03-29T14:00:00+00:00"
+        );
+        let clock_choice = ClockChoice::new(Clock::NowRoundedUpToStep);
+        assert_eq!(
+            clock_start(&clock_choice, "2025-03-29T14:00:00Z", 30),
+            "2025-03-29T14:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn offset_and_time_zone_are_applied() {
+        let mut clock_choice = ClockChoice::new(Clock::NowRoundedUpToStep);
+        clock_choice.offset = Duration::try_new(0, 10, 0).unwrap();
+        assert_eq!(
+            clock_start(&clock_choice, "2025-03-29T13:47:12Z", 15),
+            "2025-03-29T14:00:00+00:00"
+        );
+        let mut clock_choice = ClockChoice::new(Clock::NextDayMidnight);
+        clock_choice.time_zone = Some("Europe/Helsinki".to_string());
+        assert_eq!(
+            clock_start(&clock_choice, "2025-03-29T13:47:12Z", 60),
+            "2025-03-29T22:00:00+00:00"
+        );
+        clock_choice.choice = Clock::CurrentHour;
+        clock_choice.time_zone = Some("Asia/Kolkata".to_string());
+        assert_eq!(
+            clock_start(&clock_choice, "2025-03-29T13:47:12Z", 60),
+            "2025-03-29T13:30:00+00:00"
+        );
+    }
+
+    #[test]
+    fn next_day_midnight_works_at_daylight_saving_transitions() {
+        let mut clock_choice = ClockChoice::new(Clock::NextDayMidnight);
+        clock_choice.time_zone = Some("Europe/Helsinki".to_string());
+        assert_eq!(
+            clock_start(&clock_choice, "2025-03-29T01:30:00Z", 60),
+            "2025-03-29T22:00:00+00:00"
+        );
+        assert_eq!(
+            clock_start(&clock_choice, "2025-10-25T00:30:00Z", 60),
+            "2025-10-25T21:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn model_time_zone_drives_clock_and_local_times() {
+        let clock_choice = ClockChoice::new(Clock::NextDayMidnight);
+        let now = DateTime::parse_from_rfc3339("2025-10-25T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let helsinki: Tz = "Europe/Helsinki".parse().unwrap();
+        let start = clock_choice.start_time(now, TimeDelta::hours(1), helsinki);
+        assert_eq!(start.to_rfc3339(), "2025-10-25T21:00:00+00:00");
+        let time_line = [start, start + TimeDelta::days(1)];
+        let local: Vec<String> = local_time_line(&time_line, helsinki)
+            .iter()
+            .map(|t| t.to_rfc3339())
+            .collect();
+        assert_eq!(
+            local,
+            vec!["2025-10-26T00:00:00+03:00", "2025-10-26T23:00:00+02:00"]
+        );
+    }
+
+    #[test]
+    fn local_times_are_parsed_in_time_zone() {
+        let helsinki: Tz = "Europe/Helsinki".parse().unwrap();
+        assert_eq!(
+            parse_local_time("2025-01-15T08:00:00", helsinki)
+                .unwrap()
+                .to_rfc3339(),
+            "2025-01-15T06:00:00+00:00"
+        );
+        assert_eq!(
+            parse_local_time("2025-01-15T08:00:00Z", helsinki)
+                .unwrap()
+                .to_rfc3339(),
+            "2025-01-15T08:00:00+00:00"
+        );
+        assert!(parse_local_time("2025-03-30T03:30:00", helsinki).is_err());
+        let mut settings = TimeLineSettings::default();
+        assert!(settings.set_time_zone(Some("Mars/Olympus".into())).is_err());
+        assert_eq!(settings.tz(), Tz::UTC);
+    }
 }
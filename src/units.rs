@@ -0,0 +1,555 @@
+use crate::input_data::Forecast;
+use crate::input_data_base::{
+    BaseConFactor, BaseForecastable, BaseGenConstraint, BaseInputData, Constant,
+    ConstraintFactorType, ExpansionError, ForecastValue, SeriesValue, Value,
+};
+use crate::model::diff::escape_segment;
+use juniper::GraphQLEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+const KELVIN_AT_ZERO_CELSIUS: f64 = 273.15;
+
+#[derive(Clone, Copy, Debug, Deserialize, GraphQLEnum, PartialEq, Serialize)]
+#[graphql(description = "Physical unit of a parameter or forecast.")]
+pub enum Unit {
+    Celsius,
+    Kelvin,
+    Kw,
+    Mw,
+    Kwh,
+    Mwh,
+    EurPerKwh,
+    EurPerMwh,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Dimension {
+    Temperature,
+    Power,
+    Energy,
+    Price,
+}
+
+impl fmt::Display for Dimension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Dimension::Temperature => "temperature",
+            Dimension::Power => "power",
+            Dimension::Energy => "energy",
+            Dimension::Price => "price",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Unit::Celsius => "°C",
+            Unit::Kelvin => "K",
+            Unit::Kw => "kW",
+            Unit::Mw => "MW",
+            Unit::Kwh => "kWh",
+            Unit::Mwh => "MWh",
+            Unit::EurPerKwh => "EUR/kWh",
+            Unit::EurPerMwh => "EUR/MWh",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+impl Unit {
+    pub fn dimension(self) -> Dimension {
+        match self {
+            Unit::Celsius | Unit::Kelvin => Dimension::Temperature,
+            Unit::Kw | Unit::Mw => Dimension::Power,
+            Unit::Kwh | Unit::Mwh => Dimension::Energy,
+            Unit::EurPerKwh | Unit::EurPerMwh => Dimension::Price,
+        }
+    }
+
+    fn is_kilo(self) -> bool {
+        matches!(self, Unit::Kw | Unit::Kwh | Unit::EurPerKwh)
+    }
+
+    fn is_mega(self) -> bool {
+        matches!(self, Unit::Mw | Unit::Mwh | Unit::EurPerMwh)
+    }
+
+    /// Returns the MW based unit of the same dimension.
+    fn mega(self) -> Unit {
+        match self {
+            Unit::Kw => Unit::Mw,
+            Unit::Kwh => Unit::Mwh,
+            Unit::EurPerKwh => Unit::EurPerMwh,
+            unit => unit,
+        }
+    }
+
+    /// Multiplier from this unit to the MW based unit of the same dimension.
+    fn mega_factor(self) -> f64 {
+        match self {
+            Unit::Kw | Unit::Kwh => 1.0e-3,
+            Unit::EurPerKwh => 1.0e3,
+            _ => 1.0,
+        }
+    }
+}
+
+/// Converts an absolute value between units of the same dimension.
+pub fn convert(value: f64, from: Unit, to: Unit) -> Result<f64, String> {
+    if from.dimension() != to.dimension() {
+        return Err(format!("cannot convert {} to {}", from, to));
+    }
+    let converted = match (from, to) {
+        (Unit::Celsius, Unit::Kelvin) => value + KELVIN_AT_ZERO_CELSIUS,
+        (Unit::Kelvin, Unit::Celsius) => value - KELVIN_AT_ZERO_CELSIUS,
+        _ => value * from.mega_factor() / to.mega_factor(),
+    };
+    Ok(converted)
+}
+
+/// Returns the unit of the data a forecast delivers.
+///
+/// Forecasts without an explicit unit use the unit of their provider:
+/// FMI temperatures are in Kelvin and electricity prices in EUR/MWh.
+pub fn forecast_unit(forecast: &Forecast) -> Option<Unit> {
+    forecast.unit().or(match (forecast.name(), forecast.f_type()) {
+        ("FMI", _) => Some(Unit::Kelvin),
+        (_, "electricity") => Some(Unit::EurPerMwh),
+        _ => None,
+    })
+}
+
+fn forecasts(values: &[ForecastValue]) -> impl Iterator<Item = &Forecast> {
+    values.iter().filter_map(|value| match value.value {
+        BaseForecastable::Forecast(ref forecast) => Some(forecast),
+        _ => None,
+    })
+}
+
+fn check_dimension(
+    unit: Option<Unit>,
+    dimension: Dimension,
+    entity: &str,
+    field: &str,
+    problems: &mut Vec<ExpansionError>,
+) {
+    if let Some(unit) = unit {
+        if unit.dimension() != dimension {
+            problems.push(ExpansionError::new(
+                entity.to_string(),
+                field,
+                format!("{} is not a unit of {}", unit, dimension),
+            ));
+        }
+    }
+}
+
+/// Checks that units of states, topologies, market prices and forecasts are dimensionally consistent.
+pub fn check_units(input_data: &BaseInputData) -> Vec<ExpansionError> {
+    let mut problems = Vec::new();
+    for node in &input_data.nodes {
+        let entity = format!("nodes/{}", escape_segment(&node.name));
+        let mut inflow_dimension = None;
+        if let Some(ref state) = node.state {
+            let dimension = if state.is_temp {
+                Dimension::Temperature
+            } else {
+                Dimension::Energy
+            };
+            check_dimension(state.unit, dimension, &entity, "state/unit", &mut problems);
+            if state.is_temp {
+                inflow_dimension = Some(Dimension::Temperature);
+            }
+        }
+        for forecast in forecasts(&node.inflow) {
+            if let (Some(unit), Some(dimension)) = (forecast_unit(forecast), inflow_dimension) {
+                if unit.dimension() != dimension {
+                    problems.push(ExpansionError::new(
+                        entity.clone(),
+                        "inflow",
+                        format!(
+                            "forecast '{}' is in {} which is not compatible with a {} state",
+                            forecast.name(),
+                            unit,
+                            dimension
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+    for process in &input_data.processes {
+        for topology in &process.topos {
+            let entity = format!(
+                "processes/{}/topos/{}->{}",
+                escape_segment(&process.name),
+                escape_segment(&topology.source),
+                escape_segment(&topology.sink)
+            );
+            check_dimension(
+                topology.capacity_unit,
+                Dimension::Power,
+                &entity,
+                "capacity_unit",
+                &mut problems,
+            );
+        }
+    }
+    for market in &input_data.markets {
+        let entity = format!("markets/{}", escape_segment(&market.name));
+        check_dimension(
+            market.price_unit,
+            Dimension::Price,
+            &entity,
+            "price_unit",
+            &mut problems,
+        );
+        for (field, values) in [
+            ("price", &market.price),
+            ("up_price", &market.up_price),
+            ("down_price", &market.down_price),
+        ] {
+            for forecast in forecasts(values) {
+                check_dimension(
+                    forecast_unit(forecast),
+                    Dimension::Price,
+                    &entity,
+                    field,
+                    &mut problems,
+                );
+            }
+        }
+    }
+    problems
+}
+
+fn labelled_units(input_data: &BaseInputData) -> Vec<Unit> {
+    let states = input_data
+        .nodes
+        .iter()
+        .filter_map(|n| n.state.as_ref().and_then(|s| s.unit));
+    let topologies = input_data
+        .processes
+        .iter()
+        .flat_map(|p| &p.topos)
+        .filter_map(|t| t.capacity_unit);
+    let markets = input_data.markets.iter().filter_map(|m| m.price_unit);
+    states.chain(topologies).chain(markets).collect()
+}
+
+fn scale_forecast_values(values: &mut [ForecastValue], factor: f64) {
+    for value in values {
+        match value.value {
+            BaseForecastable::Constant(ref mut constant) => {
+                *constant = Constant::new(constant.value() * factor)
+            }
+            BaseForecastable::FloatList(ref mut list) => {
+                list.scale(factor)
+            }
+            BaseForecastable::Forecast(..) => (),
+        }
+    }
+}
+
+fn scale_values(values: &mut [Value], factor: f64) {
+    for value in values {
+        match value.value {
+            SeriesValue::Constant(ref mut constant) => {
+                *constant = Constant::new(constant.value() * factor)
+            }
+            SeriesValue::FloatList(ref mut list) => list.scale(factor),
+        }
+    }
+}
+
+/// Factors from kW and kWh to MW and MWh of flows keyed by (process, node) and of node states.
+struct ScaleFactors {
+    flows: HashMap<(String, String), f64>,
+    states: HashMap<String, f64>,
+}
+
+impl ScaleFactors {
+    fn new(input_data: &BaseInputData) -> Self {
+        let mut flows = HashMap::new();
+        for process in &input_data.processes {
+            for topology in &process.topos {
+                if let Some(unit) = topology.capacity_unit.filter(|u| u.is_kilo()) {
+                    for node in [&topology.source, &topology.sink] {
+                        if *node != process.name {
+                            flows.insert((process.name.clone(), node.clone()), unit.mega_factor());
+                        }
+                    }
+                }
+            }
+        }
+        let states = input_data
+            .nodes
+            .iter()
+            .filter_map(|node| {
+                let unit = node.state.as_ref()?.unit.filter(|u| u.is_kilo())?;
+                Some((node.name.clone(), unit.mega_factor()))
+            })
+            .collect();
+        ScaleFactors { flows, states }
+    }
+
+    /// Returns the factor of the constraint variable or None if the variable has no physical unit.
+    fn variable(&self, factor: &BaseConFactor) -> Option<f64> {
+        let variable = &factor.var_tuple;
+        let scale = match factor.var_type {
+            ConstraintFactorType::Flow => self.flows.get(&(
+                variable.entity.clone(),
+                variable.identifier.clone().unwrap_or_default(),
+            )),
+            ConstraintFactorType::State => self.states.get(&variable.entity),
+            ConstraintFactorType::Online => return None,
+        };
+        Some(scale.copied().unwrap_or(1.0))
+    }
+}
+
+/// Rescales a generic constraint to use converted variables.
+///
+/// If all variables are converted by the same factor, the whole constraint is scaled
+/// by it; otherwise the coefficients of the converted variables compensate their factors.
+fn harmonize_gen_constraint(constraint: &mut BaseGenConstraint, scale_factors: &ScaleFactors) {
+    let variable_factors: Vec<Option<f64>> = constraint
+        .factors
+        .iter()
+        .map(|factor| scale_factors.variable(factor))
+        .collect();
+    let mut physical = variable_factors.iter().flatten();
+    let common_factor = physical
+        .next()
+        .copied()
+        .filter(|first| physical.all(|factor| factor == first));
+    match common_factor {
+        Some(common_factor) => {
+            if common_factor == 1.0 {
+                return;
+            }
+            for (factor, variable_factor) in constraint.factors.iter_mut().zip(&variable_factors) {
+                if variable_factor.is_none() {
+                    scale_values(&mut factor.data, common_factor);
+                }
+            }
+            scale_values(&mut constraint.constant, common_factor);
+            constraint.penalty /= common_factor;
+        }
+        None => {
+            for (factor, variable_factor) in constraint.factors.iter_mut().zip(&variable_factors) {
+                if let Some(variable_factor) = variable_factor {
+                    scale_values(&mut factor.data, 1.0 / variable_factor);
+                }
+            }
+        }
+    }
+}
+
+/// Converts values given in kW, kWh or EUR/kWh to MW, MWh and EUR/MWh
+/// if the model mixes both scales; returns None if nothing needs to be converted.
+///
+/// Quantities that depend on the converted units are converted with them:
+/// capacity time series, costs and fees per energy, inflows and histories of storage nodes
+/// as well as generic constraints on converted flows and states.
+pub fn harmonize(input_data: &BaseInputData) -> Result<Option<BaseInputData>, ExpansionError> {
+    if let Some(problem) = check_units(input_data).into_iter().next() {
+        return Err(problem);
+    }
+    let units = labelled_units(input_data);
+    if !(units.iter().any(|u| u.is_kilo()) && units.iter().any(|u| u.is_mega())) {
+        return Ok(None);
+    }
+    let scale_factors = ScaleFactors::new(input_data);
+    let mut harmonized = input_data.clone();
+    for node in &mut harmonized.nodes {
+        if let Some(ref mut state) = node.state {
+            if let Some(unit) = state.unit.filter(|u| u.is_kilo()) {
+                let factor = unit.mega_factor();
+                state.state_max *= factor;
+                state.state_min *= factor;
+                state.initial_state *= factor;
+                state.in_max *= factor;
+                state.out_max *= factor;
+                state.residual_value /= factor;
+                state.unit = Some(unit.mega());
+                scale_forecast_values(&mut node.inflow, factor);
+                scale_values(&mut node.cost, 1.0 / factor);
+            }
+        }
+    }
+    for history in &mut harmonized.node_histories {
+        if let Some(factor) = scale_factors.states.get(&history.node) {
+            for series in &mut history.steps {
+                series.values.iter_mut().for_each(|value| *value *= factor);
+            }
+        }
+    }
+    for topology in harmonized.processes.iter_mut().flat_map(|p| &mut p.topos) {
+        if let Some(unit) = topology.capacity_unit.filter(|u| u.is_kilo()) {
+            let factor = unit.mega_factor();
+            topology.capacity *= factor;
+            topology.initial_flow *= factor;
+            topology.vom_cost /= factor;
+            scale_values(&mut topology.cap_ts, factor);
+            topology.capacity_unit = Some(unit.mega());
+        }
+    }
+    for market in &mut harmonized.markets {
+        if let Some(unit) = market.price_unit.filter(|u| u.is_kilo()) {
+            let factor = unit.mega_factor();
+            scale_forecast_values(&mut market.price, factor);
+            scale_forecast_values(&mut market.up_price, factor);
+            scale_forecast_values(&mut market.down_price, factor);
+            scale_values(&mut market.reserve_activation_price, factor);
+            market.fee *= factor;
+            market.price_unit = Some(unit.mega());
+        }
+    }
+    for constraint in &mut harmonized.gen_constraints {
+        harmonize_gen_constraint(constraint, &scale_factors);
+    }
+    Ok(Some(harmonized))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_data::State;
+    use crate::input_data_base::{
+        BaseNode, BaseProcess, BaseTopology, ConstraintType, Conversion, VariableId,
+    };
+    use crate::scenarios::Scenario;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn conversions_respect_dimensions() {
+        assert_eq!(convert(20.0, Unit::Celsius, Unit::Kelvin).unwrap(), 293.15);
+        assert_eq!(convert(273.15, Unit::Kelvin, Unit::Celsius).unwrap(), 0.0);
+        assert_eq!(convert(1500.0, Unit::Kw, Unit::Mw).unwrap(), 1.5);
+        assert_eq!(convert(2.0, Unit::Mwh, Unit::Kwh).unwrap(), 2000.0);
+        assert_eq!(convert(0.05, Unit::EurPerKwh, Unit::EurPerMwh).unwrap(), 50.0);
+        assert_eq!(
+            convert(1.0, Unit::Kw, Unit::Kelvin).err().unwrap(),
+            "cannot convert kW to K"
+        );
+    }
+
+    fn make_input_data() -> BaseInputData {
+        let mut storage = BaseNode::new("tank".into());
+        storage.state = Some(State {
+            state_max: 2000.0,
+            in_max: 500.0,
+            out_max: 500.0,
+            unit: Some(Unit::Kwh),
+            ..State::default()
+        });
+        let mut process = BaseProcess::new("boiler".into(), Conversion::Unit);
+        let mut topology = BaseTopology::new("boiler".into(), "tank".into());
+        topology.capacity = 1.5;
+        topology.capacity_unit = Some(Unit::Mw);
+        process.topos.push(topology);
+        BaseInputData {
+            nodes: vec![storage],
+            processes: vec![process],
+            ..BaseInputData::default()
+        }
+    }
+
+    #[test]
+    fn mixed_scales_are_harmonized() {
+        let mut input_data = make_input_data();
+        let harmonized = harmonize(&input_data).unwrap().unwrap();
+        let state = harmonized.nodes[0].state.as_ref().unwrap();
+        assert_eq!(state.state_max, 2.0);
+        assert_eq!(state.in_max, 0.5);
+        assert_eq!(state.unit, Some(Unit::Mwh));
+        assert_eq!(harmonized.processes[0].topos[0].capacity, 1.5);
+        input_data.processes[0].topos[0].capacity_unit = Some(Unit::Kw);
+        assert!(harmonize(&input_data).unwrap().is_none());
+    }
+
+    fn assert_close(value: f64, expected: f64) {
+        assert!((value - expected).abs() < 1.0e-9, "{} != {}", value, expected);
+    }
+
+    #[test]
+    fn dependent_values_are_harmonized_on_expansion() {
+        let mut input_data = make_input_data();
+        input_data.scenarios = vec![Scenario::new("s1", 1.0).unwrap()];
+        let state = input_data.nodes[0].state.as_mut().unwrap();
+        state.residual_value = 0.05;
+        let mut heater = BaseProcess::new("heater".into(), Conversion::Unit);
+        let mut topology = BaseTopology::new("heater".into(), "tank".into());
+        topology.capacity = 500.0;
+        topology.vom_cost = 0.01;
+        topology.capacity_unit = Some(Unit::Kw);
+        topology.cap_ts = vec![Value {
+            scenario: None,
+            value: SeriesValue::Constant(Constant::new(400.0)),
+        }];
+        heater.topos.push(topology);
+        input_data.processes.push(heater);
+        input_data.gen_constraints.push(BaseGenConstraint {
+            name: "heater_limit".into(),
+            gc_type: ConstraintType::LessThan,
+            is_setpoint: false,
+            penalty: 0.2,
+            factors: vec![BaseConFactor {
+                var_type: ConstraintFactorType::Flow,
+                var_tuple: VariableId {
+                    entity: "heater".into(),
+                    identifier: Some("tank".into()),
+                },
+                data: vec![Value {
+                    scenario: None,
+                    value: SeriesValue::Constant(Constant::new(1.0)),
+                }],
+            }],
+            constant: vec![Value {
+                scenario: None,
+                value: SeriesValue::Constant(Constant::new(300.0)),
+            }],
+        });
+        let time_line = vec![
+            Utc.with_ymd_and_hms(2024, 11, 19, 13, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2024, 11, 19, 14, 0, 0).unwrap(),
+        ];
+        let expanded = input_data.expand_to_time_series(&time_line).unwrap();
+        let topology = &expanded.processes["heater"].topos[0];
+        assert_close(topology.capacity, 0.5);
+        assert_close(topology.vom_cost, 10.0);
+        for value in topology.cap_ts.ts_data[0].series.values() {
+            assert_close(*value, 0.4);
+        }
+        assert_close(expanded.nodes["tank"].state.as_ref().unwrap().residual_value, 50.0);
+        let constraint = &expanded.gen_constraints["heater_limit"];
+        for value in constraint.constant.ts_data[0].series.values() {
+            assert_close(*value, 0.3);
+        }
+        assert_close(constraint.penalty, 200.0);
+    }
+
+    #[test]
+    fn inconsistent_units_are_reported() {
+        let mut input_data = make_input_data();
+        input_data.processes[0].topos[0].capacity_unit = Some(Unit::Mwh);
+        input_data.nodes[0].state.as_mut().unwrap().is_temp = true;
+        let problems: Vec<String> = check_units(&input_data)
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        assert_eq!(
+            problems,
+            vec![
+                "nodes/tank/state/unit: kWh is not a unit of temperature",
+                "processes/boiler/topos/boiler->tank/capacity_unit: MWh is not a unit of power",
+            ]
+        );
+        assert!(harmonize(&input_data).is_err());
+    }
+}